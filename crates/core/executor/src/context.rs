@@ -6,8 +6,15 @@ use hashbrown::HashMap;
 use crate::{
     hook::{hookify, BoxedHook, HookEnv, HookRegistry},
     subproof::SubproofVerifier,
+    syscalls::{Syscall, SyscallRegistry},
 };
 
+pub use sp1_primitives::consts::TIME_CONFIG_KEY;
+
+/// The reserved config key the guest reads with `sys_is_proving`. The `CONFIG_GET` syscall answers
+/// it from the executor mode, ignoring any config entry with the same key.
+pub const PROVING_CONFIG_KEY: &str = "sp1.proving";
+
 /// Context to run a program inside SP1.
 #[derive(Clone, Default)]
 pub struct SP1Context<'a> {
@@ -16,11 +23,29 @@ pub struct SP1Context<'a> {
     /// Note: `None` denotes the default list of hooks.
     pub hook_registry: Option<HookRegistry<'a>>,
 
+    /// The registry of syscalls invokable from inside SP1.
+    ///
+    /// Note: `None` denotes the default list of syscalls.
+    pub syscall_registry: Option<SyscallRegistry>,
+
     /// The verifier for verifying subproofs.
     pub subproof_verifier: Option<Arc<dyn SubproofVerifier + 'a>>,
 
     /// The maximum number of cpu cycles to use for execution.
     pub max_cycles: Option<u64>,
+
+    /// Whether to record profiling spans emitted by the guest.
+    pub profiling: bool,
+
+    /// The key/value config the guest can read with `sys_config_get`.
+    pub config: HashMap<String, Vec<u8>>,
+
+    /// The read-only input blob the guest can read at any offset with `sys_input_read_at`.
+    pub input_blob: Vec<u8>,
+
+    /// The named input streams, keyed by stream id, that the guest can read with
+    /// `sys_read_stream`.
+    pub input_streams: HashMap<u32, Vec<u8>>,
 }
 
 /// A builder for [`SP1Context`].
@@ -28,8 +53,13 @@ pub struct SP1Context<'a> {
 pub struct SP1ContextBuilder<'a> {
     no_default_hooks: bool,
     hook_registry_entries: Vec<(u32, BoxedHook<'a>)>,
+    syscall_registry_entries: Vec<(u32, Arc<dyn Syscall>)>,
     subproof_verifier: Option<Arc<dyn SubproofVerifier + 'a>>,
     max_cycles: Option<u64>,
+    profiling: bool,
+    config: HashMap<String, Vec<u8>>,
+    input_blob: Vec<u8>,
+    input_streams: HashMap<u32, Vec<u8>>,
 }
 
 impl<'a> SP1Context<'a> {
@@ -66,9 +96,28 @@ impl<'a> SP1ContextBuilder<'a> {
                 table.extend(take(&mut self.hook_registry_entries));
                 HookRegistry { table }
             });
+        let syscall_registry = (!self.syscall_registry_entries.is_empty()).then(|| {
+            let mut registry = SyscallRegistry::default();
+            // Allows overwriting default syscalls.
+            registry.table.extend(take(&mut self.syscall_registry_entries));
+            registry
+        });
         let subproof_verifier = take(&mut self.subproof_verifier);
         let cycle_limit = take(&mut self.max_cycles);
-        SP1Context { hook_registry, subproof_verifier, max_cycles: cycle_limit }
+        let profiling = take(&mut self.profiling);
+        let config = take(&mut self.config);
+        let input_blob = take(&mut self.input_blob);
+        let input_streams = take(&mut self.input_streams);
+        SP1Context {
+            hook_registry,
+            syscall_registry,
+            subproof_verifier,
+            max_cycles: cycle_limit,
+            profiling,
+            config,
+            input_blob,
+            input_streams,
+        }
     }
 
     /// Add a runtime [Hook](super::Hook) into the context.
@@ -94,6 +143,16 @@ impl<'a> SP1ContextBuilder<'a> {
         self
     }
 
+    /// Add a [`Syscall`] into the context, invoked when the guest executes `ecall` with `code` in
+    /// register t0.
+    ///
+    /// Registering a syscall under the code of a built-in syscall overrides it. See
+    /// [`SyscallRegistry`] for the layout of custom codes.
+    pub fn syscall(&mut self, code: u32, syscall: impl Syscall + 'static) -> &mut Self {
+        self.syscall_registry_entries.push((code, Arc::new(syscall)));
+        self
+    }
+
     /// Add a subproof verifier.
     ///
     /// The verifier is used to sanity check `verify_sp1_proof` during runtime.
@@ -110,21 +169,88 @@ impl<'a> SP1ContextBuilder<'a> {
         self.max_cycles = Some(max_cycles);
         self
     }
+
+    /// Record the profiling spans emitted by the guest through `sys_profile_begin` and
+    /// `sys_profile_end`.
+    ///
+    /// When disabled (the default), these syscalls are no-ops in the executor.
+    pub fn profiling(&mut self) -> &mut Self {
+        self.profiling = true;
+        self
+    }
+
+    /// Add an entry to the config, which the guest can read with `sys_config_get`.
+    ///
+    /// Setting a key again overwrites its previous value.
+    pub fn config(&mut self, key: impl Into<String>, value: impl Into<Vec<u8>>) -> &mut Self {
+        self.config.insert(key.into(), value.into());
+        self
+    }
+
+    /// Set the timestamp the guest commits to the public values with `sys_commit_time`.
+    ///
+    /// It is stored in the config under [`TIME_CONFIG_KEY`] as 8 little-endian bytes. The
+    /// timestamp is attested by the host, not proven: the proof only shows that the program
+    /// committed the value the prover chose.
+    pub fn time(&mut self, time: u64) -> &mut Self {
+        self.config(TIME_CONFIG_KEY, time.to_le_bytes())
+    }
+
+    /// Set the read-only input blob, which the guest can read at any offset with
+    /// `sys_input_read_at`, independently of the sequential input stream.
+    pub fn input_blob(&mut self, blob: impl Into<Vec<u8>>) -> &mut Self {
+        self.input_blob = blob.into();
+        self
+    }
+
+    /// Append `bytes` to the named input stream `stream_id`, which the guest reads sequentially
+    /// with `sys_read_stream`, independently of the other streams.
+    pub fn input_stream(&mut self, stream_id: u32, bytes: impl AsRef<[u8]>) -> &mut Self {
+        self.input_streams.entry(stream_id).or_default().extend_from_slice(bytes.as_ref());
+        self
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use std::sync::Arc;
 
-    use crate::{subproof::DefaultSubproofVerifier, SP1Context};
+    use crate::{subproof::DefaultSubproofVerifier, SP1Context, TIME_CONFIG_KEY};
 
     #[test]
     fn defaults() {
-        let SP1Context { hook_registry, subproof_verifier, max_cycles: cycle_limit } =
-            SP1Context::builder().build();
+        let SP1Context {
+            hook_registry,
+            syscall_registry,
+            subproof_verifier,
+            max_cycles: cycle_limit,
+            profiling,
+            config,
+            input_blob,
+            input_streams,
+        } = SP1Context::builder().build();
         assert!(hook_registry.is_none());
+        assert!(syscall_registry.is_none());
         assert!(subproof_verifier.is_none());
         assert!(cycle_limit.is_none());
+        assert!(!profiling);
+        assert!(config.is_empty());
+        assert!(input_blob.is_empty());
+        assert!(input_streams.is_empty());
+    }
+
+    #[test]
+    fn config() {
+        let SP1Context { config, .. } =
+            SP1Context::builder().config("chain_id", "1").config("chain_id", "10").build();
+        assert_eq!(config.len(), 1);
+        assert_eq!(config["chain_id"], b"10");
+    }
+
+    #[test]
+    fn time() {
+        let SP1Context { config, .. } = SP1Context::builder().time(1_700_000_000).build();
+        assert_eq!(config[TIME_CONFIG_KEY], 1_700_000_000u64.to_le_bytes());
     }
 
     #[test]
@@ -0,0 +1,36 @@
+use serde::{Deserialize, Serialize};
+use sp1_primitives::blake3::{BLAKE3_BLOCK_WORDS, BLAKE3_STATE_WORDS};
+
+use crate::events::{
+    memory::{MemoryReadRecord, MemoryWriteRecord},
+    LookupId,
+};
+
+/// Blake3 Compress Event.
+///
+/// This event is emitted when a BLAKE3 compression is performed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Blake3CompressEvent {
+    /// The lookup identifer.
+    pub lookup_id: LookupId,
+    /// The shard number.
+    pub shard: u32,
+    /// The channel number.
+    pub channel: u8,
+    /// The clock cycle.
+    pub clk: u32,
+    /// The pointer to the state.
+    pub state_ptr: u32,
+    /// The pointer to the message block.
+    pub block_ptr: u32,
+    /// The initial state of the compression function.
+    pub input: [u32; BLAKE3_STATE_WORDS],
+    /// The message block.
+    pub block: [u32; BLAKE3_BLOCK_WORDS],
+    /// The output of the compression function.
+    pub output: [u32; BLAKE3_STATE_WORDS],
+    /// The memory records for the state.
+    pub state_records: Vec<MemoryWriteRecord>,
+    /// The memory records for the message block.
+    pub block_records: Vec<MemoryReadRecord>,
+}
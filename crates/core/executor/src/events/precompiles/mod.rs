@@ -1,15 +1,19 @@
+mod blake3;
 mod ec;
 mod edwards;
 mod fptower;
 mod keccak256_permute;
+mod poseidon2;
 mod sha256_compress;
 mod sha256_extend;
 mod uint256;
 
+pub use blake3::*;
 pub use ec::*;
 pub use edwards::*;
 pub use fptower::*;
 pub use keccak256_permute::*;
+pub use poseidon2::*;
 pub use sha256_compress::*;
 pub use sha256_extend::*;
 pub use uint256::*;
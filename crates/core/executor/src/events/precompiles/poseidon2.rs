@@ -0,0 +1,29 @@
+use serde::{Deserialize, Serialize};
+
+use crate::events::{memory::MemoryWriteRecord, LookupId};
+
+/// The width of the Poseidon2 permutation state, in words.
+pub const POSEIDON2_WIDTH: usize = 16;
+
+/// Poseidon2 Permute Event.
+///
+/// This event is emitted when a Poseidon2 permutation is performed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Poseidon2PermuteEvent {
+    /// The lookup identifer.
+    pub lookup_id: LookupId,
+    /// The shard number.
+    pub shard: u32,
+    /// The channel number.
+    pub channel: u8,
+    /// The clock cycle.
+    pub clk: u32,
+    /// The pointer to the state.
+    pub state_ptr: u32,
+    /// The input state, as canonical `BabyBear` values.
+    pub input: [u32; POSEIDON2_WIDTH],
+    /// The output state, as canonical `BabyBear` values.
+    pub output: [u32; POSEIDON2_WIDTH],
+    /// The memory records for the state.
+    pub state_records: Vec<MemoryWriteRecord>,
+}
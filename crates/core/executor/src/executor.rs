@@ -18,14 +18,18 @@ use crate::{
     },
     hook::{HookEnv, HookRegistry},
     memory::{Entry, PagedMemory},
+    profile::ProfileEvent,
     record::{ExecutionRecord, MemoryAccessRecord},
     report::ExecutionReport,
     state::{ExecutionState, ForkState},
     subproof::{DefaultSubproofVerifier, SubproofVerifier},
-    syscalls::{default_syscall_map, Syscall, SyscallCode, SyscallContext},
+    syscalls::{SyscallCode, SyscallContext, SyscallRegistry},
     Instruction, Opcode, Program, Register,
 };
 
+#[cfg(any(test, feature = "fault-injection"))]
+use crate::syscalls::SyscallFaultInjector;
+
 /// An executor for the SP1 RISC-V zkVM.
 ///
 /// The exeuctor is responsible for executing a user program and tracing important events which
@@ -71,8 +75,8 @@ pub struct Executor<'a> {
     /// The state of the runtime when in unconstrained mode.
     pub unconstrained_state: ForkState,
 
-    /// The mapping between syscall codes and their implementations.
-    pub syscall_map: HashMap<SyscallCode, Arc<dyn Syscall>>,
+    /// Registry of syscalls, indexed by their syscall codes.
+    pub syscall_registry: SyscallRegistry,
 
     /// The maximum number of cycles for a syscall.
     pub max_syscall_cycles: u32,
@@ -101,6 +105,16 @@ pub struct Executor<'a> {
     /// Memory addresses that were touched in this batch of shards. Used to minimize the size of
     /// checkpoints.
     pub memory_checkpoint: PagedMemory<Option<MemoryRecord>>,
+
+    /// Whether profiling spans emitted by the guest should be recorded.
+    pub profiling: bool,
+
+    /// The timeline of profiling spans emitted by the guest.
+    pub profile_events: Vec<ProfileEvent>,
+
+    /// Faults to inject into syscall results. Test-only, see [`SyscallFaultInjector`].
+    #[cfg(any(test, feature = "fault-injection"))]
+    pub fault_injector: SyscallFaultInjector,
 }
 
 /// The different modes the executor can run in.
@@ -117,7 +131,8 @@ pub enum ExecutorMode {
 /// Errors that the [``Executor``] can throw.
 #[derive(Error, Debug, Serialize, Deserialize)]
 pub enum ExecutionError {
-    /// The execution failed with a non-zero exit code.
+    /// The execution failed with a non-zero exit code, see [`crate::syscalls::ExitCode`] for the
+    /// reserved values.
     #[error("execution failed with exit code {0}")]
     HaltWithNonZeroExitCode(u32),
 
@@ -148,6 +163,10 @@ pub enum ExecutionError {
     /// The program ended in unconstrained mode.
     #[error("program ended in unconstrained mode")]
     EndInUnconstrained(),
+
+    /// A syscall tried to resume execution at an invalid program counter.
+    #[error("invalid next pc {0:#x}")]
+    InvalidNextPc(u32),
 }
 
 macro_rules! assert_valid_memory_access {
@@ -186,9 +205,8 @@ impl<'a> Executor<'a> {
         };
 
         // Determine the maximum number of cycles for any syscall.
-        let syscall_map = default_syscall_map();
-        let max_syscall_cycles =
-            syscall_map.values().map(|syscall| syscall.num_extra_cycles()).max().unwrap_or(0);
+        let syscall_registry = context.syscall_registry.unwrap_or_default();
+        let max_syscall_cycles = syscall_registry.max_syscall_cycles();
 
         let subproof_verifier =
             context.subproof_verifier.unwrap_or_else(|| Arc::new(DefaultSubproofVerifier::new()));
@@ -197,7 +215,12 @@ impl<'a> Executor<'a> {
         Self {
             record,
             records: vec![],
-            state: ExecutionState::new(program.pc_start),
+            state: ExecutionState {
+                config: context.config,
+                input_blob: context.input_blob,
+                input_streams: context.input_streams,
+                ..ExecutionState::new(program.pc_start)
+            },
             program,
             memory_accesses: MemoryAccessRecord::default(),
             shard_size: (opts.shard_size as u32) * 4,
@@ -207,7 +230,7 @@ impl<'a> Executor<'a> {
             trace_buf,
             unconstrained: false,
             unconstrained_state: ForkState::default(),
-            syscall_map,
+            syscall_registry,
             executor_mode: ExecutorMode::Trace,
             max_syscall_cycles,
             report: ExecutionReport::default(),
@@ -217,6 +240,10 @@ impl<'a> Executor<'a> {
             opts,
             max_cycles: context.max_cycles,
             memory_checkpoint: PagedMemory::new_preallocated(),
+            profiling: context.profiling,
+            profile_events: Vec::new(),
+            #[cfg(any(test, feature = "fault-injection"))]
+            fault_injector: SyscallFaultInjector::default(),
         }
     }
 
@@ -923,10 +950,13 @@ impl<'a> Executor<'a> {
                 let syscall_id = self.register(t0);
                 c = self.rr(Register::X11, MemoryAccessPosition::C);
                 b = self.rr(Register::X10, MemoryAccessPosition::B);
-                let syscall = SyscallCode::from_u32(syscall_id);
+                // Custom syscalls in the syscall registry have no `SyscallCode`.
+                let syscall = SyscallCode::try_from_u32(syscall_id);
 
-                if self.print_report && !self.unconstrained {
-                    self.report.syscall_counts[syscall] += 1;
+                if let Some(syscall) = syscall {
+                    if self.print_report && !self.unconstrained {
+                        self.report.syscall_counts[syscall] += 1;
+                    }
                 }
 
                 // `hint_slice` is allowed in unconstrained mode since it is used to write the hint.
@@ -936,12 +966,15 @@ impl<'a> Executor<'a> {
                 // non-zero memory interactions when generating a proof.
 
                 if self.unconstrained
-                    && (syscall != SyscallCode::EXIT_UNCONSTRAINED && syscall != SyscallCode::WRITE)
+                    && !matches!(
+                        syscall,
+                        Some(SyscallCode::EXIT_UNCONSTRAINED | SyscallCode::WRITE)
+                    )
                 {
                     return Err(ExecutionError::InvalidSyscallUsage(syscall_id as u64));
                 }
 
-                let syscall_impl = self.get_syscall(syscall).cloned();
+                let syscall_impl = self.syscall_registry.get(syscall_id).cloned();
                 let mut precompile_rt = SyscallContext::new(self);
                 precompile_rt.syscall_lookup_id = syscall_lookup_id;
                 let (precompile_next_pc, precompile_cycles, returned_exit_code) =
@@ -950,14 +983,23 @@ impl<'a> Executor<'a> {
                         // register. If it returns None, we just keep the
                         // syscall_id in t0.
                         let res = syscall_impl.execute(&mut precompile_rt, b, c);
+                        #[cfg(any(test, feature = "fault-injection"))]
+                        let res = match syscall {
+                            Some(syscall) => {
+                                Some(precompile_rt.inject_fault(syscall, res.unwrap_or(syscall_id)))
+                            }
+                            None => res,
+                        };
                         if let Some(val) = res {
                             a = val;
                         } else {
                             a = syscall_id;
                         }
 
-                        // If the syscall is `HALT` and the exit code is non-zero, return an error.
-                        if syscall == SyscallCode::HALT && precompile_rt.exit_code != 0 {
+                        // If the syscall halts and the exit code is non-zero, return an error.
+                        if matches!(syscall, Some(SyscallCode::HALT | SyscallCode::EXIT_GROUP))
+                            && precompile_rt.exit_code != 0
+                        {
                             return Err(ExecutionError::HaltWithNonZeroExitCode(
                                 precompile_rt.exit_code,
                             ));
@@ -965,7 +1007,7 @@ impl<'a> Executor<'a> {
 
                         (
                             precompile_rt.next_pc,
-                            syscall_impl.num_extra_cycles(),
+                            syscall_impl.num_extra_cycles(syscall_id),
                             precompile_rt.exit_code,
                         )
                     } else {
@@ -981,18 +1023,21 @@ impl<'a> Executor<'a> {
                 self.state.clk += precompile_cycles;
                 exit_code = returned_exit_code;
 
-                // Update the syscall counts.
-                let syscall_for_count = syscall.count_map();
-                let syscall_count = self.state.syscall_counts.entry(syscall_for_count).or_insert(0);
-                let (threshold, multiplier) = match syscall_for_count {
-                    SyscallCode::KECCAK_PERMUTE => (self.opts.split_opts.keccak, 24),
-                    SyscallCode::SHA_EXTEND => (self.opts.split_opts.sha_extend, 48),
-                    SyscallCode::SHA_COMPRESS => (self.opts.split_opts.sha_compress, 80),
-                    _ => (self.opts.split_opts.deferred, 1),
-                };
-                let nonce = (((*syscall_count as usize) % threshold) * multiplier) as u32;
-                self.record.nonce_lookup.insert(syscall_lookup_id, nonce);
-                *syscall_count += 1;
+                // Update the syscall counts. Custom syscalls have no table, so they need no nonce.
+                if let Some(syscall) = syscall {
+                    let syscall_for_count = syscall.count_map();
+                    let syscall_count =
+                        self.state.syscall_counts.entry(syscall_for_count).or_insert(0);
+                    let (threshold, multiplier) = match syscall_for_count {
+                        SyscallCode::KECCAK_PERMUTE => (self.opts.split_opts.keccak, 24),
+                        SyscallCode::SHA_EXTEND => (self.opts.split_opts.sha_extend, 48),
+                        SyscallCode::SHA_COMPRESS => (self.opts.split_opts.sha_compress, 80),
+                        _ => (self.opts.split_opts.deferred, 1),
+                    };
+                    let nonce = (((*syscall_count as usize) % threshold) * multiplier) as u32;
+                    self.record.nonce_lookup.insert(syscall_lookup_id, nonce);
+                    *syscall_count += 1;
+                }
             }
             Opcode::EBREAK => {
                 return Err(ExecutionError::Breakpoint());
@@ -1130,6 +1175,7 @@ impl<'a> Executor<'a> {
         }
 
         let done = self.state.pc == 0
+            || self.state.terminated
             || self.state.pc.wrapping_sub(self.program.pc_base)
                 >= (self.program.instructions.len() * 4) as u32;
         if done && self.unconstrained {
@@ -1242,6 +1288,11 @@ impl<'a> Executor<'a> {
         // Get the program.
         let program = self.program.clone();
 
+        // Nothing runs after the program terminated the execution.
+        if self.state.terminated {
+            return Ok(true);
+        }
+
         // Get the current shard.
         let start_shard = self.state.current_shard;
 
@@ -1387,10 +1438,6 @@ impl<'a> Executor<'a> {
         }
     }
 
-    fn get_syscall(&mut self, code: SyscallCode) -> Option<&Arc<dyn Syscall>> {
-        self.syscall_map.get(&code)
-    }
-
     #[inline]
     fn log(&mut self, _: &Instruction) {
         // Write the current program counter to the trace buffer for the cycle tracer.
@@ -1433,9 +1480,12 @@ mod tests {
         ssz_withdrawals_program,
     };
 
-    use crate::Register;
+    use crate::{
+        syscalls::{Syscall, SyscallCode, SyscallContext, HINT_LEN_END_OF_STREAM},
+        ProfileEventKind, Register, SP1Context,
+    };
 
-    use super::{Executor, Instruction, Opcode, Program};
+    use super::{ExecutionError, Executor, Instruction, Opcode, Program};
 
     fn _assert_send<T: Send>() {}
 
@@ -2036,4 +2086,639 @@ mod tests {
         assert_eq!(runtime.register(Register::X12), 0x12346525);
         assert_eq!(runtime.register(Register::X11), 0x65256525);
     }
+
+    fn profile_span_program() -> Program {
+        // Store the label "main" at address 0x100 and open and close a span with it.
+        let instructions = vec![
+            Instruction::new(Opcode::ADD, 29, 0, u32::from_le_bytes(*b"main"), false, true),
+            Instruction::new(Opcode::ADD, 30, 0, 0x100, false, true),
+            Instruction::new(Opcode::SW, 29, 30, 0, false, true),
+            Instruction::new(Opcode::ADD, 5, 0, SyscallCode::PROFILE_BEGIN as u32, false, true),
+            Instruction::new(Opcode::ADD, 10, 0, 0x100, false, true),
+            Instruction::new(Opcode::ADD, 11, 0, 4, false, true),
+            Instruction::new(Opcode::ECALL, 5, 10, 11, false, false),
+            Instruction::new(Opcode::ADD, 5, 0, SyscallCode::PROFILE_END as u32, false, true),
+            Instruction::new(Opcode::ECALL, 5, 10, 11, false, false),
+        ];
+        Program::new(instructions, 0, 0)
+    }
+
+    #[test]
+    fn test_profile_spans() {
+        let context = SP1Context::builder().profiling().build();
+        let mut runtime =
+            Executor::with_context(profile_span_program(), SP1CoreOpts::default(), context);
+        runtime.run().unwrap();
+
+        let events = &runtime.profile_events;
+        assert_eq!(events.len(), 2);
+        assert!(events.iter().all(|event| event.label == "main"));
+        assert_eq!(events[0].kind, ProfileEventKind::Begin);
+        assert_eq!(events[1].kind, ProfileEventKind::End);
+        assert!(events[0].clk < events[1].clk);
+    }
+
+    #[test]
+    fn test_profile_spans_disabled() {
+        let mut runtime = Executor::new(profile_span_program(), SP1CoreOpts::default());
+        runtime.run().unwrap();
+        assert!(runtime.profile_events.is_empty());
+    }
+
+    #[test]
+    fn test_read_pc() {
+        let read_pc = || {
+            [
+                Instruction::new(Opcode::ADD, 5, 0, SyscallCode::READ_PC as u32, false, true),
+                Instruction::new(Opcode::ECALL, 5, 10, 11, false, false),
+            ]
+        };
+        let mut instructions = read_pc().to_vec();
+        instructions.push(Instruction::new(Opcode::ADD, 29, 5, 0, false, true));
+        instructions.extend(read_pc());
+        let pc_base = 0x1000;
+        let mut runtime =
+            Executor::new(Program::new(instructions, pc_base, pc_base), SP1CoreOpts::default());
+        runtime.run().unwrap();
+
+        // Each call returns the pc of its `ecall`, the second instruction of its sequence.
+        assert_eq!(runtime.register(Register::X29), pc_base + 4);
+        assert_eq!(runtime.register(Register::X5), pc_base + 4 * 4);
+    }
+
+    #[test]
+    fn test_fresh_memory_reads_zero() {
+        // Memory that was never written reads as zero.
+        let base = 0x0010_0000;
+        let mut instructions = Vec::new();
+        for offset in (0..64).step_by(4) {
+            instructions.push(Instruction::new(Opcode::LW, 6, 0, base + offset, false, true));
+            instructions.push(Instruction::new(Opcode::OR, 5, 5, 6, false, false));
+        }
+        instructions.push(Instruction::new(Opcode::LBU, 7, 0, base + 63, false, true));
+        let mut runtime = Executor::new(Program::new(instructions, 0, 0), SP1CoreOpts::default());
+        runtime.run().unwrap();
+
+        assert_eq!(runtime.register(Register::X5), 0);
+        assert_eq!(runtime.register(Register::X7), 0);
+    }
+
+    #[test]
+    #[ignore = "needs the ELF of tests/zeroed-region, built with `cargo prove build`"]
+    fn test_alloc_zeroed_region() {
+        let elf = std::fs::read(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/../../../tests/zeroed-region/elf/riscv32im-succinct-zkvm-elf"
+        ))
+        .unwrap();
+        let mut runtime = Executor::new(Program::from(&elf).unwrap(), SP1CoreOpts::default());
+        runtime.run().unwrap();
+
+        // The region is aligned to 8 bytes, zeroed over the dirtied heap, and not allocated again.
+        let expected = [
+            bincode::serialize(&0u32).unwrap(),
+            bincode::serialize(&true).unwrap(),
+            bincode::serialize(&true).unwrap(),
+        ]
+        .concat();
+        assert_eq!(runtime.state.public_values_stream, expected);
+    }
+
+    #[test]
+    #[ignore = "needs the ELF of tests/heap-peak, built with `cargo prove build`"]
+    fn test_heap_peak_report() {
+        let elf = std::fs::read(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/../../../tests/heap-peak/elf/riscv32im-succinct-zkvm-elf"
+        ))
+        .unwrap();
+        let mut runtime = Executor::new(Program::from(&elf).unwrap(), SP1CoreOpts::default());
+        runtime.run().unwrap();
+
+        // The program commits the peak that its `mem-report` feature writes to stderr at exit,
+        // which the executor prints as `stderr: heap peak: 65536 bytes`.
+        assert_eq!(runtime.state.public_values_stream, bincode::serialize(&0x1_0000u32).unwrap());
+    }
+
+    #[test]
+    fn test_shard_index() {
+        let mut instructions = vec![
+            Instruction::new(Opcode::ADD, 5, 0, SyscallCode::SHARD_INDEX as u32, false, true),
+            Instruction::new(Opcode::ECALL, 5, 10, 11, false, false),
+            Instruction::new(Opcode::ADD, 10, 5, 0, false, true),
+        ];
+        instructions.extend(vec![Instruction::new(Opcode::ADD, 29, 0, 5, false, true); 64]);
+        instructions.extend([
+            Instruction::new(Opcode::ADD, 5, 0, SyscallCode::SHARD_INDEX as u32, false, true),
+            Instruction::new(Opcode::ECALL, 5, 10, 11, false, false),
+        ]);
+        let mut opts = SP1CoreOpts::default();
+        opts.shard_size = 32;
+        let mut runtime = Executor::new(Program::new(instructions, 0, 0), opts);
+        runtime.run().unwrap();
+
+        assert_eq!(runtime.register(Register::X10), 1);
+        assert!(runtime.register(Register::X5) > 1);
+    }
+
+    #[test]
+    fn test_hint_len_end_of_stream() {
+        let instructions = vec![
+            Instruction::new(Opcode::ADD, 5, 0, SyscallCode::HINT_LEN as u32, false, true),
+            Instruction::new(Opcode::ECALL, 5, 10, 11, false, false),
+            Instruction::new(Opcode::ADD, 12, 5, 0, false, true),
+            Instruction::new(Opcode::ADD, 5, 0, SyscallCode::HINT_READ as u32, false, true),
+            Instruction::new(Opcode::ADD, 10, 0, 0x1000, false, true),
+            Instruction::new(Opcode::ADD, 11, 0, 3, false, true),
+            Instruction::new(Opcode::ECALL, 5, 10, 11, false, false),
+            Instruction::new(Opcode::ADD, 5, 0, SyscallCode::HINT_LEN as u32, false, true),
+            Instruction::new(Opcode::ECALL, 5, 10, 11, false, false),
+        ];
+        let mut runtime = Executor::new(Program::new(instructions, 0, 0), SP1CoreOpts::default());
+        runtime.write_vecs(&[vec![1, 2, 3]]);
+        runtime.run().unwrap();
+
+        assert_eq!(runtime.register(Register::X12), 3);
+        assert_eq!(runtime.register(Register::X5), HINT_LEN_END_OF_STREAM);
+    }
+
+    #[test]
+    fn test_exit_group() {
+        let program = |code: SyscallCode| {
+            let instructions = vec![
+                Instruction::new(Opcode::ADD, 29, 29, 1, false, true),
+                Instruction::new(Opcode::ADD, 5, 0, code as u32, false, true),
+                Instruction::new(Opcode::ECALL, 5, 10, 11, false, false),
+                Instruction::new(Opcode::ADD, 29, 29, 1, false, true),
+            ];
+            Program::new(instructions, 0, 0)
+        };
+
+        // Driven again after halting, the executor starts over from `pc = 0`.
+        let mut runtime = Executor::new(program(SyscallCode::HALT), SP1CoreOpts::default());
+        runtime.run().unwrap();
+        runtime.run().unwrap();
+        assert_eq!(runtime.register(Register::X29), 2);
+
+        let mut runtime = Executor::new(program(SyscallCode::EXIT_GROUP), SP1CoreOpts::default());
+        runtime.run().unwrap();
+        let global_clk = runtime.state.global_clk;
+        runtime.run().unwrap();
+        assert!(runtime.state.terminated);
+        assert_eq!(runtime.state.global_clk, global_clk);
+        assert_eq!(runtime.register(Register::X29), 1);
+    }
+
+    #[test]
+    fn test_guest_exit_codes() {
+        use crate::{syscalls::ExitCode, ExecutionError};
+        use sp1_zkvm::exit::{
+            EXIT_ABORT, EXIT_ASSERTION_FAILED, EXIT_OK, EXIT_OOM, EXIT_PANIC, EXIT_RAND_EXHAUSTED,
+        };
+
+        let run = |exit_code: u32| {
+            let instructions = vec![
+                Instruction::new(Opcode::ADD, 5, 0, SyscallCode::HALT as u32, false, true),
+                Instruction::new(Opcode::ADD, 10, 0, exit_code, false, true),
+                Instruction::new(Opcode::ECALL, 5, 10, 11, false, false),
+            ];
+            Executor::new(Program::new(instructions, 0, 0), SP1CoreOpts::default()).run()
+        };
+
+        run(EXIT_OK).unwrap();
+        for (exit_code, reason) in [
+            (EXIT_PANIC, ExitCode::Panic),
+            (EXIT_ASSERTION_FAILED, ExitCode::AssertionFailed),
+            (EXIT_OOM, ExitCode::OutOfMemory),
+            (EXIT_ABORT, ExitCode::Abort),
+            (EXIT_RAND_EXHAUSTED, ExitCode::RandExhausted),
+        ] {
+            match run(exit_code) {
+                Err(ExecutionError::HaltWithNonZeroExitCode(code)) => {
+                    assert_eq!(ExitCode::from(code), reason);
+                }
+                result => panic!("expected exit code {exit_code:#x}, got {result:?}"),
+            }
+        }
+    }
+
+    #[test]
+    #[ignore = "needs the ELF of tests/oom, built with `cargo prove build`"]
+    fn test_guest_out_of_memory() {
+        use crate::{syscalls::ExitCode, ExecutionError};
+
+        let elf = std::fs::read(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/../../../tests/oom/elf/riscv32im-succinct-zkvm-elf"
+        ))
+        .unwrap();
+        let mut runtime = Executor::new(Program::from(&elf).unwrap(), SP1CoreOpts::default());
+        match runtime.run() {
+            Err(ExecutionError::HaltWithNonZeroExitCode(code)) => {
+                assert_eq!(ExitCode::from(code), ExitCode::OutOfMemory);
+            }
+            result => panic!("expected the allocator to halt with EXIT_OOM, got {result:?}"),
+        }
+    }
+
+    #[test]
+    #[ignore = "needs the ELF of tests/abort, built with `cargo prove build`"]
+    fn test_guest_abort() {
+        use crate::{syscalls::ExitCode, ExecutionError};
+
+        let elf = std::fs::read(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/../../../tests/abort/elf/riscv32im-succinct-zkvm-elf"
+        ))
+        .unwrap();
+        let mut runtime = Executor::new(Program::from(&elf).unwrap(), SP1CoreOpts::default());
+        match runtime.run() {
+            Err(ExecutionError::HaltWithNonZeroExitCode(code)) => {
+                assert_eq!(ExitCode::from(code), ExitCode::Abort);
+            }
+            result => panic!("expected the program to halt with EXIT_ABORT, got {result:?}"),
+        }
+        // The message follows what the program committed before aborting.
+        let expected = [bincode::serialize(&42u32).unwrap(), b"invalid input".to_vec()].concat();
+        assert_eq!(runtime.state.public_values_stream, expected);
+    }
+
+    #[test]
+    fn test_uint256_mulmod_evm_vectors() {
+        use sp1_zkvm::syscalls::{uint256_from_be_bytes, uint256_to_be_bytes};
+
+        let small = |x: u64| {
+            let mut bytes = [0u8; 32];
+            bytes[24..].copy_from_slice(&x.to_be_bytes());
+            bytes
+        };
+        let max = [0xff; 32];
+        let mut secp256k1_p = max;
+        secp256k1_p[27..].copy_from_slice(&[0xfe, 0xff, 0xff, 0xfc, 0x2f]);
+        let mut secp256k1_p_minus_1 = secp256k1_p;
+        secp256k1_p_minus_1[31] -= 1;
+
+        // (a, b, m, MULMOD(a, b, m)) as 32-byte big-endian integers, as in the EVM.
+        let vectors = [
+            (small(10), small(10), small(8), small(4)),
+            (max, max, small(12), small(9)),
+            (max, small(2), max, small(0)),
+            (secp256k1_p_minus_1, secp256k1_p_minus_1, secp256k1_p, small(1)),
+        ];
+        for (a, b, m, expected) in vectors {
+            let store = |addr: u32, value: &[u8; 32]| {
+                uint256_from_be_bytes(value).into_iter().enumerate().flat_map(move |(i, word)| {
+                    [
+                        Instruction::new(Opcode::ADD, 29, 0, word, false, true),
+                        Instruction::new(Opcode::SW, 29, 0, addr + 4 * i as u32, false, true),
+                    ]
+                })
+            };
+            // `sys_uint256_mulmod` passes `a` in place of the result, followed by `b` and `m`.
+            let mut instructions = store(0x1000, &a)
+                .chain(store(0x1100, &b))
+                .chain(store(0x1120, &m))
+                .collect::<Vec<_>>();
+            instructions.extend([
+                Instruction::new(Opcode::ADD, 5, 0, SyscallCode::UINT256_MUL as u32, false, true),
+                Instruction::new(Opcode::ADD, 10, 0, 0x1000, false, true),
+                Instruction::new(Opcode::ADD, 11, 0, 0x1100, false, true),
+                Instruction::new(Opcode::ECALL, 5, 10, 11, false, false),
+            ]);
+            let mut runtime =
+                Executor::new(Program::new(instructions, 0, 0), SP1CoreOpts::default());
+            runtime.run().unwrap();
+
+            let result = core::array::from_fn(|i| runtime.word(0x1000 + 4 * i as u32));
+            assert_eq!(uint256_to_be_bytes(&result), expected);
+        }
+    }
+
+    #[test]
+    fn test_config_get() {
+        let config_get = |cap| {
+            [
+                Instruction::new(Opcode::ADD, 5, 0, SyscallCode::CONFIG_GET as u32, false, true),
+                Instruction::new(Opcode::ADD, 13, 0, cap, false, true),
+                Instruction::new(Opcode::ECALL, 5, 10, 11, false, false),
+            ]
+        };
+        let mut instructions = vec![
+            // Store the key "id" at 0x2000.
+            Instruction::new(Opcode::ADD, 29, 0, u32::from_le_bytes(*b"id\0\0"), false, true),
+            Instruction::new(Opcode::ADD, 10, 0, 0x2000, false, true),
+            Instruction::new(Opcode::SW, 29, 10, 0, false, true),
+            Instruction::new(Opcode::ADD, 11, 0, 2, false, true),
+            Instruction::new(Opcode::ADD, 12, 0, 0x3000, false, true),
+        ];
+        // The value does not fit in one byte, so nothing is written.
+        instructions.extend(config_get(1));
+        instructions.push(Instruction::new(Opcode::ADD, 20, 5, 0, false, true));
+        instructions.extend(config_get(2));
+        instructions.push(Instruction::new(Opcode::LW, 21, 12, 0, false, true));
+        // The key "i" is absent.
+        instructions.push(Instruction::new(Opcode::ADD, 11, 0, 1, false, true));
+        instructions.extend(config_get(4));
+
+        let context = SP1Context::builder().config("id", "10").build();
+        let mut runtime = Executor::with_context(
+            Program::new(instructions, 0, 0),
+            SP1CoreOpts::default(),
+            context,
+        );
+        runtime.run().unwrap();
+
+        assert_eq!(runtime.register(Register::X20), 2);
+        assert_eq!(runtime.register(Register::X21), u32::from_le_bytes(*b"10\0\0"));
+        assert_eq!(runtime.register(Register::X5), 0);
+    }
+
+    /// A custom syscall that returns its first argument.
+    struct EchoSyscall;
+
+    impl Syscall for EchoSyscall {
+        fn execute(&self, _: &mut SyscallContext, arg1: u32, _: u32) -> Option<u32> {
+            Some(arg1)
+        }
+    }
+
+    #[test]
+    fn test_custom_syscall() {
+        const ECHO: u32 = 0x00_00_00_E0;
+        let echo = |value| {
+            [
+                Instruction::new(Opcode::ADD, 10, 0, value, false, true),
+                Instruction::new(Opcode::ADD, 5, 0, ECHO, false, true),
+                Instruction::new(Opcode::ECALL, 5, 10, 11, false, false),
+            ]
+        };
+        let mut instructions = echo(42).to_vec();
+        instructions.push(Instruction::new(Opcode::ADD, 29, 5, 0, false, true));
+        // Overriding a built-in syscall replaces its handler.
+        instructions.extend([
+            Instruction::new(Opcode::ADD, 10, 0, 7, false, true),
+            Instruction::new(Opcode::ADD, 5, 0, SyscallCode::HINT_LEN as u32, false, true),
+            Instruction::new(Opcode::ECALL, 5, 10, 11, false, false),
+        ]);
+
+        let context = SP1Context::builder()
+            .syscall(ECHO, EchoSyscall)
+            .syscall(SyscallCode::HINT_LEN as u32, EchoSyscall)
+            .build();
+        let program = Program::new(instructions, 0, 0);
+        let mut runtime = Executor::with_context(program.clone(), SP1CoreOpts::default(), context);
+        runtime.run().unwrap();
+        assert_eq!(runtime.register(Register::X29), 42);
+        assert_eq!(runtime.register(Register::X5), 7);
+
+        // Without the registration, the custom syscall is unsupported.
+        let mut runtime = Executor::new(program, SP1CoreOpts::default());
+        assert!(matches!(runtime.run(), Err(ExecutionError::UnsupportedSyscall(ECHO))));
+    }
+
+    struct SlowEchoSyscall;
+
+    impl Syscall for SlowEchoSyscall {
+        fn execute(&self, ctx: &mut SyscallContext, arg1: u32, arg2: u32) -> Option<u32> {
+            EchoSyscall.execute(ctx, arg1, arg2)
+        }
+
+        fn num_extra_cycles(&self, _: u32) -> u32 {
+            10
+        }
+    }
+
+    #[test]
+    fn test_custom_syscall_cycles() {
+        // The code encodes 2 extra cycles, which `EchoSyscall` charges and `SlowEchoSyscall`
+        // overrides.
+        const ECHO: u32 = 0x00_02_00_E0;
+        let instructions = vec![
+            Instruction::new(Opcode::ADD, 10, 0, 42, false, true),
+            Instruction::new(Opcode::ADD, 5, 0, ECHO, false, true),
+            Instruction::new(Opcode::ECALL, 5, 10, 11, false, false),
+        ];
+        let clk = |context: SP1Context| {
+            let program = Program::new(instructions.clone(), 0, 0);
+            let mut runtime = Executor::with_context(program, SP1CoreOpts::default(), context);
+            runtime.run().unwrap();
+            assert_eq!(runtime.register(Register::X5), 42);
+            runtime.state.clk
+        };
+
+        let slow = clk(SP1Context::builder().syscall(ECHO, SlowEchoSyscall).build());
+        let echo = clk(SP1Context::builder().syscall(ECHO, EchoSyscall).build());
+        assert_eq!(slow - echo, 10 - 2);
+    }
+
+    #[test]
+    fn test_input_read_at() {
+        let read_at = |offset, ptr, len| {
+            [
+                Instruction::new(Opcode::ADD, 5, 0, SyscallCode::INPUT_READ_AT as u32, false, true),
+                Instruction::new(Opcode::ADD, 10, 0, offset, false, true),
+                Instruction::new(Opcode::ADD, 11, 0, ptr, false, true),
+                Instruction::new(Opcode::ADD, 12, 0, len, false, true),
+                Instruction::new(Opcode::ECALL, 5, 10, 11, false, false),
+            ]
+        };
+        let mut instructions = Vec::new();
+        // Two overlapping ranges, read into separate fresh buffers.
+        instructions.extend(read_at(2, 0x3000, 8));
+        instructions.push(Instruction::new(Opcode::ADD, 20, 5, 0, false, true));
+        instructions.extend(read_at(6, 0x3100, 4));
+        instructions.push(Instruction::new(Opcode::ADD, 21, 5, 0, false, true));
+        // A range past the end of the blob is clamped.
+        instructions.extend(read_at(12, 0x3200, 8));
+        instructions.push(Instruction::new(Opcode::ADD, 22, 5, 0, false, true));
+        instructions.extend(read_at(20, 0x3300, 4));
+        instructions.push(Instruction::new(Opcode::ADD, 23, 5, 0, false, true));
+        // Load the buffers, since the hinted bytes are only in memory once the program reads them.
+        for (reg, addr) in [(24, 0x3000), (25, 0x3004), (26, 0x3100), (27, 0x3200), (28, 0x3204)] {
+            instructions.push(Instruction::new(Opcode::LW, reg, 0, addr, false, true));
+        }
+
+        let blob = (0..16).collect::<Vec<u8>>();
+        let context = SP1Context::builder().input_blob(blob).build();
+        let mut runtime = Executor::with_context(
+            Program::new(instructions, 0, 0),
+            SP1CoreOpts::default(),
+            context,
+        );
+        runtime.write_stdin_slice(&[1, 2, 3]);
+        runtime.run().unwrap();
+
+        assert_eq!(runtime.register(Register::X20), 8);
+        assert_eq!(runtime.register(Register::X21), 4);
+        assert_eq!(runtime.register(Register::X22), 4);
+        assert_eq!(runtime.register(Register::X23), 0);
+        let words = [Register::X24, Register::X25, Register::X26, Register::X27, Register::X28]
+            .map(|reg| runtime.register(reg).to_le_bytes());
+        assert_eq!(words, [[2, 3, 4, 5], [6, 7, 8, 9], [6, 7, 8, 9], [12, 13, 14, 15], [0; 4]]);
+        // The sequential input stream is untouched.
+        assert_eq!(runtime.state.input_stream_ptr, 0);
+    }
+
+    #[test]
+    fn test_read_stream() {
+        let read_stream = |stream_id, ptr, len| {
+            [
+                Instruction::new(Opcode::ADD, 5, 0, SyscallCode::READ_STREAM as u32, false, true),
+                Instruction::new(Opcode::ADD, 10, 0, stream_id, false, true),
+                Instruction::new(Opcode::ADD, 11, 0, ptr, false, true),
+                Instruction::new(Opcode::ADD, 12, 0, len, false, true),
+                Instruction::new(Opcode::ECALL, 5, 10, 11, false, false),
+            ]
+        };
+        // Read the second stream first, then alternate between the two.
+        let reads =
+            [(7, 0x3000, 4), (1, 0x3100, 4), (7, 0x3200, 4), (1, 0x3300, 8), (3, 0x3400, 4)];
+        let mut instructions = Vec::new();
+        for (i, (stream_id, ptr, len)) in reads.into_iter().enumerate() {
+            instructions.extend(read_stream(stream_id, ptr, len));
+            instructions.push(Instruction::new(Opcode::ADD, 20 + i as u32, 5, 0, false, true));
+        }
+        // Load the buffers, since the hinted bytes are only in memory once the program reads them.
+        for (reg, addr) in [(25, 0x3000), (26, 0x3100), (27, 0x3200), (28, 0x3300), (29, 0x3304)] {
+            instructions.push(Instruction::new(Opcode::LW, reg, 0, addr, false, true));
+        }
+
+        let context = SP1Context::builder()
+            .input_stream(1, [1, 2, 3, 4])
+            .input_stream(1, [5, 6, 7, 8])
+            .input_stream(7, (100..108).collect::<Vec<u8>>())
+            .build();
+        let mut runtime = Executor::with_context(
+            Program::new(instructions, 0, 0),
+            SP1CoreOpts::default(),
+            context,
+        );
+        runtime.run().unwrap();
+
+        let lens = [Register::X20, Register::X21, Register::X22, Register::X23, Register::X24]
+            .map(|reg| runtime.register(reg));
+        // The second read of stream 1 is clamped to its end, and stream 3 does not exist.
+        assert_eq!(lens, [4, 4, 4, 4, 0]);
+        let words = [Register::X25, Register::X26, Register::X27, Register::X28, Register::X29]
+            .map(|reg| runtime.register(reg).to_le_bytes());
+        assert_eq!(
+            words,
+            [[100, 101, 102, 103], [1, 2, 3, 4], [104, 105, 106, 107], [5, 6, 7, 8], [0; 4]]
+        );
+    }
+
+    #[test]
+    fn test_is_proving() {
+        use crate::PROVING_CONFIG_KEY;
+
+        let mut key = PROVING_CONFIG_KEY.as_bytes().to_vec();
+        let key_len = key.len() as u32;
+        key.resize(key_len.next_multiple_of(4) as usize, 0);
+
+        // Store the key at 0x2000 and read its value into a fresh buffer at 0x3000.
+        let mut instructions = vec![Instruction::new(Opcode::ADD, 10, 0, 0x2000, false, true)];
+        for (i, word) in key.chunks(4).enumerate() {
+            let word = u32::from_le_bytes(word.try_into().unwrap());
+            instructions.push(Instruction::new(Opcode::ADD, 29, 0, word, false, true));
+            instructions.push(Instruction::new(Opcode::SW, 29, 10, 4 * i as u32, false, true));
+        }
+        instructions.extend([
+            Instruction::new(Opcode::ADD, 11, 0, key_len, false, true),
+            Instruction::new(Opcode::ADD, 12, 0, 0x3000, false, true),
+            Instruction::new(Opcode::ADD, 13, 0, 4, false, true),
+            Instruction::new(Opcode::ADD, 5, 0, SyscallCode::CONFIG_GET as u32, false, true),
+            Instruction::new(Opcode::ECALL, 5, 10, 11, false, false),
+            Instruction::new(Opcode::ADD, 20, 5, 0, false, true),
+            Instruction::new(Opcode::LW, 21, 12, 0, false, true),
+        ]);
+
+        let run = |fast: bool| {
+            // A config entry under the reserved key does not change the answer.
+            let context = SP1Context::builder().config(PROVING_CONFIG_KEY, [1u8]).build();
+            let mut runtime = Executor::with_context(
+                Program::new(instructions.clone(), 0, 0),
+                SP1CoreOpts::default(),
+                context,
+            );
+            if fast {
+                runtime.run_fast().unwrap();
+            } else {
+                runtime.run().unwrap();
+            }
+            assert_eq!(runtime.register(Register::X20), 1);
+            runtime.register(Register::X21)
+        };
+
+        assert_eq!(run(true), 0);
+        assert_eq!(run(false), 1);
+    }
+
+    #[test]
+    #[ignore = "needs the ELF of tests/time, built with `cargo prove build`"]
+    fn test_commit_time() {
+        let program = Program::from(
+            &std::fs::read(concat!(
+                env!("CARGO_MANIFEST_DIR"),
+                "/../../../tests/time/elf/riscv32im-succinct-zkvm-elf"
+            ))
+            .unwrap(),
+        )
+        .unwrap();
+
+        // The guest commits the timestamp with `commit_time`, then commits it again with `commit`.
+        let time = 1_700_000_000_123u64;
+        let context = SP1Context::builder().time(time).build();
+        let mut runtime = Executor::with_context(program.clone(), SP1CoreOpts::default(), context);
+        runtime.run().unwrap();
+        assert_eq!(runtime.state.public_values_stream, [time.to_le_bytes(); 2].concat());
+
+        // Without a timestamp from the host, `sys_commit_time` panics.
+        let mut runtime = Executor::new(program, SP1CoreOpts::default());
+        assert!(matches!(runtime.run(), Err(ExecutionError::HaltWithNonZeroExitCode(1))));
+    }
+
+    #[test]
+    fn test_sha256_abc() {
+        // The padded single-block message "abc", followed by the initial hash value.
+        let block: [(u32, u32); 2] = [(0, 0x6162_6380), (15, 0x18)];
+        let initial_state = [
+            0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab,
+            0x5be0cd19,
+        ];
+        let (w_ptr, state_ptr) = (0x1000, 0x1200);
+
+        let mut instructions = vec![
+            Instruction::new(Opcode::ADD, 30, 0, w_ptr, false, true),
+            Instruction::new(Opcode::ADD, 31, 0, state_ptr, false, true),
+        ];
+        for (i, word) in block {
+            instructions.push(Instruction::new(Opcode::ADD, 29, 0, word, false, true));
+            instructions.push(Instruction::new(Opcode::SW, 29, 30, 4 * i, false, true));
+        }
+        for (i, word) in initial_state.into_iter().enumerate() {
+            instructions.push(Instruction::new(Opcode::ADD, 29, 0, word, false, true));
+            instructions.push(Instruction::new(Opcode::SW, 29, 31, 4 * i as u32, false, true));
+        }
+        instructions.extend([
+            Instruction::new(Opcode::ADD, 5, 0, SyscallCode::SHA_EXTEND as u32, false, true),
+            Instruction::new(Opcode::ADD, 10, 0, w_ptr, false, true),
+            Instruction::new(Opcode::ADD, 11, 0, 0, false, true),
+            Instruction::new(Opcode::ECALL, 5, 10, 11, false, false),
+            Instruction::new(Opcode::ADD, 5, 0, SyscallCode::SHA_COMPRESS as u32, false, true),
+            Instruction::new(Opcode::ADD, 11, 0, state_ptr, false, true),
+            Instruction::new(Opcode::ECALL, 5, 10, 11, false, false),
+        ]);
+
+        let mut runtime = Executor::new(Program::new(instructions, 0, 0), SP1CoreOpts::default());
+        runtime.run().unwrap();
+
+        let digest = (0..8).map(|i| runtime.word(state_ptr + 4 * i)).collect::<Vec<_>>();
+        assert_eq!(
+            digest,
+            [
+                0xba7816bf, 0x8f01cfea, 0x414140de, 0x5dae2223, 0xb00361a3, 0x96177a9c, 0xb410ff61,
+                0xf20015ad,
+            ]
+        );
+    }
 }
@@ -28,6 +28,7 @@ mod instruction;
 mod io;
 mod memory;
 mod opcode;
+mod profile;
 mod program;
 #[cfg(any(test, feature = "programs"))]
 pub mod programs;
@@ -44,6 +45,7 @@ pub use executor::*;
 pub use hook::*;
 pub use instruction::*;
 pub use opcode::*;
+pub use profile::*;
 pub use program::*;
 pub use record::*;
 pub use register::*;
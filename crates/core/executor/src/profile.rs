@@ -0,0 +1,76 @@
+use hashbrown::HashMap;
+use serde::{Deserialize, Serialize};
+
+/// Whether a [`ProfileEvent`] opens or closes a span.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ProfileEventKind {
+    /// The span was opened with `sys_profile_begin`.
+    Begin,
+    /// The span was closed with `sys_profile_end`.
+    End,
+}
+
+/// A profiling event emitted by the guest through `sys_profile_begin` or `sys_profile_end`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProfileEvent {
+    /// The label of the span.
+    pub label: String,
+    /// The global clock at which the event was recorded.
+    pub clk: u64,
+    /// Whether the event opens or closes the span.
+    pub kind: ProfileEventKind,
+}
+
+/// Folds a timeline of profiling events into the collapsed stack format consumed by flamegraph
+/// tools, i.e. `outer;inner` mapped to the number of cycles spent exclusively in that stack.
+///
+/// Events closing a span that is not on top of the stack are ignored, as are spans that are never
+/// closed.
+#[must_use]
+pub fn folded_stacks(events: &[ProfileEvent]) -> HashMap<String, u64> {
+    let mut folded = HashMap::<String, u64>::new();
+    let mut stack: Vec<(&str, u64)> = Vec::new();
+    let mut last_clk = 0;
+
+    for event in events {
+        // Attribute the cycles since the last event to the stack that was active during them.
+        if !stack.is_empty() {
+            let path = stack.iter().map(|(label, _)| *label).collect::<Vec<_>>().join(";");
+            *folded.entry(path).or_default() += event.clk - last_clk;
+        }
+        last_clk = event.clk;
+
+        match event.kind {
+            ProfileEventKind::Begin => stack.push((&event.label, event.clk)),
+            ProfileEventKind::End => {
+                if stack.last().is_some_and(|(label, _)| *label == event.label) {
+                    stack.pop();
+                }
+            }
+        }
+    }
+
+    folded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{folded_stacks, ProfileEvent, ProfileEventKind};
+
+    fn event(label: &str, clk: u64, kind: ProfileEventKind) -> ProfileEvent {
+        ProfileEvent { label: label.to_string(), clk, kind }
+    }
+
+    #[test]
+    fn nested_spans() {
+        let events = vec![
+            event("main", 0, ProfileEventKind::Begin),
+            event("hash", 10, ProfileEventKind::Begin),
+            event("hash", 40, ProfileEventKind::End),
+            event("main", 50, ProfileEventKind::End),
+        ];
+        let folded = folded_stacks(&events);
+        assert_eq!(folded["main"], 20);
+        assert_eq!(folded["main;hash"], 30);
+    }
+}
@@ -8,11 +8,11 @@ use serde::{Deserialize, Serialize};
 
 use super::{program::Program, Opcode};
 use crate::events::{
-    add_sharded_byte_lookup_events, AluEvent, ByteLookupEvent, ByteRecord, CpuEvent,
-    EdDecompressEvent, EllipticCurveAddEvent, EllipticCurveDecompressEvent,
+    add_sharded_byte_lookup_events, AluEvent, Blake3CompressEvent, ByteLookupEvent, ByteRecord,
+    CpuEvent, EdDecompressEvent, EllipticCurveAddEvent, EllipticCurveDecompressEvent,
     EllipticCurveDoubleEvent, Fp2AddSubEvent, Fp2MulEvent, FpOpEvent, KeccakPermuteEvent, LookupId,
-    MemoryInitializeFinalizeEvent, MemoryRecordEnum, ShaCompressEvent, ShaExtendEvent,
-    Uint256MulEvent,
+    MemoryInitializeFinalizeEvent, MemoryRecordEnum, Poseidon2PermuteEvent, ShaCompressEvent,
+    ShaExtendEvent, Uint256MulEvent,
 };
 
 /// A record of the execution of a program.
@@ -68,6 +68,10 @@ pub struct ExecutionRecord {
     pub bls12381_double_events: Vec<EllipticCurveDoubleEvent>,
     /// A trace of the uint256 mul events.
     pub uint256_mul_events: Vec<Uint256MulEvent>,
+    /// A trace of the poseidon2 permute events.
+    pub poseidon2_permute_events: Vec<Poseidon2PermuteEvent>,
+    /// A trace of the blake3 compress events.
+    pub blake3_compress_events: Vec<Blake3CompressEvent>,
     /// A trace of the memory initialize events.
     pub memory_initialize_events: Vec<MemoryInitializeFinalizeEvent>,
     /// A trace of the memory finalize events.
@@ -164,6 +168,8 @@ impl ExecutionRecord {
             ed_decompress_events: std::mem::take(&mut self.ed_decompress_events),
             k256_decompress_events: std::mem::take(&mut self.k256_decompress_events),
             uint256_mul_events: std::mem::take(&mut self.uint256_mul_events),
+            poseidon2_permute_events: std::mem::take(&mut self.poseidon2_permute_events),
+            blake3_compress_events: std::mem::take(&mut self.blake3_compress_events),
             bls12381_fp_events: std::mem::take(&mut self.bls12381_fp_events),
             bls12381_fp2_addsub_events: std::mem::take(&mut self.bls12381_fp2_addsub_events),
             bls12381_fp2_mul_events: std::mem::take(&mut self.bls12381_fp2_mul_events),
@@ -219,6 +225,8 @@ impl ExecutionRecord {
         split_events!(self, ed_decompress_events, shards, opts.deferred, last);
         split_events!(self, k256_decompress_events, shards, opts.deferred, last);
         split_events!(self, uint256_mul_events, shards, opts.deferred, last);
+        split_events!(self, poseidon2_permute_events, shards, opts.deferred, last);
+        split_events!(self, blake3_compress_events, shards, opts.deferred, last);
         split_events!(self, bls12381_decompress_events, shards, opts.deferred, last);
         split_events!(self, bls12381_fp_events, shards, opts.deferred, last);
         split_events!(self, bls12381_fp2_addsub_events, shards, opts.deferred, last);
@@ -315,6 +323,8 @@ impl MachineRecord for ExecutionRecord {
         stats.insert("bls12381_add_events".to_string(), self.bls12381_add_events.len());
         stats.insert("bls12381_double_events".to_string(), self.bls12381_double_events.len());
         stats.insert("uint256_mul_events".to_string(), self.uint256_mul_events.len());
+        stats.insert("poseidon2_permute_events".to_string(), self.poseidon2_permute_events.len());
+        stats.insert("blake3_compress_events".to_string(), self.blake3_compress_events.len());
         stats.insert("bls12381_fp_event".to_string(), self.bls12381_fp_events.len());
         stats.insert(
             "bls12381_fp2_addsub_events".to_string(),
@@ -365,6 +375,8 @@ impl MachineRecord for ExecutionRecord {
         self.bls12381_add_events.append(&mut other.bls12381_add_events);
         self.bls12381_double_events.append(&mut other.bls12381_double_events);
         self.uint256_mul_events.append(&mut other.uint256_mul_events);
+        self.poseidon2_permute_events.append(&mut other.poseidon2_permute_events);
+        self.blake3_compress_events.append(&mut other.blake3_compress_events);
         self.bls12381_fp_events.append(&mut other.bls12381_fp_events);
         self.bls12381_fp2_addsub_events.append(&mut other.bls12381_fp2_addsub_events);
         self.bls12381_fp2_mul_events.append(&mut other.bls12381_fp2_mul_events);
@@ -55,6 +55,20 @@ pub struct ExecutionState {
     /// A ptr to the current position in the input stream incremented by `HINT_READ` opcode.
     pub input_stream_ptr: usize,
 
+    /// The key/value config provided by the host, read by the `CONFIG_GET` syscall.
+    pub config: HashMap<String, Vec<u8>>,
+
+    /// The read-only input blob provided by the host, read at arbitrary offsets by the
+    /// `INPUT_READ_AT` syscall.
+    pub input_blob: Vec<u8>,
+
+    /// The named input streams provided by the host, keyed by stream id and read sequentially by
+    /// the `READ_STREAM` syscall.
+    pub input_streams: HashMap<u32, Vec<u8>>,
+
+    /// The position in each of the named input streams, incremented by the `READ_STREAM` syscall.
+    pub input_stream_ptrs: HashMap<u32, usize>,
+
     /// A stream of proofs inputted to the program.
     pub proof_stream: Vec<(ShardProof<BabyBearPoseidon2>, StarkVerifyingKey<BabyBearPoseidon2>)>,
 
@@ -70,6 +84,9 @@ pub struct ExecutionState {
 
     /// Keeps track of how many times a certain syscall has been called.
     pub syscall_counts: HashMap<SyscallCode, u64>,
+
+    /// Whether the program terminated the execution with `EXIT_GROUP`.
+    pub terminated: bool,
 }
 
 impl ExecutionState {
@@ -87,11 +104,16 @@ impl ExecutionState {
             uninitialized_memory: HashMap::default(),
             input_stream: Vec::new(),
             input_stream_ptr: 0,
+            config: HashMap::new(),
+            input_blob: Vec::new(),
+            input_streams: HashMap::new(),
+            input_stream_ptrs: HashMap::new(),
             public_values_stream: Vec::new(),
             public_values_stream_ptr: 0,
             proof_stream: Vec::new(),
             proof_stream_ptr: 0,
             syscall_counts: HashMap::new(),
+            terminated: false,
         }
     }
 }
@@ -17,7 +17,8 @@ use strum_macros::EnumIter;
 ///   table to determine whether to lookup the syscall using the syscall interaction.
 /// - Byte 2: The number of additional cycles the syscall uses. This is used to make sure the # of
 ///   memory accesses is bounded.
-/// - Byte 3: Currently unused.
+/// - Byte 3: Distinguishes syscalls that share the identifier of another syscall, and are proven
+///   by the same constraints. The CPU table only reads bytes 0 to 2.
 #[derive(
     Debug, Copy, Clone, PartialEq, Eq, Hash, EnumIter, Ord, PartialOrd, Serialize, Deserialize, Enum,
 )]
@@ -27,6 +28,14 @@ pub enum SyscallCode {
     /// Halts the program.
     HALT = 0x00_00_00_00,
 
+    /// Halts the program and marks the execution as terminated, so that no further shard runs.
+    ///
+    /// This is an alias of `HALT` for the prover: it has the same identifier, table and cycles
+    /// bytes, so the CPU table reads the same syscall id and constrains it exactly like `HALT`.
+    /// Only byte 3, which the CPU table does not read, tells the two apart, and only the executor
+    /// acts on it.
+    EXIT_GROUP = 0x01_00_00_00,
+
     /// Write to the output buffer.
     WRITE = 0x00_00_00_02,
 
@@ -84,6 +93,15 @@ pub enum SyscallCode {
     /// Executes the `HINT_READ` precompile.
     HINT_READ = 0x00_00_00_F1,
 
+    /// Looks up a value in the config provided by the host.
+    CONFIG_GET = 0x00_00_00_F2,
+
+    /// Copies a range of the input blob provided by the host, at an arbitrary offset.
+    INPUT_READ_AT = 0x00_00_00_F3,
+
+    /// Reads the next bytes of one of the named input streams provided by the host.
+    READ_STREAM = 0x00_00_00_F4,
+
     /// Executes the `UINT256_MUL` precompile.
     UINT256_MUL = 0x00_01_01_1D,
 
@@ -128,49 +146,89 @@ pub enum SyscallCode {
 
     /// Executes the `BN254_FP2_MUL` precompile.
     BN254_FP2_MUL = 0x00_01_01_2B,
+
+    /// Executes the `POSEIDON2_PERMUTE` precompile.
+    POSEIDON2_PERMUTE = 0x00_01_01_2C,
+
+    /// Executes the `BLAKE3_COMPRESS` precompile.
+    BLAKE3_COMPRESS = 0x00_01_01_2D,
+
+    /// Marks the beginning of a named profiling span.
+    PROFILE_BEGIN = 0x00_00_00_30,
+
+    /// Marks the end of a named profiling span.
+    PROFILE_END = 0x00_00_00_31,
+
+    /// Returns the index of the current execution shard, which depends on the shard size used to
+    /// prove the program.
+    SHARD_INDEX = 0x00_00_00_33,
+
+    /// Returns the program counter of the `ecall` instruction that invokes it.
+    READ_PC = 0x00_00_00_34,
 }
 
 impl SyscallCode {
     /// Create a [`SyscallCode`] from a u32.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `value` is not a known syscall code.
     #[must_use]
     pub fn from_u32(value: u32) -> Self {
+        Self::try_from_u32(value).unwrap_or_else(|| panic!("invalid syscall number: {value}"))
+    }
+
+    /// Create a [`SyscallCode`] from a u32, or return `None` if `value` is not a known syscall
+    /// code, such as a custom syscall registered in a [`super::SyscallRegistry`].
+    #[must_use]
+    pub fn try_from_u32(value: u32) -> Option<Self> {
         match value {
-            0x00_00_00_00 => SyscallCode::HALT,
-            0x00_00_00_02 => SyscallCode::WRITE,
-            0x00_00_00_03 => SyscallCode::ENTER_UNCONSTRAINED,
-            0x00_00_00_04 => SyscallCode::EXIT_UNCONSTRAINED,
-            0x00_30_01_05 => SyscallCode::SHA_EXTEND,
-            0x00_01_01_06 => SyscallCode::SHA_COMPRESS,
-            0x00_01_01_07 => SyscallCode::ED_ADD,
-            0x00_00_01_08 => SyscallCode::ED_DECOMPRESS,
-            0x00_01_01_09 => SyscallCode::KECCAK_PERMUTE,
-            0x00_01_01_0A => SyscallCode::SECP256K1_ADD,
-            0x00_00_01_0B => SyscallCode::SECP256K1_DOUBLE,
-            0x00_00_01_0C => SyscallCode::SECP256K1_DECOMPRESS,
-            0x00_01_01_0E => SyscallCode::BN254_ADD,
-            0x00_00_01_0F => SyscallCode::BN254_DOUBLE,
-            0x00_01_01_1E => SyscallCode::BLS12381_ADD,
-            0x00_00_01_1F => SyscallCode::BLS12381_DOUBLE,
-            0x00_00_00_10 => SyscallCode::COMMIT,
-            0x00_00_00_1A => SyscallCode::COMMIT_DEFERRED_PROOFS,
-            0x00_00_00_1B => SyscallCode::VERIFY_SP1_PROOF,
-            0x00_00_00_F0 => SyscallCode::HINT_LEN,
-            0x00_00_00_F1 => SyscallCode::HINT_READ,
-            0x00_01_01_1D => SyscallCode::UINT256_MUL,
-            0x00_01_01_20 => SyscallCode::BLS12381_FP_ADD,
-            0x00_01_01_21 => SyscallCode::BLS12381_FP_SUB,
-            0x00_01_01_22 => SyscallCode::BLS12381_FP_MUL,
-            0x00_01_01_23 => SyscallCode::BLS12381_FP2_ADD,
-            0x00_01_01_24 => SyscallCode::BLS12381_FP2_SUB,
-            0x00_01_01_25 => SyscallCode::BLS12381_FP2_MUL,
-            0x00_01_01_26 => SyscallCode::BN254_FP_ADD,
-            0x00_01_01_27 => SyscallCode::BN254_FP_SUB,
-            0x00_01_01_28 => SyscallCode::BN254_FP_MUL,
-            0x00_01_01_29 => SyscallCode::BN254_FP2_ADD,
-            0x00_01_01_2A => SyscallCode::BN254_FP2_SUB,
-            0x00_01_01_2B => SyscallCode::BN254_FP2_MUL,
-            0x00_00_01_1C => SyscallCode::BLS12381_DECOMPRESS,
-            _ => panic!("invalid syscall number: {value}"),
+            0x00_00_00_00 => Some(SyscallCode::HALT),
+            0x01_00_00_00 => Some(SyscallCode::EXIT_GROUP),
+            0x00_00_00_02 => Some(SyscallCode::WRITE),
+            0x00_00_00_03 => Some(SyscallCode::ENTER_UNCONSTRAINED),
+            0x00_00_00_04 => Some(SyscallCode::EXIT_UNCONSTRAINED),
+            0x00_30_01_05 => Some(SyscallCode::SHA_EXTEND),
+            0x00_01_01_06 => Some(SyscallCode::SHA_COMPRESS),
+            0x00_01_01_07 => Some(SyscallCode::ED_ADD),
+            0x00_00_01_08 => Some(SyscallCode::ED_DECOMPRESS),
+            0x00_01_01_09 => Some(SyscallCode::KECCAK_PERMUTE),
+            0x00_01_01_0A => Some(SyscallCode::SECP256K1_ADD),
+            0x00_00_01_0B => Some(SyscallCode::SECP256K1_DOUBLE),
+            0x00_00_01_0C => Some(SyscallCode::SECP256K1_DECOMPRESS),
+            0x00_01_01_0E => Some(SyscallCode::BN254_ADD),
+            0x00_00_01_0F => Some(SyscallCode::BN254_DOUBLE),
+            0x00_01_01_1E => Some(SyscallCode::BLS12381_ADD),
+            0x00_00_01_1F => Some(SyscallCode::BLS12381_DOUBLE),
+            0x00_00_00_10 => Some(SyscallCode::COMMIT),
+            0x00_00_00_1A => Some(SyscallCode::COMMIT_DEFERRED_PROOFS),
+            0x00_00_00_1B => Some(SyscallCode::VERIFY_SP1_PROOF),
+            0x00_00_00_F0 => Some(SyscallCode::HINT_LEN),
+            0x00_00_00_F1 => Some(SyscallCode::HINT_READ),
+            0x00_00_00_F2 => Some(SyscallCode::CONFIG_GET),
+            0x00_00_00_F3 => Some(SyscallCode::INPUT_READ_AT),
+            0x00_00_00_F4 => Some(SyscallCode::READ_STREAM),
+            0x00_01_01_1D => Some(SyscallCode::UINT256_MUL),
+            0x00_01_01_20 => Some(SyscallCode::BLS12381_FP_ADD),
+            0x00_01_01_21 => Some(SyscallCode::BLS12381_FP_SUB),
+            0x00_01_01_22 => Some(SyscallCode::BLS12381_FP_MUL),
+            0x00_01_01_23 => Some(SyscallCode::BLS12381_FP2_ADD),
+            0x00_01_01_24 => Some(SyscallCode::BLS12381_FP2_SUB),
+            0x00_01_01_25 => Some(SyscallCode::BLS12381_FP2_MUL),
+            0x00_01_01_26 => Some(SyscallCode::BN254_FP_ADD),
+            0x00_01_01_27 => Some(SyscallCode::BN254_FP_SUB),
+            0x00_01_01_28 => Some(SyscallCode::BN254_FP_MUL),
+            0x00_01_01_29 => Some(SyscallCode::BN254_FP2_ADD),
+            0x00_01_01_2A => Some(SyscallCode::BN254_FP2_SUB),
+            0x00_01_01_2B => Some(SyscallCode::BN254_FP2_MUL),
+            0x00_01_01_2C => Some(SyscallCode::POSEIDON2_PERMUTE),
+            0x00_01_01_2D => Some(SyscallCode::BLAKE3_COMPRESS),
+            0x00_00_01_1C => Some(SyscallCode::BLS12381_DECOMPRESS),
+            0x00_00_00_30 => Some(SyscallCode::PROFILE_BEGIN),
+            0x00_00_00_31 => Some(SyscallCode::PROFILE_END),
+            0x00_00_00_33 => Some(SyscallCode::SHARD_INDEX),
+            0x00_00_00_34 => Some(SyscallCode::READ_PC),
+            _ => None,
         }
     }
 
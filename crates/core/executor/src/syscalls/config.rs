@@ -0,0 +1,37 @@
+use crate::{Register, PROVING_CONFIG_KEY};
+
+use super::{hint::write_hint, Syscall, SyscallContext};
+
+pub(crate) struct ConfigGetSyscall;
+
+impl Syscall for ConfigGetSyscall {
+    /// Looks up the key of `key_len` bytes at `key_ptr` in the host config and returns the length
+    /// of its value, or 0 if the key is absent.
+    ///
+    /// The value is written to the buffer at `val_ptr` (in `a2`) of `val_cap` bytes (in `a3`) only
+    /// if it fits. Otherwise nothing is written, and the returned length tells the program how
+    /// large a buffer to retry with. Like `HINT_READ`, the buffer must be word aligned and never
+    /// accessed before.
+    ///
+    /// The reserved [`PROVING_CONFIG_KEY`] always has a one-byte value, 1 if the execution is being
+    /// proven and 0 otherwise.
+    fn execute(&self, ctx: &mut SyscallContext, key_ptr: u32, key_len: u32) -> Option<u32> {
+        let proving = [u8::from(ctx.is_proving())];
+        let rt = &mut *ctx.rt;
+        let val_ptr = rt.register(Register::X12);
+        let val_cap = rt.register(Register::X13);
+        let key = (0..key_len).map(|i| rt.byte(key_ptr + i)).collect::<Vec<u8>>();
+        let state = &mut rt.state;
+        let Some(value) = core::str::from_utf8(&key).ok().and_then(|key| match key {
+            PROVING_CONFIG_KEY => Some(&proving[..]),
+            _ => state.config.get(key).map(Vec::as_slice),
+        }) else {
+            return Some(0);
+        };
+        let len = value.len() as u32;
+        if len <= val_cap {
+            write_hint(&mut state.uninitialized_memory, val_ptr, value);
+        }
+        Some(len)
+    }
+}
@@ -1,9 +1,11 @@
 use crate::{
     events::{LookupId, MemoryReadRecord, MemoryWriteRecord},
     record::ExecutionRecord,
-    Executor, Register,
+    ExecutionError, Executor, ExecutorMode, Register,
 };
 
+use super::ExitCode;
+
 /// A runtime for syscalls that is protected so that developers cannot arbitrarily modify the
 /// runtime.
 #[allow(dead_code)]
@@ -37,6 +39,13 @@ impl<'a, 'b> SyscallContext<'a, 'b> {
         }
     }
 
+    /// Returns the value to write back to `t0` for this invocation of `code`, which is `value`
+    /// unless a [`super::SyscallFault`] targets it.
+    #[cfg(any(test, feature = "fault-injection"))]
+    pub(crate) fn inject_fault(&mut self, code: super::SyscallCode, value: u32) -> u32 {
+        self.rt.fault_injector.apply(code, value)
+    }
+
     /// Get a mutable reference to the execution record.
     pub fn record_mut(&mut self) -> &mut ExecutionRecord {
         &mut self.rt.record
@@ -48,6 +57,43 @@ impl<'a, 'b> SyscallContext<'a, 'b> {
         self.rt.state.current_shard
     }
 
+    /// The program counter of the `ecall` instruction that invoked the syscall.
+    ///
+    /// It differs from [`Self::next_pc`], where execution resumes after the syscall.
+    #[must_use]
+    pub fn pc(&self) -> u32 {
+        self.rt.state.pc
+    }
+
+    /// Whether the execution is being proven, as opposed to only executed.
+    ///
+    /// Both the checkpointing and the tracing passes of the prover count as proving. Inside an
+    /// unconstrained block, this reports the mode the block was entered from.
+    #[must_use]
+    pub fn is_proving(&self) -> bool {
+        let mode = if self.rt.unconstrained {
+            self.rt.unconstrained_state.executor_mode
+        } else {
+            self.rt.executor_mode
+        };
+        mode != ExecutorMode::Simple
+    }
+
+    /// The read-only input blob provided by the host, see `SP1ContextBuilder::input_blob`.
+    #[must_use]
+    pub fn input_blob(&self) -> &[u8] {
+        &self.rt.state.input_blob
+    }
+
+    /// The unread bytes of the named input stream `stream_id`, see
+    /// `SP1ContextBuilder::input_stream`. A stream the host did not provide is empty.
+    #[must_use]
+    pub fn input_stream(&self, stream_id: u32) -> &[u8] {
+        let state = &self.rt.state;
+        let stream = state.input_streams.get(&stream_id).map_or(&[][..], Vec::as_slice);
+        &stream[state.input_stream_ptrs.get(&stream_id).copied().unwrap_or_default()..]
+    }
+
     /// Get the current channel.
     #[must_use]
     pub fn current_channel(&self) -> u8 {
@@ -117,12 +163,97 @@ impl<'a, 'b> SyscallContext<'a, 'b> {
     }
 
     /// Set the next program counter.
-    pub fn set_next_pc(&mut self, next_pc: u32) {
+    ///
+    /// The target must be word aligned and inside the program's code region. The only exception
+    /// is `0`, which `HALT` uses to end execution.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ExecutionError::InvalidNextPc`] and leaves the next program counter unchanged if
+    /// the target is not a valid instruction address.
+    pub fn set_next_pc(&mut self, next_pc: u32) -> Result<(), ExecutionError> {
+        let program = &self.rt.program;
+        let in_code = next_pc >= program.pc_base
+            && ((next_pc - program.pc_base) / 4) < program.instructions.len() as u32;
+        if next_pc != 0 && (next_pc % 4 != 0 || !in_code) {
+            return Err(ExecutionError::InvalidNextPc(next_pc));
+        }
         self.next_pc = next_pc;
+        Ok(())
     }
 
     /// Set the exit code.
     pub fn set_exit_code(&mut self, exit_code: u32) {
         self.exit_code = exit_code;
     }
+
+    /// Terminate the execution: once the current instruction completes, the executor runs no
+    /// further cycle, in this shard or any later one.
+    ///
+    /// The flag is part of the execution state, so it also holds for executors recovered from a
+    /// later checkpoint.
+    pub fn terminate(&mut self) {
+        self.rt.state.terminated = true;
+    }
+
+    /// Classify the exit code set by `HALT`.
+    #[must_use]
+    pub fn exit_code_reason(&self) -> ExitCode {
+        ExitCode::from(self.exit_code)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sp1_stark::SP1CoreOpts;
+
+    use crate::{ExecutionError, Executor, Instruction, Opcode, Program};
+
+    use super::{ExitCode, SyscallContext};
+
+    fn executor() -> Executor<'static> {
+        let instructions = vec![Instruction::new(Opcode::ADD, 29, 0, 5, false, true); 4];
+        Executor::new(Program::new(instructions, 0x1000, 0x1000), SP1CoreOpts::default())
+    }
+
+    #[test]
+    fn set_next_pc_aligned() {
+        let mut runtime = executor();
+        let mut ctx = SyscallContext::new(&mut runtime);
+        assert!(ctx.set_next_pc(0x1008).is_ok());
+        assert_eq!(ctx.next_pc, 0x1008);
+        assert!(ctx.set_next_pc(0).is_ok());
+        assert_eq!(ctx.next_pc, 0);
+    }
+
+    #[test]
+    fn set_next_pc_misaligned() {
+        let mut runtime = executor();
+        let mut ctx = SyscallContext::new(&mut runtime);
+        let next_pc = ctx.next_pc;
+        assert!(matches!(ctx.set_next_pc(0x1006), Err(ExecutionError::InvalidNextPc(0x1006))));
+        assert_eq!(ctx.next_pc, next_pc);
+    }
+
+    #[test]
+    fn set_next_pc_out_of_range() {
+        let mut runtime = executor();
+        let mut ctx = SyscallContext::new(&mut runtime);
+        assert!(matches!(ctx.set_next_pc(0xffc), Err(ExecutionError::InvalidNextPc(0xffc))));
+        assert!(matches!(ctx.set_next_pc(0x1010), Err(ExecutionError::InvalidNextPc(0x1010))));
+    }
+
+    #[test]
+    fn exit_code_reason() {
+        let mut runtime = executor();
+        let mut ctx = SyscallContext::new(&mut runtime);
+        assert_eq!(ctx.exit_code_reason(), ExitCode::Success);
+        ctx.set_exit_code(1);
+        assert_eq!(ctx.exit_code_reason(), ExitCode::Panic);
+        ctx.set_exit_code(ExitCode::OUT_OF_MEMORY);
+        assert_eq!(ctx.exit_code_reason(), ExitCode::OutOfMemory);
+        ctx.set_exit_code(42);
+        assert_eq!(ctx.exit_code_reason(), ExitCode::Other(42));
+        assert_eq!(u32::from(ExitCode::AssertionFailed), 0xA55E_0001);
+    }
 }
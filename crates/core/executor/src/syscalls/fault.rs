@@ -0,0 +1,77 @@
+//! Fault injection for negative testing of the prover.
+//!
+//! **The `fault-injection` feature must never be enabled in production.** An executor with faults
+//! configured produces traces that do not follow the program, and only exists so tests can check
+//! that proofs of such traces are rejected by the verifier.
+//!
+//! The executor's own tests always build it, and `sp1-core-machine` enables the feature for its
+//! tests only, through its dev-dependencies.
+
+use hashbrown::HashMap;
+
+use super::SyscallCode;
+
+/// Replaces the value a syscall writes back to `t0` on one of its invocations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SyscallFault {
+    /// The syscall to corrupt.
+    pub code: SyscallCode,
+    /// Which invocation of the syscall to corrupt, counting from zero.
+    pub invocation: u32,
+    /// The value written to `t0` instead of the real one.
+    pub value: u32,
+}
+
+/// Applies the configured [`SyscallFault`]s as the executor invokes syscalls.
+#[derive(Debug, Clone, Default)]
+pub struct SyscallFaultInjector {
+    faults: Vec<SyscallFault>,
+    invocations: HashMap<SyscallCode, u32>,
+}
+
+impl SyscallFaultInjector {
+    /// Adds a fault to inject.
+    pub fn inject(&mut self, fault: SyscallFault) {
+        self.faults.push(fault);
+    }
+
+    /// Records an invocation of `code` and returns the value to write back, which is `value`
+    /// unless a fault targets this invocation.
+    pub(crate) fn apply(&mut self, code: SyscallCode, value: u32) -> u32 {
+        let invocation = self.invocations.entry(code).or_insert(0);
+        let fault = self.faults.iter().find(|f| f.code == code && f.invocation == *invocation);
+        *invocation += 1;
+        fault.map_or(value, |f| f.value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sp1_stark::SP1CoreOpts;
+
+    use super::{SyscallCode, SyscallFault};
+    use crate::{Executor, Instruction, Opcode, Program, Register};
+
+    #[test]
+    fn corrupts_selected_invocation() {
+        let instructions = vec![
+            Instruction::new(Opcode::ADD, 5, 0, SyscallCode::HINT_LEN as u32, false, true),
+            Instruction::new(Opcode::ECALL, 5, 10, 11, false, false),
+            Instruction::new(Opcode::ADD, 29, 5, 0, false, true),
+            Instruction::new(Opcode::ADD, 5, 0, SyscallCode::HINT_LEN as u32, false, true),
+            Instruction::new(Opcode::ECALL, 5, 10, 11, false, false),
+        ];
+        let mut runtime = Executor::new(Program::new(instructions, 0, 0), SP1CoreOpts::default());
+        runtime.write_vecs(&[vec![0; 3]]);
+        runtime.fault_injector.inject(SyscallFault {
+            code: SyscallCode::HINT_LEN,
+            invocation: 1,
+            value: 7,
+        });
+        runtime.run().unwrap();
+
+        // The first invocation is untouched.
+        assert_eq!(runtime.register(Register::X29), 3);
+        assert_eq!(runtime.register(Register::X5), 7);
+    }
+}
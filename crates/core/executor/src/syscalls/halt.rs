@@ -1,11 +1,97 @@
+use sp1_primitives::consts::exit::{
+    EXIT_ABORT, EXIT_ASSERTION_FAILED, EXIT_OOM, EXIT_RAND_EXHAUSTED,
+};
+
 use super::{context::SyscallContext, Syscall};
 
 pub(crate) struct HaltSyscall;
 
 impl Syscall for HaltSyscall {
     fn execute(&self, ctx: &mut SyscallContext, exit_code: u32, _: u32) -> Option<u32> {
-        ctx.set_next_pc(0);
+        ctx.set_next_pc(0).expect("halting is always a valid transition");
         ctx.set_exit_code(exit_code);
         None
     }
 }
+
+/// Halts like [`HaltSyscall`], and also terminates the execution as a whole.
+///
+/// `HALT` ends the current flow of execution by jumping to `pc = 0`, but the execution state is
+/// left as is: an executor that is driven again, for instance from a checkpoint taken before the
+/// halt, keeps fetching instructions. `EXIT_GROUP` sets the termination flag of the execution
+/// state, after which the executor runs no further cycle or shard.
+pub(crate) struct ExitGroupSyscall;
+
+impl Syscall for ExitGroupSyscall {
+    fn execute(&self, ctx: &mut SyscallContext, exit_code: u32, arg2: u32) -> Option<u32> {
+        ctx.terminate();
+        HaltSyscall.execute(ctx, exit_code, arg2)
+    }
+}
+
+/// The reason a program halted, classified from the raw exit code passed to `HALT`.
+///
+/// Codes other than the reserved values below are program-defined and classified as
+/// [`ExitCode::Other`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ExitCode {
+    /// The program ran to completion (`0`).
+    Success,
+    /// The program panicked (`1`), as reported by `sys_panic`.
+    Panic,
+    /// A `zkvm_assert!` failed (`0xA55E_0001`).
+    AssertionFailed,
+    /// The guest allocator ran out of memory (`0xA55E_0002`).
+    OutOfMemory,
+    /// The program aborted without a more specific reason (`0xA55E_0003`).
+    Abort,
+    /// `io::rand_below` rejected too many random values in a row (`0xA55E_0004`).
+    RandExhausted,
+    /// A program-defined exit code.
+    Other(u32),
+}
+
+impl ExitCode {
+    /// The raw exit code of [`ExitCode::AssertionFailed`].
+    pub const ASSERTION_FAILED: u32 = EXIT_ASSERTION_FAILED;
+    /// The raw exit code of [`ExitCode::OutOfMemory`].
+    pub const OUT_OF_MEMORY: u32 = EXIT_OOM;
+    /// The raw exit code of [`ExitCode::Abort`].
+    pub const ABORT: u32 = EXIT_ABORT;
+    /// The raw exit code of [`ExitCode::RandExhausted`].
+    pub const RAND_EXHAUSTED: u32 = EXIT_RAND_EXHAUSTED;
+
+    /// Whether the program ran to completion.
+    #[must_use]
+    pub const fn is_success(self) -> bool {
+        matches!(self, Self::Success)
+    }
+}
+
+impl From<u32> for ExitCode {
+    fn from(code: u32) -> Self {
+        match code {
+            0 => Self::Success,
+            1 => Self::Panic,
+            Self::ASSERTION_FAILED => Self::AssertionFailed,
+            Self::OUT_OF_MEMORY => Self::OutOfMemory,
+            Self::ABORT => Self::Abort,
+            Self::RAND_EXHAUSTED => Self::RandExhausted,
+            code => Self::Other(code),
+        }
+    }
+}
+
+impl From<ExitCode> for u32 {
+    fn from(code: ExitCode) -> Self {
+        match code {
+            ExitCode::Success => 0,
+            ExitCode::Panic => 1,
+            ExitCode::AssertionFailed => ExitCode::ASSERTION_FAILED,
+            ExitCode::OutOfMemory => ExitCode::OUT_OF_MEMORY,
+            ExitCode::Abort => ExitCode::ABORT,
+            ExitCode::RandExhausted => ExitCode::RAND_EXHAUSTED,
+            ExitCode::Other(code) => code,
+        }
+    }
+}
@@ -1,17 +1,20 @@
+use hashbrown::HashMap;
+use nohash_hasher::BuildNoHashHasher;
+
 use super::{Syscall, SyscallContext};
 
+pub use sp1_primitives::consts::HINT_LEN_END_OF_STREAM;
+
 pub(crate) struct HintLenSyscall;
 
 impl Syscall for HintLenSyscall {
     fn execute(&self, ctx: &mut SyscallContext, _arg1: u32, _arg2: u32) -> Option<u32> {
-        if ctx.rt.state.input_stream_ptr >= ctx.rt.state.input_stream.len() {
-            panic!(
-                "failed reading stdin due to insufficient input data: input_stream_ptr={}, input_stream_len={}",
-                ctx.rt.state.input_stream_ptr,
-                ctx.rt.state.input_stream.len()
-            );
-        }
-        Some(ctx.rt.state.input_stream[ctx.rt.state.input_stream_ptr].len() as u32)
+        let state = &ctx.rt.state;
+        let len = state
+            .input_stream
+            .get(state.input_stream_ptr)
+            .map_or(HINT_LEN_END_OF_STREAM, |input| input.len() as u32);
+        Some(len)
     }
 }
 
@@ -26,31 +29,36 @@ impl Syscall for HintReadSyscall {
                 ctx.rt.state.input_stream.len()
             );
         }
-        let vec = &ctx.rt.state.input_stream[ctx.rt.state.input_stream_ptr];
-        ctx.rt.state.input_stream_ptr += 1;
         assert!(!ctx.rt.unconstrained, "hint read should not be used in a unconstrained block");
+        let state = &mut ctx.rt.state;
+        let vec = &state.input_stream[state.input_stream_ptr];
+        state.input_stream_ptr += 1;
         assert_eq!(vec.len() as u32, len, "hint input stream read length mismatch");
-        assert_eq!(ptr % 4, 0, "hint read address not aligned to 4 bytes");
-        // Iterate through the vec in 4-byte chunks
-        for i in (0..len).step_by(4) {
-            // Get each byte in the chunk
-            let b1 = vec[i as usize];
-            // In case the vec is not a multiple of 4, right-pad with 0s. This is fine because we
-            // are assuming the word is uninitialized, so filling it with 0s makes sense.
-            let b2 = vec.get(i as usize + 1).copied().unwrap_or(0);
-            let b3 = vec.get(i as usize + 2).copied().unwrap_or(0);
-            let b4 = vec.get(i as usize + 3).copied().unwrap_or(0);
-            let word = u32::from_le_bytes([b1, b2, b3, b4]);
-
-            // Save the data into runtime state so the runtime will use the desired data instead of
-            // 0 when first reading/writing from this address.
-            ctx.rt
-                .state
-                .uninitialized_memory
-                .entry(ptr + i)
-                .and_modify(|_| panic!("hint read address is initialized already"))
-                .or_insert(word);
-        }
+        write_hint(&mut state.uninitialized_memory, ptr, vec);
         None
     }
 }
+
+/// Writes `bytes` to the uninitialized memory starting at `ptr`, so that the runtime uses them
+/// instead of 0 when first reading/writing from these addresses.
+///
+/// # Panics
+/// Panics if `ptr` is not aligned to 4 bytes or if any of the words was initialized already.
+pub(super) fn write_hint(
+    uninitialized_memory: &mut HashMap<u32, u32, BuildNoHashHasher<u32>>,
+    ptr: u32,
+    bytes: &[u8],
+) {
+    assert_eq!(ptr % 4, 0, "hint read address not aligned to 4 bytes");
+    // Iterate through the bytes in 4-byte chunks. In case their length is not a multiple of 4,
+    // right-pad with 0s. This is fine because we are assuming the word is uninitialized, so
+    // filling it with 0s makes sense.
+    for (i, chunk) in bytes.chunks(4).enumerate() {
+        let mut word = [0; 4];
+        word[..chunk.len()].copy_from_slice(chunk);
+        uninitialized_memory
+            .entry(ptr + 4 * i as u32)
+            .and_modify(|_| panic!("hint read address is initialized already"))
+            .or_insert(u32::from_le_bytes(word));
+    }
+}
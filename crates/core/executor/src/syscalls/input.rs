@@ -0,0 +1,51 @@
+use crate::Register;
+
+use super::{hint::write_hint, Syscall, SyscallContext};
+
+pub(crate) struct InputReadAtSyscall;
+
+impl Syscall for InputReadAtSyscall {
+    /// Copies up to `len` bytes (in `a2`) of the input blob starting at `offset` to `ptr`, and
+    /// returns the number of bytes copied.
+    ///
+    /// A range that extends past the end of the blob is clamped to it, so the returned length is
+    /// smaller than `len`, and 0 if `offset` is past the end. Unlike `HINT_READ`, this does not
+    /// advance the input stream, so the same bytes can be read any number of times. Like
+    /// `HINT_READ`, the buffer must be word aligned and never accessed before.
+    fn execute(&self, ctx: &mut SyscallContext, offset: u32, ptr: u32) -> Option<u32> {
+        let len = ctx.rt.register(Register::X12);
+        let state = &mut ctx.rt.state;
+        let bytes = clamp(&state.input_blob, offset, len);
+        write_hint(&mut state.uninitialized_memory, ptr, bytes);
+        Some(bytes.len() as u32)
+    }
+}
+
+pub(crate) struct ReadStreamSyscall;
+
+impl Syscall for ReadStreamSyscall {
+    /// Copies the next bytes of the named input stream `stream_id`, up to `len` (in `a2`), to `ptr`
+    /// and returns the number of bytes copied.
+    ///
+    /// Every stream has its own position, so reads from different streams can be interleaved in
+    /// any order. Fewer than `len` bytes are copied at the end of a stream, and none from a stream
+    /// the host did not provide. Like `HINT_READ`, the buffer must be word aligned and never
+    /// accessed before.
+    fn execute(&self, ctx: &mut SyscallContext, stream_id: u32, ptr: u32) -> Option<u32> {
+        let len = ctx.rt.register(Register::X12);
+        let state = &mut ctx.rt.state;
+        let stream = state.input_streams.get(&stream_id).map_or(&[][..], Vec::as_slice);
+        let pos = state.input_stream_ptrs.entry(stream_id).or_default();
+        let bytes = clamp(stream, *pos as u32, len);
+        *pos += bytes.len();
+        write_hint(&mut state.uninitialized_memory, ptr, bytes);
+        Some(bytes.len() as u32)
+    }
+}
+
+/// The `len` bytes of `blob` starting at `offset`, truncated at the end of `blob`.
+fn clamp(blob: &[u8], offset: u32, len: u32) -> &[u8] {
+    let start = (offset as usize).min(blob.len());
+    let end = start + (len as usize).min(blob.len() - start);
+    &blob[start..end]
+}
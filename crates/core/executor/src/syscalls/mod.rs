@@ -2,11 +2,19 @@
 
 mod code;
 mod commit;
+mod config;
 mod context;
 mod deferred;
+#[cfg(any(test, feature = "fault-injection"))]
+mod fault;
 mod halt;
 mod hint;
+mod input;
+mod pc;
 mod precompiles;
+mod profile;
+mod registry;
+mod shard;
 mod unconstrained;
 mod verify;
 mod write;
@@ -14,17 +22,26 @@ mod write;
 use std::sync::Arc;
 
 use commit::CommitSyscall;
+use config::ConfigGetSyscall;
 use deferred::CommitDeferredSyscall;
-use halt::HaltSyscall;
+use halt::{ExitGroupSyscall, HaltSyscall};
 use hashbrown::HashMap;
 
 pub use code::*;
 pub use context::*;
+#[cfg(any(test, feature = "fault-injection"))]
+pub use fault::*;
+pub use halt::ExitCode;
+pub use hint::HINT_LEN_END_OF_STREAM;
 use hint::{HintLenSyscall, HintReadSyscall};
+use input::{InputReadAtSyscall, ReadStreamSyscall};
+use pc::ReadPcSyscall;
 use precompiles::{
+    blake3::Blake3CompressSyscall,
     edwards::{add::EdwardsAddAssignSyscall, decompress::EdwardsDecompressSyscall},
     fptower::{Fp2AddSubSyscall, Fp2MulSyscall, FpOpSyscall},
     keccak256::permute::Keccak256PermuteSyscall,
+    poseidon2::Poseidon2PermuteSyscall,
     sha256::{compress::Sha256CompressSyscall, extend::Sha256ExtendSyscall},
     uint256::Uint256MulSyscall,
     weierstrass::{
@@ -32,6 +49,10 @@ use precompiles::{
         double::WeierstrassDoubleAssignSyscall,
     },
 };
+use profile::{ProfileBeginSyscall, ProfileEndSyscall};
+pub(crate) use registry::num_cycles;
+pub use registry::SyscallRegistry;
+use shard::ShardIndexSyscall;
 
 use sp1_curves::{
     edwards::ed25519::{Ed25519, Ed25519Parameters},
@@ -59,21 +80,39 @@ pub trait Syscall: Send + Sync {
     /// denote the addresses of the input data, and write the result to the memory at `arg1`.
     fn execute(&self, ctx: &mut SyscallContext, arg1: u32, arg2: u32) -> Option<u32>;
 
-    /// The number of extra cycles that the syscall takes to execute.
+    /// The number of extra cycles that the syscall registered under `code` takes to execute, on
+    /// top of the cycle of the `ecall` instruction itself.
     ///
-    /// Unless this syscall is complex and requires many cycles, this should be zero.
-    fn num_extra_cycles(&self) -> u32 {
-        0
+    /// Defaults to the cycles encoded in `code`, which the CPU chip also reads to advance the
+    /// clock, see [`cost_of`]. Built-in syscalls keep the default. Only custom syscalls, which are
+    /// not proven, may charge a different number of cycles.
+    fn num_extra_cycles(&self, code: u32) -> u32 {
+        num_cycles(code)
     }
 }
 
+/// The number of extra cycles that the syscall `code` takes to execute, on top of the cycle of the
+/// `ecall` instruction itself.
+///
+/// The cost is encoded in the syscall code (see [`SyscallCode`]), which the CPU chip also reads to
+/// advance the clock, and is what the built-in handler of `code` charges through
+/// [`Syscall::num_extra_cycles`]. Multiplying these costs with a histogram of syscalls gives the
+/// cycles a program spends in them.
+#[must_use]
+pub fn cost_of(code: SyscallCode) -> u32 {
+    code.num_cycles()
+}
+
 /// Creates the default syscall map.
 #[must_use]
+#[allow(clippy::too_many_lines)]
 pub fn default_syscall_map() -> HashMap<SyscallCode, Arc<dyn Syscall>> {
     let mut syscall_map = HashMap::<SyscallCode, Arc<dyn Syscall>>::default();
 
     syscall_map.insert(SyscallCode::HALT, Arc::new(HaltSyscall));
 
+    syscall_map.insert(SyscallCode::EXIT_GROUP, Arc::new(ExitGroupSyscall));
+
     syscall_map.insert(SyscallCode::SHA_EXTEND, Arc::new(Sha256ExtendSyscall));
 
     syscall_map.insert(SyscallCode::SHA_COMPRESS, Arc::new(Sha256CompressSyscall));
@@ -122,6 +161,10 @@ pub fn default_syscall_map() -> HashMap<SyscallCode, Arc<dyn Syscall>> {
 
     syscall_map.insert(SyscallCode::UINT256_MUL, Arc::new(Uint256MulSyscall));
 
+    syscall_map.insert(SyscallCode::POSEIDON2_PERMUTE, Arc::new(Poseidon2PermuteSyscall));
+
+    syscall_map.insert(SyscallCode::BLAKE3_COMPRESS, Arc::new(Blake3CompressSyscall));
+
     syscall_map.insert(
         SyscallCode::BLS12381_FP_ADD,
         Arc::new(FpOpSyscall::<Bls12381BaseField>::new(FieldOperation::Add)),
@@ -194,10 +237,53 @@ pub fn default_syscall_map() -> HashMap<SyscallCode, Arc<dyn Syscall>> {
 
     syscall_map.insert(SyscallCode::HINT_READ, Arc::new(HintReadSyscall));
 
+    syscall_map.insert(SyscallCode::CONFIG_GET, Arc::new(ConfigGetSyscall));
+
+    syscall_map.insert(SyscallCode::INPUT_READ_AT, Arc::new(InputReadAtSyscall));
+
+    syscall_map.insert(SyscallCode::READ_STREAM, Arc::new(ReadStreamSyscall));
+
     syscall_map.insert(
         SyscallCode::BLS12381_DECOMPRESS,
         Arc::new(WeierstrassDecompressSyscall::<Bls12381>::new()),
     );
 
+    syscall_map.insert(SyscallCode::PROFILE_BEGIN, Arc::new(ProfileBeginSyscall));
+
+    syscall_map.insert(SyscallCode::PROFILE_END, Arc::new(ProfileEndSyscall));
+
+    syscall_map.insert(SyscallCode::SHARD_INDEX, Arc::new(ShardIndexSyscall));
+
+    syscall_map.insert(SyscallCode::READ_PC, Arc::new(ReadPcSyscall));
+
     syscall_map
 }
+
+#[cfg(test)]
+mod tests {
+    use strum::IntoEnumIterator;
+
+    use super::{cost_of, default_syscall_map, SyscallCode};
+
+    #[test]
+    fn cost_of_every_syscall() {
+        let syscall_map = default_syscall_map();
+        for code in SyscallCode::iter() {
+            assert!(syscall_map.contains_key(&code), "{code:?} is not in the default syscall map");
+            assert_eq!(SyscallCode::from_u32(code as u32), code);
+            assert_eq!(syscall_map[&code].num_extra_cycles(code as u32), cost_of(code));
+        }
+        assert_eq!(cost_of(SyscallCode::HALT), 0);
+        assert_eq!(cost_of(SyscallCode::SHA_EXTEND), 48);
+        assert_eq!(cost_of(SyscallCode::KECCAK_PERMUTE), 1);
+    }
+
+    #[test]
+    fn exit_group_is_an_alias_of_halt() {
+        let (halt, exit_group) = (SyscallCode::HALT, SyscallCode::EXIT_GROUP);
+        assert_ne!(halt as u32, exit_group as u32);
+        assert_eq!(halt.syscall_id(), exit_group.syscall_id());
+        assert_eq!(halt.should_send(), exit_group.should_send());
+        assert_eq!(halt.num_cycles(), exit_group.num_cycles());
+    }
+}
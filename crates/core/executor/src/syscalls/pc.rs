@@ -0,0 +1,9 @@
+use super::{Syscall, SyscallContext};
+
+pub(crate) struct ReadPcSyscall;
+
+impl Syscall for ReadPcSyscall {
+    fn execute(&self, ctx: &mut SyscallContext, _: u32, _: u32) -> Option<u32> {
+        Some(ctx.pc())
+    }
+}
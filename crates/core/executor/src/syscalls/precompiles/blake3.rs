@@ -0,0 +1,60 @@
+use sp1_primitives::blake3::{blake3_compress, BLAKE3_BLOCK_WORDS, BLAKE3_STATE_WORDS};
+
+use crate::{
+    events::Blake3CompressEvent,
+    syscalls::{Syscall, SyscallContext},
+};
+
+/// Compresses a message block into a BLAKE3 state, overwriting the state with the 16-word output
+/// of the compression function.
+///
+/// The state is laid out as the initial state of the compression function, see
+/// [`blake3_compress`]. Hashing inputs of arbitrary length, which takes one compression per block
+/// and per parent node, is left to the guest.
+pub(crate) struct Blake3CompressSyscall;
+
+impl Syscall for Blake3CompressSyscall {
+    fn execute(&self, rt: &mut SyscallContext, arg1: u32, arg2: u32) -> Option<u32> {
+        let clk = rt.clk;
+
+        let state_ptr = arg1;
+        if state_ptr % 4 != 0 {
+            panic!("state_ptr must be word aligned, got {state_ptr:#x}");
+        }
+        let block_ptr = arg2;
+        if block_ptr % 4 != 0 {
+            panic!("block_ptr must be word aligned, got {block_ptr:#x}");
+        }
+
+        // We can read a slice_unsafe here because we write the output to the state later.
+        let input: [u32; BLAKE3_STATE_WORDS] =
+            rt.slice_unsafe(state_ptr, BLAKE3_STATE_WORDS).try_into().unwrap();
+        let (block_records, block) = rt.mr_slice(block_ptr, BLAKE3_BLOCK_WORDS);
+        let block: [u32; BLAKE3_BLOCK_WORDS] = block.try_into().unwrap();
+
+        let output = blake3_compress(input, block);
+
+        // Increment clk so that the write is not at the same cycle as the read.
+        rt.clk += 1;
+        let state_records = rt.mw_slice(state_ptr, &output);
+
+        let lookup_id = rt.syscall_lookup_id;
+        let shard = rt.current_shard();
+        let channel = rt.current_channel();
+        rt.record_mut().blake3_compress_events.push(Blake3CompressEvent {
+            lookup_id,
+            shard,
+            channel,
+            clk,
+            state_ptr,
+            block_ptr,
+            input,
+            block,
+            output,
+            state_records,
+            block_records,
+        });
+
+        None
+    }
+}
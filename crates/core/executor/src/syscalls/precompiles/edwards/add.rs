@@ -19,10 +19,6 @@ impl<E: EllipticCurve + EdwardsParameters> EdwardsAddAssignSyscall<E> {
 }
 
 impl<E: EllipticCurve + EdwardsParameters> Syscall for EdwardsAddAssignSyscall<E> {
-    fn num_extra_cycles(&self) -> u32 {
-        1
-    }
-
     fn execute(&self, rt: &mut SyscallContext, arg1: u32, arg2: u32) -> Option<u32> {
         let event = create_ec_add_event::<E>(rt, arg1, arg2);
         rt.record_mut().ed_add_events.push(event);
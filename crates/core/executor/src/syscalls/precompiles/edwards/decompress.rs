@@ -75,8 +75,4 @@ impl<E: EdwardsParameters> Syscall for EdwardsDecompressSyscall<E> {
         });
         None
     }
-
-    fn num_extra_cycles(&self) -> u32 {
-        0
-    }
 }
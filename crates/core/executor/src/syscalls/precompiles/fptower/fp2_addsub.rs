@@ -101,8 +101,4 @@ impl<P: FpOpField> Syscall for Fp2AddSubSyscall<P> {
         }
         None
     }
-
-    fn num_extra_cycles(&self) -> u32 {
-        1
-    }
 }
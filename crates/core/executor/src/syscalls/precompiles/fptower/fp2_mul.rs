@@ -93,8 +93,4 @@ impl<P: FpOpField> Syscall for Fp2MulSyscall<P> {
         };
         None
     }
-
-    fn num_extra_cycles(&self) -> u32 {
-        1
-    }
 }
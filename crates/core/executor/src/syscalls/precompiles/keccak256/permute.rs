@@ -13,10 +13,6 @@ pub const STATE_NUM_WORDS: usize = STATE_SIZE * 2;
 pub(crate) struct Keccak256PermuteSyscall;
 
 impl Syscall for Keccak256PermuteSyscall {
-    fn num_extra_cycles(&self) -> u32 {
-        1
-    }
-
     fn execute(&self, rt: &mut SyscallContext, arg1: u32, arg2: u32) -> Option<u32> {
         let start_clk = rt.clk;
         let state_ptr = arg1;
@@ -1,6 +1,8 @@
+pub mod blake3;
 pub mod edwards;
 pub mod fptower;
 pub mod keccak256;
+pub mod poseidon2;
 pub mod sha256;
 pub mod uint256;
 pub mod weierstrass;
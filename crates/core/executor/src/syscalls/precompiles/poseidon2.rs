@@ -0,0 +1,62 @@
+use p3_baby_bear::BabyBear;
+use p3_field::{AbstractField, PrimeField32};
+use p3_symmetric::Permutation;
+use sp1_primitives::poseidon2_init;
+
+use crate::{
+    events::{Poseidon2PermuteEvent, POSEIDON2_WIDTH},
+    syscalls::{Syscall, SyscallContext},
+};
+
+/// Permutes a state of [`POSEIDON2_WIDTH`] `BabyBear` elements in place.
+///
+/// The round constants and linear layers are the ones used by the recursion Poseidon2 chips, so a
+/// guest can compute hashes that the recursion verifier recomputes natively.
+pub(crate) struct Poseidon2PermuteSyscall;
+
+impl Syscall for Poseidon2PermuteSyscall {
+    fn execute(&self, rt: &mut SyscallContext, arg1: u32, arg2: u32) -> Option<u32> {
+        let clk = rt.clk;
+
+        let state_ptr = arg1;
+        if state_ptr % 4 != 0 {
+            panic!("state_ptr must be word aligned, got {state_ptr:#x}");
+        }
+        if arg2 as usize != POSEIDON2_WIDTH {
+            panic!("Expected width to be {POSEIDON2_WIDTH}, got {arg2}");
+        }
+
+        // We can read a slice_unsafe here because we write the permuted state back later.
+        let input: [u32; POSEIDON2_WIDTH] =
+            rt.slice_unsafe(state_ptr, POSEIDON2_WIDTH).try_into().unwrap();
+
+        let state = input.map(|word| {
+            assert!(
+                word < BabyBear::ORDER_U32,
+                "state element {word:#x} is not a canonical field element"
+            );
+            BabyBear::from_canonical_u32(word)
+        });
+        let output = poseidon2_init().permute(state).map(|x| x.as_canonical_u32());
+
+        // Increment clk so that the write is not at the same cycle as the read.
+        rt.clk += 1;
+        let state_records = rt.mw_slice(state_ptr, &output);
+
+        let lookup_id = rt.syscall_lookup_id;
+        let shard = rt.current_shard();
+        let channel = rt.current_channel();
+        rt.record_mut().poseidon2_permute_events.push(Poseidon2PermuteEvent {
+            lookup_id,
+            shard,
+            channel,
+            clk,
+            state_ptr,
+            input,
+            output,
+            state_records,
+        });
+
+        None
+    }
+}
@@ -17,10 +17,6 @@ pub const SHA_COMPRESS_K: [u32; 64] = [
 pub(crate) struct Sha256CompressSyscall;
 
 impl Syscall for Sha256CompressSyscall {
-    fn num_extra_cycles(&self) -> u32 {
-        1
-    }
-
     #[allow(clippy::many_single_char_names)]
     fn execute(&self, rt: &mut SyscallContext, arg1: u32, arg2: u32) -> Option<u32> {
         let w_ptr = arg1;
@@ -6,10 +6,6 @@ use crate::{
 pub(crate) struct Sha256ExtendSyscall;
 
 impl Syscall for Sha256ExtendSyscall {
-    fn num_extra_cycles(&self) -> u32 {
-        48
-    }
-
     fn execute(&self, rt: &mut SyscallContext, arg1: u32, arg2: u32) -> Option<u32> {
         let clk_init = rt.clk;
         let w_ptr = arg1;
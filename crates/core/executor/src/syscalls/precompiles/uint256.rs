@@ -78,8 +78,4 @@ impl Syscall for Uint256MulSyscall {
 
         None
     }
-
-    fn num_extra_cycles(&self) -> u32 {
-        1
-    }
 }
@@ -29,8 +29,4 @@ impl<E: EllipticCurve> Syscall for WeierstrassAddAssignSyscall<E> {
         }
         None
     }
-
-    fn num_extra_cycles(&self) -> u32 {
-        1
-    }
 }
@@ -28,8 +28,4 @@ impl<E: EllipticCurve> Syscall for WeierstrassDecompressSyscall<E> {
         }
         None
     }
-
-    fn num_extra_cycles(&self) -> u32 {
-        0
-    }
 }
@@ -29,8 +29,4 @@ impl<E: EllipticCurve> Syscall for WeierstrassDoubleAssignSyscall<E> {
         }
         None
     }
-
-    fn num_extra_cycles(&self) -> u32 {
-        0
-    }
 }
@@ -0,0 +1,36 @@
+use crate::{ProfileEvent, ProfileEventKind};
+
+use super::{Syscall, SyscallContext};
+
+pub(crate) struct ProfileBeginSyscall;
+
+impl Syscall for ProfileBeginSyscall {
+    fn execute(&self, ctx: &mut SyscallContext, label_ptr: u32, label_len: u32) -> Option<u32> {
+        record_profile_event(ctx, ProfileEventKind::Begin, label_ptr, label_len);
+        None
+    }
+}
+
+pub(crate) struct ProfileEndSyscall;
+
+impl Syscall for ProfileEndSyscall {
+    fn execute(&self, ctx: &mut SyscallContext, label_ptr: u32, label_len: u32) -> Option<u32> {
+        record_profile_event(ctx, ProfileEventKind::End, label_ptr, label_len);
+        None
+    }
+}
+
+/// Records a profiling event into the executor's timeline.
+///
+/// This is a no-op unless profiling was enabled on the host, so that guests can leave their spans
+/// in place without paying for them.
+fn record_profile_event(ctx: &mut SyscallContext, kind: ProfileEventKind, ptr: u32, len: u32) {
+    let rt = &mut *ctx.rt;
+    if !rt.profiling {
+        return;
+    }
+    let bytes = (0..len).map(|i| rt.byte(ptr + i)).collect::<Vec<u8>>();
+    let label = String::from_utf8_lossy(&bytes).into_owned();
+    let clk = rt.state.global_clk;
+    rt.profile_events.push(ProfileEvent { label, clk, kind });
+}
@@ -0,0 +1,126 @@
+use core::fmt::Debug;
+use std::sync::Arc;
+
+use hashbrown::HashMap;
+
+use super::{default_syscall_map, Syscall};
+
+/// A registry of syscall handlers, indexed by the syscall code the guest puts in register t0.
+///
+/// The default registry holds the built-in handlers of every [`super::SyscallCode`]. Registering a
+/// handler overrides the built-in handler of the same code, or adds a syscall under a code that
+/// the executor does not know. Custom codes follow the layout of [`super::SyscallCode`]: byte 2
+/// gives the number of additional cycles the syscall takes.
+///
+/// Custom syscalls only change how the program executes. Proving a program that calls one only
+/// succeeds if its effects are constrained like those of a built-in syscall.
+#[derive(Clone)]
+pub struct SyscallRegistry {
+    /// Table of registered syscalls. Prefer using [`SyscallRegistry::get`] and
+    /// [`SyscallRegistry::register`] over interacting with this field directly.
+    pub(crate) table: HashMap<u32, Arc<dyn Syscall>>,
+}
+
+impl SyscallRegistry {
+    /// Create a default [`SyscallRegistry`].
+    #[must_use]
+    pub fn new() -> Self {
+        SyscallRegistry::default()
+    }
+
+    /// Create an empty [`SyscallRegistry`].
+    #[must_use]
+    pub fn empty() -> Self {
+        Self { table: HashMap::default() }
+    }
+
+    /// Register `syscall` as the handler of `code`, and return the handler it replaces, if any.
+    pub fn register(&mut self, code: u32, syscall: Arc<dyn Syscall>) -> Option<Arc<dyn Syscall>> {
+        self.table.insert(code, syscall)
+    }
+
+    /// Get the handler of `code`, if it exists.
+    #[must_use]
+    pub fn get(&self, code: u32) -> Option<&Arc<dyn Syscall>> {
+        self.table.get(&code)
+    }
+
+    /// The maximum number of additional cycles of any registered syscall.
+    #[must_use]
+    pub fn max_syscall_cycles(&self) -> u32 {
+        self.table.iter().map(|(&code, syscall)| syscall.num_extra_cycles(code)).max().unwrap_or(0)
+    }
+}
+
+impl Default for SyscallRegistry {
+    fn default() -> Self {
+        let table = default_syscall_map().into_iter().map(|(code, syscall)| (code as u32, syscall));
+        Self { table: table.collect() }
+    }
+}
+
+impl Debug for SyscallRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut keys = self.table.keys().collect::<Vec<_>>();
+        keys.sort_unstable();
+        f.debug_struct("SyscallRegistry")
+            .field(
+                "table",
+                &format_args!("{{{} syscalls registered at {:x?}}}", self.table.len(), keys),
+            )
+            .finish()
+    }
+}
+
+/// The number of additional cycles of the syscall `code`, which need not be a known
+/// [`super::SyscallCode`].
+#[must_use]
+pub(crate) fn num_cycles(code: u32) -> u32 {
+    code.to_le_bytes()[2].into()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::SyscallRegistry;
+    use crate::syscalls::{cost_of, Syscall, SyscallCode, SyscallContext};
+
+    struct NopSyscall;
+
+    impl Syscall for NopSyscall {
+        fn execute(&self, _: &mut SyscallContext, _: u32, _: u32) -> Option<u32> {
+            None
+        }
+    }
+
+    struct CostlySyscall;
+
+    impl Syscall for CostlySyscall {
+        fn execute(&self, _: &mut SyscallContext, _: u32, _: u32) -> Option<u32> {
+            None
+        }
+
+        fn num_extra_cycles(&self, _: u32) -> u32 {
+            100
+        }
+    }
+
+    #[test]
+    fn register() {
+        let mut registry = SyscallRegistry::new();
+        assert!(registry.get(SyscallCode::HALT as u32).is_some());
+        assert!(registry.register(SyscallCode::HALT as u32, Arc::new(NopSyscall)).is_some());
+        assert!(registry.register(0x00_02_00_E0, Arc::new(NopSyscall)).is_none());
+        assert_eq!(registry.max_syscall_cycles(), cost_of(SyscallCode::SHA_EXTEND));
+
+        let mut registry = SyscallRegistry::empty();
+        assert!(registry.get(SyscallCode::HALT as u32).is_none());
+        registry.register(0x00_02_00_E0, Arc::new(NopSyscall));
+        assert_eq!(registry.max_syscall_cycles(), 2);
+
+        // A handler that charges more than its code encodes raises the maximum.
+        registry.register(0x00_00_00_E1, Arc::new(CostlySyscall));
+        assert_eq!(registry.max_syscall_cycles(), 100);
+    }
+}
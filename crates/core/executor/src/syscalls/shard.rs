@@ -0,0 +1,9 @@
+use super::{Syscall, SyscallContext};
+
+pub(crate) struct ShardIndexSyscall;
+
+impl Syscall for ShardIndexSyscall {
+    fn execute(&self, ctx: &mut SyscallContext, _: u32, _: u32) -> Option<u32> {
+        Some(ctx.current_shard())
+    }
+}
@@ -82,23 +82,108 @@ impl CpuChip {
             ecall_cols.is_hint_len.result
         };
 
+        // Compute whether this ecall is SHARD_INDEX.
+        let is_shard_index = {
+            IsZeroOperation::<AB::F>::eval(
+                builder,
+                syscall_id - AB::Expr::from_canonical_u32(SyscallCode::SHARD_INDEX.syscall_id()),
+                ecall_cols.is_shard_index,
+                is_ecall_instruction.clone(),
+            );
+            ecall_cols.is_shard_index.result
+        };
+
+        // Compute whether this ecall is CONFIG_GET.
+        let is_config_get = {
+            IsZeroOperation::<AB::F>::eval(
+                builder,
+                syscall_id - AB::Expr::from_canonical_u32(SyscallCode::CONFIG_GET.syscall_id()),
+                ecall_cols.is_config_get,
+                is_ecall_instruction.clone(),
+            );
+            ecall_cols.is_config_get.result
+        };
+
+        // Compute whether this ecall is INPUT_READ_AT.
+        let is_input_read_at = {
+            IsZeroOperation::<AB::F>::eval(
+                builder,
+                syscall_id - AB::Expr::from_canonical_u32(SyscallCode::INPUT_READ_AT.syscall_id()),
+                ecall_cols.is_input_read_at,
+                is_ecall_instruction.clone(),
+            );
+            ecall_cols.is_input_read_at.result
+        };
+
+        // Compute whether this ecall is READ_STREAM.
+        let is_read_stream = {
+            IsZeroOperation::<AB::F>::eval(
+                builder,
+                syscall_id - AB::Expr::from_canonical_u32(SyscallCode::READ_STREAM.syscall_id()),
+                ecall_cols.is_read_stream,
+                is_ecall_instruction.clone(),
+            );
+            ecall_cols.is_read_stream.result
+        };
+
+        // Compute whether this ecall is READ_PC.
+        let is_read_pc = {
+            IsZeroOperation::<AB::F>::eval(
+                builder,
+                syscall_id - AB::Expr::from_canonical_u32(SyscallCode::READ_PC.syscall_id()),
+                ecall_cols.is_read_pc,
+                is_ecall_instruction.clone(),
+            );
+            ecall_cols.is_read_pc.result
+        };
+
         // When syscall_id is ENTER_UNCONSTRAINED, the new value of op_a should be 0.
         let zero_word = Word::<AB::F>::from(0);
         builder
             .when(is_ecall_instruction.clone() * is_enter_unconstrained)
             .assert_word_eq(local.op_a_val(), zero_word);
 
-        // When the syscall is not one of ENTER_UNCONSTRAINED or HINT_LEN, op_a shouldn't change.
+        // When syscall_id is SHARD_INDEX, the new value of op_a should be the shard. The shard is
+        // within 16 bits and the bytes of op_a are range checked, so the low two bytes determine
+        // it uniquely.
+        let op_a_val = local.op_a_val();
+        let mut when_shard_index = builder.when(is_ecall_instruction.clone() * is_shard_index);
+        when_shard_index.assert_eq(
+            op_a_val[0] + op_a_val[1] * AB::Expr::from_canonical_u32(1 << 8),
+            local.shard,
+        );
+        when_shard_index.assert_zero(op_a_val[2]);
+        when_shard_index.assert_zero(op_a_val[3]);
+
+        // When syscall_id is READ_PC, the new value of op_a should be the pc of the ecall. op_a is
+        // range checked to be a canonical BabyBear element, so its reduction determines it.
+        let mut when_read_pc = builder.when(is_ecall_instruction.clone() * is_read_pc);
+        when_read_pc.assert_eq(op_a_val.reduce::<AB>(), local.pc);
+        when_read_pc.assert_word_eq(op_a_val, ecall_cols.operand_to_check);
+
+        // When the syscall is not one of ENTER_UNCONSTRAINED, HINT_LEN, SHARD_INDEX, READ_PC,
+        // CONFIG_GET, INPUT_READ_AT or READ_STREAM, op_a shouldn't change. Like HINT_LEN, the
+        // lengths returned by CONFIG_GET, INPUT_READ_AT and READ_STREAM are provided by the host.
         builder
             .when(is_ecall_instruction.clone())
-            .when_not(is_enter_unconstrained + is_hint_len)
+            .when_not(
+                is_enter_unconstrained
+                    + is_hint_len
+                    + is_shard_index
+                    + is_read_pc
+                    + is_config_get
+                    + is_input_read_at
+                    + is_read_stream,
+            )
             .assert_word_eq(local.op_a_val(), local.op_a_access.prev_value);
 
         // Verify value of ecall_range_check_operand column.
         builder.assert_eq(
             local.ecall_range_check_operand,
             is_ecall_instruction
-                * (ecall_cols.is_halt.result + ecall_cols.is_commit_deferred_proofs.result),
+                * (ecall_cols.is_halt.result
+                    + ecall_cols.is_commit_deferred_proofs.result
+                    + is_read_pc),
         );
 
         // Babybear range check the operand_to_check word.
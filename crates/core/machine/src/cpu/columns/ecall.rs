@@ -15,6 +15,21 @@ pub struct EcallCols<T> {
     /// Whether the current ecall is HINT_LEN.
     pub is_hint_len: IsZeroOperation<T>,
 
+    /// Whether the current ecall is SHARD_INDEX.
+    pub is_shard_index: IsZeroOperation<T>,
+
+    /// Whether the current ecall is CONFIG_GET.
+    pub is_config_get: IsZeroOperation<T>,
+
+    /// Whether the current ecall is INPUT_READ_AT.
+    pub is_input_read_at: IsZeroOperation<T>,
+
+    /// Whether the current ecall is READ_STREAM.
+    pub is_read_stream: IsZeroOperation<T>,
+
+    /// Whether the current ecall is READ_PC.
+    pub is_read_pc: IsZeroOperation<T>,
+
     /// Whether the current ecall is HALT.
     pub is_halt: IsZeroOperation<T>,
 
@@ -31,7 +46,8 @@ pub struct EcallCols<T> {
     /// The nonce of the syscall operation.
     pub syscall_nonce: T,
 
-    /// Columns to babybear range check the halt/commit_deferred_proofs operand.
+    /// Columns to babybear range check the halt/commit_deferred_proofs operand, or the pc returned
+    /// by read_pc.
     pub operand_range_check_cols: BabyBearWordRangeChecker<T>,
 
     /// The operand value to babybear range check.
@@ -654,6 +654,31 @@ impl CpuChip {
                 syscall_id - F::from_canonical_u32(SyscallCode::HINT_LEN.syscall_id()),
             );
 
+            // Populate `is_shard_index`.
+            ecall_cols.is_shard_index.populate_from_field_element(
+                syscall_id - F::from_canonical_u32(SyscallCode::SHARD_INDEX.syscall_id()),
+            );
+
+            // Populate `is_config_get`.
+            ecall_cols.is_config_get.populate_from_field_element(
+                syscall_id - F::from_canonical_u32(SyscallCode::CONFIG_GET.syscall_id()),
+            );
+
+            // Populate `is_input_read_at`.
+            ecall_cols.is_input_read_at.populate_from_field_element(
+                syscall_id - F::from_canonical_u32(SyscallCode::INPUT_READ_AT.syscall_id()),
+            );
+
+            // Populate `is_read_stream`.
+            ecall_cols.is_read_stream.populate_from_field_element(
+                syscall_id - F::from_canonical_u32(SyscallCode::READ_STREAM.syscall_id()),
+            );
+
+            // Populate `is_read_pc`.
+            ecall_cols.is_read_pc.populate_from_field_element(
+                syscall_id - F::from_canonical_u32(SyscallCode::READ_PC.syscall_id()),
+            );
+
             // Populate `is_halt`.
             ecall_cols.is_halt.populate_from_field_element(
                 syscall_id - F::from_canonical_u32(SyscallCode::HALT.syscall_id()),
@@ -701,6 +726,14 @@ impl CpuChip {
                 ecall_cols.operand_range_check_cols.populate(event.c);
                 cols.ecall_range_check_operand = F::one();
             }
+
+            // The pc returned by read_pc is range checked so that it is determined by its
+            // reduction.
+            if syscall_id == F::from_canonical_u32(SyscallCode::READ_PC.syscall_id()) {
+                ecall_cols.operand_to_check = event.a.into();
+                ecall_cols.operand_range_check_cols.populate(event.a);
+                cols.ecall_range_check_operand = F::one();
+            }
         }
 
         is_halt
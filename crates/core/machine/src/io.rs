@@ -1,9 +1,13 @@
 use crate::utils::Buffer;
 use k256::sha2::{Digest, Sha256};
 use num_bigint::BigUint;
+use p3_baby_bear::BabyBear;
+use p3_field::{AbstractField, PrimeField32};
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use sp1_stark::{baby_bear_poseidon2::BabyBearPoseidon2, ShardProof, StarkVerifyingKey};
 
+pub use sp1_primitives::consts::NUM_PUBLIC_VALUES_CHANNELS;
+
 /// Standard input for the prover.
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct SP1Stdin {
@@ -57,6 +61,22 @@ impl SP1Stdin {
         self.buffer.push(slice.to_vec());
     }
 
+    /// Write a value to the buffer split into elements of at most `chunk_len` bytes, so that the
+    /// program can deserialize it incrementally with `sp1_zkvm::io::read_hint_stream`.
+    pub fn write_chunked<T: Serialize>(&mut self, data: &T, chunk_len: usize) {
+        assert!(chunk_len > 0, "chunk length must be positive");
+        let tmp = bincode::serialize(data).expect("serialization failed");
+        self.buffer.extend(tmp.chunks(chunk_len).map(<[u8]>::to_vec));
+    }
+
+    /// Write field elements to the buffer as a single element, for the program to read with
+    /// `sp1_zkvm::io::read_fields`, or with `sp1_zkvm::io::read_field` if there is only one.
+    ///
+    /// Each element is a little-endian 32-bit word holding its canonical value.
+    pub fn write_fields(&mut self, fields: &[BabyBear]) {
+        self.buffer.push(sp1_lib::io::encode_fields(fields));
+    }
+
     pub fn write_vec(&mut self, vec: Vec<u8>) {
         self.buffer.push(vec);
     }
@@ -113,6 +133,64 @@ impl SP1PublicValues {
         self.buffer.write_slice(slice);
     }
 
+    /// Read `len` field elements from the buffer.
+    ///
+    /// This is the inverse of `sp1_zkvm::io::commit_fields`: each element is a little-endian
+    /// 32-bit word holding its canonical value.
+    pub fn read_fields(&mut self, len: usize) -> Vec<BabyBear> {
+        let mut bytes = vec![0u8; 4 * len];
+        self.read_slice(&mut bytes);
+        bytes
+            .chunks_exact(4)
+            .map(|word| {
+                let value = u32::from_le_bytes(word.try_into().unwrap());
+                assert!(value < BabyBear::ORDER_U32, "non-canonical field element {value}");
+                BabyBear::from_canonical_u32(value)
+            })
+            .collect()
+    }
+
+    /// Returns the digests of the extra public values channels, i.e. channels
+    /// `1..NUM_PUBLIC_VALUES_CHANNELS` in order.
+    ///
+    /// These are appended to the end of the public values by programs that commit to any channel
+    /// other than 0, so this is only meaningful for such programs. Returns `None` if the public
+    /// values are too short to contain them.
+    pub fn channel_digests(&self) -> Option<[[u8; 32]; NUM_PUBLIC_VALUES_CHANNELS - 1]> {
+        let data = self.as_slice();
+        let start = data.len().checked_sub(32 * (NUM_PUBLIC_VALUES_CHANNELS - 1))?;
+        Some(core::array::from_fn(|i| {
+            data[start + 32 * i..start + 32 * (i + 1)].try_into().unwrap()
+        }))
+    }
+
+    /// The exit code committed by `sp1_zkvm::io::commit_exit`, the last 4 bytes of the public
+    /// values in little-endian order.
+    ///
+    /// Unlike the exit code the program halts with, it is covered by the public values digest, so
+    /// this is only meaningful for programs that halt with `commit_exit`.
+    pub fn committed_exit_code(&self) -> u32 {
+        let data = self.as_slice();
+        let start =
+            data.len().checked_sub(4).expect("public values are too short to contain an exit code");
+        u32::from_le_bytes(data[start..].try_into().unwrap())
+    }
+
+    /// The SHA-256 digest of the public values, as committed by the program at halt.
+    pub fn digest(&self) -> [u8; 32] {
+        Sha256::digest(self.buffer.data.as_slice()).into()
+    }
+
+    /// The digest of the previous proof in the chain, for programs that call
+    /// `sp1_zkvm::io::fold_commitment` before committing anything else.
+    pub fn folded_commitment(&self) -> [u8; 32] {
+        self.as_slice()
+            .get(..32)
+            .expect("public values are too short to contain a folded commitment")
+            .try_into()
+            .unwrap()
+    }
+
     /// Hash the public values, mask the top 3 bits and return a BigUint. Matches the implementation
     /// of `hashPublicValues` in the Solidity verifier.
     ///
@@ -181,6 +259,37 @@ pub mod proof_serde {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_read_fields() {
+        let fields = [0, 1, 0x1234_5678, BabyBear::ORDER_U32 - 1].map(BabyBear::from_canonical_u32);
+
+        let mut public_values = SP1PublicValues::new();
+        public_values.write_slice(&sp1_zkvm::lib::io::encode_fields(&fields));
+        public_values.write_slice(&[7]);
+
+        assert_eq!(public_values.read_fields(fields.len()), fields);
+        assert_eq!(public_values.read::<u8>(), 7);
+    }
+
+    #[test]
+    fn test_write_fields() {
+        use sp1_zkvm::lib::io::{decode_fields, IoError};
+
+        let fields = [3, 0, BabyBear::ORDER_U32 - 1].map(BabyBear::from_canonical_u32);
+        let mut stdin = SP1Stdin::new();
+        stdin.write_fields(&fields);
+        stdin.write_fields(&[BabyBear::two()]);
+        stdin.write_slice(&BabyBear::ORDER_U32.to_le_bytes());
+
+        assert_eq!(stdin.buffer.len(), 3);
+        assert_eq!(decode_fields(&stdin.buffer[0]), Ok(fields.to_vec()));
+        assert_eq!(decode_fields(&stdin.buffer[1]), Ok(vec![BabyBear::two()]));
+        assert_eq!(
+            decode_fields(&stdin.buffer[2]),
+            Err(IoError::NonCanonicalField(BabyBear::ORDER_U32))
+        );
+    }
+
     #[test]
     fn test_hash_public_values() {
         let test_hex = "1234567890abcdef1234567890abcdef1234567890abcdef1234567890abcdef1234567890abcdef1234567890abcdef1234567890abcdef1234567890abcdef";
@@ -195,4 +304,50 @@ mod tests {
 
         assert_eq!(hash, expected_hash_biguint);
     }
+
+    #[test]
+    fn test_fold_commitment() {
+        let outputs: [&[u8]; 3] = [b"first", b"second", b"third"];
+
+        let mut prev_digest = SP1PublicValues::from(outputs[0]).digest();
+        for output in &outputs[1..] {
+            let mut public_values = SP1PublicValues::new();
+            public_values.write_slice(&prev_digest);
+            public_values.write_slice(output);
+            assert_eq!(public_values.folded_commitment(), prev_digest);
+
+            let mut hasher = Sha256::new();
+            hasher.update(prev_digest);
+            hasher.update(output);
+            let expected: [u8; 32] = hasher.finalize().into();
+            assert_eq!(public_values.digest(), expected);
+            prev_digest = expected;
+        }
+    }
+
+    #[test]
+    fn test_committed_exit_code() {
+        let mut public_values = SP1PublicValues::new();
+        public_values.write_slice(&[0xff; 7]);
+        public_values.write_slice(&0xA55E_0004u32.to_le_bytes());
+        assert_eq!(public_values.committed_exit_code(), 0xA55E_0004);
+    }
+
+    #[test]
+    fn test_channel_digests() {
+        let digests: Vec<[u8; 32]> =
+            (1..NUM_PUBLIC_VALUES_CHANNELS).map(|i| Sha256::digest([i as u8]).into()).collect();
+
+        let mut public_values = SP1PublicValues::new();
+        public_values.write_slice(&[0xff; 7]);
+        for digest in &digests {
+            public_values.write_slice(digest);
+        }
+
+        assert_eq!(public_values.channel_digests().unwrap().to_vec(), digests);
+
+        let mut short = SP1PublicValues::new();
+        short.write_slice(&[0xff; 32 * (NUM_PUBLIC_VALUES_CHANNELS - 1) - 1]);
+        assert_eq!(short.channel_digests(), None);
+    }
 }
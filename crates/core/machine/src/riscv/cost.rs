@@ -89,6 +89,16 @@ impl CostEstimator for ExecutionReport {
         total_area += (uint256_mul_events as u64) * costs[&RiscvAirDiscriminants::Uint256Mul];
         total_chips += 1;
 
+        let poseidon2_permute_events = self.syscall_counts[SyscallCode::POSEIDON2_PERMUTE];
+        total_area +=
+            (poseidon2_permute_events as u64) * costs[&RiscvAirDiscriminants::Poseidon2Permute];
+        total_chips += 1;
+
+        let blake3_compress_events = self.syscall_counts[SyscallCode::BLAKE3_COMPRESS];
+        total_area +=
+            (blake3_compress_events as u64) * costs[&RiscvAirDiscriminants::Blake3Compress];
+        total_chips += 1;
+
         let bls12381_fp_events = self.syscall_counts[SyscallCode::BLS12381_FP_ADD]
             + self.syscall_counts[SyscallCode::BLS12381_FP_SUB]
             + self.syscall_counts[SyscallCode::BLS12381_FP_MUL];
@@ -24,8 +24,10 @@ pub(crate) mod riscv_chips {
         memory::MemoryChip,
         program::ProgramChip,
         syscall::precompiles::{
+            blake3::Blake3CompressChip,
             edwards::{EdAddAssignChip, EdDecompressChip},
             keccak256::KeccakPermuteChip,
+            poseidon2::Poseidon2PermuteChip,
             sha256::{ShaCompressChip, ShaExtendChip},
             uint256::Uint256MulChip,
             weierstrass::{
@@ -102,6 +104,10 @@ pub enum RiscvAir<F: PrimeField32> {
     Bls12381Double(WeierstrassDoubleAssignChip<SwCurve<Bls12381Parameters>>),
     /// A precompile for uint256 mul.
     Uint256Mul(Uint256MulChip),
+    /// A precompile for the Poseidon2 permutation.
+    Poseidon2Permute(Poseidon2PermuteChip),
+    /// A precompile for the BLAKE3 compression function.
+    Blake3Compress(Blake3CompressChip),
     /// A precompile for decompressing a point on the BLS12-381 curve.
     Bls12381Decompress(WeierstrassDecompressChip<SwCurve<Bls12381Parameters>>),
     /// A precompile for BLS12-381 fp operation.
@@ -221,6 +227,14 @@ impl<F: PrimeField32> RiscvAir<F> {
         costs.insert(RiscvAirDiscriminants::Uint256Mul, uint256_mul.cost());
         chips.push(uint256_mul);
 
+        let poseidon2_permute = Chip::new(RiscvAir::Poseidon2Permute(Poseidon2PermuteChip::new()));
+        costs.insert(RiscvAirDiscriminants::Poseidon2Permute, poseidon2_permute.cost());
+        chips.push(poseidon2_permute);
+
+        let blake3_compress = Chip::new(RiscvAir::Blake3Compress(Blake3CompressChip::new()));
+        costs.insert(RiscvAirDiscriminants::Blake3Compress, blake3_compress.cost());
+        chips.push(blake3_compress);
+
         let bls12381_fp = Chip::new(RiscvAir::Bls12381Fp(FpOpChip::<Bls12381BaseField>::new()));
         costs.insert(RiscvAirDiscriminants::Bls12381Fp, bls12381_fp.cost());
         chips.push(bls12381_fp);
@@ -342,6 +356,64 @@ pub mod tests {
         StarkVerifyingKey,
     };
 
+    #[test]
+    fn test_syscall_fault_rejected() {
+        use p3_baby_bear::BabyBear;
+        use sp1_core_executor::{
+            syscalls::{SyscallCode, SyscallFault},
+            ExecutionRecord, Executor,
+        };
+        use sp1_stark::{air::PublicValues, MachineProver, StarkGenericConfig};
+
+        use crate::utils::assign_public_values;
+
+        fn prove_and_verify(codes: &[SyscallCode], fault: Option<SyscallFault>) -> bool {
+            let instructions = codes
+                .iter()
+                .flat_map(|&code| {
+                    [
+                        Instruction::new(Opcode::ADD, 5, 0, code as u32, false, true),
+                        Instruction::new(Opcode::ECALL, 5, 10, 11, false, false),
+                    ]
+                })
+                .collect();
+            let program = Program::new(instructions, 0, 0);
+            let opts = SP1CoreOpts::default();
+            let mut runtime = Executor::new(program.clone(), opts);
+            if let Some(fault) = fault {
+                runtime.fault_injector.inject(fault);
+            }
+            runtime.run().unwrap();
+
+            let machine = RiscvAir::<BabyBear>::machine(BabyBearPoseidon2::new());
+            let prover = CpuProver::new(machine);
+            let (pk, vk) = prover.setup(&program);
+
+            // Assign the public values the same way the checkpoint prover does.
+            let mut records = std::mem::take(&mut runtime.records);
+            let mut state = PublicValues::<u32, u32>::default().reset();
+            let mut deferred = ExecutionRecord::new(program.into());
+            assign_public_values(&mut records, &mut state, &mut deferred, true, opts);
+
+            let mut challenger = prover.config().challenger();
+            let proof = prover.prove(&pk, records, &mut challenger, opts).unwrap();
+            let mut challenger = prover.config().challenger();
+            prover.machine().verify(&vk, &proof, &mut challenger).is_ok()
+        }
+
+        utils::setup_logger();
+        assert!(prove_and_verify(&[SyscallCode::HALT], None));
+
+        // `HALT` must leave t0 unchanged, so the CPU chip rejects the corrupted write.
+        let fault = SyscallFault { code: SyscallCode::HALT, invocation: 0, value: 1 };
+        assert!(!prove_and_verify(&[SyscallCode::HALT], Some(fault)));
+
+        // `READ_PC` must return the pc of its ecall, which is 4.
+        let codes = [SyscallCode::READ_PC, SyscallCode::HALT];
+        let fault = SyscallFault { code: SyscallCode::READ_PC, invocation: 0, value: 8 };
+        assert!(!prove_and_verify(&codes, Some(fault)));
+    }
+
     #[test]
     fn test_simple_prove() {
         utils::setup_logger();
@@ -485,6 +557,284 @@ pub mod tests {
         prove::<_, CpuProver<_, _>>(program, &stdin, BabyBearPoseidon2::new(), opts).unwrap();
     }
 
+    #[test]
+    fn test_read_pc_prove() {
+        use sp1_core_executor::syscalls::SyscallCode;
+
+        setup_logger();
+        let read_pc = || {
+            [
+                Instruction::new(Opcode::ADD, 5, 0, SyscallCode::READ_PC as u32, false, true),
+                Instruction::new(Opcode::ECALL, 5, 10, 11, false, false),
+            ]
+        };
+        let mut instructions = read_pc().to_vec();
+        instructions.push(Instruction::new(Opcode::ADD, 29, 5, 0, false, true));
+        instructions.extend(read_pc());
+        run_test::<CpuProver<_, _>>(Program::new(instructions, 0x1000, 0x1000)).unwrap();
+    }
+
+    #[test]
+    fn test_shard_index_prove() {
+        use p3_baby_bear::BabyBear;
+        use sp1_core_executor::syscalls::SyscallCode;
+        use sp1_stark::StarkGenericConfig;
+
+        setup_logger();
+        let shard_index = || {
+            [
+                Instruction::new(Opcode::ADD, 5, 0, SyscallCode::SHARD_INDEX as u32, false, true),
+                Instruction::new(Opcode::ECALL, 5, 10, 11, false, false),
+            ]
+        };
+        let mut instructions = shard_index().to_vec();
+        instructions.extend(vec![Instruction::new(Opcode::ADD, 29, 0, 5, false, true); 64]);
+        instructions.extend(shard_index());
+        let program = Program::new(instructions, 0, 0);
+
+        let mut opts = SP1CoreOpts::default();
+        opts.shard_size = 32;
+        let (proof, _, _) = prove::<_, CpuProver<_, _>>(
+            program.clone(),
+            &SP1Stdin::new(),
+            BabyBearPoseidon2::new(),
+            opts,
+        )
+        .unwrap();
+        assert!(proof.shard_proofs.len() > 2);
+
+        let machine = RiscvAir::<BabyBear>::machine(BabyBearPoseidon2::new());
+        let (_, vk) = machine.setup(&program);
+        let mut challenger = machine.config().challenger();
+        machine.verify(&vk, &proof, &mut challenger).unwrap();
+    }
+
+    #[test]
+    fn test_exit_group_prove() {
+        use sp1_core_executor::syscalls::SyscallCode;
+
+        setup_logger();
+        let instructions = vec![
+            Instruction::new(Opcode::ADD, 29, 0, 5, false, true),
+            Instruction::new(Opcode::ADD, 5, 0, SyscallCode::EXIT_GROUP as u32, false, true),
+            Instruction::new(Opcode::ECALL, 5, 10, 11, false, false),
+            Instruction::new(Opcode::ADD, 29, 29, 1, false, true),
+        ];
+        let program = Program::new(instructions, 0, 0);
+        run_test::<CpuProver<_, _>>(program).unwrap();
+    }
+
+    #[test]
+    fn test_config_get_prove() {
+        use p3_baby_bear::BabyBear;
+        use sp1_core_executor::{syscalls::SyscallCode, SP1Context};
+        use sp1_stark::{MachineProver, StarkGenericConfig};
+
+        use crate::utils::prove_with_context;
+
+        setup_logger();
+        let config_get = |val_ptr| {
+            [
+                Instruction::new(Opcode::ADD, 5, 0, SyscallCode::CONFIG_GET as u32, false, true),
+                Instruction::new(Opcode::ADD, 12, 0, val_ptr, false, true),
+                Instruction::new(Opcode::ECALL, 5, 10, 11, false, false),
+                Instruction::new(Opcode::LW, 29, 12, 0, false, true),
+            ]
+        };
+        let mut instructions = vec![
+            Instruction::new(Opcode::ADD, 29, 0, u32::from_le_bytes(*b"id\0\0"), false, true),
+            Instruction::new(Opcode::ADD, 10, 0, 0x2000, false, true),
+            Instruction::new(Opcode::SW, 29, 10, 0, false, true),
+            Instruction::new(Opcode::ADD, 11, 0, 2, false, true),
+            Instruction::new(Opcode::ADD, 13, 0, 4, false, true),
+        ];
+        instructions.extend(config_get(0x3000));
+        instructions.extend(vec![Instruction::new(Opcode::ADD, 30, 0, 5, false, true); 64]);
+        // Read the config again in a later shard, which is executed from a checkpoint.
+        instructions.extend(config_get(0x3100));
+        let program = Program::new(instructions, 0, 0);
+
+        let mut opts = SP1CoreOpts::default();
+        opts.shard_size = 32;
+        let machine = RiscvAir::<BabyBear>::machine(BabyBearPoseidon2::new());
+        let prover = CpuProver::new(machine);
+        let (pk, vk) = prover.setup(&program);
+        let context = SP1Context::builder().config("id", "10").build();
+        let (proof, _, _) =
+            prove_with_context(&prover, &pk, program, &SP1Stdin::new(), opts, context).unwrap();
+        assert!(proof.shard_proofs.len() > 2);
+
+        let machine = prover.machine();
+        let mut challenger = machine.config().challenger();
+        machine.verify(&vk, &proof, &mut challenger).unwrap();
+    }
+
+    #[test]
+    fn test_input_read_at_prove() {
+        use p3_baby_bear::BabyBear;
+        use sp1_core_executor::{syscalls::SyscallCode, SP1Context};
+        use sp1_stark::{MachineProver, StarkGenericConfig};
+
+        use crate::utils::prove_with_context;
+
+        setup_logger();
+        let read_at = |offset, ptr| {
+            [
+                Instruction::new(Opcode::ADD, 5, 0, SyscallCode::INPUT_READ_AT as u32, false, true),
+                Instruction::new(Opcode::ADD, 10, 0, offset, false, true),
+                Instruction::new(Opcode::ADD, 11, 0, ptr, false, true),
+                Instruction::new(Opcode::ECALL, 5, 10, 11, false, false),
+                Instruction::new(Opcode::LW, 29, 11, 0, false, true),
+            ]
+        };
+        let mut instructions = vec![Instruction::new(Opcode::ADD, 12, 0, 8, false, true)];
+        instructions.extend(read_at(2, 0x3000));
+        // The second range overlaps the first one and is clamped to the end of the blob.
+        instructions.extend(read_at(6, 0x3100));
+        let program = Program::new(instructions, 0, 0);
+
+        let machine = RiscvAir::<BabyBear>::machine(BabyBearPoseidon2::new());
+        let prover = CpuProver::new(machine);
+        let (pk, vk) = prover.setup(&program);
+        let context = SP1Context::builder().input_blob((0..10).collect::<Vec<u8>>()).build();
+        let (proof, _, _) = prove_with_context(
+            &prover,
+            &pk,
+            program,
+            &SP1Stdin::new(),
+            SP1CoreOpts::default(),
+            context,
+        )
+        .unwrap();
+
+        let machine = prover.machine();
+        let mut challenger = machine.config().challenger();
+        machine.verify(&vk, &proof, &mut challenger).unwrap();
+    }
+
+    #[test]
+    fn test_read_stream_prove() {
+        use p3_baby_bear::BabyBear;
+        use sp1_core_executor::{syscalls::SyscallCode, SP1Context};
+        use sp1_stark::{MachineProver, StarkGenericConfig};
+
+        use crate::utils::prove_with_context;
+
+        setup_logger();
+        let read_stream = |stream_id, ptr| {
+            [
+                Instruction::new(Opcode::ADD, 5, 0, SyscallCode::READ_STREAM as u32, false, true),
+                Instruction::new(Opcode::ADD, 10, 0, stream_id, false, true),
+                Instruction::new(Opcode::ADD, 11, 0, ptr, false, true),
+                Instruction::new(Opcode::ECALL, 5, 10, 11, false, false),
+                Instruction::new(Opcode::LW, 29, 11, 0, false, true),
+            ]
+        };
+        let mut instructions = vec![Instruction::new(Opcode::ADD, 12, 0, 4, false, true)];
+        instructions.extend(read_stream(2, 0x3000));
+        instructions.extend(read_stream(1, 0x3100));
+        // The first read of stream 2 is clamped to its end, so the second one reads nothing.
+        instructions.extend(read_stream(2, 0x3200));
+        let program = Program::new(instructions, 0, 0);
+
+        let machine = RiscvAir::<BabyBear>::machine(BabyBearPoseidon2::new());
+        let prover = CpuProver::new(machine);
+        let (pk, vk) = prover.setup(&program);
+        let context =
+            SP1Context::builder().input_stream(1, [1, 2, 3, 4]).input_stream(2, [5, 6, 7]).build();
+        let (proof, _, _) = prove_with_context(
+            &prover,
+            &pk,
+            program,
+            &SP1Stdin::new(),
+            SP1CoreOpts::default(),
+            context,
+        )
+        .unwrap();
+
+        let machine = prover.machine();
+        let mut challenger = machine.config().challenger();
+        machine.verify(&vk, &proof, &mut challenger).unwrap();
+    }
+
+    #[test]
+    #[ignore = "needs the ELF of tests/commit-exit, built with `cargo prove build`"]
+    fn test_commit_exit_prove() {
+        use std::borrow::BorrowMut;
+
+        use p3_baby_bear::BabyBear;
+        use p3_field::AbstractField;
+        use sp1_core_executor::SP1Context;
+        use sp1_stark::{air::PublicValues, MachineProof, MachineProver, StarkGenericConfig, Word};
+
+        use crate::{io::SP1PublicValues, utils::prove_with_context};
+
+        let elf = std::fs::read(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/../../../tests/commit-exit/elf/riscv32im-succinct-zkvm-elf"
+        ))
+        .unwrap();
+        let program = Program::from(&elf).unwrap();
+
+        setup_logger();
+        let prover = CpuProver::new(RiscvAir::<BabyBear>::machine(BabyBearPoseidon2::new()));
+        let (pk, vk) = prover.setup(&program);
+        let (proof, public_values, _) = prove_with_context(
+            &prover,
+            &pk,
+            program,
+            &SP1Stdin::new(),
+            SP1CoreOpts::default(),
+            SP1Context::default(),
+        )
+        .unwrap();
+
+        // The program commits 42, then halts through `io::commit_exit(0)`, which commits the code
+        // last.
+        let expected = [bincode::serialize(&42u32).unwrap(), 0u32.to_le_bytes().to_vec()].concat();
+        let public_values = SP1PublicValues::from(&public_values);
+        assert_eq!(public_values.as_slice(), expected);
+        assert_eq!(public_values.committed_exit_code(), 0);
+
+        let machine = prover.machine();
+        machine.verify(&vk, &proof, &mut machine.config().challenger()).unwrap();
+
+        // Replaces the public values of every shard of the proof, through `tamper`.
+        let tampered = |tamper: &dyn Fn(&mut PublicValues<Word<BabyBear>, BabyBear>)| {
+            let mut proof: MachineProof<BabyBearPoseidon2> = proof.clone();
+            for shard_proof in &mut proof.shard_proofs {
+                tamper(shard_proof.public_values.as_mut_slice().borrow_mut());
+            }
+            proof
+        };
+        // Claims the digest of `claimed` instead of the public values the program committed.
+        let claim = |claimed: Vec<u8>| {
+            let digest = SP1PublicValues::from(&claimed).digest();
+            tampered(&|public_values| {
+                public_values.committed_value_digest = core::array::from_fn(|i| {
+                    Word::from(u32::from_le_bytes(digest[4 * i..4 * i + 4].try_into().unwrap()))
+                });
+            })
+        };
+
+        // Another committed value.
+        let mut claimed = expected.clone();
+        claimed[0] = 43;
+        let proof = claim(claimed);
+        assert!(machine.verify(&vk, &proof, &mut machine.config().challenger()).is_err());
+
+        // Another committed exit code.
+        let mut claimed = expected.clone();
+        let len = claimed.len();
+        claimed[len - 4..].copy_from_slice(&1u32.to_le_bytes());
+        let proof = claim(claimed);
+        assert!(machine.verify(&vk, &proof, &mut machine.config().challenger()).is_err());
+
+        // Another exit code for the halt itself.
+        let proof = tampered(&|public_values| public_values.exit_code = BabyBear::one());
+        assert!(machine.verify(&vk, &proof, &mut machine.config().challenger()).is_err());
+    }
+
     #[test]
     fn test_fibonacci_prove_batch() {
         setup_logger();
@@ -0,0 +1,277 @@
+use std::{array, borrow::Borrow};
+
+use p3_air::{Air, AirBuilder, BaseAir};
+use p3_field::AbstractField;
+use p3_matrix::Matrix;
+use sp1_core_executor::syscalls::SyscallCode;
+use sp1_primitives::blake3::{
+    blake3_message_schedule, BLAKE3_BLOCK_WORDS, BLAKE3_G_INDICES, BLAKE3_NUM_ROUNDS,
+    BLAKE3_STATE_WORDS,
+};
+use sp1_stark::{air::SP1AirBuilder, Word};
+
+use crate::{
+    air::{MemoryAirBuilder, WordAirBuilder},
+    memory::MemoryCols,
+    operations::{AddOperation, FixedRotateRightOperation, XorOperation},
+};
+
+use super::{
+    columns::{Blake3CompressCols, GCols, NUM_BLAKE3_COMPRESS_COLS},
+    rotate_right_bytes, Blake3CompressChip,
+};
+
+impl<F> BaseAir<F> for Blake3CompressChip {
+    fn width(&self) -> usize {
+        NUM_BLAKE3_COMPRESS_COLS
+    }
+}
+
+impl<AB> Air<AB> for Blake3CompressChip
+where
+    AB: SP1AirBuilder,
+{
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let local = main.row_slice(0);
+        let local: &Blake3CompressCols<AB::Var> = (*local).borrow();
+        let next = main.row_slice(1);
+        let next: &Blake3CompressCols<AB::Var> = (*next).borrow();
+
+        // Constrain the incrementing nonce.
+        builder.when_first_row().assert_zero(local.nonce);
+        builder.when_transition().assert_eq(local.nonce + AB::Expr::one(), next.nonce);
+
+        // Assert that is_real is a boolean.
+        builder.assert_bool(local.is_real);
+
+        // The input is the value of the state in memory before the write. Its words and the words
+        // of the block are range checked by the first operation that uses them.
+        let input: [Word<AB::Var>; BLAKE3_STATE_WORDS] =
+            array::from_fn(|i| *local.state_memory[i].prev_value());
+        let block: [Word<AB::Var>; BLAKE3_BLOCK_WORDS] =
+            array::from_fn(|i| *local.block_memory[i].value());
+
+        let mut state = input;
+        for round in 0..BLAKE3_NUM_ROUNDS {
+            let schedule = blake3_message_schedule(round);
+            for (i, indices) in BLAKE3_G_INDICES.into_iter().enumerate() {
+                self.eval_g(
+                    builder,
+                    &mut state,
+                    indices,
+                    [block[schedule[2 * i]], block[schedule[2 * i + 1]]],
+                    &local.rounds[round][i],
+                    local,
+                );
+            }
+        }
+
+        for i in 0..BLAKE3_STATE_WORDS / 2 {
+            XorOperation::<AB::F>::eval(
+                builder,
+                state[i],
+                state[i + 8],
+                local.output[i],
+                local.shard,
+                local.channel,
+                local.is_real,
+            );
+            XorOperation::<AB::F>::eval(
+                builder,
+                state[i + 8],
+                input[i],
+                local.output[i + 8],
+                local.shard,
+                local.channel,
+                local.is_real,
+            );
+        }
+
+        // The output is written back to the state.
+        for i in 0..BLAKE3_STATE_WORDS {
+            builder
+                .when(local.is_real)
+                .assert_word_eq(*local.state_memory[i].value(), local.output[i].value);
+        }
+
+        // Read the block.
+        builder.eval_memory_access_slice(
+            local.shard,
+            local.channel,
+            local.clk,
+            local.block_ptr,
+            &local.block_memory,
+            local.is_real,
+        );
+
+        // Read and write the state.
+        builder.eval_memory_access_slice(
+            local.shard,
+            local.channel,
+            local.clk.into() + AB::Expr::one(),
+            local.state_ptr,
+            &local.state_memory,
+            local.is_real,
+        );
+
+        // Receive the arguments.
+        builder.receive_syscall(
+            local.shard,
+            local.channel,
+            local.clk,
+            local.nonce,
+            AB::F::from_canonical_u32(SyscallCode::BLAKE3_COMPRESS.syscall_id()),
+            local.state_ptr,
+            local.block_ptr,
+            local.is_real,
+        );
+    }
+}
+
+impl Blake3CompressChip {
+    /// Eval the constraints of one application of `G`, and update `state` with its output.
+    fn eval_g<AB: SP1AirBuilder>(
+        &self,
+        builder: &mut AB,
+        state: &mut [Word<AB::Var>; BLAKE3_STATE_WORDS],
+        [a, b, c, d]: [usize; 4],
+        [x, y]: [Word<AB::Var>; 2],
+        cols: &GCols<AB::Var>,
+        local: &Blake3CompressCols<AB::Var>,
+    ) {
+        let (shard, channel, is_real) = (local.shard, local.channel, local.is_real);
+
+        // a = a + b + x.
+        AddOperation::<AB::F>::eval(
+            builder,
+            state[a],
+            state[b],
+            cols.a_plus_b,
+            shard,
+            channel,
+            is_real.into(),
+        );
+        AddOperation::<AB::F>::eval(
+            builder,
+            cols.a_plus_b.value,
+            x,
+            cols.a_plus_b_plus_x,
+            shard,
+            channel,
+            is_real.into(),
+        );
+        state[a] = cols.a_plus_b_plus_x.value;
+
+        // d = (d ^ a) >>> 16.
+        XorOperation::<AB::F>::eval(
+            builder,
+            state[d],
+            state[a],
+            cols.d_xor_a,
+            shard,
+            channel,
+            is_real,
+        );
+        state[d] = rotate_right_bytes(cols.d_xor_a.value, 2);
+
+        // c = c + d.
+        AddOperation::<AB::F>::eval(
+            builder,
+            state[c],
+            state[d],
+            cols.c_plus_d,
+            shard,
+            channel,
+            is_real.into(),
+        );
+        state[c] = cols.c_plus_d.value;
+
+        // b = (b ^ c) >>> 12.
+        XorOperation::<AB::F>::eval(
+            builder,
+            state[b],
+            state[c],
+            cols.b_xor_c,
+            shard,
+            channel,
+            is_real,
+        );
+        FixedRotateRightOperation::<AB::F>::eval(
+            builder,
+            cols.b_xor_c.value,
+            12,
+            cols.b_xor_c_rotate_right_12,
+            shard,
+            channel,
+            is_real,
+        );
+        state[b] = cols.b_xor_c_rotate_right_12.value;
+
+        // a = a + b + y.
+        AddOperation::<AB::F>::eval(
+            builder,
+            state[a],
+            state[b],
+            cols.a_plus_b_2,
+            shard,
+            channel,
+            is_real.into(),
+        );
+        AddOperation::<AB::F>::eval(
+            builder,
+            cols.a_plus_b_2.value,
+            y,
+            cols.a_plus_b_plus_y,
+            shard,
+            channel,
+            is_real.into(),
+        );
+        state[a] = cols.a_plus_b_plus_y.value;
+
+        // d = (d ^ a) >>> 8.
+        XorOperation::<AB::F>::eval(
+            builder,
+            state[d],
+            state[a],
+            cols.d_xor_a_2,
+            shard,
+            channel,
+            is_real,
+        );
+        state[d] = rotate_right_bytes(cols.d_xor_a_2.value, 1);
+
+        // c = c + d.
+        AddOperation::<AB::F>::eval(
+            builder,
+            state[c],
+            state[d],
+            cols.c_plus_d_2,
+            shard,
+            channel,
+            is_real.into(),
+        );
+        state[c] = cols.c_plus_d_2.value;
+
+        // b = (b ^ c) >>> 7.
+        XorOperation::<AB::F>::eval(
+            builder,
+            state[b],
+            state[c],
+            cols.b_xor_c_2,
+            shard,
+            channel,
+            is_real,
+        );
+        FixedRotateRightOperation::<AB::F>::eval(
+            builder,
+            cols.b_xor_c_2.value,
+            7,
+            cols.b_xor_c_2_rotate_right_7,
+            shard,
+            channel,
+            is_real,
+        );
+        state[b] = cols.b_xor_c_2_rotate_right_7.value;
+    }
+}
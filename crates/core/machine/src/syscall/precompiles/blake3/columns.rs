@@ -0,0 +1,71 @@
+use std::mem::size_of;
+
+use sp1_derive::AlignedBorrow;
+use sp1_primitives::blake3::{BLAKE3_BLOCK_WORDS, BLAKE3_NUM_ROUNDS, BLAKE3_STATE_WORDS};
+
+use crate::{
+    memory::{MemoryReadCols, MemoryWriteCols},
+    operations::{AddOperation, FixedRotateRightOperation, XorOperation},
+};
+
+use super::NUM_G_PER_ROUND;
+
+pub const NUM_BLAKE3_COMPRESS_COLS: usize = size_of::<Blake3CompressCols<u8>>();
+
+/// The columns of one application of the `G` function.
+///
+/// The rotations by 16 and 8 bits only move bytes around, so they don't need columns.
+#[derive(AlignedBorrow, Default, Debug, Clone, Copy)]
+#[repr(C)]
+pub struct GCols<T> {
+    pub a_plus_b: AddOperation<T>,
+    pub a_plus_b_plus_x: AddOperation<T>,
+    pub d_xor_a: XorOperation<T>,
+    pub c_plus_d: AddOperation<T>,
+    pub b_xor_c: XorOperation<T>,
+    pub b_xor_c_rotate_right_12: FixedRotateRightOperation<T>,
+
+    pub a_plus_b_2: AddOperation<T>,
+    pub a_plus_b_plus_y: AddOperation<T>,
+    pub d_xor_a_2: XorOperation<T>,
+    pub c_plus_d_2: AddOperation<T>,
+    pub b_xor_c_2: XorOperation<T>,
+    pub b_xor_c_2_rotate_right_7: FixedRotateRightOperation<T>,
+}
+
+/// A set of columns for the BLAKE3 compress precompile. Each row holds one compression.
+#[derive(AlignedBorrow, Default, Debug, Clone, Copy)]
+#[repr(C)]
+pub struct Blake3CompressCols<T> {
+    /// The shard number of the syscall.
+    pub shard: T,
+
+    /// The byte lookup channel.
+    pub channel: T,
+
+    /// The clock cycle of the syscall.
+    pub clk: T,
+
+    /// The nonce of the operation.
+    pub nonce: T,
+
+    /// The pointer to the state.
+    pub state_ptr: T,
+
+    /// The pointer to the message block.
+    pub block_ptr: T,
+
+    /// The state is read and then overwritten with the output of the compression.
+    pub state_memory: [MemoryWriteCols<T>; BLAKE3_STATE_WORDS],
+
+    /// The message block is only read.
+    pub block_memory: [MemoryReadCols<T>; BLAKE3_BLOCK_WORDS],
+
+    pub rounds: [[GCols<T>; NUM_G_PER_ROUND]; BLAKE3_NUM_ROUNDS],
+
+    /// The output, which is the xor of the two halves of the final state for the first half, and
+    /// the xor of the second half with the input chaining value for the second half.
+    pub output: [XorOperation<T>; BLAKE3_STATE_WORDS],
+
+    pub is_real: T,
+}
@@ -0,0 +1,100 @@
+mod air;
+pub mod columns;
+mod trace;
+
+use sp1_primitives::{blake3::BLAKE3_G_INDICES, consts::WORD_SIZE};
+use sp1_stark::Word;
+
+/// The number of applications of `G` in a round.
+pub const NUM_G_PER_ROUND: usize = BLAKE3_G_INDICES.len();
+
+/// A chip that implements the BLAKE3 compression function.
+#[derive(Default)]
+pub struct Blake3CompressChip;
+
+impl Blake3CompressChip {
+    pub const fn new() -> Self {
+        Self
+    }
+}
+
+/// Rotates a word right by a whole number of bytes, which only reorders its limbs.
+pub(crate) fn rotate_right_bytes<T: Copy>(word: Word<T>, bytes: usize) -> Word<T> {
+    Word(core::array::from_fn(|i| word[(i + bytes) % WORD_SIZE]))
+}
+
+#[cfg(test)]
+pub mod compress_tests {
+    use sp1_core_executor::{syscalls::SyscallCode, Executor, Instruction, Opcode, Program};
+    use sp1_primitives::blake3::{BLAKE3_BLOCK_WORDS, BLAKE3_IV, BLAKE3_STATE_WORDS};
+    use sp1_stark::{CpuProver, SP1CoreOpts};
+
+    use crate::utils::{self, run_test};
+
+    const STATE_PTR: u32 = 100;
+    const BLOCK_PTR: u32 = 200;
+
+    /// The flags of the only block of an input that fits in one block: `CHUNK_START`,
+    /// `CHUNK_END` and `ROOT`.
+    const ONE_BLOCK_FLAGS: u32 = 1 | 2 | 8;
+
+    /// The official BLAKE3 test vector of length 64, whose input is the bytes `0, 1, ..., 63`.
+    const HASH_64: &str = "4eed7141ea4a5cd4b788606bd23f46e212af9cacebacdc7d1f4c6dc7f2511b98";
+
+    fn test_input() -> ([u32; BLAKE3_STATE_WORDS], [u32; BLAKE3_BLOCK_WORDS]) {
+        let mut state = [0; BLAKE3_STATE_WORDS];
+        state[..8].copy_from_slice(&BLAKE3_IV);
+        state[8..12].copy_from_slice(&BLAKE3_IV[..4]);
+        state[14] = 64;
+        state[15] = ONE_BLOCK_FLAGS;
+        let block = core::array::from_fn(|i| {
+            u32::from_le_bytes(core::array::from_fn(|j| (4 * i + j) as u8))
+        });
+        (state, block)
+    }
+
+    pub fn blake3_compress_program(
+        state: [u32; BLAKE3_STATE_WORDS],
+        block: [u32; BLAKE3_BLOCK_WORDS],
+    ) -> Program {
+        let mut instructions = vec![];
+        let words = state.into_iter().zip(0..).map(|(word, i)| (word, STATE_PTR + 4 * i));
+        let words =
+            words.chain(block.into_iter().zip(0..).map(|(word, i)| (word, BLOCK_PTR + 4 * i)));
+        for (word, addr) in words {
+            instructions.extend(vec![
+                Instruction::new(Opcode::ADD, 29, 0, word, false, true),
+                Instruction::new(Opcode::ADD, 30, 0, addr, false, true),
+                Instruction::new(Opcode::SW, 29, 30, 0, false, true),
+            ]);
+        }
+        instructions.extend(vec![
+            Instruction::new(Opcode::ADD, 5, 0, SyscallCode::BLAKE3_COMPRESS as u32, false, true),
+            Instruction::new(Opcode::ADD, 10, 0, STATE_PTR, false, true),
+            Instruction::new(Opcode::ADD, 11, 0, BLOCK_PTR, false, true),
+            Instruction::new(Opcode::ECALL, 5, 10, 11, false, false),
+        ]);
+
+        Program::new(instructions, 0, 0)
+    }
+
+    #[test]
+    fn test_blake3_compress_known_answer() {
+        utils::setup_logger();
+        let (state, block) = test_input();
+        let mut runtime =
+            Executor::new(blake3_compress_program(state, block), SP1CoreOpts::default());
+        runtime.run().unwrap();
+
+        let hash =
+            (0..8).flat_map(|i| runtime.word(STATE_PTR + 4 * i).to_le_bytes()).collect::<Vec<_>>();
+        assert_eq!(hash, hex::decode(HASH_64).unwrap());
+    }
+
+    #[test]
+    fn test_blake3_compress_prove_babybear() {
+        utils::setup_logger();
+        let (state, block) = test_input();
+        run_test::<CpuProver<_, _>>(blake3_compress_program(state, block)).unwrap();
+    }
+}
@@ -0,0 +1,147 @@
+use std::borrow::BorrowMut;
+
+use p3_field::PrimeField32;
+use p3_matrix::{dense::RowMajorMatrix, Matrix};
+use sp1_core_executor::{
+    events::{Blake3CompressEvent, ByteRecord},
+    ExecutionRecord, Program,
+};
+use sp1_primitives::blake3::{
+    blake3_message_schedule, BLAKE3_G_INDICES, BLAKE3_NUM_ROUNDS, BLAKE3_STATE_WORDS,
+};
+use sp1_stark::{air::MachineAir, MachineRecord};
+
+use crate::utils::pad_rows;
+
+use super::{
+    columns::{Blake3CompressCols, GCols, NUM_BLAKE3_COMPRESS_COLS},
+    Blake3CompressChip,
+};
+
+impl<F: PrimeField32> MachineAir<F> for Blake3CompressChip {
+    type Record = ExecutionRecord;
+    type Program = Program;
+
+    fn name(&self) -> String {
+        "Blake3Compress".to_string()
+    }
+
+    fn generate_trace(
+        &self,
+        input: &ExecutionRecord,
+        output: &mut ExecutionRecord,
+    ) -> RowMajorMatrix<F> {
+        let mut new_byte_lookup_events = Vec::new();
+        let mut rows = input
+            .blake3_compress_events
+            .iter()
+            .map(|event| {
+                let mut row = [F::zero(); NUM_BLAKE3_COMPRESS_COLS];
+                let cols: &mut Blake3CompressCols<F> = row.as_mut_slice().borrow_mut();
+                self.event_to_row(event, cols, &mut new_byte_lookup_events);
+                row
+            })
+            .collect::<Vec<_>>();
+
+        let mut record = ExecutionRecord::default();
+        record.add_byte_lookup_events(new_byte_lookup_events);
+        output.append(&mut record);
+
+        // Padding rows are all zeros, as every constraint of the compression is conditioned on
+        // is_real.
+        pad_rows(&mut rows, || [F::zero(); NUM_BLAKE3_COMPRESS_COLS]);
+
+        // Convert the trace to a row major matrix.
+        let mut trace = RowMajorMatrix::new(
+            rows.into_iter().flatten().collect::<Vec<_>>(),
+            NUM_BLAKE3_COMPRESS_COLS,
+        );
+
+        // Write the nonces to the trace.
+        for i in 0..trace.height() {
+            let cols: &mut Blake3CompressCols<F> = trace.values
+                [i * NUM_BLAKE3_COMPRESS_COLS..(i + 1) * NUM_BLAKE3_COMPRESS_COLS]
+                .borrow_mut();
+            cols.nonce = F::from_canonical_usize(i);
+        }
+
+        trace
+    }
+
+    fn included(&self, shard: &Self::Record) -> bool {
+        !shard.blake3_compress_events.is_empty()
+    }
+}
+
+impl Blake3CompressChip {
+    fn event_to_row<F: PrimeField32>(
+        &self,
+        event: &Blake3CompressEvent,
+        cols: &mut Blake3CompressCols<F>,
+        blu: &mut impl ByteRecord,
+    ) {
+        let (shard, channel) = (event.shard, event.channel);
+        cols.shard = F::from_canonical_u32(shard);
+        cols.channel = F::from_canonical_u8(channel);
+        cols.clk = F::from_canonical_u32(event.clk);
+        cols.state_ptr = F::from_canonical_u32(event.state_ptr);
+        cols.block_ptr = F::from_canonical_u32(event.block_ptr);
+        cols.is_real = F::one();
+
+        for i in 0..BLAKE3_STATE_WORDS {
+            cols.state_memory[i].populate(channel, event.state_records[i], blu);
+            cols.block_memory[i].populate(channel, event.block_records[i], blu);
+        }
+
+        let mut state = event.input;
+        for round in 0..BLAKE3_NUM_ROUNDS {
+            let schedule = blake3_message_schedule(round);
+            for (i, indices) in BLAKE3_G_INDICES.into_iter().enumerate() {
+                let [x, y] = [event.block[schedule[2 * i]], event.block[schedule[2 * i + 1]]];
+                populate_g(
+                    &mut cols.rounds[round][i],
+                    blu,
+                    shard,
+                    channel,
+                    &mut state,
+                    indices,
+                    x,
+                    y,
+                );
+            }
+        }
+
+        for i in 0..BLAKE3_STATE_WORDS / 2 {
+            cols.output[i].populate(blu, shard, channel, state[i], state[i + 8]);
+            cols.output[i + 8].populate(blu, shard, channel, state[i + 8], event.input[i]);
+        }
+        debug_assert_eq!(cols.output.map(|xor| xor.value.to_u32()), event.output);
+    }
+}
+
+/// Populates the columns of one application of `G`, and updates `state` with its output.
+#[allow(clippy::too_many_arguments)]
+fn populate_g<F: PrimeField32>(
+    cols: &mut GCols<F>,
+    blu: &mut impl ByteRecord,
+    shard: u32,
+    channel: u8,
+    state: &mut [u32; BLAKE3_STATE_WORDS],
+    [a, b, c, d]: [usize; 4],
+    x: u32,
+    y: u32,
+) {
+    let a_plus_b = cols.a_plus_b.populate(blu, shard, channel, state[a], state[b]);
+    state[a] = cols.a_plus_b_plus_x.populate(blu, shard, channel, a_plus_b, x);
+    state[d] = cols.d_xor_a.populate(blu, shard, channel, state[d], state[a]).rotate_right(16);
+    state[c] = cols.c_plus_d.populate(blu, shard, channel, state[c], state[d]);
+    let b_xor_c = cols.b_xor_c.populate(blu, shard, channel, state[b], state[c]);
+    state[b] = cols.b_xor_c_rotate_right_12.populate(blu, shard, channel, b_xor_c, 12);
+
+    let a_plus_b = cols.a_plus_b_2.populate(blu, shard, channel, state[a], state[b]);
+    state[a] = cols.a_plus_b_plus_y.populate(blu, shard, channel, a_plus_b, y);
+    state[d] = cols.d_xor_a_2.populate(blu, shard, channel, state[d], state[a]).rotate_right(8);
+    state[c] = cols.c_plus_d_2.populate(blu, shard, channel, state[c], state[d]);
+    let b_xor_c = cols.b_xor_c_2.populate(blu, shard, channel, state[b], state[c]);
+    state[b] = cols.b_xor_c_2_rotate_right_7.populate(blu, shard, channel, b_xor_c, 7);
+}
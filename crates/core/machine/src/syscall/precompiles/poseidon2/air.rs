@@ -0,0 +1,184 @@
+use std::{array, borrow::Borrow};
+
+use p3_air::{Air, AirBuilder, BaseAir};
+use p3_field::AbstractField;
+use p3_matrix::Matrix;
+use sp1_core_executor::syscalls::SyscallCode;
+use sp1_primitives::RC_16_30_U32;
+use sp1_stark::air::SP1AirBuilder;
+
+use crate::{
+    air::{MemoryAirBuilder, WordAirBuilder},
+    memory::MemoryCols,
+    operations::BabyBearWordRangeChecker,
+};
+
+use super::{
+    columns::{Poseidon2PermutationCols, Poseidon2PermuteCols, NUM_POSEIDON2_PERMUTE_COLS},
+    external_linear_layer, internal_linear_layer, Poseidon2PermuteChip, NUM_EXTERNAL_ROUNDS,
+    NUM_INTERNAL_ROUNDS, WIDTH,
+};
+
+impl<F> BaseAir<F> for Poseidon2PermuteChip {
+    fn width(&self) -> usize {
+        NUM_POSEIDON2_PERMUTE_COLS
+    }
+}
+
+impl<AB> Air<AB> for Poseidon2PermuteChip
+where
+    AB: SP1AirBuilder,
+{
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let local = main.row_slice(0);
+        let local: &Poseidon2PermuteCols<AB::Var> = (*local).borrow();
+        let next = main.row_slice(1);
+        let next: &Poseidon2PermuteCols<AB::Var> = (*next).borrow();
+
+        // Constrain the incrementing nonce.
+        builder.when_first_row().assert_zero(local.nonce);
+        builder.when_transition().assert_eq(local.nonce + AB::Expr::one(), next.nonce);
+
+        // Assert that is_real is a boolean.
+        builder.assert_bool(local.is_real);
+
+        // The permutation is constrained on every row, padding rows hold the permutation of the
+        // zero state.
+        let permutation = &local.permutation;
+        for r in 0..NUM_EXTERNAL_ROUNDS {
+            self.eval_external_round(builder, permutation, r);
+        }
+        self.eval_internal_rounds(builder, permutation);
+
+        for i in 0..WIDTH {
+            let memory = &local.state_memory[i];
+
+            // The input is the value in memory before the write, and must be a canonical field
+            // element.
+            BabyBearWordRangeChecker::<AB::F>::range_check(
+                builder,
+                *memory.prev_value(),
+                local.input_range_checks[i],
+                local.is_real.into(),
+            );
+            builder.when(local.is_real).assert_eq(
+                memory.prev_value().reduce::<AB>(),
+                permutation.external_rounds_state[0][i],
+            );
+
+            // The output is written back as a canonical field element in little endian bytes.
+            builder.slice_range_check_u8(
+                &memory.value().0,
+                local.shard,
+                local.channel,
+                local.is_real,
+            );
+            BabyBearWordRangeChecker::<AB::F>::range_check(
+                builder,
+                *memory.value(),
+                local.output_range_checks[i],
+                local.is_real.into(),
+            );
+            builder
+                .when(local.is_real)
+                .assert_eq(memory.value().reduce::<AB>(), permutation.output_state[i]);
+        }
+
+        // Read and write the state.
+        builder.eval_memory_access_slice(
+            local.shard,
+            local.channel,
+            local.clk.into() + AB::Expr::one(),
+            local.state_ptr,
+            &local.state_memory,
+            local.is_real,
+        );
+
+        // Receive the arguments.
+        builder.receive_syscall(
+            local.shard,
+            local.channel,
+            local.clk,
+            local.nonce,
+            AB::F::from_canonical_u32(SyscallCode::POSEIDON2_PERMUTE.syscall_id()),
+            local.state_ptr,
+            AB::F::from_canonical_usize(WIDTH),
+            local.is_real,
+        );
+    }
+}
+
+impl Poseidon2PermuteChip {
+    /// Eval the constraints for the external rounds.
+    fn eval_external_round<AB: SP1AirBuilder>(
+        &self,
+        builder: &mut AB,
+        cols: &Poseidon2PermutationCols<AB::Var>,
+        r: usize,
+    ) {
+        let mut local_state: [AB::Expr; WIDTH] =
+            array::from_fn(|i| cols.external_rounds_state[r][i].into());
+
+        // For the first round, apply the linear layer.
+        if r == 0 {
+            external_linear_layer(&mut local_state);
+        }
+
+        // Add the round constants and apply the sboxes.
+        let round = if r < NUM_EXTERNAL_ROUNDS / 2 { r } else { r + NUM_INTERNAL_ROUNDS };
+        let mut state: [AB::Expr; WIDTH] = array::from_fn(|_| AB::Expr::zero());
+        for i in 0..WIDTH {
+            let add_rc = local_state[i].clone() + AB::F::from_wrapped_u32(RC_16_30_U32[round][i]);
+            let sbox_deg_3 = cols.external_rounds_sbox[r][i];
+            builder.assert_eq(sbox_deg_3, add_rc.clone() * add_rc.clone() * add_rc.clone());
+            state[i] = sbox_deg_3.into() * sbox_deg_3.into() * add_rc;
+        }
+
+        // Apply the linear layer.
+        external_linear_layer(&mut state);
+
+        let next_state = if r == (NUM_EXTERNAL_ROUNDS / 2) - 1 {
+            &cols.internal_rounds_state
+        } else if r == NUM_EXTERNAL_ROUNDS - 1 {
+            &cols.output_state
+        } else {
+            &cols.external_rounds_state[r + 1]
+        };
+
+        for i in 0..WIDTH {
+            builder.assert_eq(next_state[i], state[i].clone());
+        }
+    }
+
+    /// Eval the constraints for the internal rounds.
+    fn eval_internal_rounds<AB: SP1AirBuilder>(
+        &self,
+        builder: &mut AB,
+        cols: &Poseidon2PermutationCols<AB::Var>,
+    ) {
+        let s0 = &cols.internal_rounds_s0;
+        let mut state: [AB::Expr; WIDTH] = array::from_fn(|i| cols.internal_rounds_state[i].into());
+        for r in 0..NUM_INTERNAL_ROUNDS {
+            // Add the round constant and apply the sbox.
+            let round = r + NUM_EXTERNAL_ROUNDS / 2;
+            let add_rc = if r == 0 { state[0].clone() } else { s0[r - 1].into() }
+                + AB::Expr::from_wrapped_u32(RC_16_30_U32[round][0]);
+            let sbox_deg_3 = cols.internal_rounds_sbox[r];
+            builder.assert_eq(sbox_deg_3, add_rc.clone() * add_rc.clone() * add_rc.clone());
+
+            // Apply the linear layer.
+            state[0] = sbox_deg_3.into() * sbox_deg_3.into() * add_rc;
+            internal_linear_layer(&mut state);
+
+            if r < NUM_INTERNAL_ROUNDS - 1 {
+                builder.assert_eq(s0[r], state[0].clone());
+            }
+        }
+
+        let external_state = cols.external_rounds_state[NUM_EXTERNAL_ROUNDS / 2];
+        for i in 0..WIDTH {
+            builder.assert_eq(external_state[i], state[i].clone())
+        }
+    }
+}
@@ -0,0 +1,56 @@
+use std::mem::size_of;
+
+use sp1_derive::AlignedBorrow;
+
+use crate::{memory::MemoryWriteCols, operations::BabyBearWordRangeChecker};
+
+use super::{NUM_EXTERNAL_ROUNDS, NUM_INTERNAL_ROUNDS, WIDTH};
+
+pub const NUM_POSEIDON2_PERMUTE_COLS: usize = size_of::<Poseidon2PermuteCols<u8>>();
+
+/// The columns of a single Poseidon2 permutation with degree 3 S-boxes.
+///
+/// The layout follows the degree 3 `Poseidon2WideChip` of the recursion machine.
+#[derive(AlignedBorrow, Default, Debug, Clone, Copy)]
+#[repr(C)]
+pub struct Poseidon2PermutationCols<T> {
+    pub external_rounds_state: [[T; WIDTH]; NUM_EXTERNAL_ROUNDS],
+    pub external_rounds_sbox: [[T; WIDTH]; NUM_EXTERNAL_ROUNDS],
+    pub internal_rounds_state: [T; WIDTH],
+    pub internal_rounds_s0: [T; NUM_INTERNAL_ROUNDS - 1],
+    pub internal_rounds_sbox: [T; NUM_INTERNAL_ROUNDS],
+    pub output_state: [T; WIDTH],
+}
+
+/// A set of columns for the Poseidon2 permute precompile. Each row holds one permutation.
+#[derive(AlignedBorrow, Default, Debug, Clone, Copy)]
+#[repr(C)]
+pub struct Poseidon2PermuteCols<T> {
+    /// The shard number of the syscall.
+    pub shard: T,
+
+    /// The byte lookup channel.
+    pub channel: T,
+
+    /// The clock cycle of the syscall.
+    pub clk: T,
+
+    /// The nonce of the operation.
+    pub nonce: T,
+
+    /// The pointer to the state.
+    pub state_ptr: T,
+
+    /// The state is read and then overwritten with the permuted state.
+    pub state_memory: [MemoryWriteCols<T>; WIDTH],
+
+    /// Checks that the input words are canonical field elements.
+    pub input_range_checks: [BabyBearWordRangeChecker<T>; WIDTH],
+
+    /// Checks that the output words are canonical field elements.
+    pub output_range_checks: [BabyBearWordRangeChecker<T>; WIDTH],
+
+    pub permutation: Poseidon2PermutationCols<T>,
+
+    pub is_real: T,
+}
@@ -0,0 +1,126 @@
+#![allow(clippy::needless_range_loop)]
+
+mod air;
+pub mod columns;
+mod trace;
+
+use p3_baby_bear::{MONTY_INVERSE, POSEIDON2_INTERNAL_MATRIX_DIAG_16_BABYBEAR_MONTY};
+use p3_field::{AbstractField, PrimeField32};
+use p3_poseidon2::matmul_internal;
+
+/// The width of the permutation.
+pub const WIDTH: usize = 16;
+
+pub const NUM_EXTERNAL_ROUNDS: usize = 8;
+pub const NUM_INTERNAL_ROUNDS: usize = 13;
+
+/// A chip that implements the Poseidon2 permutation over `BabyBear` used by the recursion
+/// machine.
+#[derive(Default)]
+pub struct Poseidon2PermuteChip;
+
+impl Poseidon2PermuteChip {
+    pub const fn new() -> Self {
+        Self
+    }
+}
+
+pub(crate) fn apply_m_4<AF>(x: &mut [AF])
+where
+    AF: AbstractField,
+{
+    let t01 = x[0].clone() + x[1].clone();
+    let t23 = x[2].clone() + x[3].clone();
+    let t0123 = t01.clone() + t23.clone();
+    let t01123 = t0123.clone() + x[1].clone();
+    let t01233 = t0123.clone() + x[3].clone();
+    // The order here is important. Need to overwrite x[0] and x[2] after x[1] and x[3].
+    x[3] = t01233.clone() + x[0].double(); // 3*x[0] + x[1] + x[2] + 2*x[3]
+    x[1] = t01123.clone() + x[2].double(); // x[0] + 2*x[1] + 3*x[2] + x[3]
+    x[0] = t01123 + t01; // 2*x[0] + 3*x[1] + x[2] + x[3]
+    x[2] = t01233 + t23; // x[0] + x[1] + 2*x[2] + 3*x[3]
+}
+
+pub(crate) fn external_linear_layer<AF: AbstractField>(state: &mut [AF; WIDTH]) {
+    for j in (0..WIDTH).step_by(4) {
+        apply_m_4(&mut state[j..j + 4]);
+    }
+    let sums: [AF; 4] =
+        core::array::from_fn(|k| (0..WIDTH).step_by(4).map(|j| state[j + k].clone()).sum::<AF>());
+
+    for j in 0..WIDTH {
+        state[j] += sums[j % 4].clone();
+    }
+}
+
+pub(crate) fn internal_linear_layer<F: AbstractField>(state: &mut [F; WIDTH]) {
+    let matmul_constants: [<F as AbstractField>::F; WIDTH] =
+        POSEIDON2_INTERNAL_MATRIX_DIAG_16_BABYBEAR_MONTY
+            .iter()
+            .map(|x| <F as AbstractField>::F::from_wrapped_u32(x.as_canonical_u32()))
+            .collect::<Vec<_>>()
+            .try_into()
+            .unwrap();
+    matmul_internal(state, matmul_constants);
+    let monty_inverse = F::from_wrapped_u32(MONTY_INVERSE.as_canonical_u32());
+    state.iter_mut().for_each(|i| *i *= monty_inverse.clone());
+}
+
+#[cfg(test)]
+pub mod permute_tests {
+    use p3_baby_bear::BabyBear;
+    use p3_field::{AbstractField, PrimeField32};
+    use p3_symmetric::Permutation;
+    use sp1_core_executor::{syscalls::SyscallCode, Executor, Instruction, Opcode, Program};
+    use sp1_stark::{inner_perm, CpuProver, SP1CoreOpts};
+
+    use crate::utils::{self, run_test};
+
+    use super::WIDTH;
+
+    const STATE_PTR: u32 = 100;
+
+    fn test_input() -> [u32; WIDTH] {
+        std::array::from_fn(|i| (i as u32 + 1) * 0x0101_0101 % BabyBear::ORDER_U32)
+    }
+
+    pub fn poseidon2_permute_program(input: [u32; WIDTH]) -> Program {
+        let mut instructions = vec![];
+        for (i, word) in input.into_iter().enumerate() {
+            instructions.extend(vec![
+                Instruction::new(Opcode::ADD, 29, 0, word, false, true),
+                Instruction::new(Opcode::ADD, 30, 0, STATE_PTR + i as u32 * 4, false, true),
+                Instruction::new(Opcode::SW, 29, 30, 0, false, true),
+            ]);
+        }
+        instructions.extend(vec![
+            Instruction::new(Opcode::ADD, 5, 0, SyscallCode::POSEIDON2_PERMUTE as u32, false, true),
+            Instruction::new(Opcode::ADD, 10, 0, STATE_PTR, false, true),
+            Instruction::new(Opcode::ADD, 11, 0, WIDTH as u32, false, true),
+            Instruction::new(Opcode::ECALL, 5, 10, 11, false, false),
+        ]);
+
+        Program::new(instructions, 0, 0)
+    }
+
+    #[test]
+    fn test_poseidon2_permute_matches_recursion_perm() {
+        utils::setup_logger();
+        let input = test_input();
+        let mut runtime = Executor::new(poseidon2_permute_program(input), SP1CoreOpts::default());
+        runtime.run().unwrap();
+
+        let expected = inner_perm().permute(input.map(BabyBear::from_canonical_u32));
+        for i in 0..WIDTH {
+            let word = runtime.word(STATE_PTR + i as u32 * 4);
+            assert_eq!(word, expected[i].as_canonical_u32());
+        }
+    }
+
+    #[test]
+    fn test_poseidon2_permute_prove_babybear() {
+        utils::setup_logger();
+        let program = poseidon2_permute_program(test_input());
+        run_test::<CpuProver<_, _>>(program).unwrap();
+    }
+}
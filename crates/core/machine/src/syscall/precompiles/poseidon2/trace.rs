@@ -0,0 +1,193 @@
+use std::borrow::BorrowMut;
+
+use p3_field::PrimeField32;
+use p3_matrix::{dense::RowMajorMatrix, Matrix};
+use sp1_core_executor::{
+    events::{ByteRecord, Poseidon2PermuteEvent},
+    ExecutionRecord, Program,
+};
+use sp1_primitives::RC_16_30_U32;
+use sp1_stark::{air::MachineAir, MachineRecord};
+
+use crate::utils::pad_rows;
+
+use super::{
+    columns::{Poseidon2PermutationCols, Poseidon2PermuteCols, NUM_POSEIDON2_PERMUTE_COLS},
+    external_linear_layer, internal_linear_layer, Poseidon2PermuteChip, NUM_EXTERNAL_ROUNDS,
+    NUM_INTERNAL_ROUNDS, WIDTH,
+};
+
+impl<F: PrimeField32> MachineAir<F> for Poseidon2PermuteChip {
+    type Record = ExecutionRecord;
+    type Program = Program;
+
+    fn name(&self) -> String {
+        "Poseidon2Permute".to_string()
+    }
+
+    fn generate_trace(
+        &self,
+        input: &ExecutionRecord,
+        output: &mut ExecutionRecord,
+    ) -> RowMajorMatrix<F> {
+        let mut new_byte_lookup_events = Vec::new();
+        let mut rows = input
+            .poseidon2_permute_events
+            .iter()
+            .map(|event| {
+                let mut row = [F::zero(); NUM_POSEIDON2_PERMUTE_COLS];
+                let cols: &mut Poseidon2PermuteCols<F> = row.as_mut_slice().borrow_mut();
+                self.event_to_row(event, cols, &mut new_byte_lookup_events);
+                row
+            })
+            .collect::<Vec<_>>();
+
+        let mut record = ExecutionRecord::default();
+        record.add_byte_lookup_events(new_byte_lookup_events);
+        output.append(&mut record);
+
+        // Padding rows hold the permutation of the zero state.
+        pad_rows(&mut rows, || {
+            let mut row = [F::zero(); NUM_POSEIDON2_PERMUTE_COLS];
+            let cols: &mut Poseidon2PermuteCols<F> = row.as_mut_slice().borrow_mut();
+            populate_permutation(&mut cols.permutation, [F::zero(); WIDTH]);
+            row
+        });
+
+        // Convert the trace to a row major matrix.
+        let mut trace = RowMajorMatrix::new(
+            rows.into_iter().flatten().collect::<Vec<_>>(),
+            NUM_POSEIDON2_PERMUTE_COLS,
+        );
+
+        // Write the nonces to the trace.
+        for i in 0..trace.height() {
+            let cols: &mut Poseidon2PermuteCols<F> = trace.values
+                [i * NUM_POSEIDON2_PERMUTE_COLS..(i + 1) * NUM_POSEIDON2_PERMUTE_COLS]
+                .borrow_mut();
+            cols.nonce = F::from_canonical_usize(i);
+        }
+
+        trace
+    }
+
+    fn included(&self, shard: &Self::Record) -> bool {
+        !shard.poseidon2_permute_events.is_empty()
+    }
+}
+
+impl Poseidon2PermuteChip {
+    fn event_to_row<F: PrimeField32>(
+        &self,
+        event: &Poseidon2PermuteEvent,
+        cols: &mut Poseidon2PermuteCols<F>,
+        new_byte_lookup_events: &mut impl ByteRecord,
+    ) {
+        cols.shard = F::from_canonical_u32(event.shard);
+        cols.channel = F::from_canonical_u8(event.channel);
+        cols.clk = F::from_canonical_u32(event.clk);
+        cols.state_ptr = F::from_canonical_u32(event.state_ptr);
+        cols.is_real = F::one();
+
+        for i in 0..WIDTH {
+            cols.state_memory[i].populate(
+                event.channel,
+                event.state_records[i],
+                new_byte_lookup_events,
+            );
+            new_byte_lookup_events.add_u8_range_checks(
+                event.shard,
+                event.channel,
+                &event.output[i].to_le_bytes(),
+            );
+            cols.input_range_checks[i].populate(event.input[i]);
+            cols.output_range_checks[i].populate(event.output[i]);
+        }
+
+        let output =
+            populate_permutation(&mut cols.permutation, event.input.map(F::from_canonical_u32));
+        debug_assert_eq!(output, event.output.map(F::from_canonical_u32));
+    }
+}
+
+/// Populates the permutation columns for `input` and returns the permuted state.
+fn populate_permutation<F: PrimeField32>(
+    cols: &mut Poseidon2PermutationCols<F>,
+    input: [F; WIDTH],
+) -> [F; WIDTH] {
+    cols.external_rounds_state[0] = input;
+
+    // Apply the first half of external rounds.
+    for r in 0..NUM_EXTERNAL_ROUNDS / 2 {
+        let next_state = populate_external_round(cols, r);
+        if r == NUM_EXTERNAL_ROUNDS / 2 - 1 {
+            cols.internal_rounds_state = next_state;
+        } else {
+            cols.external_rounds_state[r + 1] = next_state;
+        }
+    }
+
+    // Apply the internal rounds.
+    cols.external_rounds_state[NUM_EXTERNAL_ROUNDS / 2] = populate_internal_rounds(cols);
+
+    // Apply the second half of external rounds.
+    for r in NUM_EXTERNAL_ROUNDS / 2..NUM_EXTERNAL_ROUNDS {
+        let next_state = populate_external_round(cols, r);
+        if r == NUM_EXTERNAL_ROUNDS - 1 {
+            cols.output_state = next_state;
+        } else {
+            cols.external_rounds_state[r + 1] = next_state;
+        }
+    }
+
+    cols.output_state
+}
+
+fn populate_external_round<F: PrimeField32>(
+    cols: &mut Poseidon2PermutationCols<F>,
+    r: usize,
+) -> [F; WIDTH] {
+    let mut round_state = cols.external_rounds_state[r];
+
+    // For the first round, apply the linear layer.
+    if r == 0 {
+        external_linear_layer(&mut round_state);
+    }
+
+    // Add round constants and apply the sboxes. The sbox output is folded into the constraint
+    // for the linear layer, so only the x^3 part gets its own column.
+    let round = if r < NUM_EXTERNAL_ROUNDS / 2 { r } else { r + NUM_INTERNAL_ROUNDS };
+    let mut state = [F::zero(); WIDTH];
+    for i in 0..WIDTH {
+        let add_rc = round_state[i] + F::from_wrapped_u32(RC_16_30_U32[round][i]);
+        let sbox_deg_3 = add_rc * add_rc * add_rc;
+        cols.external_rounds_sbox[r][i] = sbox_deg_3;
+        state[i] = sbox_deg_3 * sbox_deg_3 * add_rc;
+    }
+
+    // Apply the linear layer.
+    external_linear_layer(&mut state);
+    state
+}
+
+fn populate_internal_rounds<F: PrimeField32>(cols: &mut Poseidon2PermutationCols<F>) -> [F; WIDTH] {
+    let mut state = cols.internal_rounds_state;
+    for r in 0..NUM_INTERNAL_ROUNDS {
+        // Add the round constant to the 0th state element and apply the sbox.
+        let round = r + NUM_EXTERNAL_ROUNDS / 2;
+        let add_rc = state[0] + F::from_wrapped_u32(RC_16_30_U32[round][0]);
+        let sbox_deg_3 = add_rc * add_rc * add_rc;
+        cols.internal_rounds_sbox[r] = sbox_deg_3;
+
+        // Apply the linear layer.
+        state[0] = sbox_deg_3 * sbox_deg_3 * add_rc;
+        internal_linear_layer(&mut state);
+
+        // Only the 0th state element needs a column per round, since the rest of the state is a
+        // linear function of the state at the start of the internal rounds and earlier s0 values.
+        if r < NUM_INTERNAL_ROUNDS - 1 {
+            cols.internal_rounds_s0[r] = state[0];
+        }
+    }
+    state
+}
@@ -40,6 +40,16 @@ pub fn pad_to_power_of_two<const N: usize, T: Clone + Default>(values: &mut Vec<
     values.resize(n_real_rows.next_power_of_two() * N, T::default());
 }
 
+/// Pads `values` to [`padded_height`] rows of width `N`, so that the trace has at least
+/// `2^min_log_height` rows.
+pub fn pad_to_min_log_height<const N: usize, T: Clone + Default>(
+    values: &mut Vec<T>,
+    min_log_height: Option<usize>,
+) {
+    debug_assert!(values.len() % N == 0);
+    values.resize(padded_height(values.len() / N, min_log_height) * N, T::default());
+}
+
 pub fn limbs_from_prev_access<T: Copy, N: ArrayLength, M: MemoryCols<T>>(
     cols: &[M],
 ) -> Limbs<T, N> {
@@ -79,6 +89,13 @@ pub fn pad_rows_fixed<R: Clone>(
     rows.resize(next_power_of_two(nb_rows, size_log2), dummy_row);
 }
 
+/// Returns the next power of two that is >= `n` and >= 16, raised to `2^min_log_height` if that is
+/// larger. Unlike a fixed power, a minimum height never panics when `n` outgrows it.
+pub fn padded_height(n: usize, min_log_height: Option<usize>) -> usize {
+    let height = next_power_of_two(n, None);
+    min_log_height.map_or(height, |log_height| height.max(1 << log_height))
+}
+
 /// Returns the next power of two that is >= `n` and >= 16. If `fixed_power` is set, it will return
 /// `2^fixed_power` after checking that `n <= 2^fixed_power`.
 pub fn next_power_of_two(n: usize, fixed_power: Option<usize>) -> usize {
@@ -231,49 +231,16 @@ where
                             // Wait for our turn to update the state.
                             record_gen_sync.wait_for_turn(index);
 
-                            // Update the public values & prover state for the shards which contain
-                            // "cpu events".
+                            // Assign the public values of the shards, and add the deferred shards
+                            // that are ready to be committed to.
                             let mut state = state.lock().unwrap();
-                            for record in records.iter_mut() {
-                                state.shard += 1;
-                                state.execution_shard = record.public_values.execution_shard;
-                                state.start_pc = record.public_values.start_pc;
-                                state.next_pc = record.public_values.next_pc;
-                                state.committed_value_digest =
-                                    record.public_values.committed_value_digest;
-                                state.deferred_proofs_digest =
-                                    record.public_values.deferred_proofs_digest;
-                                record.public_values = *state;
-                            }
-
-                            // Defer events that are too expensive to include in every shard.
-                            let mut deferred = deferred.lock().unwrap();
-                            for record in records.iter_mut() {
-                                deferred.append(&mut record.defer());
-                            }
-
-                            // See if any deferred shards are ready to be commited to.
-                            let mut deferred = deferred.split(done, opts.split_opts);
-
-                            // Update the public values & prover state for the shards which do not
-                            // contain "cpu events" before committing to them.
-                            if !done {
-                                state.execution_shard += 1;
-                            }
-                            for record in deferred.iter_mut() {
-                                state.shard += 1;
-                                state.previous_init_addr_bits =
-                                    record.public_values.previous_init_addr_bits;
-                                state.last_init_addr_bits =
-                                    record.public_values.last_init_addr_bits;
-                                state.previous_finalize_addr_bits =
-                                    record.public_values.previous_finalize_addr_bits;
-                                state.last_finalize_addr_bits =
-                                    record.public_values.last_finalize_addr_bits;
-                                state.start_pc = state.next_pc;
-                                record.public_values = *state;
-                            }
-                            records.append(&mut deferred);
+                            assign_public_values(
+                                &mut records,
+                                &mut state,
+                                &mut deferred.lock().unwrap(),
+                                done,
+                                opts,
+                            );
 
                             // Collect the checkpoints to be used again in the phase 2 prover.
                             let mut checkpoints = checkpoints.lock().unwrap();
@@ -424,49 +391,16 @@ where
                             // Wait for our turn to update the state.
                             record_gen_sync.wait_for_turn(index);
 
-                            // Update the public values & prover state for the shards which contain
-                            // "cpu events".
+                            // Assign the public values of the shards, and add the deferred shards
+                            // that are ready to be committed to.
                             let mut state = state.lock().unwrap();
-                            for record in records.iter_mut() {
-                                state.shard += 1;
-                                state.execution_shard = record.public_values.execution_shard;
-                                state.start_pc = record.public_values.start_pc;
-                                state.next_pc = record.public_values.next_pc;
-                                state.committed_value_digest =
-                                    record.public_values.committed_value_digest;
-                                state.deferred_proofs_digest =
-                                    record.public_values.deferred_proofs_digest;
-                                record.public_values = *state;
-                            }
-
-                            // Defer events that are too expensive to include in every shard.
-                            let mut deferred = deferred.lock().unwrap();
-                            for record in records.iter_mut() {
-                                deferred.append(&mut record.defer());
-                            }
-
-                            // See if any deferred shards are ready to be commited to.
-                            let mut deferred = deferred.split(done, opts.split_opts);
-
-                            // Update the public values & prover state for the shards which do not
-                            // contain "cpu events" before committing to them.
-                            if !done {
-                                state.execution_shard += 1;
-                            }
-                            for record in deferred.iter_mut() {
-                                state.shard += 1;
-                                state.previous_init_addr_bits =
-                                    record.public_values.previous_init_addr_bits;
-                                state.last_init_addr_bits =
-                                    record.public_values.last_init_addr_bits;
-                                state.previous_finalize_addr_bits =
-                                    record.public_values.previous_finalize_addr_bits;
-                                state.last_finalize_addr_bits =
-                                    record.public_values.last_finalize_addr_bits;
-                                state.start_pc = state.next_pc;
-                                record.public_values = *state;
-                            }
-                            records.append(&mut deferred);
+                            assign_public_values(
+                                &mut records,
+                                &mut state,
+                                &mut deferred.lock().unwrap(),
+                                done,
+                                opts,
+                            );
 
                             // Let another worker update the state.
                             record_gen_sync.advance_turn();
@@ -690,6 +624,54 @@ where
     run_test_machine_with_prover::<SC, A, CpuProver<_, _>>(records, machine, pk, vk)
 }
 
+/// Assigns the public values of the records traced from one checkpoint, continuing from the
+/// prover `state`, then appends the shards of deferred events that are ready to be committed to.
+///
+/// `done` is whether the checkpoint is the last one of the execution, in which case every deferred
+/// event is committed to.
+pub(crate) fn assign_public_values(
+    records: &mut Vec<ExecutionRecord>,
+    state: &mut PublicValues<u32, u32>,
+    deferred: &mut ExecutionRecord,
+    done: bool,
+    opts: SP1CoreOpts,
+) {
+    // Update the public values & prover state for the shards which contain "cpu events".
+    for record in records.iter_mut() {
+        state.shard += 1;
+        state.execution_shard = record.public_values.execution_shard;
+        state.start_pc = record.public_values.start_pc;
+        state.next_pc = record.public_values.next_pc;
+        state.committed_value_digest = record.public_values.committed_value_digest;
+        state.deferred_proofs_digest = record.public_values.deferred_proofs_digest;
+        record.public_values = *state;
+    }
+
+    // Defer events that are too expensive to include in every shard.
+    for record in records.iter_mut() {
+        deferred.append(&mut record.defer());
+    }
+
+    // See if any deferred shards are ready to be commited to.
+    let mut deferred = deferred.split(done, opts.split_opts);
+
+    // Update the public values & prover state for the shards which do not contain "cpu events"
+    // before committing to them.
+    if !done {
+        state.execution_shard += 1;
+    }
+    for record in deferred.iter_mut() {
+        state.shard += 1;
+        state.previous_init_addr_bits = record.public_values.previous_init_addr_bits;
+        state.last_init_addr_bits = record.public_values.last_init_addr_bits;
+        state.previous_finalize_addr_bits = record.public_values.previous_finalize_addr_bits;
+        state.last_finalize_addr_bits = record.public_values.last_finalize_addr_bits;
+        state.start_pc = state.next_pc;
+        record.public_values = *state;
+    }
+    records.append(&mut deferred);
+}
+
 fn trace_checkpoint(
     program: Program,
     file: &File,
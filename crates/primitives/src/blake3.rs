@@ -0,0 +1,77 @@
+//! The BLAKE3 compression function, proven by the `BLAKE3_COMPRESS` precompile.
+
+/// The number of words in the state of the compression function.
+pub const BLAKE3_STATE_WORDS: usize = 16;
+
+/// The number of words in a message block.
+pub const BLAKE3_BLOCK_WORDS: usize = 16;
+
+/// The number of rounds of the compression function.
+pub const BLAKE3_NUM_ROUNDS: usize = 7;
+
+/// The BLAKE3 initialization vector, which is the same as the SHA-256 one.
+pub const BLAKE3_IV: [u32; 8] = [
+    0x6A09E667, 0xBB67AE85, 0x3C6EF372, 0xA54FF53A, 0x510E527F, 0x9B05688C, 0x1F83D9AB, 0x5BE0CD19,
+];
+
+/// The permutation applied to the message words after each round.
+pub const BLAKE3_MSG_PERMUTATION: [usize; BLAKE3_BLOCK_WORDS] =
+    [2, 6, 3, 10, 7, 0, 4, 13, 1, 11, 12, 5, 9, 14, 15, 8];
+
+/// The state words mixed by each of the eight applications of `G` in a round. The `i`-th one also
+/// mixes in the message words `2 * i` and `2 * i + 1` of the round.
+pub const BLAKE3_G_INDICES: [[usize; 4]; 8] = [
+    [0, 4, 8, 12],
+    [1, 5, 9, 13],
+    [2, 6, 10, 14],
+    [3, 7, 11, 15],
+    [0, 5, 10, 15],
+    [1, 6, 11, 12],
+    [2, 7, 8, 13],
+    [3, 4, 9, 14],
+];
+
+/// The indices into the original message block of the message words used by `round`.
+pub fn blake3_message_schedule(round: usize) -> [usize; BLAKE3_BLOCK_WORDS] {
+    let mut schedule = core::array::from_fn(|i| i);
+    for _ in 0..round {
+        schedule = BLAKE3_MSG_PERMUTATION.map(|i| schedule[i]);
+    }
+    schedule
+}
+
+/// The `G` function, which mixes the message words `x` and `y` into the state words `a`, `b`, `c`
+/// and `d`.
+pub fn blake3_g(state: &mut [u32; BLAKE3_STATE_WORDS], [a, b, c, d]: [usize; 4], x: u32, y: u32) {
+    state[a] = state[a].wrapping_add(state[b]).wrapping_add(x);
+    state[d] = (state[d] ^ state[a]).rotate_right(16);
+    state[c] = state[c].wrapping_add(state[d]);
+    state[b] = (state[b] ^ state[c]).rotate_right(12);
+    state[a] = state[a].wrapping_add(state[b]).wrapping_add(y);
+    state[d] = (state[d] ^ state[a]).rotate_right(8);
+    state[c] = state[c].wrapping_add(state[d]);
+    state[b] = (state[b] ^ state[c]).rotate_right(7);
+}
+
+/// Compresses `block` into `state`, and returns the full 16-word output.
+///
+/// `state` is the initial state of the compression function: the input chaining value, the first
+/// four words of [`BLAKE3_IV`], the low and high words of the counter, the block length and the
+/// flags. The output chaining value is the first eight words of the output.
+pub fn blake3_compress(
+    state: [u32; BLAKE3_STATE_WORDS],
+    block: [u32; BLAKE3_BLOCK_WORDS],
+) -> [u32; BLAKE3_STATE_WORDS] {
+    let mut v = state;
+    for round in 0..BLAKE3_NUM_ROUNDS {
+        let schedule = blake3_message_schedule(round);
+        for (i, indices) in BLAKE3_G_INDICES.into_iter().enumerate() {
+            blake3_g(&mut v, indices, block[schedule[2 * i]], block[schedule[2 * i + 1]]);
+        }
+    }
+    for i in 0..8 {
+        v[i] ^= v[i + 8];
+        v[i + 8] ^= state[i];
+    }
+    v
+}
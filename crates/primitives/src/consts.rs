@@ -4,6 +4,50 @@ pub const MAXIMUM_MEMORY_SIZE: u32 = u32::MAX;
 /// The size of a word in bytes.
 pub const WORD_SIZE: usize = 4;
 
+/// The number of 32 bit words that the public values digest is composed of.
+pub const PV_DIGEST_NUM_WORDS: usize = 8;
+
+/// The number of independent public values channels. Channel 0 is the public values stream, and
+/// the digests of the other channels are appended to it at halt if any of them was used.
+pub const NUM_PUBLIC_VALUES_CHANNELS: usize = 4;
+
+/// The length the `HINT_LEN` syscall returns once every element of the input stream has been read.
+///
+/// The executor used to panic on `HINT_LEN` past the end of the stream. It now returns this value,
+/// which programs built against `sp1_zkvm::io` report as `IoError::EndOfStream`. Programs built
+/// before that do not check for it: they take it as the length of the next element and fail on
+/// the `HINT_READ` that follows instead.
+pub const HINT_LEN_END_OF_STREAM: u32 = u32::MAX;
+
+/// The config key under which the host provides the timestamp that the guest commits with
+/// `sys_commit_time`, as 8 little-endian bytes.
+pub const TIME_CONFIG_KEY: &str = "sp1.time";
+
+/// The exit codes reserved by the zkVM, re-exported to programs by `sp1_zkvm::exit` and classified
+/// by the executor as `sp1_core_executor::syscalls::ExitCode`.
+pub mod exit {
+    /// The program ran to completion.
+    pub const EXIT_OK: u32 = 0;
+
+    /// The program panicked, as reported by `sys_panic`.
+    pub const EXIT_PANIC: u32 = 1;
+
+    /// A `zkvm_assert!` failed.
+    pub const EXIT_ASSERTION_FAILED: u32 = 0xA55E_0001;
+
+    /// The guest allocator ran out of memory.
+    pub const EXIT_OOM: u32 = 0xA55E_0002;
+
+    /// The program aborted without a more specific reason.
+    ///
+    /// This is the conventional code for `io::abort` when no program-defined code applies.
+    pub const EXIT_ABORT: u32 = 0xA55E_0003;
+
+    /// `io::rand_below` rejected too many random values in a row, which only happens if the host's
+    /// random values are far from uniform.
+    pub const EXIT_RAND_EXHAUSTED: u32 = 0xA55E_0004;
+}
+
 /// Converts a slice of words to a byte vector in little endian.
 pub fn words_to_bytes_le_vec(words: &[u32]) -> Vec<u8> {
     words.iter().flat_map(|word| word.to_le_bytes().to_vec()).collect::<Vec<_>>()
@@ -6,6 +6,7 @@ use p3_baby_bear::{BabyBear, DiffusionMatrixBabyBear};
 use p3_field::AbstractField;
 use p3_poseidon2::{Poseidon2, Poseidon2ExternalMatrixGeneral};
 
+pub mod blake3;
 pub mod consts;
 pub mod types;
 
@@ -150,10 +150,10 @@ impl<C: SP1ProverComponents> SP1Prover<C> {
         let core_machine = RiscvAir::machine(CoreSC::default());
         let core_prover = C::CoreProver::new(core_machine);
 
-        let compress_machine = CompressAir::machine(InnerSC::default());
+        let compress_machine = CompressAir::compress_machine(InnerSC::default());
         let compress_prover = C::CompressProver::new(compress_machine);
 
-        let shrink_machine = ShrinkAir::wrap_machine_dyn(InnerSC::compressed());
+        let shrink_machine = ShrinkAir::shrink_machine(InnerSC::compressed());
         let shrink_prover = C::ShrinkProver::new(shrink_machine);
 
         let wrap_machine = WrapAir::wrap_machine(OuterSC::default());
@@ -1146,4 +1146,82 @@ pub mod tests {
         setup_logger();
         test_e2e_with_deferred_proofs_prover::<DefaultProverComponents>()
     }
+
+    /// Tests that the syscall `sp1_zkvm::io::verify_proof` issues accepts a compressed proof under
+    /// its verifying key and public values, and rejects it under another verifying key.
+    #[test]
+    #[serial]
+    fn test_verify_proof() -> Result<()> {
+        use std::panic::{catch_unwind, AssertUnwindSafe};
+
+        use sp1_core_executor::{syscalls::SyscallCode, Instruction, Opcode};
+        use sp1_core_machine::utils::prove_with_context;
+
+        setup_logger();
+        let prover: SP1Prover = SP1Prover::new();
+        let opts = SP1ProverOpts::default();
+
+        tracing::info!("prove and compress the inner proof");
+        let elf = include_bytes!("../../../tests/fibonacci/elf/riscv32im-succinct-zkvm-elf");
+        let (inner_pk, inner_vk) = prover.setup(elf);
+        let inner_proof =
+            prover.prove_core(&inner_pk, &SP1Stdin::new(), opts, Default::default())?;
+        let digest = inner_proof.public_values.digest();
+        let inner_proof = prover.compress(&inner_vk, inner_proof, vec![], opts)?;
+        let mut stdin = SP1Stdin::new();
+        stdin.write_proof(inner_proof.proof, inner_vk.vk.clone());
+
+        // The instructions `sp1_zkvm::io::verify_proof(&vkey, public_values)` runs: store the
+        // verifying key digest and the SHA-256 digest of the public values, and verify.
+        let digest: [u32; 8] = core::array::from_fn(|i| {
+            u32::from_le_bytes(digest[4 * i..4 * i + 4].try_into().unwrap())
+        });
+        let verify_program = |vkey: [u32; 8]| {
+            let store = |addr: u32, words: [u32; 8]| {
+                words.into_iter().enumerate().flat_map(move |(i, word)| {
+                    [
+                        Instruction::new(Opcode::ADD, 29, 0, word, false, true),
+                        Instruction::new(Opcode::SW, 29, 0, addr + 4 * i as u32, false, true),
+                    ]
+                })
+            };
+            let mut instructions =
+                store(0x1000, vkey).chain(store(0x1100, digest)).collect::<Vec<_>>();
+            instructions.extend([
+                Instruction::new(
+                    Opcode::ADD,
+                    5,
+                    0,
+                    SyscallCode::VERIFY_SP1_PROOF as u32,
+                    false,
+                    true,
+                ),
+                Instruction::new(Opcode::ADD, 10, 0, 0x1000, false, true),
+                Instruction::new(Opcode::ADD, 11, 0, 0x1100, false, true),
+                Instruction::new(Opcode::ECALL, 5, 10, 11, false, false),
+            ]);
+            Program::new(instructions, 0, 0)
+        };
+
+        tracing::info!("prove the verify program");
+        let program = verify_program(inner_vk.hash_u32());
+        let (pk, vk) = prover.core_prover.setup(&program);
+        let context = SP1Context::builder().subproof_verifier(Arc::new(&prover)).build();
+        let (proof, _, _) =
+            prove_with_context(&prover.core_prover, &pk, program, &stdin, opts.core_opts, context)?;
+        let machine = prover.core_prover.machine();
+        machine.verify(&vk, &proof, &mut machine.config().challenger())?;
+
+        tracing::info!("execute the verify program with another verifying key");
+        let mut vkey = inner_vk.hash_u32();
+        vkey[0] ^= 1;
+        let context = SP1Context::builder().subproof_verifier(Arc::new(&prover)).build();
+        let mut runtime = Executor::with_context(verify_program(vkey), opts.core_opts, context);
+        for (proof, vkey) in stdin.proofs.iter() {
+            runtime.write_proof(proof.clone(), vkey.clone());
+        }
+        assert!(catch_unwind(AssertUnwindSafe(|| runtime.run())).is_err());
+
+        Ok(())
+    }
 }
@@ -249,7 +249,7 @@ pub mod tests {
 
     use crate::{
         challenger::{CanCopyChallenger, CanObserveVariable, DuplexChallengerVariable},
-        utils::tests::run_test_recursion_with_prover,
+        utils::tests::{run_test_recursion_shrink, run_test_recursion_with_prover},
         BabyBearFriConfig,
     };
 
@@ -358,4 +358,16 @@ pub mod tests {
             >(BabyBearPoseidon2::new(), FIBONACCI_ELF, SP1CoreOpts::default(), Some(2));
         run_test_recursion_with_prover::<CpuProver<_, _>>(operations, stream);
     }
+
+    #[test]
+    fn test_verify_shard_shrink_machine() {
+        let (operations, stream) =
+            build_verify_shard_with_provers::<
+                InnerConfig,
+                BabyBearPoseidon2,
+                CpuProver<_, _>,
+                CpuProver<_, _>,
+            >(BabyBearPoseidon2::new(), FIBONACCI_ELF, SP1CoreOpts::default(), Some(2));
+        run_test_recursion_shrink(operations, stream);
+    }
 }
@@ -123,7 +123,7 @@ pub(crate) mod tests {
     use sp1_recursion_compiler::{asm::AsmConfig, circuit::AsmCompiler, ir::DslIr};
 
     use sp1_recursion_compiler::ir::TracedVec;
-    use sp1_recursion_core_v2::{machine::RecursionAir, Runtime};
+    use sp1_recursion_core_v2::{machine::RecursionAir, shape::RecursionShape, Runtime};
     use sp1_stark::{
         baby_bear_poseidon2::BabyBearPoseidon2, CpuProver, InnerChallenge, InnerVal, MachineProver,
     };
@@ -178,4 +178,40 @@ pub(crate) mod tests {
     ) {
         run_test_recursion_with_prover::<CpuProver<_, _>>(operations, witness_stream)
     }
+
+    /// Compiles and runs a program like [`run_test_recursion_with_prover`], proves it with the
+    /// shrink machine pinned to the program's own shape, and checks that the proof has exactly
+    /// that shape.
+    pub(crate) fn run_test_recursion_shrink(
+        operations: TracedVec<DslIr<AsmConfig<F, EF>>>,
+        witness_stream: impl IntoIterator<Item = WitnessBlock<AsmConfig<F, EF>>>,
+    ) {
+        setup_logger();
+
+        let mut compiler = AsmCompiler::<AsmConfig<F, EF>>::default();
+        let program = Arc::new(compiler.compile(operations));
+
+        let mut runtime = Runtime::<F, EF, _>::new(program.clone(), SC::default().perm);
+        runtime.witness_stream.extend(witness_stream);
+        runtime.run().unwrap();
+
+        let shape = RecursionAir::<F, 3, 0>::shrink_shape(&program);
+        let machine = RecursionAir::<F, 3, 0>::shrink_machine(SC::default(), &program);
+        let (pk, vk) = machine.setup(&program);
+        let proof = run_test_machine_with_prover::<_, _, CpuProver<_, _>>(
+            vec![runtime.record],
+            machine,
+            pk,
+            vk,
+        )
+        .unwrap_or_else(|e| panic!("Verification failed: {:?}", e));
+
+        let shard_proof = &proof.shard_proofs[0];
+        let inner = shard_proof
+            .chip_ordering
+            .iter()
+            .map(|(chip, &i)| (chip.clone(), shard_proof.opened_values.chips[i].log_degree))
+            .collect();
+        assert_eq!(RecursionShape { inner }.diff(&shape), vec![]);
+    }
 }
@@ -185,6 +185,8 @@ where
             opcode,
             mult: C::F::zero(),
             addrs: BaseAluIo { out: dst.write(self), in1: lhs.read(self), in2: rhs.read(self) },
+            flag_addr: Address(C::F::zero()),
+            flag_mult: C::F::zero(),
         })
     }
 
@@ -199,6 +201,8 @@ where
             opcode,
             mult: C::F::zero(),
             addrs: ExtAluIo { out: dst.write(self), in1: lhs.read(self), in2: rhs.read(self) },
+            flag_addr: Address(C::F::zero()),
+            flag_mult: C::F::zero(),
         })
     }
 
@@ -630,7 +634,7 @@ where
             }
         });
         debug_assert!(self.addr_to_mult.is_empty());
-        RecursionProgram { instructions, total_memory, traces }
+        RecursionProgram { instructions, total_memory, traces, ..Default::default() }
     }
 }
 
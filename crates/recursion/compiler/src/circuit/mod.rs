@@ -110,9 +110,10 @@ mod tests {
                 fixed_log2_rows: Some(((EXP_REVERSE_BITS_LEN_OPERATIONS - 1).ilog2() + 1) as usize),
                 pad: true,
             }),
-            A::FriFold(FriFoldChip::<DEGREE> {
-                fixed_log2_rows: Some(((FRI_FOLD_OPERATIONS - 1).ilog2() + 1) as usize),
-                pad: true,
+            A::FriFold({
+                let mut chip = FriFoldChip::<DEGREE>::default();
+                chip.fixed_log2_rows = Some(((FRI_FOLD_OPERATIONS - 1).ilog2() + 1) as usize);
+                chip
             }),
         ]
         .into_iter()
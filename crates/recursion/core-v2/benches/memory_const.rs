@@ -0,0 +1,48 @@
+use std::sync::Arc;
+
+use criterion::*;
+use p3_field::AbstractField;
+use sp1_recursion_core_v2::{
+    chips::mem::MemoryConstChip, runtime::instruction as instr, ExecutionRecord, MemAccessKind,
+    RecursionProgram,
+};
+use sp1_stark::{
+    air::MachineAir, baby_bear_poseidon2::BabyBearPoseidon2, Chip, StarkGenericConfig,
+};
+
+type SC = BabyBearPoseidon2;
+type F = <SC as StarkGenericConfig>::Val;
+type EF = <SC as StarkGenericConfig>::Challenge;
+
+/// Generates the permutation trace of a `MemoryConst` chip with a few real rows, pinned to a height
+/// of `2^log_height`, so that almost every row is padding.
+fn padded_permutation_trace(c: &mut Criterion) {
+    let mut group = c.benchmark_group("memory_const_permutation_trace");
+    for log_height in [14, 16, 18] {
+        let mut program = RecursionProgram::<F> {
+            instructions: (0..16)
+                .map(|addr| instr::mem(MemAccessKind::Write, 1, addr, addr))
+                .collect(),
+            ..Default::default()
+        };
+        program.set_fixed_log2_rows("MemoryConst", log_height);
+
+        let chip = Chip::new(MemoryConstChip::<F>::default());
+        let preprocessed = chip.generate_preprocessed_trace(&program).unwrap();
+        let record = ExecutionRecord {
+            program: Arc::new(program),
+            mem_const_count: 16,
+            ..Default::default()
+        };
+        let main = chip.generate_trace(&record, &mut ExecutionRecord::default());
+        let random_elements = [EF::from_canonical_u32(7), EF::from_canonical_u32(11)];
+
+        group.bench_function(BenchmarkId::from_parameter(format!("2^{log_height} rows")), |b| {
+            b.iter(|| chip.generate_permutation_trace(Some(&preprocessed), &main, &random_elements))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, padded_permutation_trace);
+criterion_main!(benches);
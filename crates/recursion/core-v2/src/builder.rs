@@ -1,5 +1,4 @@
 use p3_air::AirBuilderWithPublicValues;
-use p3_field::AbstractField;
 use sp1_recursion_core::air::Block;
 use sp1_stark::{
     air::{AirInteraction, BaseAirBuilder, MachineAirBuilder},
@@ -21,9 +20,7 @@ pub trait RecursionAirBuilder: BaseAirBuilder {
         val: E,
         mult: impl Into<Self::Expr>,
     ) {
-        let mut padded_value = core::array::from_fn(|_| Self::Expr::zero());
-        padded_value[0] = val.into();
-        self.send_block(Address(addr.0.into()), Block(padded_value), mult)
+        self.send_block(Address(addr.0.into()), Block::from_base(val.into()), mult)
     }
 
     fn send_block<E: Into<Self::Expr>>(
@@ -39,15 +36,28 @@ pub trait RecursionAirBuilder: BaseAirBuilder {
         ));
     }
 
+    /// Like [`Self::send_block`] with multiplicity `cond * mult`: the block is sent with
+    /// multiplicity `mult` when the boolean `cond` is one, and not at all when it is zero.
+    ///
+    /// The caller must constrain `cond` to be boolean. Interaction multiplicities must be of
+    /// degree 1, so usually one of `cond` and `mult` is a constant.
+    fn send_block_if<E: Into<Self::Expr>>(
+        &mut self,
+        cond: impl Into<Self::Expr>,
+        addr: Address<E>,
+        val: Block<E>,
+        mult: impl Into<Self::Expr>,
+    ) {
+        self.send_block(addr, val, cond.into() * mult.into())
+    }
+
     fn receive_single<E: Into<Self::Expr>>(
         &mut self,
         addr: Address<E>,
         val: E,
         mult: impl Into<Self::Expr>,
     ) {
-        let mut padded_value = core::array::from_fn(|_| Self::Expr::zero());
-        padded_value[0] = val.into();
-        self.receive_block(Address(addr.0.into()), Block(padded_value), mult)
+        self.receive_block(Address(addr.0.into()), Block::from_base(val.into()), mult)
     }
 
     fn receive_block<E: Into<Self::Expr>>(
@@ -62,4 +72,65 @@ pub trait RecursionAirBuilder: BaseAirBuilder {
             InteractionKind::Memory,
         ));
     }
+
+    /// Like [`Self::receive_block`] with multiplicity `cond * mult`, see [`Self::send_block_if`].
+    fn receive_block_if<E: Into<Self::Expr>>(
+        &mut self,
+        cond: impl Into<Self::Expr>,
+        addr: Address<E>,
+        val: Block<E>,
+        mult: impl Into<Self::Expr>,
+    ) {
+        self.receive_block(addr, val, cond.into() * mult.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use p3_air::{AirBuilder, PairBuilder};
+    use p3_baby_bear::BabyBear;
+    use p3_field::AbstractField;
+    use p3_matrix::Matrix;
+    use sp1_recursion_core::air::Block;
+    use sp1_stark::{Interaction, InteractionBuilder};
+
+    use super::RecursionAirBuilder;
+    use crate::Address;
+
+    type F = BabyBear;
+
+    /// Evaluates the values and the multiplicity of `interaction` on the given rows.
+    fn evaluate(interaction: &Interaction<F>, prep: &[F], main: &[F]) -> (Vec<F>, F) {
+        let values = interaction.values.iter().map(|value| value.apply::<F, F>(prep, main));
+        (values.collect(), interaction.multiplicity.apply::<F, F>(prep, main))
+    }
+
+    #[test]
+    fn block_if_matches_premultiplied_block() {
+        let mut builder = InteractionBuilder::<F>::new(1, 6);
+        let main = builder.main();
+        let row = main.row_slice(0);
+        let (cond, addr, val) = (row[0], Address(row[1]), Block([row[2], row[3], row[4], row[5]]));
+        let mult = builder.preprocessed().row_slice(0)[0];
+        let three = <InteractionBuilder<F> as AirBuilder>::Expr::from_canonical_u32(3);
+
+        builder.send_block_if(cond, addr, val, three.clone());
+        builder.send_block(addr, val, cond * three);
+        builder.receive_block_if(F::one(), addr, val, mult);
+        builder.receive_block(addr, val, mult);
+        let (sends, receives) = builder.interactions();
+
+        // The address and the block, after the condition.
+        let accessed = [5, 6, 7, 8, 9].map(F::from_canonical_u32);
+        for cond in [F::zero(), F::one()] {
+            let main = [&[cond][..], &accessed].concat();
+            let prep = [F::from_canonical_u32(4)];
+            for pair in [&sends, &receives] {
+                let conditional = evaluate(&pair[0], &prep, &main);
+                assert_eq!(conditional, evaluate(&pair[1], &prep, &main));
+                assert_eq!(conditional.0, accessed);
+            }
+            assert_eq!(evaluate(&sends[0], &prep, &main).1, cond * F::from_canonical_u32(3));
+        }
+    }
 }
@@ -1,19 +1,25 @@
 use core::borrow::Borrow;
 use p3_air::{Air, AirBuilder, BaseAir, PairBuilder};
-use p3_field::{Field, PrimeField32};
+use p3_field::{AbstractField, Field, PrimeField32};
 use p3_matrix::{dense::RowMajorMatrix, Matrix};
 use p3_maybe_rayon::prelude::*;
-use sp1_core_machine::utils::next_power_of_two;
 use sp1_derive::AlignedBorrow;
 use sp1_stark::air::MachineAir;
 use std::{borrow::BorrowMut, iter::zip};
 
-use crate::{builder::SP1RecursionAirBuilder, *};
+use crate::{
+    builder::SP1RecursionAirBuilder,
+    chips::{padded_trace_height, DebugInstrId},
+    *,
+};
 
 pub const NUM_BASE_ALU_ENTRIES_PER_ROW: usize = 8;
 
 #[derive(Default)]
-pub struct BaseAluChip {}
+pub struct BaseAluChip {
+    /// Pins the traces to `2^fixed_log2_rows` rows, overriding the program's minimum height.
+    pub fixed_log2_rows: Option<usize>,
+}
 
 pub const NUM_BASE_ALU_COLS: usize = core::mem::size_of::<BaseAluCols<u8>>();
 
@@ -29,6 +35,10 @@ pub const NUM_BASE_ALU_VALUE_COLS: usize = core::mem::size_of::<BaseAluValueCols
 #[repr(C)]
 pub struct BaseAluValueCols<F: Copy> {
     pub vals: BaseAluIo<F>,
+    /// Whether `in2` is nonzero, written out by `DivFChecked`.
+    pub flag: F,
+    /// The inverse of `in2`, or zero if `in2` is zero.
+    pub in2_inv: F,
 }
 
 pub const NUM_BASE_ALU_PREPROCESSED_COLS: usize =
@@ -50,7 +60,11 @@ pub struct BaseAluAccessCols<F: Copy> {
     pub is_sub: F,
     pub is_mul: F,
     pub is_div: F,
+    pub is_div_checked: F,
     pub mult: F,
+    pub flag_addr: Address<F>,
+    pub flag_mult: F,
+    pub instr_id: DebugInstrId<F>,
 }
 
 impl<F: Field> BaseAir<F> for BaseAluChip {
@@ -77,20 +91,26 @@ impl<F: PrimeField32> MachineAir<F> for BaseAluChip {
         let instrs = program
             .instructions
             .iter() // Faster than using `rayon` for some reason. Maybe vectorization?
-            .filter_map(|instruction| match instruction {
-                Instruction::BaseAlu(x) => Some(x),
+            .enumerate()
+            .filter_map(|(index, instruction)| match instruction {
+                Instruction::BaseAlu(x) => Some((index, x)),
                 _ => None,
             })
             .collect::<Vec<_>>();
 
         let nb_rows = instrs.len().div_ceil(NUM_BASE_ALU_ENTRIES_PER_ROW);
-        let padded_nb_rows = next_power_of_two(nb_rows, None);
+        let padded_nb_rows = padded_trace_height(
+            nb_rows,
+            self.fixed_log2_rows,
+            program,
+            &MachineAir::<F>::name(self),
+        );
         let mut values = vec![F::zero(); padded_nb_rows * NUM_BASE_ALU_PREPROCESSED_COLS];
         // Generate the trace rows & corresponding records for each chunk of events in parallel.
         let populate_len = instrs.len() * NUM_BASE_ALU_ACCESS_COLS;
         values[..populate_len].par_chunks_mut(NUM_BASE_ALU_ACCESS_COLS).zip_eq(instrs).for_each(
-            |(row, instr)| {
-                let BaseAluInstr { opcode, mult, addrs } = instr;
+            |(row, (index, instr))| {
+                let BaseAluInstr { opcode, mult, addrs, flag_addr, flag_mult } = instr;
                 let access: &mut BaseAluAccessCols<_> = row.borrow_mut();
                 *access = BaseAluAccessCols {
                     addrs: addrs.to_owned(),
@@ -98,13 +118,18 @@ impl<F: PrimeField32> MachineAir<F> for BaseAluChip {
                     is_sub: F::from_bool(false),
                     is_mul: F::from_bool(false),
                     is_div: F::from_bool(false),
+                    is_div_checked: F::from_bool(false),
                     mult: mult.to_owned(),
+                    flag_addr: flag_addr.to_owned(),
+                    flag_mult: flag_mult.to_owned(),
+                    instr_id: DebugInstrId::new(index),
                 };
                 let target_flag = match opcode {
                     BaseAluOpcode::AddF => &mut access.is_add,
                     BaseAluOpcode::SubF => &mut access.is_sub,
                     BaseAluOpcode::MulF => &mut access.is_mul,
                     BaseAluOpcode::DivF => &mut access.is_div,
+                    BaseAluOpcode::DivFChecked => &mut access.is_div_checked,
                 };
                 *target_flag = F::from_bool(true);
             },
@@ -121,14 +146,23 @@ impl<F: PrimeField32> MachineAir<F> for BaseAluChip {
     fn generate_trace(&self, input: &Self::Record, _: &mut Self::Record) -> RowMajorMatrix<F> {
         let events = &input.base_alu_events;
         let nb_rows = events.len().div_ceil(NUM_BASE_ALU_ENTRIES_PER_ROW);
-        let padded_nb_rows = next_power_of_two(nb_rows, None);
+        let padded_nb_rows = padded_trace_height(
+            nb_rows,
+            self.fixed_log2_rows,
+            &input.program,
+            &MachineAir::<F>::name(self),
+        );
         let mut values = vec![F::zero(); padded_nb_rows * NUM_BASE_ALU_COLS];
         // Generate the trace rows & corresponding records for each chunk of events in parallel.
         let populate_len = events.len() * NUM_BASE_ALU_VALUE_COLS;
         values[..populate_len].par_chunks_mut(NUM_BASE_ALU_VALUE_COLS).zip_eq(events).for_each(
             |(row, &vals)| {
                 let cols: &mut BaseAluValueCols<_> = row.borrow_mut();
-                *cols = BaseAluValueCols { vals };
+                *cols = BaseAluValueCols {
+                    vals,
+                    flag: F::from_bool(!vals.in2.is_zero()),
+                    in2_inv: vals.in2.try_inverse().unwrap_or_else(F::zero),
+                };
             },
         );
 
@@ -154,12 +188,23 @@ where
         let prep_local: &BaseAluPreprocessedCols<AB::Var> = (*prep_local).borrow();
 
         for (
-            BaseAluValueCols { vals: BaseAluIo { out, in1, in2 } },
-            BaseAluAccessCols { addrs, is_add, is_sub, is_mul, is_div, mult },
+            BaseAluValueCols { vals: BaseAluIo { out, in1, in2 }, flag, in2_inv },
+            BaseAluAccessCols {
+                addrs,
+                is_add,
+                is_sub,
+                is_mul,
+                is_div,
+                is_div_checked,
+                mult,
+                flag_addr,
+                flag_mult,
+                instr_id: _,
+            },
         ) in zip(local.values, prep_local.accesses)
         {
             // Check exactly one flag is enabled.
-            let is_real = is_add + is_sub + is_mul + is_div;
+            let is_real = is_add + is_sub + is_mul + is_div + is_div_checked;
             builder.assert_bool(is_real.clone());
 
             builder.when(is_add).assert_eq(in1 + in2, out);
@@ -167,11 +212,22 @@ where
             builder.when(is_mul).assert_eq(in1 * in2, out);
             builder.when(is_div).assert_eq(in1, in2 * out);
 
+            // For a checked division, `flag` is one exactly when `in2` is nonzero, in which case
+            // `out` is the quotient. Otherwise `out` is zero.
+            let not_flag = AB::Expr::one() - flag;
+            let mut when_checked = builder.when(is_div_checked);
+            when_checked.assert_eq(flag, in2 * in2_inv);
+            when_checked.assert_zero(in2 * not_flag.clone());
+            when_checked.assert_eq(in2 * out, in1 * flag);
+            when_checked.assert_zero(not_flag * out);
+
             builder.receive_single(addrs.in1, in1, is_real.clone());
 
             builder.receive_single(addrs.in2, in2, is_real);
 
             builder.send_single(addrs.out, out, mult);
+
+            builder.send_single(flag_addr, flag, flag_mult);
         }
     }
 }
@@ -203,6 +259,34 @@ mod tests {
         println!("{:?}", trace.values)
     }
 
+    #[test]
+    #[cfg(feature = "debug-instr-id")]
+    fn preprocessed_instr_ids() {
+        type F = BabyBear;
+
+        let program = RecursionProgram {
+            instructions: vec![
+                instr::mem(MemAccessKind::Write, 2, 0, 1),
+                instr::base_alu(BaseAluOpcode::AddF, 1, 1, 0, 0),
+                instr::mem(MemAccessKind::Read, 1, 1, 2),
+                instr::base_alu(BaseAluOpcode::MulF, 1, 2, 1, 1),
+            ],
+            ..Default::default()
+        };
+        let trace: RowMajorMatrix<F> =
+            BaseAluChip::default().generate_preprocessed_trace(&program).unwrap();
+        let row = trace.row_slice(0);
+        let cols: &BaseAluPreprocessedCols<F> = (*row).borrow();
+        assert_eq!(cols.accesses[0].instr_id.id, F::one());
+        assert_eq!(cols.accesses[1].instr_id.id, F::from_canonical_u32(3));
+    }
+
+    #[test]
+    #[cfg(not(feature = "debug-instr-id"))]
+    fn instr_id_takes_no_columns() {
+        assert_eq!(core::mem::size_of::<DebugInstrId<u8>>(), 0);
+    }
+
     #[test]
     pub fn four_ops() {
         type SC = BabyBearPoseidon2;
@@ -239,4 +323,39 @@ mod tests {
 
         run_recursion_test_machines(program);
     }
+
+    #[test]
+    pub fn div_checked() {
+        type SC = BabyBearPoseidon2;
+        type F = <SC as StarkGenericConfig>::Val;
+
+        let mut rng = StdRng::seed_from_u64(0xDEADBEEF);
+        let mut random_felt = move || -> F { rng.sample(rand::distributions::Standard) };
+        let mut addr = 0;
+
+        let instructions = (0..100)
+            .flat_map(|i| {
+                let quot = random_felt();
+                // Every other divisor is zero.
+                let in2 = if i % 2 == 0 { F::zero() } else { random_felt() };
+                let in1 = if in2.is_zero() { random_felt() } else { in2 * quot };
+                let (expected_quot, expected_flag) =
+                    if in2.is_zero() { (F::zero(), F::zero()) } else { (quot, F::one()) };
+                let alloc_size = 4;
+                let a = (0..alloc_size).map(|x| x + addr).collect::<Vec<_>>();
+                addr += alloc_size;
+                [
+                    instr::mem_single(MemAccessKind::Write, 1, a[0], in1),
+                    instr::mem_single(MemAccessKind::Write, 1, a[1], in2),
+                    instr::base_alu_div_checked(1, a[2], a[0], a[1], 1, a[3]),
+                    instr::mem_single(MemAccessKind::Read, 1, a[2], expected_quot),
+                    instr::mem_single(MemAccessKind::Read, 1, a[3], expected_flag),
+                ]
+            })
+            .collect::<Vec<Instruction<F>>>();
+
+        let program = RecursionProgram { instructions, ..Default::default() };
+
+        run_recursion_test_machines(program);
+    }
 }
@@ -1,19 +1,32 @@
 use core::borrow::Borrow;
-use p3_air::{Air, BaseAir, PairBuilder};
-use p3_field::{extension::BinomiallyExtendable, Field, PrimeField32};
+use p3_air::{Air, AirBuilder, BaseAir, PairBuilder};
+use p3_field::{
+    extension::{BinomialExtensionField, BinomiallyExtendable},
+    AbstractExtensionField, AbstractField, Field, PrimeField32,
+};
 use p3_matrix::{dense::RowMajorMatrix, Matrix};
 use p3_maybe_rayon::prelude::*;
-use sp1_core_machine::utils::next_power_of_two;
 use sp1_derive::AlignedBorrow;
-use sp1_stark::air::{ExtensionAirBuilder, MachineAir};
+use sp1_stark::air::MachineAir;
 use std::{borrow::BorrowMut, iter::zip};
 
-use crate::{builder::SP1RecursionAirBuilder, *};
+use crate::{
+    builder::SP1RecursionAirBuilder,
+    chips::{padded_trace_height, DebugInstrId},
+    *,
+};
 
 pub const NUM_EXT_ALU_ENTRIES_PER_ROW: usize = 4;
 
+/// Proves extension field arithmetic over the binomial extension of degree `EXT_DEGREE`.
+///
+/// Extension elements are stored in the low `EXT_DEGREE` limbs of a [`Block`], with the rest
+/// zero, so the degree can be at most [`D`].
 #[derive(Default)]
-pub struct ExtAluChip {}
+pub struct ExtAluChip<const EXT_DEGREE: usize = D> {
+    /// Pins the traces to `2^fixed_log2_rows` rows, overriding the program's minimum height.
+    pub fixed_log2_rows: Option<usize>,
+}
 
 pub const NUM_EXT_ALU_COLS: usize = core::mem::size_of::<ExtAluCols<u8>>();
 
@@ -28,6 +41,10 @@ const NUM_EXT_ALU_VALUE_COLS: usize = core::mem::size_of::<ExtAluValueCols<u8>>(
 #[repr(C)]
 pub struct ExtAluValueCols<F: Copy> {
     pub vals: ExtAluIo<Block<F>>,
+    /// Whether `in2` is nonzero, written out by `DivEChecked`.
+    pub flag: F,
+    /// The inverse of `in2`, or zero if `in2` is zero.
+    pub in2_inv: Block<F>,
 }
 
 pub const NUM_EXT_ALU_PREPROCESSED_COLS: usize = core::mem::size_of::<ExtAluPreprocessedCols<u8>>();
@@ -48,16 +65,24 @@ pub struct ExtAluAccessCols<F: Copy> {
     pub is_sub: F,
     pub is_mul: F,
     pub is_div: F,
+    pub is_div_checked: F,
+    pub is_inv: F,
+    pub is_square: F,
     pub mult: F,
+    pub flag_addr: Address<F>,
+    pub flag_mult: F,
+    pub instr_id: DebugInstrId<F>,
 }
 
-impl<F: Field> BaseAir<F> for ExtAluChip {
+impl<F: Field, const EXT_DEGREE: usize> BaseAir<F> for ExtAluChip<EXT_DEGREE> {
     fn width(&self) -> usize {
         NUM_EXT_ALU_COLS
     }
 }
 
-impl<F: PrimeField32 + BinomiallyExtendable<D>> MachineAir<F> for ExtAluChip {
+impl<F: PrimeField32 + BinomiallyExtendable<EXT_DEGREE>, const EXT_DEGREE: usize> MachineAir<F>
+    for ExtAluChip<EXT_DEGREE>
+{
     type Record = ExecutionRecord<F>;
 
     type Program = crate::RecursionProgram<F>;
@@ -75,20 +100,26 @@ impl<F: PrimeField32 + BinomiallyExtendable<D>> MachineAir<F> for ExtAluChip {
         let instrs = program
             .instructions
             .iter() // Faster than using `rayon` for some reason. Maybe vectorization?
-            .filter_map(|instruction| match instruction {
-                Instruction::ExtAlu(x) => Some(x),
+            .enumerate()
+            .filter_map(|(index, instruction)| match instruction {
+                Instruction::ExtAlu(x) => Some((index, x)),
                 _ => None,
             })
             .collect::<Vec<_>>();
 
         let nb_rows = instrs.len().div_ceil(NUM_EXT_ALU_ENTRIES_PER_ROW);
-        let padded_nb_rows = next_power_of_two(nb_rows, None);
+        let padded_nb_rows = padded_trace_height(
+            nb_rows,
+            self.fixed_log2_rows,
+            program,
+            &MachineAir::<F>::name(self),
+        );
         let mut values = vec![F::zero(); padded_nb_rows * NUM_EXT_ALU_PREPROCESSED_COLS];
         // Generate the trace rows & corresponding records for each chunk of events in parallel.
         let populate_len = instrs.len() * NUM_EXT_ALU_ACCESS_COLS;
         values[..populate_len].par_chunks_mut(NUM_EXT_ALU_ACCESS_COLS).zip_eq(instrs).for_each(
-            |(row, instr)| {
-                let ExtAluInstr { opcode, mult, addrs } = instr;
+            |(row, (index, instr))| {
+                let ExtAluInstr { opcode, mult, addrs, flag_addr, flag_mult } = instr;
                 let access: &mut ExtAluAccessCols<_> = row.borrow_mut();
                 *access = ExtAluAccessCols {
                     addrs: addrs.to_owned(),
@@ -96,13 +127,22 @@ impl<F: PrimeField32 + BinomiallyExtendable<D>> MachineAir<F> for ExtAluChip {
                     is_sub: F::from_bool(false),
                     is_mul: F::from_bool(false),
                     is_div: F::from_bool(false),
+                    is_div_checked: F::from_bool(false),
+                    is_inv: F::from_bool(false),
+                    is_square: F::from_bool(false),
                     mult: mult.to_owned(),
+                    flag_addr: flag_addr.to_owned(),
+                    flag_mult: flag_mult.to_owned(),
+                    instr_id: DebugInstrId::new(index),
                 };
                 let target_flag = match opcode {
                     ExtAluOpcode::AddE => &mut access.is_add,
                     ExtAluOpcode::SubE => &mut access.is_sub,
                     ExtAluOpcode::MulE => &mut access.is_mul,
                     ExtAluOpcode::DivE => &mut access.is_div,
+                    ExtAluOpcode::DivEChecked => &mut access.is_div_checked,
+                    ExtAluOpcode::InvE => &mut access.is_inv,
+                    ExtAluOpcode::SquareE => &mut access.is_square,
                 };
                 *target_flag = F::from_bool(true);
             },
@@ -119,14 +159,30 @@ impl<F: PrimeField32 + BinomiallyExtendable<D>> MachineAir<F> for ExtAluChip {
     fn generate_trace(&self, input: &Self::Record, _: &mut Self::Record) -> RowMajorMatrix<F> {
         let events = &input.ext_alu_events;
         let nb_rows = events.len().div_ceil(NUM_EXT_ALU_ENTRIES_PER_ROW);
-        let padded_nb_rows = next_power_of_two(nb_rows, None);
+        let padded_nb_rows = padded_trace_height(
+            nb_rows,
+            self.fixed_log2_rows,
+            &input.program,
+            &MachineAir::<F>::name(self),
+        );
         let mut values = vec![F::zero(); padded_nb_rows * NUM_EXT_ALU_COLS];
         // Generate the trace rows & corresponding records for each chunk of events in parallel.
         let populate_len = events.len() * NUM_EXT_ALU_VALUE_COLS;
         values[..populate_len].par_chunks_mut(NUM_EXT_ALU_VALUE_COLS).zip_eq(events).for_each(
             |(row, &vals)| {
+                let in2 = BinomialExtensionField::<F, EXT_DEGREE>::from_base_slice(
+                    &vals.in2.0[..EXT_DEGREE],
+                );
+                let in2_inv = in2.try_inverse().unwrap_or_else(BinomialExtensionField::zero);
+                let in2_inv = in2_inv.as_base_slice();
                 let cols: &mut ExtAluValueCols<_> = row.borrow_mut();
-                *cols = ExtAluValueCols { vals };
+                *cols = ExtAluValueCols {
+                    vals,
+                    flag: F::from_bool(!in2.is_zero()),
+                    in2_inv: Block(core::array::from_fn(|i| {
+                        in2_inv.get(i).copied().unwrap_or_else(F::zero)
+                    })),
+                };
             },
         );
 
@@ -139,9 +195,57 @@ impl<F: PrimeField32 + BinomiallyExtendable<D>> MachineAir<F> for ExtAluChip {
     }
 }
 
-impl<AB> Air<AB> for ExtAluChip
+/// The coefficients of the extension element stored in the low limbs of `block`.
+fn ext_limbs<AB: AirBuilder, const EXT_DEGREE: usize>(
+    block: &Block<AB::Var>,
+) -> [AB::Expr; EXT_DEGREE] {
+    core::array::from_fn(|i| block.0[i].into())
+}
+
+fn ext_add<E: AbstractField, const EXT_DEGREE: usize>(
+    x: &[E; EXT_DEGREE],
+    y: &[E; EXT_DEGREE],
+) -> [E; EXT_DEGREE] {
+    core::array::from_fn(|i| x[i].clone() + y[i].clone())
+}
+
+/// Multiplies `x` and `y` modulo `X^EXT_DEGREE - w`.
+fn ext_mul<E: AbstractField, const EXT_DEGREE: usize>(
+    x: &[E; EXT_DEGREE],
+    y: &[E; EXT_DEGREE],
+    w: &E,
+) -> [E; EXT_DEGREE] {
+    let mut result = core::array::from_fn(|_| E::zero());
+    for i in 0..EXT_DEGREE {
+        for j in 0..EXT_DEGREE {
+            if i + j >= EXT_DEGREE {
+                result[i + j - EXT_DEGREE] += w.clone() * x[i].clone() * y[j].clone();
+            } else {
+                result[i + j] += x[i].clone() * y[j].clone();
+            }
+        }
+    }
+    result
+}
+
+fn ext_from_base<E: AbstractField, const EXT_DEGREE: usize>(x: E) -> [E; EXT_DEGREE] {
+    core::array::from_fn(|i| if i == 0 { x.clone() } else { E::zero() })
+}
+
+fn assert_ext_eq<AB: AirBuilder, const EXT_DEGREE: usize>(
+    builder: &mut AB,
+    x: [AB::Expr; EXT_DEGREE],
+    y: [AB::Expr; EXT_DEGREE],
+) {
+    for (x, y) in zip(x, y) {
+        builder.assert_eq(x, y);
+    }
+}
+
+impl<AB, const EXT_DEGREE: usize> Air<AB> for ExtAluChip<EXT_DEGREE>
 where
     AB: SP1RecursionAirBuilder + PairBuilder,
+    AB::F: BinomiallyExtendable<EXT_DEGREE>,
 {
     fn eval(&self, builder: &mut AB) {
         let main = builder.main();
@@ -152,30 +256,70 @@ where
         let prep_local: &ExtAluPreprocessedCols<AB::Var> = (*prep_local).borrow();
 
         for (
-            ExtAluValueCols { vals },
-            ExtAluAccessCols { addrs, is_add, is_sub, is_mul, is_div, mult },
+            ExtAluValueCols { vals, flag, in2_inv },
+            ExtAluAccessCols {
+                addrs,
+                is_add,
+                is_sub,
+                is_mul,
+                is_div,
+                is_div_checked,
+                is_inv,
+                is_square,
+                mult,
+                flag_addr,
+                flag_mult,
+                instr_id: _,
+            },
         ) in zip(local.values, prep_local.accesses)
         {
-            let in1 = vals.in1.as_extension::<AB>();
-            let in2 = vals.in2.as_extension::<AB>();
-            let out = vals.out.as_extension::<AB>();
+            let w = AB::Expr::from(AB::F::w());
+            let in1 = ext_limbs::<AB, EXT_DEGREE>(&vals.in1);
+            let in2 = ext_limbs::<AB, EXT_DEGREE>(&vals.in2);
+            let out = ext_limbs::<AB, EXT_DEGREE>(&vals.out);
+            let in2_inv = ext_limbs::<AB, EXT_DEGREE>(&in2_inv);
+            let mul = |x: &[AB::Expr; EXT_DEGREE], y: &[AB::Expr; EXT_DEGREE]| ext_mul(x, y, &w);
 
             // Check exactly one flag is enabled.
-            let is_real = is_add + is_sub + is_mul + is_div;
+            let is_real = is_add + is_sub + is_mul + is_div + is_div_checked + is_inv + is_square;
             builder.assert_bool(is_real.clone());
 
-            builder.when(is_add).assert_ext_eq(in1.clone() + in2.clone(), out.clone());
-            builder.when(is_sub).assert_ext_eq(in1.clone(), in2.clone() + out.clone());
-            builder.when(is_mul).assert_ext_eq(in1.clone() * in2.clone(), out.clone());
-            builder.when(is_div).assert_ext_eq(in1, in2 * out);
+            assert_ext_eq(&mut builder.when(is_add), ext_add(&in1, &in2), out.clone());
+            assert_ext_eq(&mut builder.when(is_sub), in1.clone(), ext_add(&in2, &out));
+            assert_ext_eq(&mut builder.when(is_mul), mul(&in1, &in2), out.clone());
+            assert_ext_eq(&mut builder.when(is_square), mul(&in1, &in1), out.clone());
+            assert_ext_eq(&mut builder.when(is_div), in1.clone(), mul(&in2, &out));
+
+            // An inverse has no solution for a zero input.
+            let one = ext_from_base(AB::Expr::one());
+            assert_ext_eq(&mut builder.when(is_inv), mul(&in1, &out), one);
+
+            // For a checked division, `flag` is one exactly when `in2` is nonzero, in which case
+            // `out` is the quotient. Otherwise `out` is zero.
+            let flag_ext = ext_from_base(flag.into());
+            let not_flag = ext_from_base(AB::Expr::one() - flag);
+            let zero = ext_from_base(AB::Expr::zero());
+            let mut when_checked = builder.when(is_div_checked);
+            assert_ext_eq(&mut when_checked, mul(&in2, &in2_inv), flag_ext.clone());
+            assert_ext_eq(&mut when_checked, mul(&in2, &not_flag), zero.clone());
+            assert_ext_eq(&mut when_checked, mul(&in2, &out), mul(&in1, &flag_ext));
+            assert_ext_eq(&mut when_checked, mul(&not_flag, &out), zero);
+
+            // The limbs of the output above the extension degree are zero.
+            for &limb in vals.out.0.iter().skip(EXT_DEGREE) {
+                builder.when(is_real.clone()).assert_zero(limb);
+            }
 
             // Read the inputs from memory.
             builder.receive_block(addrs.in1, vals.in1, is_real.clone());
 
-            builder.receive_block(addrs.in2, vals.in2, is_real);
+            // Squaring only reads `in1`.
+            builder.receive_block(addrs.in2, vals.in2, is_real - is_square);
 
             // Write the output to memory.
             builder.send_block(addrs.out, vals.out, mult);
+
+            builder.send_single(flag_addr, flag, flag_mult);
         }
     }
 }
@@ -207,7 +351,7 @@ mod tests {
             }],
             ..Default::default()
         };
-        let chip = ExtAluChip::default();
+        let chip = ExtAluChip::<D>::default();
         let trace: RowMajorMatrix<F> = chip.generate_trace(&shard, &mut ExecutionRecord::default());
         println!("{:?}", trace.values)
     }
@@ -251,4 +395,142 @@ mod tests {
 
         run_recursion_test_machines(program);
     }
+
+    #[test]
+    pub fn inverse() {
+        type SC = BabyBearPoseidon2Outer;
+        type F = <SC as StarkGenericConfig>::Val;
+
+        let mut rng = StdRng::seed_from_u64(0xDEADBEEF);
+        let mut random_extfelt = move || {
+            let inner: [F; 4] = core::array::from_fn(|_| rng.sample(rand::distributions::Standard));
+            BinomialExtensionField::<F, D>::from_base_slice(&inner)
+        };
+        let mut addr = 0;
+
+        let instructions = (0..100)
+            .flat_map(|_| {
+                let in1 = random_extfelt();
+                let alloc_size = 3;
+                let a = (0..alloc_size).map(|x| x + addr).collect::<Vec<_>>();
+                addr += alloc_size;
+                [
+                    instr::mem_ext(MemAccessKind::Write, 3, a[0], in1),
+                    instr::ext_alu_inv(2, a[1], a[0]),
+                    instr::mem_ext(MemAccessKind::Read, 1, a[1], in1.inverse()),
+                    instr::ext_alu(ExtAluOpcode::MulE, 1, a[2], a[0], a[1]),
+                    instr::mem_ext(
+                        MemAccessKind::Read,
+                        1,
+                        a[2],
+                        BinomialExtensionField::<F, D>::one(),
+                    ),
+                ]
+            })
+            .collect::<Vec<Instruction<F>>>();
+
+        let program = RecursionProgram { instructions, ..Default::default() };
+
+        run_recursion_test_machines(program);
+    }
+
+    #[test]
+    pub fn square() {
+        type SC = BabyBearPoseidon2Outer;
+        type F = <SC as StarkGenericConfig>::Val;
+
+        let mut rng = StdRng::seed_from_u64(0xDEADBEEF);
+        let mut random_extfelt = move || {
+            let inner: [F; 4] = core::array::from_fn(|_| rng.sample(rand::distributions::Standard));
+            BinomialExtensionField::<F, D>::from_base_slice(&inner)
+        };
+        let mut addr = 0;
+
+        let instructions = (0..100)
+            .flat_map(|_| {
+                let x = random_extfelt();
+                let a = [addr, addr + 1, addr + 2];
+                addr += 3;
+                // The square reads `x` once, and the multiplication twice.
+                [
+                    instr::mem_ext(MemAccessKind::Write, 3, a[0], x),
+                    instr::ext_alu_square(1, a[1], a[0]),
+                    instr::ext_alu(ExtAluOpcode::MulE, 1, a[2], a[0], a[0]),
+                    instr::mem_ext(MemAccessKind::Read, 1, a[1], x * x),
+                    instr::mem_ext(MemAccessKind::Read, 1, a[2], x * x),
+                ]
+            })
+            .collect::<Vec<Instruction<F>>>();
+
+        let program = RecursionProgram { instructions, ..Default::default() };
+        assert!(program.validate_memory().is_empty());
+
+        run_recursion_test_machines(program);
+    }
+
+    #[test]
+    #[should_panic]
+    pub fn inverse_of_zero() {
+        let instructions = vec![
+            instr::mem_ext(
+                MemAccessKind::Write,
+                2,
+                0,
+                BinomialExtensionField::<BabyBear, D>::zero(),
+            ),
+            instr::ext_alu_inv(1, 1, 0),
+            instr::mem_ext(
+                MemAccessKind::Read,
+                1,
+                1,
+                BinomialExtensionField::<BabyBear, D>::zero(),
+            ),
+        ];
+
+        let program = RecursionProgram::<BabyBear> { instructions, ..Default::default() };
+
+        run_recursion_test_machines(program);
+    }
+
+    #[test]
+    pub fn div_checked() {
+        type SC = BabyBearPoseidon2Outer;
+        type F = <SC as StarkGenericConfig>::Val;
+
+        let mut rng = StdRng::seed_from_u64(0xDEADBEEF);
+        let mut random_extfelt = move || {
+            let inner: [F; 4] = core::array::from_fn(|_| rng.sample(rand::distributions::Standard));
+            BinomialExtensionField::<F, D>::from_base_slice(&inner)
+        };
+        let mut addr = 0;
+
+        let instructions = (0..100)
+            .flat_map(|i| {
+                let quot = random_extfelt();
+                // Every other divisor is zero.
+                let in2 =
+                    if i % 2 == 0 { BinomialExtensionField::zero() } else { random_extfelt() };
+                let in1 = if in2.is_zero() { random_extfelt() } else { in2 * quot };
+                let (expected_quot, expected_flag) = if in2.is_zero() {
+                    (BinomialExtensionField::zero(), F::zero())
+                } else {
+                    (quot, F::one())
+                };
+                let alloc_size = 4;
+                let a = (0..alloc_size).map(|x| x + addr).collect::<Vec<_>>();
+                addr += alloc_size;
+                [
+                    instr::mem_ext(MemAccessKind::Write, 1, a[0], in1),
+                    instr::mem_ext(MemAccessKind::Write, 1, a[1], in2),
+                    instr::ext_alu_div_checked(1, a[2], a[0], a[1], 1, a[3]),
+                    instr::mem_ext(MemAccessKind::Read, 1, a[2], expected_quot),
+                    instr::mem_single(MemAccessKind::Read, 1, a[3], expected_flag),
+                ]
+            })
+            .collect::<Vec<Instruction<F>>>();
+
+        let program = RecursionProgram { instructions, ..Default::default() };
+
+        run_recursion_test_machines(program);
+    }
 }
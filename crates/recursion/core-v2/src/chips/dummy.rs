@@ -22,6 +22,15 @@ impl<const COL_PADDING: usize> DummyChip<COL_PADDING> {
     pub fn new(log_height: usize) -> Self {
         Self { log_height }
     }
+
+    /// Sets the height of the traces to exactly `1 << log_height` rows, whatever the record holds.
+    ///
+    /// This is an alias of [`DummyChip::new`] in builder form: the chip has no other state, so the
+    /// result is the same as `DummyChip::new(log_height)`.
+    #[must_use]
+    pub fn with_log_height(self, log_height: usize) -> Self {
+        Self::new(log_height)
+    }
 }
 
 #[derive(AlignedBorrow, Debug, Clone, Copy)]
@@ -76,3 +85,28 @@ where
         builder.assert_zero(local[0]);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use p3_baby_bear::BabyBear;
+    use p3_matrix::Matrix;
+    use sp1_stark::air::MachineAir;
+
+    use super::DummyChip;
+    use crate::{machine::tests::fibonacci_instructions, ExecutionRecord, RecursionProgram};
+
+    #[test]
+    fn with_log_height() {
+        let chip = DummyChip::<5>::default().with_log_height(4);
+        let record = ExecutionRecord::<BabyBear>::default();
+        let trace = chip.generate_trace(&record, &mut ExecutionRecord::default());
+        assert_eq!(trace.height(), 1 << 4);
+        assert_eq!(trace.width(), 5);
+
+        let program =
+            RecursionProgram { instructions: fibonacci_instructions(), ..Default::default() };
+        let preprocessed = chip.generate_preprocessed_trace(&program).unwrap();
+        assert_eq!(preprocessed.height(), 1 << 4);
+        assert_eq!(chip.log_height, DummyChip::<5>::new(4).log_height);
+    }
+}
@@ -7,7 +7,7 @@ use p3_matrix::{dense::RowMajorMatrix, Matrix};
 use sp1_core_machine::utils::pad_rows_fixed;
 use sp1_derive::AlignedBorrow;
 use sp1_stark::air::{BaseAirBuilder, ExtensionAirBuilder, MachineAir, SP1AirBuilder};
-use std::borrow::BorrowMut;
+use std::{borrow::BorrowMut, ops::RangeInclusive};
 use tracing::instrument;
 
 use crate::{
@@ -22,6 +22,10 @@ pub const NUM_EXP_REVERSE_BITS_LEN_COLS: usize = core::mem::size_of::<ExpReverse
 pub const NUM_EXP_REVERSE_BITS_LEN_PREPROCESSED_COLS: usize =
     core::mem::size_of::<ExpReverseBitsLenPreprocessedCols<u8>>();
 
+/// The supported numbers of bits of an exponent. The runtime accumulates exponents in a `u32`, and
+/// the trace has one row per bit, so an exponent needs at least one.
+pub const EXP_REVERSE_BITS_LEN_BITS: RangeInclusive<usize> = 1..=32;
+
 pub struct ExpReverseBitsLenChip<const DEGREE: usize> {
     pub fixed_log2_rows: Option<usize>,
     pub pad: bool,
@@ -323,7 +327,7 @@ mod tests {
         chips::exp_reverse_bits::ExpReverseBitsLenChip,
         machine::tests::run_recursion_test_machines,
         runtime::{instruction as instr, ExecutionRecord},
-        ExpReverseBitsEvent, Instruction, MemAccessKind, RecursionProgram,
+        Address, ExpReverseBitsEvent, Instruction, MemAccessKind, RecursionProgram,
     };
 
     #[test]
@@ -362,16 +366,14 @@ mod tests {
                         F::from_canonical_u32(exponent_bits[j]),
                     )
                 });
+                let addr = |addr: usize| Address(F::from_canonical_usize(addr));
                 once(instr::mem_single(MemAccessKind::Write, 1, x_a as u32, base))
                     .chain(exp_bit_instructions)
                     .chain(once(instr::exp_reverse_bits_len(
                         1,
-                        F::from_canonical_u32(x_a as u32),
-                        exp_a
-                            .into_iter()
-                            .map(|bit| F::from_canonical_u32(bit as u32))
-                            .collect_vec(),
-                        F::from_canonical_u32(result_a as u32),
+                        addr(result_a),
+                        addr(x_a),
+                        &exp_a.into_iter().map(addr).collect_vec(),
                     )))
                     .chain(once(instr::mem_single(MemAccessKind::Read, 1, result_a as u32, result)))
             })
@@ -382,6 +384,40 @@ mod tests {
         run_recursion_test_machines(program);
     }
 
+    #[test]
+    fn exp_reverse_bits_len_helper() {
+        type F = BabyBear;
+        let addr = |addr: u32| Address(F::from_canonical_u32(addr));
+
+        // The exponent 0b1101 = 13 has the bits [1, 0, 1, 1], reversed to 0b1011 = 11.
+        let bits = [1, 0, 1, 1];
+        let instructions = once(instr::mem(MemAccessKind::Write, 1, 0, 3))
+            .chain(
+                bits.iter().zip(1..).map(|(&bit, a)| instr::mem(MemAccessKind::Write, 1, a, bit)),
+            )
+            .chain(once(instr::exp_reverse_bits_len(
+                1,
+                addr(5),
+                addr(0),
+                &(1..=4).map(addr).collect_vec(),
+            )))
+            .chain(once(instr::mem(MemAccessKind::Read, 1, 5, 3u32.pow(11))))
+            .collect();
+
+        run_recursion_test_machines(RecursionProgram { instructions, ..Default::default() });
+    }
+
+    #[test]
+    #[should_panic = "not in the supported range"]
+    fn exp_reverse_bits_len_no_bits() {
+        instr::exp_reverse_bits_len::<BabyBear>(
+            1,
+            Address(BabyBear::zero()),
+            Address(BabyBear::one()),
+            &[],
+        );
+    }
+
     #[test]
     fn generate_erbl_circuit_trace() {
         type F = BabyBear;
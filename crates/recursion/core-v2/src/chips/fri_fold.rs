@@ -24,21 +24,28 @@ use crate::{
 use super::mem::MemoryAccessCols;
 
 pub const NUM_FRI_FOLD_COLS: usize = core::mem::size_of::<FriFoldCols<u8>>();
+pub const NUM_FRI_FOLD_LANE_COLS: usize = core::mem::size_of::<FriFoldLaneCols<u8>>();
 pub const NUM_FRI_FOLD_PREPROCESSED_COLS: usize =
     core::mem::size_of::<FriFoldPreprocessedCols<u8>>();
+pub const NUM_FRI_FOLD_LANE_PREPROCESSED_COLS: usize =
+    core::mem::size_of::<FriFoldLanePreprocessedCols<u8>>();
 
 pub struct FriFoldChip<const DEGREE: usize> {
     pub fixed_log2_rows: Option<usize>,
     pub pad: bool,
+    /// The number of folds of a single `FriFold` instruction that share a row. Each row has one
+    /// lane per fold, so a larger batch size makes the trace wider and shorter. At least 1, see
+    /// [`Self::with_batch_size`].
+    batch_size: usize,
 }
 
 impl<const DEGREE: usize> Default for FriFoldChip<DEGREE> {
     fn default() -> Self {
-        Self { fixed_log2_rows: None, pad: true }
+        Self { fixed_log2_rows: None, pad: true, batch_size: 1 }
     }
 }
 
-/// The preprocessed columns for a FRI fold invocation.
+/// The preprocessed columns for a FRI fold invocation that are shared by all lanes of a row.
 #[derive(AlignedBorrow, Debug, Clone, Copy)]
 #[repr(C)]
 pub struct FriFoldPreprocessedCols<T: Copy> {
@@ -49,6 +56,13 @@ pub struct FriFoldPreprocessedCols<T: Copy> {
     pub alpha_mem: MemoryAccessCols<T>,
     pub x_mem: MemoryAccessCols<T>,
 
+    pub is_real: T,
+}
+
+/// The preprocessed columns for a single fold within a row.
+#[derive(AlignedBorrow, Debug, Clone, Copy)]
+#[repr(C)]
+pub struct FriFoldLanePreprocessedCols<T: Copy> {
     // Memory accesses for the vector field inputs.
     pub alpha_pow_input_mem: MemoryAccessCols<T>,
     pub ro_input_mem: MemoryAccessCols<T>,
@@ -58,8 +72,6 @@ pub struct FriFoldPreprocessedCols<T: Copy> {
     // Memory accesses for the vector field outputs.
     pub ro_output_mem: MemoryAccessCols<T>,
     pub alpha_pow_output_mem: MemoryAccessCols<T>,
-
-    pub is_real: T,
 }
 
 #[derive(AlignedBorrow, Debug, Clone, Copy)]
@@ -68,7 +80,11 @@ pub struct FriFoldCols<T: Copy> {
     pub z: Block<T>,
     pub alpha: Block<T>,
     pub x: T,
+}
 
+#[derive(AlignedBorrow, Debug, Clone, Copy)]
+#[repr(C)]
+pub struct FriFoldLaneCols<T: Copy> {
     pub p_at_x: Block<T>,
     pub p_at_z: Block<T>,
     pub alpha_pow_input: Block<T>,
@@ -78,9 +94,31 @@ pub struct FriFoldCols<T: Copy> {
     pub ro_output: Block<T>,
 }
 
+impl<const DEGREE: usize> FriFoldChip<DEGREE> {
+    /// A chip that proves `batch_size` folds of a `FriFold` instruction per row.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `batch_size` is zero.
+    pub fn with_batch_size(batch_size: usize) -> Self {
+        assert!(batch_size >= 1, "the FRI fold batch size must be at least 1");
+        Self { batch_size, ..Default::default() }
+    }
+
+    /// The number of folds of a single `FriFold` instruction that share a row.
+    pub const fn batch_size(&self) -> usize {
+        self.batch_size
+    }
+
+    /// The number of rows used by a `FriFold` instruction with `len` folds.
+    pub const fn num_rows(&self, len: usize) -> usize {
+        len.div_ceil(self.batch_size)
+    }
+}
+
 impl<F, const DEGREE: usize> BaseAir<F> for FriFoldChip<DEGREE> {
     fn width(&self) -> usize {
-        NUM_FRI_FOLD_COLS
+        NUM_FRI_FOLD_COLS + self.batch_size * NUM_FRI_FOLD_LANE_COLS
     }
 }
 
@@ -98,10 +136,11 @@ impl<F: PrimeField32, const DEGREE: usize> MachineAir<F> for FriFoldChip<DEGREE>
     }
 
     fn preprocessed_width(&self) -> usize {
-        NUM_FRI_FOLD_PREPROCESSED_COLS
+        NUM_FRI_FOLD_PREPROCESSED_COLS + self.batch_size * NUM_FRI_FOLD_LANE_PREPROCESSED_COLS
     }
     fn generate_preprocessed_trace(&self, program: &Self::Program) -> Option<RowMajorMatrix<F>> {
-        let mut rows: Vec<[F; NUM_FRI_FOLD_PREPROCESSED_COLS]> = Vec::new();
+        let width = <Self as MachineAir<F>>::preprocessed_width(self);
+        let mut rows: Vec<Vec<F>> = Vec::new();
         program
             .instructions
             .iter()
@@ -120,62 +159,71 @@ impl<F: PrimeField32, const DEGREE: usize> MachineAir<F> for FriFoldChip<DEGREE>
                     alpha_pow_mults,
                     ro_mults,
                 } = instruction.as_ref();
-                let mut row_add =
-                    vec![[F::zero(); NUM_FRI_FOLD_PREPROCESSED_COLS]; ext_vec_addrs.ps_at_z.len()];
-
-                row_add.iter_mut().enumerate().for_each(|(i, row)| {
-                    let row: &mut FriFoldPreprocessedCols<F> = row.as_mut_slice().borrow_mut();
-                    row.is_first = F::from_bool(i == 0);
-
-                    // Only need to read z, x, and alpha on the first iteration, hence the
-                    // multiplicities are i==0.
-                    row.z_mem =
-                        MemoryAccessCols { addr: ext_single_addrs.z, mult: -F::from_bool(i == 0) };
-                    row.x_mem =
-                        MemoryAccessCols { addr: base_single_addrs.x, mult: -F::from_bool(i == 0) };
-                    row.alpha_mem = MemoryAccessCols {
+                let len = ext_vec_addrs.ps_at_z.len();
+                let mut row_add = vec![vec![F::zero(); width]; self.num_rows(len)];
+
+                row_add.iter_mut().enumerate().for_each(|(r, row)| {
+                    let (shared, lanes) = row.split_at_mut(NUM_FRI_FOLD_PREPROCESSED_COLS);
+                    let shared: &mut FriFoldPreprocessedCols<F> = shared.borrow_mut();
+                    shared.is_first = F::from_bool(r == 0);
+
+                    // Only need to read z, x, and alpha on the first row, hence the
+                    // multiplicities are r==0.
+                    shared.z_mem =
+                        MemoryAccessCols { addr: ext_single_addrs.z, mult: -F::from_bool(r == 0) };
+                    shared.x_mem =
+                        MemoryAccessCols { addr: base_single_addrs.x, mult: -F::from_bool(r == 0) };
+                    shared.alpha_mem = MemoryAccessCols {
                         addr: ext_single_addrs.alpha,
-                        mult: -F::from_bool(i == 0),
+                        mult: -F::from_bool(r == 0),
                     };
-
-                    // Read the memory for the input vectors.
-                    row.alpha_pow_input_mem = MemoryAccessCols {
-                        addr: ext_vec_addrs.alpha_pow_input[i],
-                        mult: F::neg_one(),
-                    };
-                    row.ro_input_mem =
-                        MemoryAccessCols { addr: ext_vec_addrs.ro_input[i], mult: F::neg_one() };
-                    row.p_at_z_mem =
-                        MemoryAccessCols { addr: ext_vec_addrs.ps_at_z[i], mult: F::neg_one() };
-                    row.p_at_x_mem =
-                        MemoryAccessCols { addr: ext_vec_addrs.mat_opening[i], mult: F::neg_one() };
-
-                    // Write the memory for the output vectors.
-                    row.alpha_pow_output_mem = MemoryAccessCols {
-                        addr: ext_vec_addrs.alpha_pow_output[i],
-                        mult: alpha_pow_mults[i],
-                    };
-                    row.ro_output_mem =
-                        MemoryAccessCols { addr: ext_vec_addrs.ro_output[i], mult: ro_mults[i] };
-
-                    row.is_real = F::one();
+                    shared.is_real = F::one();
+
+                    // The trailing lanes of the last row are left empty.
+                    lanes
+                        .chunks_exact_mut(NUM_FRI_FOLD_LANE_PREPROCESSED_COLS)
+                        .zip(r * self.batch_size..len)
+                        .for_each(|(lane, i)| {
+                            let lane: &mut FriFoldLanePreprocessedCols<F> = lane.borrow_mut();
+
+                            // Read the memory for the input vectors.
+                            lane.alpha_pow_input_mem = MemoryAccessCols {
+                                addr: ext_vec_addrs.alpha_pow_input[i],
+                                mult: F::neg_one(),
+                            };
+                            lane.ro_input_mem = MemoryAccessCols {
+                                addr: ext_vec_addrs.ro_input[i],
+                                mult: F::neg_one(),
+                            };
+                            lane.p_at_z_mem = MemoryAccessCols {
+                                addr: ext_vec_addrs.ps_at_z[i],
+                                mult: F::neg_one(),
+                            };
+                            lane.p_at_x_mem = MemoryAccessCols {
+                                addr: ext_vec_addrs.mat_opening[i],
+                                mult: F::neg_one(),
+                            };
+
+                            // Write the memory for the output vectors.
+                            lane.alpha_pow_output_mem = MemoryAccessCols {
+                                addr: ext_vec_addrs.alpha_pow_output[i],
+                                mult: alpha_pow_mults[i],
+                            };
+                            lane.ro_output_mem = MemoryAccessCols {
+                                addr: ext_vec_addrs.ro_output[i],
+                                mult: ro_mults[i],
+                            };
+                        });
                 });
                 rows.extend(row_add);
             });
 
         // Pad the trace to a power of two.
         if self.pad {
-            pad_rows_fixed(
-                &mut rows,
-                || [F::zero(); NUM_FRI_FOLD_PREPROCESSED_COLS],
-                self.fixed_log2_rows,
-            );
+            pad_rows_fixed(&mut rows, || vec![F::zero(); width], self.fixed_log2_rows);
         }
 
-        let trace = RowMajorMatrix::new(
-            rows.into_iter().flatten().collect(),
-            NUM_FRI_FOLD_PREPROCESSED_COLS,
-        );
+        let trace = RowMajorMatrix::new(rows.into_iter().flatten().collect(), width);
         Some(trace)
     }
 
@@ -185,25 +233,54 @@ impl<F: PrimeField32, const DEGREE: usize> MachineAir<F> for FriFoldChip<DEGREE>
         input: &ExecutionRecord<F>,
         _: &mut ExecutionRecord<F>,
     ) -> RowMajorMatrix<F> {
-        let mut rows = input
-            .fri_fold_events
-            .iter()
-            .map(|event| {
-                let mut row = [F::zero(); NUM_FRI_FOLD_COLS];
-
-                let cols: &mut FriFoldCols<F> = row.as_mut_slice().borrow_mut();
-
-                cols.x = event.base_single.x;
-                cols.z = event.ext_single.z;
-                cols.alpha = event.ext_single.alpha;
-
-                cols.p_at_z = event.ext_vec.ps_at_z;
-                cols.p_at_x = event.ext_vec.mat_opening;
-                cols.alpha_pow_input = event.ext_vec.alpha_pow_input;
-                cols.ro_input = event.ext_vec.ro_input;
+        let width = <Self as BaseAir<F>>::width(self);
+
+        // Events are emitted in program order, one per fold. A row can only hold folds of the same
+        // instruction, so the events are grouped by instruction before being split into rows. With
+        // a batch size of one, every event is a row of its own.
+        let mut events = input.fri_fold_events.as_slice();
+        let groups = if self.batch_size == 1 {
+            events.chunks(1).collect_vec()
+        } else {
+            input
+                .program
+                .instructions
+                .iter()
+                .filter_map(|instruction| match instruction {
+                    Instruction::FriFold(instr) => Some(instr.ext_vec_addrs.ps_at_z.len()),
+                    _ => None,
+                })
+                .flat_map(|len| {
+                    let (group, rest) = events.split_at(len);
+                    events = rest;
+                    group.chunks(self.batch_size)
+                })
+                .collect_vec()
+        };
 
-                cols.alpha_pow_output = event.ext_vec.alpha_pow_output;
-                cols.ro_output = event.ext_vec.ro_output;
+        let mut rows = groups
+            .into_iter()
+            .map(|group| {
+                let mut row = vec![F::zero(); width];
+                let (shared, lanes) = row.split_at_mut(NUM_FRI_FOLD_COLS);
+
+                let cols: &mut FriFoldCols<F> = shared.borrow_mut();
+                cols.x = group[0].base_single.x;
+                cols.z = group[0].ext_single.z;
+                cols.alpha = group[0].ext_single.alpha;
+
+                lanes.chunks_exact_mut(NUM_FRI_FOLD_LANE_COLS).zip(group).for_each(
+                    |(lane, event)| {
+                        let lane: &mut FriFoldLaneCols<F> = lane.borrow_mut();
+                        lane.p_at_z = event.ext_vec.ps_at_z;
+                        lane.p_at_x = event.ext_vec.mat_opening;
+                        lane.alpha_pow_input = event.ext_vec.alpha_pow_input;
+                        lane.ro_input = event.ext_vec.ro_input;
+
+                        lane.alpha_pow_output = event.ext_vec.alpha_pow_output;
+                        lane.ro_output = event.ext_vec.ro_output;
+                    },
+                );
 
                 row
             })
@@ -211,11 +288,11 @@ impl<F: PrimeField32, const DEGREE: usize> MachineAir<F> for FriFoldChip<DEGREE>
 
         // Pad the trace to a power of two.
         if self.pad {
-            pad_rows_fixed(&mut rows, || [F::zero(); NUM_FRI_FOLD_COLS], self.fixed_log2_rows);
+            pad_rows_fixed(&mut rows, || vec![F::zero(); width], self.fixed_log2_rows);
         }
 
         // Convert the trace to a row major matrix.
-        let trace = RowMajorMatrix::new(rows.into_iter().flatten().collect(), NUM_FRI_FOLD_COLS);
+        let trace = RowMajorMatrix::new(rows.into_iter().flatten().collect(), width);
 
         #[cfg(debug_assertions)]
         println!("fri fold trace dims is width: {:?}, height: {:?}", trace.width(), trace.height());
@@ -266,56 +343,64 @@ impl<const DEGREE: usize> FriFoldChip<DEGREE> {
             .when(next_prepr.is_real)
             .when_not(next_prepr.is_first)
             .assert_ext_eq(local.alpha.as_extension::<AB>(), next.alpha.as_extension::<AB>());
+    }
 
+    pub fn eval_fri_fold_lane<AB: SP1RecursionAirBuilder>(
+        &self,
+        builder: &mut AB,
+        local: &FriFoldCols<AB::Var>,
+        lane: &FriFoldLaneCols<AB::Var>,
+        lane_prepr: &FriFoldLanePreprocessedCols<AB::Var>,
+    ) {
         // Constrain read for alpha_pow_input.
         builder.send_block(
-            local_prepr.alpha_pow_input_mem.addr,
-            local.alpha_pow_input,
-            local_prepr.alpha_pow_input_mem.mult,
+            lane_prepr.alpha_pow_input_mem.addr,
+            lane.alpha_pow_input,
+            lane_prepr.alpha_pow_input_mem.mult,
         );
 
         // Constrain read for ro_input.
         builder.send_block(
-            local_prepr.ro_input_mem.addr,
-            local.ro_input,
-            local_prepr.ro_input_mem.mult,
+            lane_prepr.ro_input_mem.addr,
+            lane.ro_input,
+            lane_prepr.ro_input_mem.mult,
         );
 
         // Constrain read for p_at_z.
-        builder.send_block(local_prepr.p_at_z_mem.addr, local.p_at_z, local_prepr.p_at_z_mem.mult);
+        builder.send_block(lane_prepr.p_at_z_mem.addr, lane.p_at_z, lane_prepr.p_at_z_mem.mult);
 
         // Constrain read for p_at_x.
-        builder.send_block(local_prepr.p_at_x_mem.addr, local.p_at_x, local_prepr.p_at_x_mem.mult);
+        builder.send_block(lane_prepr.p_at_x_mem.addr, lane.p_at_x, lane_prepr.p_at_x_mem.mult);
 
         // Constrain write for alpha_pow_output.
         builder.send_block(
-            local_prepr.alpha_pow_output_mem.addr,
-            local.alpha_pow_output,
-            local_prepr.alpha_pow_output_mem.mult,
+            lane_prepr.alpha_pow_output_mem.addr,
+            lane.alpha_pow_output,
+            lane_prepr.alpha_pow_output_mem.mult,
         );
 
         // Constrain write for ro_output.
         builder.send_block(
-            local_prepr.ro_output_mem.addr,
-            local.ro_output,
-            local_prepr.ro_output_mem.mult,
+            lane_prepr.ro_output_mem.addr,
+            lane.ro_output,
+            lane_prepr.ro_output_mem.mult,
         );
 
         // 1. Constrain new_value = old_value * alpha.
         let alpha = local.alpha.as_extension::<AB>();
-        let old_alpha_pow = local.alpha_pow_input.as_extension::<AB>();
-        let new_alpha_pow = local.alpha_pow_output.as_extension::<AB>();
+        let old_alpha_pow = lane.alpha_pow_input.as_extension::<AB>();
+        let new_alpha_pow = lane.alpha_pow_output.as_extension::<AB>();
         builder.assert_ext_eq(old_alpha_pow.clone() * alpha, new_alpha_pow.clone());
 
         // 2. Constrain new_value = old_alpha_pow * quotient + old_ro,
         // where quotient = (p_at_x - p_at_z) / (x - z)
         // <=> (new_ro - old_ro) * (z - x) = old_alpha_pow * (p_at_x - p_at_z)
-        let p_at_z = local.p_at_z.as_extension::<AB>();
-        let p_at_x = local.p_at_x.as_extension::<AB>();
+        let p_at_z = lane.p_at_z.as_extension::<AB>();
+        let p_at_x = lane.p_at_x.as_extension::<AB>();
         let z = local.z.as_extension::<AB>();
         let x = local.x.into();
-        let old_ro = local.ro_input.as_extension::<AB>();
-        let new_ro = local.ro_output.as_extension::<AB>();
+        let old_ro = lane.ro_input.as_extension::<AB>();
+        let new_ro = lane.ro_output.as_extension::<AB>();
         builder.assert_ext_eq(
             (new_ro.clone() - old_ro) * (BinomialExtension::from_base(x) - z),
             (p_at_x - p_at_z) * old_alpha_pow,
@@ -334,12 +419,15 @@ where
     fn eval(&self, builder: &mut AB) {
         let main = builder.main();
         let (local, next) = (main.row_slice(0), main.row_slice(1));
-        let local: &FriFoldCols<AB::Var> = (*local).borrow();
-        let next: &FriFoldCols<AB::Var> = (*next).borrow();
+        let (local, local_lanes) = local.split_at(NUM_FRI_FOLD_COLS);
+        let local: &FriFoldCols<AB::Var> = local.borrow();
+        let next: &FriFoldCols<AB::Var> = next[..NUM_FRI_FOLD_COLS].borrow();
         let prepr = builder.preprocessed();
         let (prepr_local, prepr_next) = (prepr.row_slice(0), prepr.row_slice(1));
-        let prepr_local: &FriFoldPreprocessedCols<AB::Var> = (*prepr_local).borrow();
-        let prepr_next: &FriFoldPreprocessedCols<AB::Var> = (*prepr_next).borrow();
+        let (prepr_local, prepr_local_lanes) = prepr_local.split_at(NUM_FRI_FOLD_PREPROCESSED_COLS);
+        let prepr_local: &FriFoldPreprocessedCols<AB::Var> = prepr_local.borrow();
+        let prepr_next: &FriFoldPreprocessedCols<AB::Var> =
+            prepr_next[..NUM_FRI_FOLD_PREPROCESSED_COLS].borrow();
 
         // Dummy constraints to normalize to DEGREE.
         let lhs = (0..DEGREE).map(|_| prepr_local.is_real.into()).product::<AB::Expr>();
@@ -347,6 +435,13 @@ where
         builder.assert_eq(lhs, rhs);
 
         self.eval_fri_fold::<AB>(builder, local, next, prepr_local, prepr_next);
+
+        for (lane, lane_prepr) in local_lanes
+            .chunks_exact(NUM_FRI_FOLD_LANE_COLS)
+            .zip(prepr_local_lanes.chunks_exact(NUM_FRI_FOLD_LANE_PREPROCESSED_COLS))
+        {
+            self.eval_fri_fold_lane::<AB>(builder, local, lane.borrow(), lane_prepr.borrow());
+        }
     }
 }
 
@@ -354,30 +449,32 @@ where
 mod tests {
     use p3_field::AbstractExtensionField;
     use rand::{rngs::StdRng, Rng, SeedableRng};
-    use sp1_core_machine::utils::setup_logger;
+    use sp1_core_machine::utils::{run_test_machine, setup_logger};
     use sp1_recursion_core::{air::Block, stark::config::BabyBearPoseidon2Outer};
-    use sp1_stark::{air::MachineAir, StarkGenericConfig};
-    use std::mem::size_of;
+    use sp1_stark::{
+        air::MachineAir, baby_bear_poseidon2::BabyBearPoseidon2, Chip, StarkGenericConfig,
+        StarkMachine, PROOF_MAX_NUM_PVS,
+    };
+    use std::{mem::size_of, sync::Arc};
 
-    use p3_baby_bear::BabyBear;
+    use p3_baby_bear::{BabyBear, DiffusionMatrixBabyBear};
     use p3_field::AbstractField;
-    use p3_matrix::dense::RowMajorMatrix;
+    use p3_matrix::{dense::RowMajorMatrix, Matrix};
 
     use crate::{
         chips::fri_fold::FriFoldChip,
-        machine::tests::run_recursion_test_machines,
+        machine::{tests::run_recursion_test_machines, RecursionAir},
         runtime::{instruction as instr, ExecutionRecord},
         FriFoldBaseIo, FriFoldEvent, FriFoldExtSingleIo, FriFoldExtVecIo, Instruction,
-        MemAccessKind, RecursionProgram,
+        MemAccessKind, RecursionProgram, Runtime,
     };
 
-    #[test]
-    fn prove_babybear_circuit_fri_fold() {
-        setup_logger();
-        type SC = BabyBearPoseidon2Outer;
-        type F = <SC as StarkGenericConfig>::Val;
-        type EF = <SC as StarkGenericConfig>::Challenge;
+    type SC = BabyBearPoseidon2Outer;
+    type F = <SC as StarkGenericConfig>::Val;
+    type EF = <SC as StarkGenericConfig>::Challenge;
 
+    /// A program running FRI fold instructions with 2 to 16 folds each.
+    fn fri_fold_program() -> RecursionProgram<F> {
         let mut rng = StdRng::seed_from_u64(0xDEADBEEF);
         let mut random_felt = move || -> F { F::from_canonical_u32(rng.gen_range(0..1 << 16)) };
         let mut rng = StdRng::seed_from_u64(0xDEADBEEF);
@@ -507,9 +604,57 @@ mod tests {
             })
             .collect::<Vec<Instruction<F>>>();
 
-        let program = RecursionProgram { instructions, ..Default::default() };
+        RecursionProgram { instructions, ..Default::default() }
+    }
+
+    #[test]
+    fn prove_babybear_circuit_fri_fold() {
+        setup_logger();
+        run_recursion_test_machines(fri_fold_program());
+    }
+
+    #[test]
+    fn prove_batched_fri_fold() {
+        setup_logger();
+        type A = RecursionAir<F, 3, 0>;
+
+        let program = Arc::new(fri_fold_program());
+        let mut runtime = Runtime::<F, EF, DiffusionMatrixBabyBear>::new(
+            program.clone(),
+            BabyBearPoseidon2::new().perm,
+        );
+        runtime.run().unwrap();
+
+        for batch_size in [1, 2, 3] {
+            // Folding several lanes per row must shrink the trace accordingly.
+            let mut chip = FriFoldChip::<3>::with_batch_size(batch_size);
+            chip.pad = false;
+            let trace: RowMajorMatrix<F> =
+                chip.generate_trace(&runtime.record, &mut ExecutionRecord::default());
+            let expected_height = (2..17).map(|len| chip.num_rows(len)).sum::<usize>();
+            assert_eq!(trace.height(), expected_height);
+
+            // The batched chip must accept exactly the same execution as the unbatched one.
+            let chips = A::get_all_wide()
+                .into_iter()
+                .map(|air| match air {
+                    RecursionAir::FriFold(_) => {
+                        RecursionAir::FriFold(FriFoldChip::with_batch_size(batch_size))
+                    }
+                    air => air,
+                })
+                .map(Chip::new)
+                .collect();
+            let machine = StarkMachine::new(BabyBearPoseidon2::default(), chips, PROOF_MAX_NUM_PVS);
+            let (pk, vk) = machine.setup(&program);
+            run_test_machine(vec![runtime.record.clone()], machine, pk, vk).unwrap();
+        }
+    }
 
-        run_recursion_test_machines(program);
+    #[test]
+    #[should_panic(expected = "batch size must be at least 1")]
+    fn zero_batch_size() {
+        FriFoldChip::<3>::with_batch_size(0);
     }
 
     #[test]
@@ -3,19 +3,24 @@ use itertools::Itertools;
 use p3_air::{Air, BaseAir, PairBuilder};
 use p3_field::PrimeField32;
 use p3_matrix::{dense::RowMajorMatrix, Matrix};
-use sp1_core_machine::utils::pad_to_power_of_two;
 use sp1_derive::AlignedBorrow;
 use sp1_stark::air::MachineAir;
 use std::{borrow::BorrowMut, iter::zip, marker::PhantomData};
 
-use crate::{builder::SP1RecursionAirBuilder, *};
+use crate::{builder::SP1RecursionAirBuilder, chips::padded_trace_height, *};
 
 use super::MemoryAccessCols;
 
 pub const NUM_MEM_ENTRIES_PER_ROW: usize = 6;
 
+/// Proves the accesses of the `Mem` instructions, `ENTRIES_PER_ROW` of them per row.
+///
+/// Both the preprocessed and main traces group the accesses by `ENTRIES_PER_ROW`, so they always
+/// have the same number of rows.
 #[derive(Default)]
-pub struct MemoryChip<F> {
+pub struct MemoryChip<F, const ENTRIES_PER_ROW: usize = NUM_MEM_ENTRIES_PER_ROW> {
+    /// Pins the traces to `2^fixed_log2_rows` rows, overriding the program's minimum height.
+    pub fixed_log2_rows: Option<usize>,
     _data: PhantomData<F>,
 }
 
@@ -29,20 +34,22 @@ pub struct MemoryCols<F: Copy> {
 }
 
 pub const NUM_MEM_PREPROCESSED_INIT_COLS: usize =
-    core::mem::size_of::<MemoryPreprocessedCols<u8>>();
+    core::mem::size_of::<MemoryPreprocessedCols<u8, NUM_MEM_ENTRIES_PER_ROW>>();
 
 #[derive(AlignedBorrow, Debug, Clone, Copy)]
 #[repr(C)]
-pub struct MemoryPreprocessedCols<F: Copy> {
-    values_and_accesses: [(Block<F>, MemoryAccessCols<F>); NUM_MEM_ENTRIES_PER_ROW],
+pub struct MemoryPreprocessedCols<F: Copy, const ENTRIES_PER_ROW: usize> {
+    values_and_accesses: [(Block<F>, MemoryAccessCols<F>); ENTRIES_PER_ROW],
 }
-impl<F: Send + Sync> BaseAir<F> for MemoryChip<F> {
+impl<F: Send + Sync, const ENTRIES_PER_ROW: usize> BaseAir<F> for MemoryChip<F, ENTRIES_PER_ROW> {
     fn width(&self) -> usize {
         NUM_MEM_INIT_COLS
     }
 }
 
-impl<F: PrimeField32> MachineAir<F> for MemoryChip<F> {
+impl<F: PrimeField32, const ENTRIES_PER_ROW: usize> MachineAir<F>
+    for MemoryChip<F, ENTRIES_PER_ROW>
+{
     type Record = crate::ExecutionRecord<F>;
 
     type Program = crate::RecursionProgram<F>;
@@ -51,11 +58,17 @@ impl<F: PrimeField32> MachineAir<F> for MemoryChip<F> {
         "MemoryConst".to_string()
     }
     fn preprocessed_width(&self) -> usize {
-        NUM_MEM_PREPROCESSED_INIT_COLS
+        core::mem::size_of::<MemoryPreprocessedCols<u8, ENTRIES_PER_ROW>>()
+    }
+
+    fn skip_zero_multiplicity(&self) -> bool {
+        // Padding rows only have interactions with zero multiplicity.
+        true
     }
 
     fn generate_preprocessed_trace(&self, program: &Self::Program) -> Option<RowMajorMatrix<F>> {
-        let rows = program
+        let width = MachineAir::<F>::preprocessed_width(self);
+        let values = program
             .instructions
             .iter()
             .filter_map(|instruction| match instruction {
@@ -70,11 +83,12 @@ impl<F: PrimeField32> MachineAir<F> for MemoryChip<F> {
                 }
                 _ => None,
             })
-            .chunks(NUM_MEM_ENTRIES_PER_ROW)
+            .chunks(ENTRIES_PER_ROW)
             .into_iter()
-            .map(|row_vs_as| {
-                let mut row = [F::zero(); NUM_MEM_PREPROCESSED_INIT_COLS];
-                let cols: &mut MemoryPreprocessedCols<_> = row.as_mut_slice().borrow_mut();
+            .flat_map(|row_vs_as| {
+                let mut row = vec![F::zero(); width];
+                let cols: &mut MemoryPreprocessedCols<_, ENTRIES_PER_ROW> =
+                    row.as_mut_slice().borrow_mut();
                 for (cell, access) in zip(&mut cols.values_and_accesses, row_vs_as) {
                     *cell = access;
                 }
@@ -83,13 +97,12 @@ impl<F: PrimeField32> MachineAir<F> for MemoryChip<F> {
             .collect::<Vec<_>>();
 
         // Convert the trace to a row major matrix.
-        let mut trace = RowMajorMatrix::new(
-            rows.into_iter().flatten().collect::<Vec<_>>(),
-            NUM_MEM_PREPROCESSED_INIT_COLS,
-        );
+        let mut trace = RowMajorMatrix::new(values, width);
 
-        // Pad the trace to a power of two.
-        pad_to_power_of_two::<NUM_MEM_PREPROCESSED_INIT_COLS, F>(&mut trace.values);
+        // Pad the trace to a power of two, at least the minimum height, or to the pinned height.
+        let padded_nb_rows =
+            padded_trace_height(trace.height(), self.fixed_log2_rows, program, &self.name());
+        trace.values.resize(padded_nb_rows * width, F::zero());
 
         Some(trace)
     }
@@ -99,21 +112,12 @@ impl<F: PrimeField32> MachineAir<F> for MemoryChip<F> {
     }
 
     fn generate_trace(&self, input: &Self::Record, _: &mut Self::Record) -> RowMajorMatrix<F> {
-        // Match number of rows generated by the `.chunks` call in `generate_preprocessed_trace`.
-        let num_rows = input
-            .mem_const_count
-            .checked_sub(1)
-            .map(|x| x / NUM_MEM_ENTRIES_PER_ROW + 1)
-            .unwrap_or_default();
-        let rows =
-            std::iter::repeat([F::zero(); NUM_MEM_INIT_COLS]).take(num_rows).collect::<Vec<_>>();
+        let mut trace = self.generate_trace_range(input, 0, Self::num_rows(input));
 
-        // Convert the trace to a row major matrix.
-        let mut trace =
-            RowMajorMatrix::new(rows.into_iter().flatten().collect::<Vec<_>>(), NUM_MEM_INIT_COLS);
-
-        // Pad the trace to a power of two.
-        pad_to_power_of_two::<NUM_MEM_INIT_COLS, F>(&mut trace.values);
+        // Pad the trace to a power of two, at least the minimum height, or to the pinned height.
+        let padded_nb_rows =
+            padded_trace_height(trace.height(), self.fixed_log2_rows, &input.program, &self.name());
+        trace.values.resize(padded_nb_rows * NUM_MEM_INIT_COLS, F::zero());
 
         trace
     }
@@ -123,15 +127,42 @@ impl<F: PrimeField32> MachineAir<F> for MemoryChip<F> {
     }
 }
 
-impl<AB> Air<AB> for MemoryChip<AB::F>
+impl<F: PrimeField32, const ENTRIES_PER_ROW: usize> MemoryChip<F, ENTRIES_PER_ROW> {
+    /// The number of main trace rows before padding.
+    ///
+    /// Matches the number of rows `generate_preprocessed_trace` generates by chunking the `Mem`
+    /// instructions, as both group them by `ENTRIES_PER_ROW`.
+    pub fn num_rows(input: &crate::ExecutionRecord<F>) -> usize {
+        input.mem_const_count.div_ceil(ENTRIES_PER_ROW)
+    }
+
+    /// Generates the main trace rows `start..start + len` without padding.
+    ///
+    /// Rows past [`Self::num_rows`] are omitted, so padding the concatenation of ranges covering
+    /// `0..num_rows` gives the same trace as `generate_trace`.
+    pub fn generate_trace_range(
+        &self,
+        input: &crate::ExecutionRecord<F>,
+        start: usize,
+        len: usize,
+    ) -> RowMajorMatrix<F> {
+        let num_rows = Self::num_rows(input).saturating_sub(start).min(len);
+        RowMajorMatrix::new(vec![F::zero(); num_rows * NUM_MEM_INIT_COLS], NUM_MEM_INIT_COLS)
+    }
+}
+
+impl<AB, const ENTRIES_PER_ROW: usize> Air<AB> for MemoryChip<AB::F, ENTRIES_PER_ROW>
 where
     AB: SP1RecursionAirBuilder + PairBuilder,
 {
     fn eval(&self, builder: &mut AB) {
         let prep = builder.preprocessed();
         let prep_local = prep.row_slice(0);
-        let prep_local: &MemoryPreprocessedCols<AB::Var> = (*prep_local).borrow();
+        let prep_local: &MemoryPreprocessedCols<AB::Var, ENTRIES_PER_ROW> = (*prep_local).borrow();
 
+        // Padding rows send zero blocks with zero multiplicity. They add nothing to the cumulative
+        // sum, and generating the permutation trace skips their fingerprints, as this chip opts in
+        // with `skip_zero_multiplicity`.
         for (value, access) in prep_local.values_and_accesses {
             builder.send_block(access.addr, value, access.mult);
         }
@@ -142,12 +173,15 @@ where
 mod tests {
     use std::sync::Arc;
 
-    use machine::{tests::run_recursion_test_machines, RecursionAir};
+    use machine::{
+        tests::{run_recursion_test_machines, trace_to_csv},
+        RecursionAir,
+    };
     use p3_baby_bear::{BabyBear, DiffusionMatrixBabyBear};
-    use p3_field::AbstractField;
+    use p3_field::{AbstractField, Field};
     use p3_matrix::dense::RowMajorMatrix;
 
-    use sp1_core_machine::utils::run_test_machine;
+    use sp1_core_machine::utils::{pad_to_min_log_height, run_test_machine};
     use sp1_recursion_core::stark::config::BabyBearPoseidon2Outer;
     use sp1_stark::{BabyBearPoseidon2Inner, StarkGenericConfig};
 
@@ -179,19 +213,76 @@ mod tests {
 
     #[test]
     pub fn generate_trace() {
-        let shard = ExecutionRecord::<BabyBear> {
-            mem_var_events: vec![
+        let shard = ExecutionRecord::<BabyBear>::builder()
+            .mem_var(vec![
                 MemEvent { inner: BabyBear::one().into() },
                 MemEvent { inner: BabyBear::one().into() },
-            ],
-            ..Default::default()
-        };
-        let chip = MemoryChip::default();
+            ])
+            .build();
+        let chip = MemoryChip::<BabyBear>::default();
         let trace: RowMajorMatrix<BabyBear> =
             chip.generate_trace(&shard, &mut ExecutionRecord::default());
         println!("{:?}", trace.values)
     }
 
+    #[test]
+    pub fn generate_trace_range() {
+        let shard = ExecutionRecord::<BabyBear>::builder().mem_const_count(20).build();
+        let chip = MemoryChip::<BabyBear>::default();
+        let trace = chip.generate_trace(&shard, &mut ExecutionRecord::default());
+
+        let mut values = [(0, 1), (1, 2), (3, 5)]
+            .into_iter()
+            .flat_map(|(start, len)| chip.generate_trace_range(&shard, start, len).values)
+            .collect::<Vec<_>>();
+        assert_eq!(values.len(), MemoryChip::<BabyBear>::num_rows(&shard) * NUM_MEM_INIT_COLS);
+        pad_to_min_log_height::<NUM_MEM_INIT_COLS, BabyBear>(&mut values, None);
+        assert_eq!(values, trace.values);
+    }
+
+    /// The names of the columns of [`MemoryPreprocessedCols`], in trace order.
+    fn preprocessed_column_names() -> Vec<String> {
+        let indices: [usize; NUM_MEM_PREPROCESSED_INIT_COLS] = core::array::from_fn(|i| i);
+        let cols: &MemoryPreprocessedCols<usize, NUM_MEM_ENTRIES_PER_ROW> =
+            indices.as_slice().borrow();
+        let mut names = vec![String::new(); NUM_MEM_PREPROCESSED_INIT_COLS];
+        for (i, (value, access)) in cols.values_and_accesses.iter().enumerate() {
+            for (j, &col) in value.0.iter().enumerate() {
+                names[col] = format!("values_and_accesses[{i}].value[{j}]");
+            }
+            names[access.addr.0] = format!("values_and_accesses[{i}].addr");
+            names[access.mult] = format!("values_and_accesses[{i}].mult");
+        }
+        names
+    }
+
+    #[test]
+    pub fn preprocessed_trace_to_csv() {
+        let program = RecursionProgram {
+            instructions: vec![
+                instr::mem(MemAccessKind::Write, 1, 1, 2),
+                instr::mem(MemAccessKind::Read, 1, 1, 2),
+            ],
+            ..Default::default()
+        };
+        let trace = MemoryChip::<F>::default().generate_preprocessed_trace(&program).unwrap();
+        let csv = trace_to_csv(&trace, &preprocessed_column_names());
+        let lines = csv.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 1 + trace.height());
+
+        // The two accesses share the first row, and the read has multiplicity -1.
+        let header = lines[0].split(',').collect::<Vec<_>>();
+        let row = lines[1].split(',').collect::<Vec<_>>();
+        let cell = |name: &str| row[header.iter().position(|&col| col == name).unwrap()];
+        assert_eq!(cell("values_and_accesses[0].value[0]"), "2");
+        assert_eq!(cell("values_and_accesses[0].value[1]"), "0");
+        assert_eq!(cell("values_and_accesses[0].addr"), "1");
+        assert_eq!(cell("values_and_accesses[0].mult"), "1");
+        assert_eq!(cell("values_and_accesses[1].addr"), "1");
+        assert_eq!(cell("values_and_accesses[1].mult"), (-F::one()).to_string());
+        assert_eq!(cell("values_and_accesses[2].mult"), "0");
+    }
+
     #[test]
     pub fn prove_basic_mem() {
         run_recursion_test_machines(RecursionProgram {
@@ -203,6 +294,109 @@ mod tests {
         });
     }
 
+    #[test]
+    pub fn prove_min_log_height() {
+        let program = RecursionProgram {
+            instructions: vec![
+                instr::mem(MemAccessKind::Write, 1, 1, 2),
+                instr::mem(MemAccessKind::Read, 1, 1, 2),
+            ],
+            min_log_heights: [("MemoryConst".to_string(), 6)].into(),
+            ..Default::default()
+        };
+
+        let chip = MemoryChip::<F>::default();
+        let preprocessed = chip.generate_preprocessed_trace(&program).unwrap();
+        let shard = ExecutionRecord::builder()
+            .program(Arc::new(program.clone()))
+            .mem_const_count(2)
+            .build();
+        let main = chip.generate_trace(&shard, &mut ExecutionRecord::default());
+        assert_eq!(preprocessed.height(), 1 << 6);
+        assert_eq!(main.height(), 1 << 6);
+
+        prove_program(program);
+    }
+
+    #[test]
+    pub fn fixed_log2_rows() {
+        let mut program = RecursionProgram {
+            instructions: vec![
+                instr::mem(MemAccessKind::Write, 1, 1, 2),
+                instr::mem(MemAccessKind::Read, 1, 1, 2),
+            ],
+            ..Default::default()
+        };
+        program.set_fixed_log2_rows("MemoryConst", 10);
+        assert_eq!(program.fixed_log2_rows("MemoryConst"), Some(10));
+        assert_eq!(program.fixed_log2_rows("MemoryVar"), None);
+
+        let chip = MemoryChip::<F>::default();
+        let preprocessed = chip.generate_preprocessed_trace(&program).unwrap();
+        let shard =
+            ExecutionRecord::builder().program(Arc::new(program)).mem_const_count(2).build();
+        let main = chip.generate_trace(&shard, &mut ExecutionRecord::default());
+        assert_eq!(preprocessed.height(), 1 << 10);
+        assert_eq!(main.height(), 1 << 10);
+    }
+
+    #[test]
+    #[should_panic(expected = "fixed log2 rows is too small")]
+    pub fn fixed_log2_rows_too_small() {
+        let mut program = RecursionProgram {
+            instructions: vec![
+                instr::mem(MemAccessKind::Write, 1, 1, 2),
+                instr::mem(MemAccessKind::Write, 1, 2, 3),
+                instr::mem(MemAccessKind::Write, 1, 3, 4),
+            ],
+            ..Default::default()
+        };
+        // A pin is exact: unlike a minimum height, it does not grow to fit the three rows.
+        program.set_fixed_log2_rows("MemoryConst", 1);
+        program.min_log_heights.insert("MemoryConst".to_string(), 4);
+
+        MemoryChip::<F>::default().generate_preprocessed_trace(&program);
+    }
+
+    #[test]
+    pub fn entries_per_row() {
+        /// The number of rows before padding, and the heights of the preprocessed and main traces.
+        fn rows_and_heights<const N: usize>(
+            program: &RecursionProgram<F>,
+            record: &ExecutionRecord<F>,
+        ) -> (usize, usize, usize) {
+            let chip = MemoryChip::<F, N>::default();
+            let num_rows = MemoryChip::<F, N>::num_rows(record);
+            let preprocessed = chip.generate_preprocessed_trace(program).unwrap();
+            let main = chip.generate_trace(record, &mut ExecutionRecord::default());
+
+            // The preprocessed trace has exactly `num_rows` rows before its padding.
+            assert!(preprocessed.row_slice(num_rows - 1).iter().any(|x| !x.is_zero()));
+            assert!(preprocessed.row_slice(num_rows).iter().all(|x| x.is_zero()));
+            (num_rows, preprocessed.height(), main.height())
+        }
+
+        // 100 accesses, which do not fill the last row for 8 entries per row.
+        let instructions = (0..50)
+            .flat_map(|i| {
+                [
+                    instr::mem(MemAccessKind::Write, 1, i, i),
+                    instr::mem(MemAccessKind::Read, 1, i, i),
+                ]
+            })
+            .collect();
+        let program = Arc::new(RecursionProgram { instructions, ..Default::default() });
+        let mut runtime = Runtime::<F, EF, DiffusionMatrixBabyBear>::new(
+            program.clone(),
+            BabyBearPoseidon2Inner::new().perm,
+        );
+        runtime.run().unwrap();
+
+        assert_eq!(rows_and_heights::<2>(&program, &runtime.record), (50, 64, 64));
+        assert_eq!(rows_and_heights::<4>(&program, &runtime.record), (25, 32, 32));
+        assert_eq!(rows_and_heights::<8>(&program, &runtime.record), (13, 16, 16));
+    }
+
     #[test]
     #[should_panic]
     pub fn basic_mem_bad_mult() {
@@ -4,12 +4,11 @@ use p3_air::{Air, BaseAir, PairBuilder};
 use p3_field::PrimeField32;
 use p3_matrix::{dense::RowMajorMatrix, Matrix};
 use p3_maybe_rayon::prelude::*;
-use sp1_core_machine::utils::{next_power_of_two, pad_to_power_of_two};
 use sp1_derive::AlignedBorrow;
 use sp1_stark::air::MachineAir;
 use std::{borrow::BorrowMut, iter::zip, marker::PhantomData};
 
-use crate::{builder::SP1RecursionAirBuilder, *};
+use crate::{builder::SP1RecursionAirBuilder, chips::padded_trace_height, *};
 
 use super::{MemoryAccessCols, NUM_MEM_ACCESS_COLS};
 
@@ -17,6 +16,8 @@ pub const NUM_MEM_ENTRIES_PER_ROW: usize = 16;
 
 #[derive(Default)]
 pub struct MemoryChip<F> {
+    /// Pins the traces to `2^fixed_log2_rows` rows, overriding the program's minimum height.
+    pub fixed_log2_rows: Option<usize>,
     _data: PhantomData<F>,
 }
 
@@ -55,6 +56,11 @@ impl<F: PrimeField32> MachineAir<F> for MemoryChip<F> {
         NUM_MEM_PREPROCESSED_INIT_COLS
     }
 
+    fn skip_zero_multiplicity(&self) -> bool {
+        // Padding rows only have interactions with zero multiplicity.
+        true
+    }
+
     fn generate_preprocessed_trace(&self, program: &Self::Program) -> Option<RowMajorMatrix<F>> {
         // Allocating an intermediate `Vec` is faster.
         let accesses = program
@@ -75,7 +81,8 @@ impl<F: PrimeField32> MachineAir<F> for MemoryChip<F> {
             .collect::<Vec<_>>();
 
         let nb_rows = accesses.len().div_ceil(NUM_MEM_ENTRIES_PER_ROW);
-        let padded_nb_rows = next_power_of_two(nb_rows, None);
+        let padded_nb_rows =
+            padded_trace_height(nb_rows, self.fixed_log2_rows, program, &self.name());
         let mut values = vec![F::zero(); padded_nb_rows * NUM_MEM_PREPROCESSED_INIT_COLS];
         // Generate the trace rows & corresponding records for each chunk of events in parallel.
         let populate_len = accesses.len() * NUM_MEM_ACCESS_COLS;
@@ -92,11 +99,44 @@ impl<F: PrimeField32> MachineAir<F> for MemoryChip<F> {
     }
 
     fn generate_trace(&self, input: &Self::Record, _: &mut Self::Record) -> RowMajorMatrix<F> {
-        // Generate the trace rows & corresponding records for each chunk of events in parallel.
-        let rows = input
-            .mem_var_events
+        let mut trace = self.generate_trace_range(input, 0, Self::num_rows(input));
+
+        // Pad the trace to a power of two, at least the minimum height, or to the pinned height.
+        let padded_nb_rows =
+            padded_trace_height(trace.height(), self.fixed_log2_rows, &input.program, &self.name());
+        trace.values.resize(padded_nb_rows * NUM_MEM_INIT_COLS, F::zero());
+
+        trace
+    }
+
+    fn included(&self, _record: &Self::Record) -> bool {
+        true
+    }
+}
+
+impl<F: PrimeField32> MemoryChip<F> {
+    /// The number of main trace rows before padding.
+    pub fn num_rows(input: &crate::ExecutionRecord<F>) -> usize {
+        input.mem_var_events.len().div_ceil(NUM_MEM_ENTRIES_PER_ROW)
+    }
+
+    /// Generates the main trace rows `start..start + len` without padding.
+    ///
+    /// Rows past [`Self::num_rows`] are omitted, so padding the concatenation of ranges covering
+    /// `0..num_rows` gives the same trace as `generate_trace`.
+    pub fn generate_trace_range(
+        &self,
+        input: &crate::ExecutionRecord<F>,
+        start: usize,
+        len: usize,
+    ) -> RowMajorMatrix<F> {
+        let events = &input.mem_var_events;
+        let event_start = (start * NUM_MEM_ENTRIES_PER_ROW).min(events.len());
+        let event_end = (start + len).saturating_mul(NUM_MEM_ENTRIES_PER_ROW).min(events.len());
+
+        let values = events[event_start..event_end]
             .chunks(NUM_MEM_ENTRIES_PER_ROW)
-            .map(|row_events| {
+            .flat_map(|row_events| {
                 let mut row = [F::zero(); NUM_MEM_INIT_COLS];
                 let cols: &mut MemoryCols<_> = row.as_mut_slice().borrow_mut();
                 for (cell, vals) in zip(&mut cols.values, row_events) {
@@ -106,18 +146,7 @@ impl<F: PrimeField32> MachineAir<F> for MemoryChip<F> {
             })
             .collect::<Vec<_>>();
 
-        // Convert the trace to a row major matrix.
-        let mut trace =
-            RowMajorMatrix::new(rows.into_iter().flatten().collect::<Vec<_>>(), NUM_MEM_INIT_COLS);
-
-        // Pad the trace to a power of two.
-        pad_to_power_of_two::<NUM_MEM_INIT_COLS, F>(&mut trace.values);
-
-        trace
-    }
-
-    fn included(&self, _record: &Self::Record) -> bool {
-        true
+        RowMajorMatrix::new(values, NUM_MEM_INIT_COLS)
     }
 }
 
@@ -145,6 +174,7 @@ mod tests {
     use p3_baby_bear::BabyBear;
     use p3_field::AbstractField;
     use p3_matrix::dense::RowMajorMatrix;
+    use sp1_core_machine::utils::pad_to_min_log_height;
 
     use super::*;
 
@@ -152,19 +182,39 @@ mod tests {
 
     #[test]
     pub fn generate_trace() {
-        let shard = ExecutionRecord::<BabyBear> {
-            mem_var_events: vec![
+        let shard = ExecutionRecord::<BabyBear>::builder()
+            .mem_var(vec![
                 MemEvent { inner: BabyBear::one().into() },
                 MemEvent { inner: BabyBear::one().into() },
-            ],
-            ..Default::default()
-        };
+            ])
+            .build();
         let chip = MemoryChip::default();
         let trace: RowMajorMatrix<BabyBear> =
             chip.generate_trace(&shard, &mut ExecutionRecord::default());
         println!("{:?}", trace.values)
     }
 
+    #[test]
+    pub fn generate_trace_range() {
+        let shard = ExecutionRecord::<BabyBear>::builder()
+            .mem_var(
+                (0..40u32)
+                    .map(|i| MemEvent { inner: BabyBear::from_canonical_u32(i).into() })
+                    .collect(),
+            )
+            .build();
+        let chip = MemoryChip::default();
+        let trace = chip.generate_trace(&shard, &mut ExecutionRecord::default());
+
+        let mut values = [(0, 1), (1, 1), (2, 5)]
+            .into_iter()
+            .flat_map(|(start, len)| chip.generate_trace_range(&shard, start, len).values)
+            .collect::<Vec<_>>();
+        assert_eq!(values.len(), MemoryChip::num_rows(&shard) * NUM_MEM_INIT_COLS);
+        pad_to_min_log_height::<NUM_MEM_INIT_COLS, BabyBear>(&mut values, None);
+        assert_eq!(values, trace.values);
+    }
+
     #[test]
     pub fn prove_basic_mem() {
         let program = RecursionProgram {
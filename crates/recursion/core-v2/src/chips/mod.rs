@@ -7,3 +7,63 @@ pub mod mem;
 pub mod poseidon2_skinny;
 pub mod poseidon2_wide;
 pub mod public_values;
+
+#[cfg(not(feature = "debug-instr-id"))]
+use core::marker::PhantomData;
+
+use sp1_core_machine::utils::{next_power_of_two, padded_height};
+
+use crate::RecursionProgram;
+
+#[cfg(feature = "debug-instr-id")]
+use p3_field::Field;
+
+/// The index in the program of the instruction that a preprocessed row, or an entry of one, was
+/// generated from.
+///
+/// With the `debug-instr-id` feature, this is an unconstrained column, so that a row reported by a
+/// failing constraint can be traced back to instruction #N. Otherwise it takes no columns, so the
+/// traces of release proofs keep their width.
+#[derive(Debug, Clone, Copy)]
+#[repr(C)]
+pub struct DebugInstrId<F: Copy> {
+    #[cfg(feature = "debug-instr-id")]
+    pub id: F,
+    #[cfg(not(feature = "debug-instr-id"))]
+    _marker: PhantomData<F>,
+}
+
+#[cfg(feature = "debug-instr-id")]
+impl<F: Field> DebugInstrId<F> {
+    /// The column for the instruction at `index`.
+    pub fn new(index: usize) -> Self {
+        Self { id: F::from_canonical_usize(index) }
+    }
+}
+
+#[cfg(not(feature = "debug-instr-id"))]
+impl<F: Copy> DebugInstrId<F> {
+    /// The column for the instruction at `index`, which is not recorded without the
+    /// `debug-instr-id` feature.
+    pub fn new(_index: usize) -> Self {
+        Self { _marker: PhantomData }
+    }
+}
+
+/// The number of rows the trace of the chip named `chip_name` is padded to, from its `nb_rows`
+/// rows.
+///
+/// A chip pinned by its own `fixed_log2_rows`, or else by
+/// [`RecursionProgram::set_fixed_log2_rows`], gets exactly that height, and panics if the trace does
+/// not fit. Otherwise the trace is padded to a power of two, at least `2^min_log_height`.
+pub(crate) fn padded_trace_height<F>(
+    nb_rows: usize,
+    fixed_log2_rows: Option<usize>,
+    program: &RecursionProgram<F>,
+    chip_name: &str,
+) -> usize {
+    match fixed_log2_rows.or_else(|| program.fixed_log2_rows(chip_name)) {
+        Some(log_height) => next_power_of_two(nb_rows, Some(log_height)),
+        None => padded_height(nb_rows, program.min_log_height(chip_name)),
+    }
+}
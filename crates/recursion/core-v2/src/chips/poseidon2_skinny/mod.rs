@@ -1,5 +1,6 @@
 use p3_baby_bear::{MONTY_INVERSE, POSEIDON2_INTERNAL_MATRIX_DIAG_16_BABYBEAR_MONTY};
 use p3_field::{AbstractField, PrimeField32};
+use sp1_primitives::RC_16_30_U32;
 
 pub mod air;
 pub mod columns;
@@ -15,18 +16,36 @@ pub const NUM_EXTERNAL_ROUNDS: usize = 8;
 pub const NUM_INTERNAL_ROUNDS: usize = 13;
 pub const NUM_ROUNDS: usize = NUM_EXTERNAL_ROUNDS + NUM_INTERNAL_ROUNDS;
 
+/// The number of rounds in a [`Poseidon2Constants`] set.
+pub const NUM_ROUND_CONSTANTS: usize = 30;
+
+/// The round constants of a Poseidon2 instance, as `WIDTH` constants per round. The constants are
+/// reduced into the field when used.
+pub type Poseidon2Constants = [[u32; WIDTH]; NUM_ROUND_CONSTANTS];
+
 /// A chip that implements the Poseidon2 permutation in the skinny variant (one external round per
 /// row and one row for all internal rounds).
 pub struct Poseidon2SkinnyChip<const DEGREE: usize> {
     pub fixed_log2_rows: Option<usize>,
     pub pad: bool,
+    /// The round constants of the permutation. They are committed to in the preprocessed trace,
+    /// which is where the constraints read them from.
+    pub constants: &'static Poseidon2Constants,
 }
 
-impl<const DEGREE: usize> Default for Poseidon2SkinnyChip<DEGREE> {
-    fn default() -> Self {
+impl<const DEGREE: usize> Poseidon2SkinnyChip<DEGREE> {
+    /// A chip for the Poseidon2 instance with the given round constants.
+    pub fn new(constants: &'static Poseidon2Constants) -> Self {
         // We only support machines with degree 9.
         assert!(DEGREE >= 9);
-        Self { fixed_log2_rows: None, pad: true }
+        Self { fixed_log2_rows: None, pad: true, constants }
+    }
+}
+
+impl<const DEGREE: usize> Default for Poseidon2SkinnyChip<DEGREE> {
+    /// A chip for the Poseidon2 instance used by the SP1 recursion config.
+    fn default() -> Self {
+        Self::new(&RC_16_30_U32)
     }
 }
 pub fn apply_m_4<AF>(x: &mut [AF])
@@ -76,36 +95,128 @@ pub(crate) mod tests {
     use std::{iter::once, sync::Arc};
 
     use crate::{
-        machine::RecursionAir, runtime::instruction as instr, MemAccessKind, RecursionProgram,
-        Runtime,
+        machine::RecursionAir, runtime::instruction as instr, ExecutionRecord, MemAccessKind,
+        RecursionProgram, Runtime,
     };
     use p3_baby_bear::{BabyBear, DiffusionMatrixBabyBear};
     use p3_field::{AbstractField, PrimeField32};
+    use p3_poseidon2::{Poseidon2, Poseidon2ExternalMatrixGeneral};
     use p3_symmetric::Permutation;
 
     use sp1_core_machine::utils::{run_test_machine, setup_logger};
+    use sp1_primitives::RC_16_30_U32;
     use sp1_recursion_core::stark::config::BabyBearPoseidon2Outer;
-    use sp1_stark::{baby_bear_poseidon2::BabyBearPoseidon2, inner_perm, StarkGenericConfig};
+    use sp1_stark::{
+        inner_perm, Chip, InnerPerm, StarkGenericConfig, StarkMachine, PROOF_MAX_NUM_PVS,
+    };
     use zkhash::ark_ff::UniformRand;
 
-    use super::WIDTH;
+    use super::{
+        Poseidon2Constants, Poseidon2SkinnyChip, NUM_EXTERNAL_ROUNDS, NUM_INTERNAL_ROUNDS, WIDTH,
+    };
+
+    type SC = BabyBearPoseidon2Outer;
+    type F = <SC as StarkGenericConfig>::Val;
+    type EF = <SC as StarkGenericConfig>::Challenge;
+    type B = RecursionAir<F, 9, 1>;
 
     #[test]
     fn test_poseidon2() {
         setup_logger();
-        type SC = BabyBearPoseidon2Outer;
-        type F = <SC as StarkGenericConfig>::Val;
-        type EF = <SC as StarkGenericConfig>::Challenge;
-        type B = RecursionAir<F, 9, 1>;
+        let (program, record) = poseidon2_program();
+
+        let config = SC::new();
+        let machine_deg_9 = B::machine(config);
+        let (pk_9, vk_9) = machine_deg_9.setup(&program);
+        let result_deg_9 = run_test_machine(vec![record], machine_deg_9, pk_9, vk_9);
+        if let Err(e) = result_deg_9 {
+            panic!("Verification failed: {:?}", e);
+        }
+    }
+
+    #[test]
+    fn test_poseidon2_with_constants() {
+        setup_logger();
+        let (program, record) = poseidon2_program();
+
+        let machine = machine_with_constants(&RC_16_30_U32);
+        let (pk, vk) = machine.setup(&program);
+        run_test_machine(vec![record], machine, pk, vk).unwrap();
+    }
 
+    #[test]
+    fn test_poseidon2_with_custom_constants() {
+        setup_logger();
+        let constants: &'static Poseidon2Constants =
+            Box::leak(Box::new(RC_16_30_U32.map(|round| round.map(|c| c ^ 1))));
+
+        // A trace of the permutation with the default constants does not verify against a machine
+        // committed to other constants.
+        let (program, record) = poseidon2_program();
+        let default_machine = machine_with_constants(&RC_16_30_U32);
+        let (pk, vk) = default_machine.setup(&program);
+        let proof = run_test_machine(vec![record], default_machine, pk, vk).unwrap();
+        let custom_machine = machine_with_constants(constants);
+        let (_, custom_vk) = custom_machine.setup(&program);
+        let mut challenger = custom_machine.config().challenger();
+        assert!(custom_machine.verify(&custom_vk, &proof, &mut challenger).is_err());
+
+        // A trace of the permutation with the same constants as the machine verifies.
+        let (program, record) = poseidon2_program_with_perm(perm_with_constants(constants));
+        let (pk, vk) = custom_machine.setup(&program);
+        run_test_machine(vec![record], custom_machine, pk, vk).unwrap();
+    }
+
+    /// The recursion machine, with the Poseidon2 chip using the given round constants.
+    fn machine_with_constants(constants: &'static Poseidon2Constants) -> StarkMachine<SC, B> {
+        let chips = B::get_all()
+            .into_iter()
+            .map(|chip| match chip {
+                RecursionAir::Poseidon2Skinny(_) => {
+                    RecursionAir::Poseidon2Skinny(Poseidon2SkinnyChip::new(constants))
+                }
+                chip => chip,
+            })
+            .map(Chip::new)
+            .collect();
+        StarkMachine::new(SC::new(), chips, PROOF_MAX_NUM_PVS)
+    }
+
+    /// The Poseidon2 permutation with the given round constants, laid out as in
+    /// [`sp1_primitives::poseidon2_init`].
+    fn perm_with_constants(constants: &Poseidon2Constants) -> InnerPerm {
+        let mut round_constants =
+            constants.map(|round| round.map(BabyBear::from_wrapped_u32)).to_vec();
+        let internal_round_constants = round_constants
+            .drain(NUM_EXTERNAL_ROUNDS / 2..NUM_EXTERNAL_ROUNDS / 2 + NUM_INTERNAL_ROUNDS)
+            .map(|round| round[0])
+            .collect();
+        Poseidon2::new(
+            NUM_EXTERNAL_ROUNDS,
+            round_constants,
+            Poseidon2ExternalMatrixGeneral,
+            NUM_INTERNAL_ROUNDS,
+            internal_round_constants,
+            DiffusionMatrixBabyBear,
+        )
+    }
+
+    /// A program hashing two inputs, along with its execution record.
+    pub(crate) fn poseidon2_program() -> (Arc<RecursionProgram<F>>, ExecutionRecord<F>) {
+        poseidon2_program_with_perm(inner_perm())
+    }
+
+    /// A program hashing two inputs with `perm`, along with its execution record.
+    fn poseidon2_program_with_perm(
+        perm: InnerPerm,
+    ) -> (Arc<RecursionProgram<F>>, ExecutionRecord<F>) {
         let input = [1; WIDTH];
-        let output = inner_perm()
-            .permute(input.map(BabyBear::from_canonical_u32))
-            .map(|x| BabyBear::as_canonical_u32(&x));
+        let output =
+            perm.permute(input.map(BabyBear::from_canonical_u32)).map(|x| x.as_canonical_u32());
 
         let rng = &mut rand::thread_rng();
         let input_1: [BabyBear; WIDTH] = std::array::from_fn(|_| BabyBear::rand(rng));
-        let output_1 = inner_perm().permute(input_1).map(|x| BabyBear::as_canonical_u32(&x));
+        let output_1 = perm.permute(input_1).map(|x| x.as_canonical_u32());
         let input_1 = input_1.map(|x| BabyBear::as_canonical_u32(&x));
 
         let instructions =
@@ -134,18 +245,8 @@ pub(crate) mod tests {
                 .collect::<Vec<_>>();
 
         let program = Arc::new(RecursionProgram { instructions, ..Default::default() });
-        let mut runtime = Runtime::<F, EF, DiffusionMatrixBabyBear>::new(
-            program.clone(),
-            BabyBearPoseidon2::new().perm,
-        );
+        let mut runtime = Runtime::<F, EF, DiffusionMatrixBabyBear>::new(program.clone(), perm);
         runtime.run().unwrap();
-
-        let config = SC::new();
-        let machine_deg_9 = B::machine(config);
-        let (pk_9, vk_9) = machine_deg_9.setup(&program);
-        let result_deg_9 = run_test_machine(vec![runtime.record], machine_deg_9, pk_9, vk_9);
-        if let Err(e) = result_deg_9 {
-            panic!("Verification failed: {:?}", e);
-        }
+        (program, runtime.record)
     }
 }
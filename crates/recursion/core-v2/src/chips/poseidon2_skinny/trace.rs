@@ -8,7 +8,6 @@ use itertools::Itertools;
 use p3_field::PrimeField32;
 use p3_matrix::{dense::RowMajorMatrix, Matrix};
 use sp1_core_machine::utils::pad_rows_fixed;
-use sp1_primitives::RC_16_30_U32;
 use sp1_stark::air::MachineAir;
 use tracing::instrument;
 
@@ -166,9 +165,9 @@ impl<F: PrimeField32, const DEGREE: usize> MachineAir<F> for Poseidon2SkinnyChip
                                 r + NUM_INTERNAL_ROUNDS - 1
                             };
 
-                            F::from_wrapped_u32(RC_16_30_U32[round][j])
+                            F::from_wrapped_u32(self.constants[round][j])
                         } else if i == INTERNAL_ROUND_IDX {
-                            F::from_wrapped_u32(RC_16_30_U32[NUM_EXTERNAL_ROUNDS / 2 + j][0])
+                            F::from_wrapped_u32(self.constants[NUM_EXTERNAL_ROUNDS / 2 + j][0])
                         } else {
                             F::zero()
                         };
@@ -218,7 +217,7 @@ impl<const DEGREE: usize> Poseidon2SkinnyChip<DEGREE> {
             // sbox.
             let round = if r < NUM_EXTERNAL_ROUNDS / 2 { r } else { r + NUM_INTERNAL_ROUNDS - 1 };
             let mut add_rc = *round_state;
-            (0..WIDTH).for_each(|i| add_rc[i] += F::from_wrapped_u32(RC_16_30_U32[round][i]));
+            (0..WIDTH).for_each(|i| add_rc[i] += F::from_wrapped_u32(self.constants[round][i]));
 
             // Apply the sboxes.
             // Optimization: since the linear layer that comes after the sbox is degree 1, we can
@@ -248,7 +247,7 @@ impl<const DEGREE: usize> Poseidon2SkinnyChip<DEGREE> {
             // Optimization: Since adding a constant is a degree 1 operation, we can avoid adding
             // columns for it, just like for external rounds.
             let round = r + NUM_EXTERNAL_ROUNDS / 2;
-            let add_rc = new_state[0] + F::from_wrapped_u32(RC_16_30_U32[round][0]);
+            let add_rc = new_state[0] + F::from_wrapped_u32(self.constants[round][0]);
 
             // Apply the sboxes.
             // Optimization: since the linear layer that comes after the sbox is degree 1, we can
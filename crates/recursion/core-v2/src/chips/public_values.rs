@@ -22,6 +22,10 @@ pub const NUM_PUBLIC_VALUES_COLS: usize = core::mem::size_of::<PublicValuesCols<
 pub const NUM_PUBLIC_VALUES_PREPROCESSED_COLS: usize =
     core::mem::size_of::<PublicValuesPreprocessedCols<u8>>();
 
+/// The log2 height of the traces of [`PublicValuesChip`], which always have one row per digest
+/// element.
+pub const PUB_VALUES_LOG_HEIGHT: usize = 3;
+
 #[derive(Default)]
 pub struct PublicValuesChip {}
 
@@ -94,7 +98,11 @@ impl<F: PrimeField32> MachineAir<F> for PublicValuesChip {
         }
 
         // Pad the preprocessed rows to 8 rows.
-        pad_rows_fixed(&mut rows, || [F::zero(); NUM_PUBLIC_VALUES_PREPROCESSED_COLS], Some(3));
+        pad_rows_fixed(
+            &mut rows,
+            || [F::zero(); NUM_PUBLIC_VALUES_PREPROCESSED_COLS],
+            Some(PUB_VALUES_LOG_HEIGHT),
+        );
 
         let trace = RowMajorMatrix::new(
             rows.into_iter().flatten().collect(),
@@ -127,7 +135,11 @@ impl<F: PrimeField32> MachineAir<F> for PublicValuesChip {
         }
 
         // Pad the trace to 8 rows.
-        pad_rows_fixed(&mut rows, || [F::zero(); NUM_PUBLIC_VALUES_COLS], Some(3));
+        pad_rows_fixed(
+            &mut rows,
+            || [F::zero(); NUM_PUBLIC_VALUES_COLS],
+            Some(PUB_VALUES_LOG_HEIGHT),
+        );
 
         // Convert the trace to a row major matrix.
         RowMajorMatrix::new(rows.into_iter().flatten().collect(), NUM_PUBLIC_VALUES_COLS)
@@ -10,6 +10,7 @@ pub mod builder;
 pub mod chips;
 pub mod machine;
 pub mod runtime;
+pub mod shape;
 
 pub use runtime::*;
 
@@ -50,6 +51,9 @@ pub struct BaseAluInstr<F> {
     pub opcode: BaseAluOpcode,
     pub mult: F,
     pub addrs: BaseAluIo<Address<F>>,
+    /// Where `DivFChecked` writes whether the divisor was nonzero. Unused by other opcodes.
+    pub flag_addr: Address<F>,
+    pub flag_mult: F,
 }
 
 // -------------------------------------------------------------------------------------------------
@@ -71,6 +75,9 @@ pub struct ExtAluInstr<F> {
     pub opcode: ExtAluOpcode,
     pub mult: F,
     pub addrs: ExtAluIo<Address<F>>,
+    /// Where `DivEChecked` writes whether the divisor was nonzero. Unused by other opcodes.
+    pub flag_addr: Address<F>,
+    pub flag_mult: F,
 }
 
 // -------------------------------------------------------------------------------------------------
@@ -1,6 +1,14 @@
+use std::collections::{HashMap, HashSet};
+
+use p3_air::{Air, BaseAir};
 use p3_field::{extension::BinomiallyExtendable, PrimeField32};
-use sp1_recursion_core::runtime::D;
-use sp1_stark::{Chip, StarkGenericConfig, StarkMachine, PROOF_MAX_NUM_PVS};
+use p3_matrix::{dense::RowMajorMatrix, Matrix};
+use sp1_recursion_core::{air::RECURSIVE_PROOF_NUM_PV_ELTS, runtime::D};
+use sp1_stark::{
+    air::MachineAir, Chip, MachineProof, MachineVerificationError, StarkGenericConfig,
+    StarkMachine, StarkProvingKey, StarkVerifyingKey, VerifierConstraintFolder, PROOF_MAX_NUM_PVS,
+};
+use thiserror::Error;
 
 use crate::chips::{
     alu_base::BaseAluChip,
@@ -11,7 +19,11 @@ use crate::chips::{
     mem::{MemoryConstChip, MemoryVarChip},
     poseidon2_skinny::Poseidon2SkinnyChip,
     poseidon2_wide::Poseidon2WideChip,
-    public_values::PublicValuesChip,
+    public_values::{PublicValuesChip, PUB_VALUES_LOG_HEIGHT},
+};
+use crate::{
+    shape::{RecursionShape, ShapeViolation},
+    AddressError, Instruction, MemoryDiagnostic, RecursionProgram,
 };
 
 #[derive(sp1_derive::MachineAir)]
@@ -21,15 +33,16 @@ use crate::chips::{
 #[builder_path = "crate::builder::SP1RecursionAirBuilder<F = F>"]
 #[eval_trait_bound = "AB::Var: 'static"]
 pub enum RecursionAir<
-    F: PrimeField32 + BinomiallyExtendable<D>,
+    F: PrimeField32 + BinomiallyExtendable<EXT_DEGREE>,
     const DEGREE: usize,
     const COL_PADDING: usize,
+    const EXT_DEGREE: usize = D,
 > {
     // Program(ProgramChip<F>),
     MemoryConst(MemoryConstChip<F>),
     MemoryVar(MemoryVarChip<F>),
     BaseAlu(BaseAluChip),
-    ExtAlu(ExtAluChip),
+    ExtAlu(ExtAluChip<EXT_DEGREE>),
     // Cpu(CpuChip<F, DEGREE>),
     // MemoryGlobal(MemoryGlobalChip),
     Poseidon2Skinny(Poseidon2SkinnyChip<DEGREE>),
@@ -42,9 +55,370 @@ pub enum RecursionAir<
     DummyWide(DummyChip<COL_PADDING>),
 }
 
-impl<F: PrimeField32 + BinomiallyExtendable<D>, const DEGREE: usize, const COL_PADDING: usize>
-    RecursionAir<F, DEGREE, COL_PADDING>
+/// Names a chip of [`RecursionAir`], for assembling machines from a custom set of chips with
+/// [`RecursionAir::machine_from_chips`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RecursionAirKind {
+    MemoryConst,
+    MemoryVar,
+    BaseAlu,
+    ExtAlu,
+    Poseidon2Skinny,
+    Poseidon2Wide,
+    FriFold,
+    ExpReverseBitsLen,
+    PublicValues,
+}
+
+impl RecursionAirKind {
+    /// The chips every recursion machine must contain.
+    pub const REQUIRED: [Self; 3] = [Self::MemoryConst, Self::MemoryVar, Self::PublicValues];
+
+    /// The chip that proves `instruction`, or `None` if it is not proven, like `Print`.
+    ///
+    /// Hints are proven by the [`MemoryVar`](Self::MemoryVar) chip, which checks the writes of
+    /// their outputs.
+    pub const fn for_instruction<F>(instruction: &Instruction<F>) -> Option<Self> {
+        match instruction {
+            Instruction::BaseAlu(_) => Some(Self::BaseAlu),
+            Instruction::ExtAlu(_) => Some(Self::ExtAlu),
+            Instruction::Mem(_) => Some(Self::MemoryConst),
+            Instruction::Poseidon2(_) => Some(Self::Poseidon2Wide),
+            Instruction::ExpReverseBitsLen(_) => Some(Self::ExpReverseBitsLen),
+            Instruction::FriFold(_) => Some(Self::FriFold),
+            Instruction::CommitPublicValues(_) => Some(Self::PublicValues),
+            Instruction::HintBits(_) | Instruction::HintExt2Felts(_) | Instruction::Hint(_) => {
+                Some(Self::MemoryVar)
+            }
+            Instruction::Print(_) => None,
+        }
+    }
+
+    /// The chips needed to prove `program`: the [`REQUIRED`](Self::REQUIRED) chips and those that
+    /// prove at least one of its instructions, in the order of [`RecursionAir::get_all_wide`].
+    pub fn for_program<F>(program: &RecursionProgram<F>) -> Vec<Self> {
+        let used =
+            program.instructions.iter().filter_map(Self::for_instruction).collect::<HashSet<_>>();
+
+        [
+            Self::MemoryConst,
+            Self::MemoryVar,
+            Self::BaseAlu,
+            Self::ExtAlu,
+            Self::Poseidon2Wide,
+            Self::ExpReverseBitsLen,
+            Self::FriFold,
+            Self::PublicValues,
+        ]
+        .into_iter()
+        .filter(|kind| Self::REQUIRED.contains(kind) || used.contains(kind))
+        .collect()
+    }
+}
+
+/// An error returned by [`RecursionAir::validate_col_padding`].
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum ColPaddingError {
+    #[error(
+        "COL_PADDING is {col_padding}, but chip {chip} has {width} columns, so padding cannot make \
+        the widths uniform"
+    )]
+    TooSmall { col_padding: usize, chip: String, width: usize },
+}
+
+/// An error returned by [`RecursionAir::check_disabled_chips`].
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum DisabledChipError {
+    #[error("chip {0} is disabled, but the program needs it")]
+    Needed(String),
+}
+
+/// An error returned by [`RecursionAir::try_setup`].
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum SetupError<F: std::fmt::Debug> {
+    #[error(transparent)]
+    DisabledChip(#[from] DisabledChipError),
+    #[error("the program needs chip {0}, which the machine lacks")]
+    MissingChip(String),
+    #[error(transparent)]
+    Address(#[from] AddressError<F>),
+    #[error("the program's memory accesses are inconsistent: {0:?}")]
+    Memory(Vec<MemoryDiagnostic<F>>),
+    #[error("the program's traces do not fit the heights it pins: {0:?}")]
+    Shape(Vec<ShapeViolation>),
+}
+
+impl<
+        F: PrimeField32 + BinomiallyExtendable<EXT_DEGREE>,
+        const DEGREE: usize,
+        const COL_PADDING: usize,
+        const EXT_DEGREE: usize,
+    > RecursionAir<F, DEGREE, COL_PADDING, EXT_DEGREE>
 {
+    /// A recursion machine with the given chips, in the given order, with their default
+    /// configurations.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a chip is listed twice or if one of [`RecursionAirKind::REQUIRED`] is missing.
+    pub fn machine_from_chips<SC: StarkGenericConfig<Val = F>>(
+        config: SC,
+        chips: &[RecursionAirKind],
+    ) -> StarkMachine<SC, Self> {
+        let chips = Self::get_chips(chips).into_iter().map(Chip::new).collect::<Vec<_>>();
+        StarkMachine::new(config, chips, PROOF_MAX_NUM_PVS)
+    }
+
+    /// The chips named by `kinds`, in order, with their default configurations.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a chip is listed twice or if one of [`RecursionAirKind::REQUIRED`] is missing.
+    pub fn get_chips(kinds: &[RecursionAirKind]) -> Vec<Self> {
+        for (i, kind) in kinds.iter().enumerate() {
+            assert!(!kinds[..i].contains(kind), "chip {kind:?} is listed more than once");
+        }
+        for required in RecursionAirKind::REQUIRED {
+            assert!(kinds.contains(&required), "recursion machines require the {required:?} chip");
+        }
+
+        kinds
+            .iter()
+            .map(|kind| match kind {
+                RecursionAirKind::MemoryConst => {
+                    RecursionAir::MemoryConst(MemoryConstChip::default())
+                }
+                RecursionAirKind::MemoryVar => RecursionAir::MemoryVar(MemoryVarChip::default()),
+                RecursionAirKind::BaseAlu => RecursionAir::BaseAlu(BaseAluChip::default()),
+                RecursionAirKind::ExtAlu => RecursionAir::ExtAlu(ExtAluChip::default()),
+                RecursionAirKind::Poseidon2Skinny => {
+                    RecursionAir::Poseidon2Skinny(Poseidon2SkinnyChip::<DEGREE>::default())
+                }
+                RecursionAirKind::Poseidon2Wide => {
+                    RecursionAir::Poseidon2Wide(Poseidon2WideChip::<DEGREE>::default())
+                }
+                RecursionAirKind::FriFold => {
+                    RecursionAir::FriFold(FriFoldChip::<DEGREE>::default())
+                }
+                RecursionAirKind::ExpReverseBitsLen => {
+                    RecursionAir::ExpReverseBitsLen(ExpReverseBitsLenChip::<DEGREE>::default())
+                }
+                RecursionAirKind::PublicValues => {
+                    RecursionAir::PublicValues(PublicValuesChip::default())
+                }
+            })
+            .collect()
+    }
+
+    /// The number of main columns of the [`DummyChip`] this machine pads with. Zero means no
+    /// padding.
+    pub const fn col_padding() -> usize {
+        COL_PADDING
+    }
+
+    /// Checks that the dummy chip is at least as wide as every other chip in `chips`, which padding
+    /// to a uniform width requires. A `COL_PADDING` of zero disables padding and always passes.
+    pub fn validate_col_padding(chips: &[Self]) -> Result<(), ColPaddingError> {
+        if COL_PADDING == 0 {
+            return Ok(());
+        }
+        let widest = chips
+            .iter()
+            .filter(|chip| !matches!(chip, RecursionAir::DummyWide(_)))
+            .map(|chip| (chip, <Self as BaseAir<F>>::width(chip)))
+            .max_by_key(|&(_, width)| width);
+        match widest {
+            Some((chip, width)) if width > COL_PADDING => Err(ColPaddingError::TooSmall {
+                col_padding: COL_PADDING,
+                chip: chip.name(),
+                width,
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    /// The preprocessed traces of the chips in [`Self::get_all`] for `program`, keyed by chip
+    /// name. Chips without a preprocessed trace are skipped.
+    pub fn generate_all_preprocessed(
+        program: &crate::RecursionProgram<F>,
+    ) -> HashMap<String, RowMajorMatrix<F>> {
+        Self::get_all()
+            .into_iter()
+            .filter_map(|chip| {
+                chip.generate_preprocessed_trace(program).map(|trace| (chip.name(), trace))
+            })
+            .collect()
+    }
+
+    /// A recursion machine with only the chips that `program` needs, see
+    /// [`RecursionAirKind::for_program`]. Proving small programs with it is much faster than with
+    /// [`RecursionAir::machine_wide`], but its verifying key only accepts proofs of programs that
+    /// use the same chips.
+    ///
+    /// # Panics
+    ///
+    /// Panics if one of the chips the program needs is in its
+    /// [`disabled_chips`](RecursionProgram::disabled_chips).
+    pub fn machine_for_program<SC: StarkGenericConfig<Val = F>>(
+        config: SC,
+        program: &RecursionProgram<F>,
+    ) -> StarkMachine<SC, Self> {
+        if let Err(e) = Self::check_disabled_chips(program) {
+            panic!("{e}");
+        }
+        Self::machine_from_chips(config, &RecursionAirKind::for_program(program))
+    }
+
+    /// The [`machine_wide`](Self::machine_wide) recursion machine without the chips `program`
+    /// disables, e.g. to compare the cost of proving with and without a chip.
+    ///
+    /// # Panics
+    ///
+    /// Panics if one of the chips the program needs is in its
+    /// [`disabled_chips`](RecursionProgram::disabled_chips).
+    pub fn machine_wide_for_program<SC: StarkGenericConfig<Val = F>>(
+        config: SC,
+        program: &RecursionProgram<F>,
+    ) -> StarkMachine<SC, Self> {
+        if let Err(e) = Self::check_disabled_chips(program) {
+            panic!("{e}");
+        }
+        let chips = Self::get_all_wide()
+            .into_iter()
+            .filter(|chip| !program.disabled_chips.contains(&chip.name()))
+            .map(Chip::new)
+            .collect::<Vec<_>>();
+        StarkMachine::new(config, chips, PROOF_MAX_NUM_PVS)
+    }
+
+    /// Validates `program` for `machine`, then sets it up like [`StarkMachine::setup`], returning
+    /// an error instead of panicking or producing keys that cannot prove the program.
+    ///
+    /// This checks that:
+    /// - the machine has every chip the program needs, and the program disables none of them;
+    /// - every address is below [`RecursionProgram::total_memory`], if it is set;
+    /// - [`RecursionProgram::validate_memory`] finds no problem;
+    /// - every chip pinned with [`RecursionProgram::set_fixed_log2_rows`] fits its pinned height.
+    #[allow(clippy::type_complexity)]
+    pub fn try_setup<SC: StarkGenericConfig<Val = F>>(
+        machine: &StarkMachine<SC, Self>,
+        program: &RecursionProgram<F>,
+    ) -> Result<(StarkProvingKey<SC>, StarkVerifyingKey<SC>), SetupError<F>> {
+        Self::check_disabled_chips(program)?;
+        let chips = machine.chips().iter().map(|chip| chip.name()).collect::<HashSet<_>>();
+        // Either variant of the Poseidon2 chip proves Poseidon2 instructions.
+        let has_chip = |name: &String| {
+            chips.contains(name)
+                || (name.starts_with("Poseidon2")
+                    && chips.iter().any(|chip| chip.starts_with("Poseidon2")))
+        };
+        if let Some(missing) = Self::get_chips(&RecursionAirKind::for_program(program))
+            .into_iter()
+            .map(|chip| chip.name())
+            .find(|name| !has_chip(name))
+        {
+            return Err(SetupError::MissingChip(missing));
+        }
+
+        if program.total_memory != 0 {
+            program.validate_addresses(program.total_memory)?;
+        }
+        let diagnostics = program.validate_memory();
+        if !diagnostics.is_empty() {
+            return Err(SetupError::Memory(diagnostics));
+        }
+
+        let pins = RecursionShape {
+            inner: program
+                .pinned_log_heights
+                .iter()
+                .map(|(chip, &log)| (chip.clone(), log))
+                .collect(),
+        };
+        let unpinned =
+            RecursionProgram { instructions: program.instructions.clone(), ..Default::default() };
+        let heights = Self::heights(&unpinned)
+            .into_iter()
+            .filter(|(chip, _)| pins.inner.contains_key(chip))
+            .collect::<Vec<_>>();
+        pins.check_fits(&heights).map_err(SetupError::Shape)?;
+
+        Ok(machine.setup(program))
+    }
+
+    /// Verifies `proof` against `vk` with a fresh challenger, like [`StarkMachine::verify`], and
+    /// checks that every shard commits to `public_values`, e.g. the record's
+    /// [`public_values`](sp1_stark::MachineRecord::public_values). Unlike proving, this needs
+    /// neither the program nor its execution.
+    ///
+    /// Only the first [`num_pv_elts`](StarkMachine::num_pv_elts) public values, which the proof
+    /// binds, are compared.
+    pub fn verify<SC: StarkGenericConfig<Val = F>>(
+        machine: &StarkMachine<SC, Self>,
+        vk: &StarkVerifyingKey<SC>,
+        proof: &MachineProof<SC>,
+        public_values: &[F],
+    ) -> Result<(), MachineVerificationError<SC>>
+    where
+        SC::Challenger: Clone,
+        Self: for<'a> Air<VerifierConstraintFolder<'a, SC>>,
+    {
+        let num_pvs = machine.num_pv_elts();
+        if proof.shard_proofs.iter().any(|shard_proof| {
+            shard_proof.public_values.get(..num_pvs) != public_values.get(..num_pvs)
+        }) {
+            return Err(MachineVerificationError::InvalidPublicValues(
+                "shard public values do not match the expected public values",
+            ));
+        }
+        machine.verify(vk, proof, &mut machine.config().challenger())
+    }
+
+    /// Checks that `program` does not disable a chip it needs, see
+    /// [`RecursionAirKind::for_program`].
+    pub fn check_disabled_chips(program: &RecursionProgram<F>) -> Result<(), DisabledChipError> {
+        match Self::get_chips(&RecursionAirKind::for_program(program))
+            .into_iter()
+            .map(|chip| chip.name())
+            .find(|name| program.disabled_chips.contains(name))
+        {
+            Some(name) => Err(DisabledChipError::Needed(name)),
+            None => Ok(()),
+        }
+    }
+
+    /// The heights of the preprocessed traces of the chips in [`Self::get_all_wide`] for
+    /// `program`, in order, keyed by chip name. These are the heights of the chips' main traces
+    /// too, and they only depend on the program. Chips without a preprocessed trace are skipped.
+    pub fn heights(program: &RecursionProgram<F>) -> Vec<(String, usize)> {
+        Self::get_all_wide()
+            .into_iter()
+            .filter_map(|chip| {
+                chip.generate_preprocessed_trace(program).map(|trace| (chip.name(), trace.height()))
+            })
+            .collect()
+    }
+
+    /// The main and preprocessed widths of every chip, as `(name, main_width,
+    /// preprocessed_width)`, in the order of [`RecursionAirKind`]. Unlike [`Self::heights`], these
+    /// do not depend on the program, so no trace is generated.
+    pub fn chip_widths() -> Vec<(String, usize, usize)> {
+        Self::get_chips(&[
+            RecursionAirKind::MemoryConst,
+            RecursionAirKind::MemoryVar,
+            RecursionAirKind::BaseAlu,
+            RecursionAirKind::ExtAlu,
+            RecursionAirKind::Poseidon2Skinny,
+            RecursionAirKind::Poseidon2Wide,
+            RecursionAirKind::FriFold,
+            RecursionAirKind::ExpReverseBitsLen,
+            RecursionAirKind::PublicValues,
+        ])
+        .into_iter()
+        .map(|chip| (chip.name(), <Self as BaseAir<F>>::width(&chip), chip.preprocessed_width()))
+        .collect()
+    }
+
     /// A recursion machine that can have dynamic trace sizes.
     pub fn machine<SC: StarkGenericConfig<Val = F>>(config: SC) -> StarkMachine<SC, Self> {
         let chips = Self::get_all().into_iter().map(Chip::new).collect::<Vec<_>>();
@@ -58,6 +432,66 @@ impl<F: PrimeField32 + BinomiallyExtendable<D>, const DEGREE: usize, const COL_P
         StarkMachine::new(config, chips, PROOF_MAX_NUM_PVS)
     }
 
+    /// A recursion machine for the shrink layer, with every trace pinned to the height given by
+    /// [`Self::shrink_shape`] for the shrink program `program`.
+    ///
+    /// Unlike [`Self::machine_wide`], which has the same chips, proofs of this machine all have the
+    /// same shape. Generating a trace that does not fit its pinned height panics.
+    pub fn shrink_machine<SC: StarkGenericConfig<Val = F>>(
+        config: SC,
+        program: &RecursionProgram<F>,
+    ) -> StarkMachine<SC, Self> {
+        let shape = Self::shrink_shape(program);
+        let chips = Self::get_all_wide()
+            .into_iter()
+            .map(|chip| {
+                let log_height = shape.inner[&chip.name()];
+                Chip::new(chip.with_fixed_log2_rows(log_height))
+            })
+            .collect::<Vec<_>>();
+        StarkMachine::new(config, chips, PROOF_MAX_NUM_PVS)
+    }
+
+    /// The log2 trace heights of the chips of [`Self::shrink_machine`] for the shrink program
+    /// `program`, keyed by chip name.
+    ///
+    /// These are the heights of the traces of `program`, see [`Self::heights`]. The shrink layer
+    /// always proves the same program, the verifier of compressed proofs, so pinning the machine to
+    /// its heights gives every shrink proof the same shape, whatever proof it verifies.
+    pub fn shrink_shape(program: &RecursionProgram<F>) -> RecursionShape {
+        let inner = Self::heights(program)
+            .into_iter()
+            .map(|(chip, height)| (chip, height.ilog2() as usize))
+            .collect();
+        RecursionShape { inner }
+    }
+
+    /// This chip with its traces pinned to `2^log_height` rows.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the chip's height cannot be changed, which is the case for [`PublicValuesChip`]
+    /// at any height other than its own.
+    pub fn with_fixed_log2_rows(mut self, log_height: usize) -> Self {
+        let fixed_log2_rows = Some(log_height);
+        match &mut self {
+            RecursionAir::MemoryConst(chip) => chip.fixed_log2_rows = fixed_log2_rows,
+            RecursionAir::MemoryVar(chip) => chip.fixed_log2_rows = fixed_log2_rows,
+            RecursionAir::BaseAlu(chip) => chip.fixed_log2_rows = fixed_log2_rows,
+            RecursionAir::ExtAlu(chip) => chip.fixed_log2_rows = fixed_log2_rows,
+            RecursionAir::Poseidon2Skinny(chip) => chip.fixed_log2_rows = fixed_log2_rows,
+            RecursionAir::Poseidon2Wide(chip) => chip.fixed_log2_rows = fixed_log2_rows,
+            RecursionAir::FriFold(chip) => chip.fixed_log2_rows = fixed_log2_rows,
+            RecursionAir::ExpReverseBitsLen(chip) => chip.fixed_log2_rows = fixed_log2_rows,
+            RecursionAir::PublicValues(_) => assert_eq!(
+                log_height, PUB_VALUES_LOG_HEIGHT,
+                "the public values chip has a fixed log2 height of {PUB_VALUES_LOG_HEIGHT}"
+            ),
+            RecursionAir::DummyWide(chip) => *chip = DummyChip::new(log_height),
+        }
+        self
+    }
+
     pub fn machine_with_padding<SC: StarkGenericConfig<Val = F>>(
         config: SC,
         fri_fold_padding: usize,
@@ -78,6 +512,32 @@ impl<F: PrimeField32 + BinomiallyExtendable<D>, const DEGREE: usize, const COL_P
         let chips = vec![RecursionAir::DummyWide(DummyChip::new(log_height))];
         StarkMachine::new(config, chips.into_iter().map(Chip::new).collect(), PROOF_MAX_NUM_PVS)
     }
+
+    /// `machine` with `num_pvs` public values elements instead of [`PROOF_MAX_NUM_PVS`]. This
+    /// works with any of the constructors above, e.g. `A::with_num_pvs(A::machine_wide(config), n)`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `num_pvs` exceeds [`PROOF_MAX_NUM_PVS`], the number of public values in a
+    /// record, or if the machine has a [`PublicValuesChip`] and `num_pvs` is below
+    /// [`RECURSIVE_PROOF_NUM_PV_ELTS`], the number of public values that chip constrains.
+    pub fn with_num_pvs<SC: StarkGenericConfig<Val = F>>(
+        machine: StarkMachine<SC, Self>,
+        num_pvs: usize,
+    ) -> StarkMachine<SC, Self> {
+        assert!(
+            num_pvs <= PROOF_MAX_NUM_PVS,
+            "{num_pvs} public values exceed the maximum of {PROOF_MAX_NUM_PVS}"
+        );
+        let has_public_values_chip =
+            machine.chips().iter().any(|chip| chip.name() == "PublicValues");
+        assert!(
+            !has_public_values_chip || num_pvs >= RECURSIVE_PROOF_NUM_PV_ELTS,
+            "{num_pvs} public values are fewer than the {RECURSIVE_PROOF_NUM_PV_ELTS} the \
+            PublicValues chip constrains"
+        );
+        machine.with_num_pv_elts(num_pvs)
+    }
     // /// A recursion machine with fixed trace sizes tuned to work specifically for the wrap layer.
     // pub fn wrap_machine<SC: StarkGenericConfig<Val = F>>(config: SC) -> StarkMachine<SC, Self> {
     //     let chips = Self::get_wrap_all()
@@ -97,32 +557,29 @@ impl<F: PrimeField32 + BinomiallyExtendable<D>, const DEGREE: usize, const COL_P
     // }
 
     pub fn get_all() -> Vec<Self> {
-        vec![
-            RecursionAir::MemoryConst(MemoryConstChip::default()),
-            RecursionAir::MemoryVar(MemoryVarChip::default()),
-            RecursionAir::BaseAlu(BaseAluChip::default()),
-            RecursionAir::ExtAlu(ExtAluChip::default()),
-            RecursionAir::Poseidon2Skinny(Poseidon2SkinnyChip::<DEGREE>::default()),
-            // RecursionAir::Poseidon2Wide(Poseidon2WideChip::<DEGREE>::default()),
-            RecursionAir::ExpReverseBitsLen(ExpReverseBitsLenChip::<DEGREE>::default()),
-            RecursionAir::FriFold(FriFoldChip::<DEGREE>::default()),
-            RecursionAir::PublicValues(PublicValuesChip::default()),
-        ]
+        Self::get_chips(&[
+            RecursionAirKind::MemoryConst,
+            RecursionAirKind::MemoryVar,
+            RecursionAirKind::BaseAlu,
+            RecursionAirKind::ExtAlu,
+            RecursionAirKind::Poseidon2Skinny,
+            RecursionAirKind::ExpReverseBitsLen,
+            RecursionAirKind::FriFold,
+            RecursionAirKind::PublicValues,
+        ])
     }
 
     pub fn get_all_wide() -> Vec<Self> {
-        vec![
-            // RecursionAir::Program(ProgramChip::default()),
-            RecursionAir::MemoryConst(MemoryConstChip::default()),
-            RecursionAir::MemoryVar(MemoryVarChip::default()),
-            RecursionAir::BaseAlu(BaseAluChip::default()),
-            RecursionAir::ExtAlu(ExtAluChip::default()),
-            // RecursionAir::Poseidon2Skinny(Poseidon2SkinnyChip::<DEGREE>::default()),
-            RecursionAir::Poseidon2Wide(Poseidon2WideChip::<DEGREE>::default()),
-            RecursionAir::ExpReverseBitsLen(ExpReverseBitsLenChip::<DEGREE>::default()),
-            RecursionAir::FriFold(FriFoldChip::<DEGREE>::default()),
-            RecursionAir::PublicValues(PublicValuesChip::default()),
-        ]
+        Self::get_chips(&[
+            RecursionAirKind::MemoryConst,
+            RecursionAirKind::MemoryVar,
+            RecursionAirKind::BaseAlu,
+            RecursionAirKind::ExtAlu,
+            RecursionAirKind::Poseidon2Wide,
+            RecursionAirKind::ExpReverseBitsLen,
+            RecursionAirKind::FriFold,
+            RecursionAirKind::PublicValues,
+        ])
     }
 
     pub fn get_all_with_padding(
@@ -139,15 +596,16 @@ impl<F: PrimeField32 + BinomiallyExtendable<D>, const DEGREE: usize, const COL_P
             // RecursionAir::Poseidon2Wide(Poseidon2WideChip::<DEGREE>::default()),
             RecursionAir::Poseidon2Skinny(Poseidon2SkinnyChip::<DEGREE> {
                 fixed_log2_rows: Some(poseidon2_padding),
-                pad: true,
+                ..Default::default()
             }),
             RecursionAir::ExpReverseBitsLen(ExpReverseBitsLenChip::<DEGREE> {
                 fixed_log2_rows: Some(erbl_padding),
                 pad: true,
             }),
-            RecursionAir::FriFold(FriFoldChip::<DEGREE> {
-                fixed_log2_rows: Some(fri_fold_padding),
-                pad: true,
+            RecursionAir::FriFold({
+                let mut chip = FriFoldChip::<DEGREE>::default();
+                chip.fixed_log2_rows = Some(fri_fold_padding);
+                chip
             }),
             RecursionAir::PublicValues(PublicValuesChip::default()),
         ]
@@ -198,23 +656,204 @@ impl<F: PrimeField32 + BinomiallyExtendable<D>, const DEGREE: usize, const COL_P
     // }
 }
 
+#[cfg(any(test, feature = "test-util"))]
+impl<F: PrimeField32 + BinomiallyExtendable<D>> crate::RecursionProgram<F> {
+    /// Generates a random program that executes and proves successfully.
+    ///
+    /// The program writes a few constants and then performs base and extension field arithmetic
+    /// on previously computed values. Divisions by zero are replaced by multiplications, every
+    /// write's multiplicity matches the number of times it is read, and every value that is
+    /// never used as an operand is checked by a final memory read.
+    pub fn arbitrary_valid<R: rand::Rng>(rng: &mut R) -> Self {
+        use p3_field::{extension::BinomialExtensionField, AbstractExtensionField, Field};
+
+        use crate::{runtime::instruction as instr, BaseAluOpcode, ExtAluOpcode, MemAccessKind};
+
+        type EF<F> = BinomialExtensionField<F, D>;
+
+        enum Step {
+            Const,
+            BaseAlu(BaseAluOpcode, u32, u32),
+            ExtAlu(ExtAluOpcode, u32, u32),
+        }
+
+        let random_felt = |rng: &mut R| F::from_canonical_u32(rng.gen_range(0..F::ORDER_U32));
+
+        // Each address holds either a base or an extension element, tracked in `vals`. Base
+        // elements are stored as extension elements with zero higher coefficients.
+        let mut steps = Vec::new();
+        let mut vals: Vec<(bool, EF<F>)> = Vec::new();
+        let mut reads: Vec<u32> = Vec::new();
+
+        let num_consts = rng.gen_range(2..8);
+        for _ in 0..num_consts {
+            let is_ext = rng.gen_bool(0.5);
+            let val = if is_ext {
+                EF::<F>::from_base_fn(|_| random_felt(rng))
+            } else {
+                EF::<F>::from_base(random_felt(rng))
+            };
+            steps.push(Step::Const);
+            vals.push((is_ext, val));
+            reads.push(0);
+        }
+
+        let num_ops = rng.gen_range(1..64);
+        for _ in 0..num_ops {
+            let is_ext = rng.gen_bool(0.5);
+            let candidates = (0..vals.len() as u32)
+                .filter(|&a| vals[a as usize].0 == is_ext)
+                .collect::<Vec<_>>();
+            if candidates.is_empty() {
+                continue;
+            }
+            let in1 = candidates[rng.gen_range(0..candidates.len())];
+            let in2 = candidates[rng.gen_range(0..candidates.len())];
+            let (x, y) = (vals[in1 as usize].1, vals[in2 as usize].1);
+            let divisor_is_zero = y.is_zero();
+
+            let val = if is_ext {
+                let mut opcode = match rng.gen_range(0..4) {
+                    0 => ExtAluOpcode::AddE,
+                    1 => ExtAluOpcode::SubE,
+                    2 => ExtAluOpcode::MulE,
+                    _ => ExtAluOpcode::DivE,
+                };
+                if opcode == ExtAluOpcode::DivE && divisor_is_zero {
+                    opcode = ExtAluOpcode::MulE;
+                }
+                steps.push(Step::ExtAlu(opcode, in1, in2));
+                match opcode {
+                    ExtAluOpcode::AddE => x + y,
+                    ExtAluOpcode::SubE => x - y,
+                    ExtAluOpcode::MulE => x * y,
+                    ExtAluOpcode::DivE => x / y,
+                    ExtAluOpcode::DivEChecked => {
+                        unreachable!("checked divisions are not generated")
+                    }
+                    ExtAluOpcode::InvE => unreachable!("inverses are not generated"),
+                    ExtAluOpcode::SquareE => unreachable!("squares are not generated"),
+                }
+            } else {
+                let mut opcode = match rng.gen_range(0..4) {
+                    0 => BaseAluOpcode::AddF,
+                    1 => BaseAluOpcode::SubF,
+                    2 => BaseAluOpcode::MulF,
+                    _ => BaseAluOpcode::DivF,
+                };
+                if opcode == BaseAluOpcode::DivF && divisor_is_zero {
+                    opcode = BaseAluOpcode::MulF;
+                }
+                steps.push(Step::BaseAlu(opcode, in1, in2));
+                match opcode {
+                    BaseAluOpcode::AddF => x + y,
+                    BaseAluOpcode::SubF => x - y,
+                    BaseAluOpcode::MulF => x * y,
+                    BaseAluOpcode::DivF => x / y,
+                    BaseAluOpcode::DivFChecked => {
+                        unreachable!("checked divisions are not generated")
+                    }
+                }
+            };
+            reads[in1 as usize] += 1;
+            reads[in2 as usize] += 1;
+            vals.push((is_ext, val));
+            reads.push(0);
+        }
+
+        // Check every value that is not consumed by another instruction.
+        let checked = (0..vals.len()).filter(|&a| reads[a] == 0).collect::<Vec<_>>();
+        for &addr in &checked {
+            reads[addr] = 1;
+        }
+
+        let mut instructions = Vec::with_capacity(steps.len() + checked.len());
+        for (addr, step) in steps.into_iter().enumerate() {
+            let out = addr as u32;
+            let mult = reads[addr];
+            instructions.push(match step {
+                Step::Const => instr::mem_ext(MemAccessKind::Write, mult, out, vals[addr].1),
+                Step::BaseAlu(opcode, in1, in2) => instr::base_alu(opcode, mult, out, in1, in2),
+                Step::ExtAlu(opcode, in1, in2) => instr::ext_alu(opcode, mult, out, in1, in2),
+            });
+        }
+        for addr in checked {
+            instructions.push(instr::mem_ext(MemAccessKind::Read, 1, addr as u32, vals[addr].1));
+        }
+
+        Self { instructions, total_memory: vals.len(), ..Default::default() }
+    }
+}
+
+/// Proves `record` with `prover`, making up to `attempts` attempts, and returns the error of the
+/// last one if none succeeds.
+///
+/// Every attempt regenerates the traces from `record` and starts from a fresh challenger. Trace
+/// generation is deterministic, so a retry after a transient failure, like a worker dying
+/// mid-proof, proves exactly what the first attempt would have.
+#[cfg(any(test, feature = "test-util"))]
+pub fn prove_with_retry<SC, M, P>(
+    prover: &P,
+    pk: &StarkProvingKey<SC>,
+    record: &crate::ExecutionRecord<sp1_stark::Val<SC>>,
+    attempts: usize,
+) -> Result<MachineProof<SC>, P::Error>
+where
+    SC: StarkGenericConfig,
+    sp1_stark::Val<SC>: PrimeField32,
+    M: MachineAir<sp1_stark::Val<SC>, Record = crate::ExecutionRecord<sp1_stark::Val<SC>>>
+        + for<'a> Air<sp1_stark::DebugConstraintBuilder<'a, sp1_stark::Val<SC>, SC::Challenge>>,
+    P: sp1_stark::MachineProver<SC, M>,
+{
+    assert!(attempts > 0, "proving needs at least one attempt");
+    let prove = || {
+        let mut challenger = prover.config().challenger();
+        prover.prove(pk, vec![record.clone()], &mut challenger, sp1_stark::SP1CoreOpts::default())
+    };
+    let mut result = prove();
+    for _ in 1..attempts {
+        if result.is_ok() {
+            break;
+        }
+        result = prove();
+    }
+    result
+}
+
 #[cfg(test)]
 pub mod tests {
 
-    use std::sync::Arc;
+    use std::{
+        collections::BTreeMap,
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+    };
 
-    use machine::RecursionAir;
+    use itertools::Itertools;
+    use machine::{
+        prove_with_retry, ColPaddingError, DisabledChipError, RecursionAir, RecursionAirKind,
+        SetupError,
+    };
+    use p3_air::BaseAir;
     use p3_baby_bear::DiffusionMatrixBabyBear;
     use p3_field::{
         extension::{BinomialExtensionField, HasFrobenius},
-        AbstractExtensionField, AbstractField, Field,
+        AbstractExtensionField, AbstractField, Field, PrimeField32,
     };
+    use p3_matrix::dense::RowMajorMatrix;
     use rand::prelude::*;
     use sp1_core_machine::utils::run_test_machine;
-    use sp1_stark::{baby_bear_poseidon2::BabyBearPoseidon2, StarkGenericConfig};
+    use sp1_recursion_core::air::RECURSIVE_PROOF_NUM_PV_ELTS;
+    use sp1_stark::{
+        air::MachineAir, baby_bear_poseidon2::BabyBearPoseidon2, CpuProver, CpuProverError,
+        MachineProof, MachineProver, MachineRecord, MachineVerificationError, SP1CoreOpts,
+        ShardMainData, ShardProof, StarkGenericConfig, StarkMachine, StarkProvingKey,
+    };
 
     // TODO expand glob import
-    use crate::{runtime::instruction as instr, *};
+    use crate::{runtime::instruction as instr, shape::RecursionShape, *};
 
     type SC = BabyBearPoseidon2;
     type F = <SC as StarkGenericConfig>::Val;
@@ -222,47 +861,430 @@ pub mod tests {
     type A = RecursionAir<F, 3, 0>;
     type B = RecursionAir<F, 9, 0>;
 
-    /// Runs the given program on machines that use the wide and skinny Poseidon2 chips.
+    /// Enters an info span for a phase of proving a test program, recording the Poseidon2 variant
+    /// and the degree, if the `tracing` feature is enabled.
+    macro_rules! phase_span {
+        ($phase:literal, $poseidon2:expr, $degree:expr) => {{
+            #[cfg(feature = "tracing")]
+            let span =
+                tracing::info_span!($phase, poseidon2 = $poseidon2, degree = $degree).entered();
+            #[cfg(not(feature = "tracing"))]
+            let span = ($poseidon2, $degree);
+            span
+        }};
+    }
+
+    /// Runs a program on the test machines of one degree, given the program and its record.
+    type DegreeRunner = fn(&Arc<RecursionProgram<F>>, &ExecutionRecord<F>);
+
+    /// The degrees [`run_recursion_test_machines`] runs programs at.
+    ///
+    /// The test configs only have enough blowup for degrees up to 9, and the Poseidon2 chips
+    /// support no other degree in that range.
+    const TEST_DEGREES: [DegreeRunner; 2] =
+        [run_recursion_test_machines_of_degree::<3>, run_recursion_test_machines_of_degree::<9>];
+
+    /// Runs the given program on machines of every degree in [`TEST_DEGREES`].
     pub fn run_recursion_test_machines(program: RecursionProgram<F>) {
         let program = Arc::new(program);
         let mut runtime =
             Runtime::<F, EF, DiffusionMatrixBabyBear>::new(program.clone(), SC::new().perm);
         runtime.run().unwrap();
 
-        // Run with the poseidon2 wide chip.
-        let wide_machine = A::machine_wide(BabyBearPoseidon2::default());
-        let (pk, vk) = wide_machine.setup(&program);
-        let result = run_test_machine(vec![runtime.record.clone()], wide_machine, pk, vk);
-        if let Err(e) = result {
-            panic!("Verification failed: {:?}", e);
+        for run in TEST_DEGREES {
+            run(&program, &runtime.record);
         }
+    }
 
-        // Run with the poseidon2 skinny chip.
-        let skinny_machine = B::machine(BabyBearPoseidon2::compressed());
-        let (pk, vk) = skinny_machine.setup(&program);
-        let result = run_test_machine(vec![runtime.record], skinny_machine, pk, vk);
-        if let Err(e) = result {
-            panic!("Verification failed: {:?}", e);
+    /// Runs the given program on the machines of degree `DEGREE` that use the wide and skinny
+    /// Poseidon2 chips. The skinny chip requires a degree of at least 9.
+    pub fn run_recursion_test_machines_of_degree<const DEGREE: usize>(
+        program: &Arc<RecursionProgram<F>>,
+        record: &ExecutionRecord<F>,
+    ) {
+        let config = || {
+            if DEGREE > 3 {
+                BabyBearPoseidon2::compressed()
+            } else {
+                BabyBearPoseidon2::default()
+            }
+        };
+        let mut machines = vec![("wide", RecursionAir::<F, DEGREE, 0>::machine_wide(config()))];
+        if DEGREE >= 9 {
+            machines.push(("skinny", RecursionAir::<F, DEGREE, 0>::machine(config())));
+        }
+
+        for (poseidon2, machine) in machines {
+            let prover = CpuProver::new(machine);
+            let (pk, vk) = {
+                let _span = phase_span!("setup", poseidon2, DEGREE);
+                prover.setup(program)
+            };
+            // Proving generates the traces, under the "generate traces for shard" span.
+            let proof = {
+                let _span = phase_span!("prove", poseidon2, DEGREE);
+                let mut challenger = prover.config().challenger();
+                prover.prove(&pk, vec![record.clone()], &mut challenger, SP1CoreOpts::default())
+            }
+            .unwrap();
+            let result = {
+                let _span = phase_span!("verify", poseidon2, DEGREE);
+                let mut challenger = prover.config().challenger();
+                prover.machine().verify(&vk, &proof, &mut challenger)
+            };
+            if let Err(e) = result {
+                panic!(
+                    "Verification failed with the {poseidon2} Poseidon2 chip at degree {DEGREE}: \
+                    {e:?}"
+                );
+            }
         }
     }
 
+    /// Generates the Poseidon2 traces of `program` with the skinny chip and with the wide chip at
+    /// degrees 3 and 9, and checks that the permutation outputs they contain match the execution
+    /// record, although the chips lay their columns out differently.
+    pub fn compare_poseidon2_traces(program: &Arc<RecursionProgram<F>>) {
+        use std::borrow::Borrow;
+
+        use crate::chips::{
+            poseidon2_skinny::{
+                columns::Poseidon2 as SkinnyCols, Poseidon2SkinnyChip, NUM_EXTERNAL_ROUNDS,
+            },
+            poseidon2_wide::{columns::permutation::permutation, Poseidon2WideChip, WIDTH},
+        };
+
+        let mut runtime =
+            Runtime::<F, EF, DiffusionMatrixBabyBear>::new(program.clone(), SC::new().perm);
+        runtime.run().unwrap();
+        let record = runtime.record;
+        let expected = record.poseidon2_events.iter().map(|event| event.output).collect::<Vec<_>>();
+
+        // The skinny chip spends `NUM_EXTERNAL_ROUNDS + 3` rows on each permutation, and the last
+        // of them holds the output.
+        let skinny = Poseidon2SkinnyChip::<9>::default()
+            .generate_trace(&record, &mut ExecutionRecord::default());
+        let rows_per_perm = NUM_EXTERNAL_ROUNDS + 3;
+        let skinny_outputs = skinny
+            .values
+            .chunks_exact(skinny.width * rows_per_perm)
+            .take(expected.len())
+            .map(|rows| {
+                let output: &SkinnyCols<F> = rows[rows.len() - skinny.width..].borrow();
+                output.state_var
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(skinny_outputs, expected, "the skinny Poseidon2 trace diverges");
+
+        fn wide_outputs<const DEGREE: usize>(record: &ExecutionRecord<F>) -> Vec<[F; WIDTH]> {
+            let wide = Poseidon2WideChip::<DEGREE>::default()
+                .generate_trace(record, &mut ExecutionRecord::default());
+            wide.values
+                .chunks_exact(wide.width)
+                .take(record.poseidon2_events.len())
+                .map(|row| *permutation::<F, DEGREE>(row).perm_output())
+                .collect()
+        }
+        assert_eq!(wide_outputs::<3>(&record), skinny_outputs, "the degree 3 wide trace diverges");
+        assert_eq!(wide_outputs::<9>(&record), skinny_outputs, "the degree 9 wide trace diverges");
+    }
+
+    /// Renders a trace as CSV, with a header row of `column_names` followed by one line per row of
+    /// canonical field elements, so that traces can be inspected and diffed.
+    ///
+    /// To derive the names from a column struct, borrow the indices `0..width` as that struct and
+    /// name the index found in each field.
+    pub fn trace_to_csv<T: PrimeField32>(
+        trace: &RowMajorMatrix<T>,
+        column_names: &[String],
+    ) -> String {
+        assert_eq!(column_names.len(), trace.width, "expected one name per column");
+        let mut csv = column_names.join(",");
+        csv.push('\n');
+        for row in trace.values.chunks_exact(trace.width) {
+            csv.push_str(&row.iter().map(|x| x.as_canonical_u32().to_string()).join(","));
+            csv.push('\n');
+        }
+        csv
+    }
+
     fn test_instructions(instructions: Vec<Instruction<F>>) {
         let program = RecursionProgram { instructions, ..Default::default() };
         run_recursion_test_machines(program);
     }
 
-    #[test]
-    pub fn fibonacci() {
+    pub fn fibonacci_instructions() -> Vec<Instruction<F>> {
         let n = 10;
 
-        let instructions = once(instr::mem(MemAccessKind::Write, 1, 0, 0))
+        once(instr::mem(MemAccessKind::Write, 1, 0, 0))
             .chain(once(instr::mem(MemAccessKind::Write, 2, 1, 1)))
             .chain((2..=n).map(|i| instr::base_alu(BaseAluOpcode::AddF, 2, i, i - 2, i - 1)))
             .chain(once(instr::mem(MemAccessKind::Read, 1, n - 1, 34)))
             .chain(once(instr::mem(MemAccessKind::Read, 2, n, 55)))
+            .collect()
+    }
+
+    #[test]
+    pub fn fibonacci() {
+        test_instructions(fibonacci_instructions());
+    }
+
+    #[test]
+    pub fn fibonacci_bytes_round_trip() {
+        let mut program =
+            RecursionProgram { instructions: fibonacci_instructions(), ..Default::default() };
+        program.set_fixed_log2_rows("BaseAlu", 4);
+        let bytes = program.to_bytes().unwrap();
+        let decoded = RecursionProgram::<F>::from_bytes(&bytes).unwrap();
+        assert_eq!(decoded.to_bytes().unwrap(), bytes);
+        assert_eq!(decoded.fixed_log2_rows("BaseAlu"), Some(4));
+
+        run_recursion_test_machines(decoded);
+    }
+
+    #[test]
+    pub fn fibonacci_dry_run() {
+        let program = Arc::new(RecursionProgram {
+            instructions: fibonacci_instructions(),
+            ..Default::default()
+        });
+        let mut runtime =
+            Runtime::<F, EF, DiffusionMatrixBabyBear>::new(program.clone(), SC::new().perm);
+        runtime.run().unwrap();
+        let full = runtime.record;
+        let dry =
+            Runtime::<F, EF, DiffusionMatrixBabyBear>::dry_run(program, SC::new().perm).unwrap();
+
+        assert_eq!(dry.event_counts(), full.event_counts());
+        assert_eq!(dry.event_counts().base_alu, 9);
+        assert_eq!(dry.event_counts().mem_const, 4);
+        assert!(dry.base_alu_events.is_empty());
+        assert_eq!(full.unrecorded_events, EventCounts::default());
+    }
+
+    #[test]
+    pub fn constant_fold_fibonacci() {
+        let base_alu_rows = |program: RecursionProgram<F>| {
+            let mut runtime =
+                Runtime::<F, EF, DiffusionMatrixBabyBear>::new(Arc::new(program), SC::new().perm);
+            runtime.run().unwrap();
+            runtime.record.base_alu_events.len()
+        };
+        let mut program =
+            RecursionProgram { instructions: fibonacci_instructions(), ..Default::default() };
+        assert_eq!(base_alu_rows(program.clone()), 9);
+
+        // Every term is computed from the two constant initial terms, so everything folds into
+        // the writes of the two terms that are read.
+        assert_eq!(program.constant_fold(), 9);
+        assert_eq!(
+            program.stats::<3>().instruction_counts,
+            BTreeMap::from([("Mem".to_owned(), 4)])
+        );
+        assert_eq!(base_alu_rows(program.clone()), 0);
+
+        run_recursion_test_machines(program);
+    }
+
+    #[test]
+    pub fn snapshot_restore() {
+        let mut rng = StdRng::seed_from_u64(3);
+        let program = std::iter::repeat_with(|| RecursionProgram::arbitrary_valid(&mut rng))
+            .find(|program| program.instructions.len() >= 32)
+            .map(Arc::new)
+            .unwrap();
+        let runtime =
+            || Runtime::<F, EF, DiffusionMatrixBabyBear>::new(program.clone(), SC::new().perm);
+
+        let mut expected = runtime();
+        expected.run().unwrap();
+        let total = expected.timestamp;
+        let (first, second) = (total / 3, 2 * total / 3);
+        assert!(0 < first && first < second);
+
+        let mut runtime = runtime();
+        runtime.run_until(first).unwrap();
+        let snapshot = runtime.snapshot();
+        let events = format!("{:?}", snapshot.record());
+
+        // Running further does not affect the snapshot.
+        runtime.run_until(second).unwrap();
+        assert_eq!(format!("{:?}", snapshot.record()), events);
+
+        runtime.restore(snapshot.clone());
+        assert_eq!(runtime.timestamp, first);
+        assert_eq!(runtime.pc, F::from_canonical_usize(first));
+        assert_eq!(format!("{:?}", runtime.record), events);
+
+        // Restoring twice from the same snapshot replays the same execution.
+        for _ in 0..2 {
+            runtime.restore(snapshot.clone());
+            runtime.run().unwrap();
+            assert_eq!(runtime.timestamp, total);
+            assert_eq!(format!("{:?}", runtime.record), format!("{:?}", expected.record));
+        }
+    }
+
+    #[test]
+    pub fn step() {
+        let program = Arc::new(RecursionProgram {
+            instructions: fibonacci_instructions(),
+            ..Default::default()
+        });
+        let mut runtime =
+            Runtime::<F, EF, DiffusionMatrixBabyBear>::new(program.clone(), SC::new().perm);
+        let access = |addr: u32, val: u32| {
+            (Address(F::from_canonical_u32(addr)), Block::from(F::from_canonical_u32(val)))
+        };
+
+        let info = runtime.step().unwrap();
+        assert_eq!((info.pc, info.reads, info.writes), (0, vec![], vec![access(0, 0)]));
+        let info = runtime.step().unwrap();
+        assert_eq!((info.pc, info.reads, info.writes), (1, vec![], vec![access(1, 1)]));
+
+        let mut fib = [0, 1];
+        for i in 2..=10 {
+            let info = runtime.step().unwrap();
+            assert!(matches!(info.instruction, Instruction::BaseAlu(_)));
+            let next = fib[0] + fib[1];
+            assert_eq!(info.pc, i as usize);
+            assert_eq!(info.reads, vec![access(i - 2, fib[0]), access(i - 1, fib[1])]);
+            assert_eq!(info.writes, vec![access(i, next)]);
+            fib = [fib[1], next];
+        }
+
+        // Stepping and running can be mixed.
+        runtime.run_until(runtime.timestamp + 1).unwrap();
+        let info = runtime.step().unwrap();
+        assert_eq!((info.pc, info.reads, info.writes), (12, vec![access(10, 55)], vec![]));
+        assert!(matches!(runtime.step(), Err(RuntimeError::Finished)));
+        assert_eq!(runtime.record.base_alu_events.len(), 9);
+    }
+
+    #[test]
+    pub fn max_steps() {
+        let program = Arc::new(RecursionProgram {
+            instructions: fibonacci_instructions(),
+            ..Default::default()
+        });
+        let len = program.instructions.len();
+        let runtime =
+            || Runtime::<F, EF, DiffusionMatrixBabyBear>::new(program.clone(), SC::new().perm);
+
+        // A limit of exactly the program length is enough.
+        let mut runtime_at_limit = runtime();
+        runtime_at_limit.set_max_steps(len);
+        runtime_at_limit.run().unwrap();
+
+        let mut runtime = runtime();
+        runtime.set_max_steps(len - 1);
+        assert!(
+            matches!(runtime.run(), Err(RuntimeError::StepLimitExceeded(max)) if max == len - 1)
+        );
+        assert_eq!(runtime.timestamp, len - 1);
+    }
+
+    #[test]
+    #[cfg(feature = "tracing")]
+    pub fn tracing_spans() {
+        use std::sync::Mutex;
+
+        use tracing::{span, Subscriber};
+        use tracing_subscriber::{
+            layer::{Context, SubscriberExt},
+            registry::LookupSpan,
+            Layer,
+        };
+
+        /// Records the names of the spans created on the current thread.
+        struct SpanNames(Arc<Mutex<Vec<&'static str>>>);
+
+        impl<S: Subscriber + for<'a> LookupSpan<'a>> Layer<S> for SpanNames {
+            fn on_new_span(&self, attrs: &span::Attributes<'_>, _: &span::Id, _: Context<'_, S>) {
+                self.0.lock().unwrap().push(attrs.metadata().name());
+            }
+        }
+
+        let names = Arc::default();
+        let subscriber = tracing_subscriber::registry().with(SpanNames(Arc::clone(&names)));
+        tracing::subscriber::with_default(subscriber, || {
+            test_instructions(fibonacci_instructions())
+        });
+
+        let names = names.lock().unwrap();
+        for phase in ["execute recursion program", "setup", "prove", "verify"] {
+            assert!(names.contains(&phase), "no {phase:?} span in {names:?}");
+        }
+    }
+
+    #[test]
+    pub fn hint_ext_array() {
+        let mut rng = StdRng::seed_from_u64(4);
+        let values = (0..8)
+            .map(|_| EF::from_base_fn(|_| F::from_canonical_u32(rng.gen_range(0..F::ORDER_U32))))
             .collect::<Vec<_>>();
 
-        test_instructions(instructions);
+        // Hint the whole array with one instruction, then read each element back.
+        let outputs = (0..values.len() as u32).map(|addr| (addr, 1)).collect::<Vec<_>>();
+        let instructions = once(instr::hint(&outputs))
+            .chain(
+                values
+                    .iter()
+                    .enumerate()
+                    .map(|(addr, &val)| instr::mem_ext(MemAccessKind::Read, 1, addr as u32, val)),
+            )
+            .collect();
+        let program = Arc::new(RecursionProgram { instructions, ..Default::default() });
+
+        let mut runtime =
+            Runtime::<F, EF, DiffusionMatrixBabyBear>::new(program.clone(), SC::new().perm);
+        runtime.witness_stream =
+            values.iter().map(|val| Block::from(val.as_base_slice())).collect();
+        runtime.run().unwrap();
+        assert_eq!(runtime.record.mem_var_events.len(), values.len());
+
+        for run in TEST_DEGREES {
+            run(&program, &runtime.record);
+        }
+    }
+
+    #[test]
+    pub fn append_records() {
+        let run = |program: &Arc<RecursionProgram<F>>| {
+            let mut runtime =
+                Runtime::<F, EF, DiffusionMatrixBabyBear>::new(program.clone(), SC::new().perm);
+            runtime.run().unwrap();
+            runtime.record
+        };
+        let program =
+            |instructions| Arc::new(RecursionProgram { instructions, ..Default::default() });
+
+        let first = fibonacci_instructions();
+        let second = vec![
+            instr::mem(MemAccessKind::Write, 1, 100, 3),
+            instr::mem(MemAccessKind::Write, 1, 101, 4),
+            instr::base_alu(BaseAluOpcode::MulF, 1, 102, 100, 101),
+            instr::mem(MemAccessKind::Read, 1, 102, 12),
+        ];
+        let concatenated = program(first.iter().chain(&second).cloned().collect());
+
+        let mut merged = run(&program(first));
+        merged.append(run(&program(second)));
+        merged.program = concatenated.clone();
+        assert_eq!(merged.check_invariants(), Ok(()));
+        let expected = run(&concatenated);
+
+        for chip in B::get_all() {
+            assert_eq!(
+                chip.generate_trace(&merged, &mut ExecutionRecord::default()),
+                chip.generate_trace(&expected, &mut ExecutionRecord::default()),
+                "{}",
+                chip.name()
+            );
+        }
+        for run in TEST_DEGREES {
+            run(&concatenated, &merged);
+        }
     }
 
     #[test]
@@ -290,6 +1312,384 @@ pub mod tests {
         test_instructions(instructions);
     }
 
+    #[test]
+    pub fn machine_from_chips() {
+        let program = Arc::new(RecursionProgram::arbitrary_valid(&mut StdRng::seed_from_u64(1)));
+        let mut runtime =
+            Runtime::<F, EF, DiffusionMatrixBabyBear>::new(program.clone(), SC::new().perm);
+        runtime.run().unwrap();
+
+        let machine = A::machine_from_chips(
+            BabyBearPoseidon2::default(),
+            &[
+                RecursionAirKind::MemoryConst,
+                RecursionAirKind::MemoryVar,
+                RecursionAirKind::BaseAlu,
+                RecursionAirKind::ExtAlu,
+                RecursionAirKind::PublicValues,
+            ],
+        );
+        assert_eq!(machine.chips().len(), 5);
+        let (pk, vk) = machine.setup(&program);
+        run_test_machine(vec![runtime.record], machine, pk, vk).unwrap();
+    }
+
+    #[test]
+    pub fn with_num_pvs() {
+        let program = Arc::new(RecursionProgram {
+            instructions: fibonacci_instructions(),
+            ..Default::default()
+        });
+        let mut runtime =
+            Runtime::<F, EF, DiffusionMatrixBabyBear>::new(program.clone(), SC::new().perm);
+        runtime.run().unwrap();
+
+        let machine = A::with_num_pvs(
+            A::machine_wide(BabyBearPoseidon2::default()),
+            RECURSIVE_PROOF_NUM_PV_ELTS,
+        );
+        assert_eq!(machine.num_pv_elts(), RECURSIVE_PROOF_NUM_PV_ELTS);
+        let (pk, vk) = machine.setup(&program);
+        run_test_machine(vec![runtime.record], machine, pk, vk).unwrap();
+    }
+
+    #[test]
+    #[should_panic = "fewer than"]
+    pub fn with_num_pvs_too_few() {
+        A::with_num_pvs(
+            A::machine_wide(BabyBearPoseidon2::default()),
+            RECURSIVE_PROOF_NUM_PV_ELTS - 1,
+        );
+    }
+
+    #[test]
+    pub fn poseidon2_traces_match() {
+        let (program, _) = crate::chips::poseidon2_skinny::tests::poseidon2_program();
+        compare_poseidon2_traces(&program);
+
+        let mut rng = StdRng::seed_from_u64(3);
+        compare_poseidon2_traces(&Arc::new(RecursionProgram::arbitrary_valid(&mut rng)));
+        compare_poseidon2_traces(&Arc::new(RecursionProgram {
+            instructions: fibonacci_instructions(),
+            ..Default::default()
+        }));
+    }
+
+    #[test]
+    pub fn machine_for_program() {
+        let program = Arc::new(RecursionProgram {
+            instructions: vec![
+                instr::mem(MemAccessKind::Write, 2, 0, 7),
+                instr::mem(MemAccessKind::Read, 1, 0, 7),
+                instr::mem(MemAccessKind::Read, 1, 0, 7),
+            ],
+            ..Default::default()
+        });
+        assert_eq!(RecursionAirKind::for_program(&program), RecursionAirKind::REQUIRED);
+
+        let mut runtime =
+            Runtime::<F, EF, DiffusionMatrixBabyBear>::new(program.clone(), SC::new().perm);
+        runtime.run().unwrap();
+
+        let machine = A::machine_for_program(BabyBearPoseidon2::default(), &program);
+        assert_eq!(machine.chips().len(), RecursionAirKind::REQUIRED.len());
+        let (pk, vk) = machine.setup(&program);
+        run_test_machine(vec![runtime.record], machine, pk, vk).unwrap();
+
+        let kinds = RecursionAirKind::for_program(&RecursionProgram {
+            instructions: fibonacci_instructions(),
+            ..Default::default()
+        });
+        assert_eq!(
+            kinds,
+            [
+                RecursionAirKind::MemoryConst,
+                RecursionAirKind::MemoryVar,
+                RecursionAirKind::BaseAlu,
+                RecursionAirKind::PublicValues
+            ]
+        );
+    }
+
+    #[test]
+    pub fn disabled_chips() {
+        let mut program =
+            RecursionProgram { instructions: fibonacci_instructions(), ..Default::default() };
+        program.disable_chip("ExpReverseBitsLen");
+        assert_eq!(A::check_disabled_chips(&program), Ok(()));
+
+        let mut runtime = Runtime::<F, EF, DiffusionMatrixBabyBear>::new(
+            Arc::new(program.clone()),
+            SC::new().perm,
+        );
+        runtime.run().unwrap();
+        let machine = A::machine_wide_for_program(BabyBearPoseidon2::default(), &program);
+        let names = machine.chips().iter().map(|chip| chip.name()).collect::<Vec<_>>();
+        assert_eq!(names.len(), A::get_all_wide().len() - 1);
+        assert!(!names.contains(&"ExpReverseBitsLen".to_string()));
+        let (pk, vk) = machine.setup(&program);
+        run_test_machine(vec![runtime.record], machine, pk, vk).unwrap();
+
+        program.disable_chip("BaseAlu");
+        assert_eq!(
+            A::check_disabled_chips(&program),
+            Err(DisabledChipError::Needed("BaseAlu".to_string()))
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "chip MemoryConst is disabled, but the program needs it")]
+    pub fn disabled_chips_required() {
+        let mut program =
+            RecursionProgram { instructions: fibonacci_instructions(), ..Default::default() };
+        program.disable_chip("MemoryConst");
+        A::machine_for_program(BabyBearPoseidon2::default(), &program);
+    }
+
+    #[test]
+    pub fn shrink_machine() {
+        let program = Arc::new(RecursionProgram {
+            instructions: fibonacci_instructions(),
+            ..Default::default()
+        });
+        let mut runtime =
+            Runtime::<F, EF, DiffusionMatrixBabyBear>::new(program.clone(), SC::new().perm);
+        runtime.run().unwrap();
+
+        let shape = A::shrink_shape(&program);
+        assert_eq!(shape.inner.len(), A::get_all_wide().len());
+        let machine = A::shrink_machine(BabyBearPoseidon2::default(), &program);
+        let (pk, vk) = machine.setup(&program);
+        let proof = run_test_machine(vec![runtime.record], machine, pk, vk).unwrap();
+
+        let shard_proof = &proof.shard_proofs[0];
+        let inner = shard_proof
+            .chip_ordering
+            .iter()
+            .map(|(chip, &i)| (chip.clone(), shard_proof.opened_values.chips[i].log_degree))
+            .collect();
+        assert_eq!(RecursionShape { inner }.diff(&shape), vec![]);
+    }
+
+    #[test]
+    #[should_panic(expected = "fixed log2 rows is too small")]
+    pub fn fixed_log2_rows_too_small() {
+        let program =
+            RecursionProgram { instructions: fibonacci_instructions(), ..Default::default() };
+        let chip = A::BaseAlu(Default::default()).with_fixed_log2_rows(0);
+        chip.generate_preprocessed_trace(&program);
+    }
+
+    #[test]
+    pub fn try_setup() {
+        let machine = A::machine_wide(BabyBearPoseidon2::default());
+        let fibonacci =
+            || RecursionProgram { instructions: fibonacci_instructions(), ..Default::default() };
+        assert!(A::try_setup(&machine, &fibonacci()).is_ok());
+
+        let minimal =
+            A::machine_from_chips(BabyBearPoseidon2::default(), &RecursionAirKind::REQUIRED);
+        assert_eq!(
+            A::try_setup(&minimal, &fibonacci()).err().unwrap(),
+            SetupError::MissingChip("BaseAlu".to_string())
+        );
+
+        // The last term is written to address 10.
+        let program = RecursionProgram { total_memory: 10, ..fibonacci() };
+        let err = A::try_setup(&machine, &program).err().unwrap();
+        assert!(matches!(err, SetupError::Address(AddressError::OutOfRange { index: 10, .. })));
+        assert!(err.to_string().contains("outside of an address space of size 10"), "{err}");
+
+        let mut program = fibonacci();
+        program.instructions.push(instr::mem(MemAccessKind::Write, 1, 0, 0));
+        assert!(matches!(
+            A::try_setup(&machine, &program).err().unwrap(),
+            SetupError::Memory(diagnostics) if diagnostics.len() == 1
+        ));
+
+        let mut program = fibonacci();
+        program.set_fixed_log2_rows("BaseAlu", 3);
+        let err = A::try_setup(&machine, &program).err().unwrap();
+        assert!(matches!(&err, SetupError::Shape(violations) if violations[0].chip == "BaseAlu"));
+    }
+
+    #[test]
+    pub fn verify() {
+        let machine = A::machine_wide(BabyBearPoseidon2::default());
+        let (vk, proof, public_values) = {
+            let program = Arc::new(RecursionProgram {
+                instructions: fibonacci_instructions(),
+                ..Default::default()
+            });
+            let mut runtime =
+                Runtime::<F, EF, DiffusionMatrixBabyBear>::new(program.clone(), SC::new().perm);
+            runtime.run().unwrap();
+            let public_values = MachineRecord::public_values::<F>(&runtime.record);
+
+            let prover = CpuProver::new(A::machine_wide(BabyBearPoseidon2::default()));
+            let (pk, vk) = prover.setup(&program);
+            let mut challenger = prover.config().challenger();
+            let proof = prover
+                .prove(&pk, vec![runtime.record], &mut challenger, SP1CoreOpts::default())
+                .unwrap();
+            (vk, proof, public_values)
+        };
+
+        A::verify(&machine, &vk, &proof, &public_values).unwrap();
+
+        let mut wrong_public_values = public_values;
+        wrong_public_values[0] += F::one();
+        assert!(matches!(
+            A::verify(&machine, &vk, &proof, &wrong_public_values),
+            Err(MachineVerificationError::InvalidPublicValues(_))
+        ));
+    }
+
+    /// A prover that fails its next `failures` proofs, and otherwise proves like [`CpuProver`].
+    struct FlakyProver {
+        inner: CpuProver<SC, A>,
+        failures: AtomicUsize,
+    }
+
+    impl MachineProver<SC, A> for FlakyProver {
+        type DeviceMatrix = <CpuProver<SC, A> as MachineProver<SC, A>>::DeviceMatrix;
+        type DeviceProverData = <CpuProver<SC, A> as MachineProver<SC, A>>::DeviceProverData;
+        type Error = CpuProverError;
+
+        fn new(machine: StarkMachine<SC, A>) -> Self {
+            Self { inner: CpuProver::new(machine), failures: AtomicUsize::new(0) }
+        }
+
+        fn machine(&self) -> &StarkMachine<SC, A> {
+            self.inner.machine()
+        }
+
+        fn commit(
+            &self,
+            record: ExecutionRecord<F>,
+            traces: Vec<(String, RowMajorMatrix<F>)>,
+        ) -> ShardMainData<SC, Self::DeviceMatrix, Self::DeviceProverData> {
+            self.inner.commit(record, traces)
+        }
+
+        fn open(
+            &self,
+            pk: &StarkProvingKey<SC>,
+            data: ShardMainData<SC, Self::DeviceMatrix, Self::DeviceProverData>,
+            challenger: &mut <SC as StarkGenericConfig>::Challenger,
+        ) -> Result<ShardProof<SC>, Self::Error> {
+            self.inner.open(pk, data, challenger)
+        }
+
+        fn prove(
+            &self,
+            pk: &StarkProvingKey<SC>,
+            records: Vec<ExecutionRecord<F>>,
+            challenger: &mut <SC as StarkGenericConfig>::Challenger,
+            opts: SP1CoreOpts,
+        ) -> Result<MachineProof<SC>, Self::Error> {
+            let fail = |n: usize| n.checked_sub(1);
+            if self.failures.fetch_update(Ordering::SeqCst, Ordering::SeqCst, fail).is_ok() {
+                return Err(CpuProverError);
+            }
+            self.inner.prove(pk, records, challenger, opts)
+        }
+    }
+
+    #[test]
+    pub fn prove_with_retry_after_transient_failure() {
+        let program = Arc::new(RecursionProgram {
+            instructions: fibonacci_instructions(),
+            ..Default::default()
+        });
+        let mut runtime =
+            Runtime::<F, EF, DiffusionMatrixBabyBear>::new(program.clone(), SC::new().perm);
+        runtime.run().unwrap();
+
+        let prover = FlakyProver::new(A::machine_wide(BabyBearPoseidon2::default()));
+        let (pk, vk) = prover.setup(&program);
+
+        // A single attempt gives up on the transient failure.
+        prover.failures.store(1, Ordering::SeqCst);
+        assert!(prove_with_retry(&prover, &pk, &runtime.record, 1).is_err());
+
+        prover.failures.store(1, Ordering::SeqCst);
+        let proof = prove_with_retry(&prover, &pk, &runtime.record, 2).unwrap();
+        assert_eq!(prover.failures.load(Ordering::SeqCst), 0);
+        prover.machine().verify(&vk, &proof, &mut prover.config().challenger()).unwrap();
+    }
+
+    #[test]
+    pub fn generate_all_preprocessed() {
+        use p3_matrix::Matrix;
+
+        use crate::chips::mem::constant::NUM_MEM_PREPROCESSED_INIT_COLS;
+
+        let program = RecursionProgram::arbitrary_valid(&mut StdRng::seed_from_u64(2));
+        let traces = B::generate_all_preprocessed(&program);
+        assert_eq!(traces["MemoryConst"].width(), NUM_MEM_PREPROCESSED_INIT_COLS);
+        assert_eq!(traces.len(), B::get_all().len());
+    }
+
+    #[test]
+    pub fn chip_widths() {
+        use crate::chips::mem::constant::{NUM_MEM_INIT_COLS, NUM_MEM_PREPROCESSED_INIT_COLS};
+
+        let widths = A::chip_widths();
+        assert_eq!(
+            widths[0],
+            ("MemoryConst".to_string(), NUM_MEM_INIT_COLS, NUM_MEM_PREPROCESSED_INIT_COLS)
+        );
+        assert_eq!(widths.len(), 9);
+        assert!(widths.iter().all(|(_, main_width, _)| *main_width > 0));
+    }
+
+    #[test]
+    pub fn validate_col_padding() {
+        type Padded<const COL_PADDING: usize> = RecursionAir<F, 3, COL_PADDING>;
+
+        assert_eq!(Padded::<1>::col_padding(), 1);
+        assert_eq!(Padded::<0>::validate_col_padding(&Padded::<0>::get_all_wide()), Ok(()));
+        assert_eq!(Padded::<1024>::validate_col_padding(&Padded::<1024>::get_all_wide()), Ok(()));
+
+        let chips = Padded::<1>::get_all_wide();
+        let widest = chips.iter().map(BaseAir::<F>::width).max().unwrap();
+        assert!(matches!(
+            Padded::<1>::validate_col_padding(&chips),
+            Err(ColPaddingError::TooSmall { col_padding: 1, width, .. }) if width == widest
+        ));
+    }
+
+    #[test]
+    pub fn alternate_ext_degree() {
+        fn names<A: MachineAir<F, Program = RecursionProgram<F>>>(chips: &[A]) -> Vec<String> {
+            chips.iter().map(A::name).collect()
+        }
+
+        // BabyBear also has a quintic binomial extension. The machine type-checks at that degree,
+        // though its elements do not fit in a block of `D` limbs, so it cannot execute.
+        type Quintic = RecursionAir<F, 3, 0, 5>;
+        assert_eq!(names(&Quintic::get_all_wide()), names(&A::get_all_wide()));
+    }
+
+    #[test]
+    #[should_panic(expected = "MemoryVar")]
+    pub fn machine_from_chips_missing_required() {
+        A::get_chips(&[
+            RecursionAirKind::MemoryConst,
+            RecursionAirKind::BaseAlu,
+            RecursionAirKind::PublicValues,
+        ]);
+    }
+
+    #[test]
+    pub fn arbitrary_valid() {
+        let mut rng = StdRng::seed_from_u64(0xDEADBEEF);
+        for _ in 0..2 {
+            run_recursion_test_machines(RecursionProgram::arbitrary_valid(&mut rng));
+        }
+    }
+
     #[test]
     pub fn field_norm() {
         let mut instructions = Vec::new();
@@ -3,7 +3,7 @@ use std::borrow::Borrow;
 use p3_field::{AbstractExtensionField, AbstractField};
 use serde::{Deserialize, Serialize};
 
-use crate::*;
+use crate::{chips::exp_reverse_bits::EXP_REVERSE_BITS_LEN_BITS, *};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum Instruction<F> {
@@ -20,6 +20,25 @@ pub enum Instruction<F> {
     Hint(HintInstr<F>),
 }
 
+impl<F> Instruction<F> {
+    /// The name of the instruction's variant, e.g. `BaseAlu`.
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Instruction::BaseAlu(_) => "BaseAlu",
+            Instruction::ExtAlu(_) => "ExtAlu",
+            Instruction::Mem(_) => "Mem",
+            Instruction::Poseidon2(_) => "Poseidon2",
+            Instruction::ExpReverseBitsLen(_) => "ExpReverseBitsLen",
+            Instruction::HintBits(_) => "HintBits",
+            Instruction::FriFold(_) => "FriFold",
+            Instruction::Print(_) => "Print",
+            Instruction::HintExt2Felts(_) => "HintExt2Felts",
+            Instruction::CommitPublicValues(_) => "CommitPublicValues",
+            Instruction::Hint(_) => "Hint",
+        }
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct HintBitsInstr<F> {
     /// Addresses and mults of the output bits.
@@ -34,9 +53,13 @@ pub struct PrintInstr<F> {
     pub addr: Address<F>,
 }
 
+/// Writes the next blocks of the witness stream, one per output address, to variable memory.
+///
+/// Each output is a whole [`Block`], so a single instruction can hint an array of extension
+/// elements. The `MemoryVar` chip proves one access per output.
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct HintInstr<F> {
-    /// Addresses and mults of the output felts.
+    /// Addresses and mults of the output blocks.
     pub output_addrs_mults: Vec<(Address<F>, F)>,
 }
 
@@ -69,6 +92,30 @@ pub fn base_alu<F: AbstractField>(
             in1: Address(F::from_canonical_u32(in1)),
             in2: Address(F::from_canonical_u32(in2)),
         },
+        flag_addr: Address(F::zero()),
+        flag_mult: F::zero(),
+    })
+}
+
+/// A `DivFChecked` instruction, which also writes whether `in2` was nonzero to `flag`.
+pub fn base_alu_div_checked<F: AbstractField>(
+    mult: u32,
+    out: u32,
+    in1: u32,
+    in2: u32,
+    flag_mult: u32,
+    flag: u32,
+) -> Instruction<F> {
+    Instruction::BaseAlu(BaseAluInstr {
+        opcode: BaseAluOpcode::DivFChecked,
+        mult: F::from_canonical_u32(mult),
+        addrs: BaseAluIo {
+            out: Address(F::from_canonical_u32(out)),
+            in1: Address(F::from_canonical_u32(in1)),
+            in2: Address(F::from_canonical_u32(in2)),
+        },
+        flag_addr: Address(F::from_canonical_u32(flag)),
+        flag_mult: F::from_canonical_u32(flag_mult),
     })
 }
 
@@ -87,6 +134,44 @@ pub fn ext_alu<F: AbstractField>(
             in1: Address(F::from_canonical_u32(in1)),
             in2: Address(F::from_canonical_u32(in2)),
         },
+        flag_addr: Address(F::zero()),
+        flag_mult: F::zero(),
+    })
+}
+
+/// An `InvE` instruction writing the inverse of `input` to `out`.
+///
+/// The input is read as both operands, so its writer must account for two reads.
+pub fn ext_alu_inv<F: AbstractField>(mult: u32, out: u32, input: u32) -> Instruction<F> {
+    ext_alu(ExtAluOpcode::InvE, mult, out, input, input)
+}
+
+/// A `SquareE` instruction writing the square of `input` to `out`.
+///
+/// Unlike [`ext_alu_inv`], the input is read once.
+pub fn ext_alu_square<F: AbstractField>(mult: u32, out: u32, input: u32) -> Instruction<F> {
+    ext_alu(ExtAluOpcode::SquareE, mult, out, input, input)
+}
+
+/// A `DivEChecked` instruction, which also writes whether `in2` was nonzero to `flag`.
+pub fn ext_alu_div_checked<F: AbstractField>(
+    mult: u32,
+    out: u32,
+    in1: u32,
+    in2: u32,
+    flag_mult: u32,
+    flag: u32,
+) -> Instruction<F> {
+    Instruction::ExtAlu(ExtAluInstr {
+        opcode: ExtAluOpcode::DivEChecked,
+        mult: F::from_canonical_u32(mult),
+        addrs: ExtAluIo {
+            out: Address(F::from_canonical_u32(out)),
+            in1: Address(F::from_canonical_u32(in1)),
+            in2: Address(F::from_canonical_u32(in2)),
+        },
+        flag_addr: Address(F::from_canonical_u32(flag)),
+        flag_mult: F::from_canonical_u32(flag_mult),
     })
 }
 
@@ -131,6 +216,17 @@ pub fn mem_block<F: AbstractField>(
     })
 }
 
+pub fn hint<F: AbstractField>(output_addrs_mults: &[(u32, u32)]) -> Instruction<F> {
+    Instruction::Hint(HintInstr {
+        output_addrs_mults: output_addrs_mults
+            .iter()
+            .map(|&(addr, mult)| {
+                (Address(F::from_canonical_u32(addr)), F::from_canonical_u32(mult))
+            })
+            .collect(),
+    })
+}
+
 pub fn poseidon2<F: AbstractField>(
     mults: [u32; WIDTH],
     output: [u32; WIDTH],
@@ -145,19 +241,27 @@ pub fn poseidon2<F: AbstractField>(
     }))
 }
 
-pub fn exp_reverse_bits_len<F: AbstractField>(
+/// An `ExpReverseBitsLen` instruction, which writes `base^rev(exp)` to `result`, where `exp` is the
+/// number whose little-endian bits are the values at `exponent_bits`, and `rev(exp)` reverses its
+/// `exponent_bits.len()` bits.
+///
+/// # Panics
+///
+/// Panics if the number of bits is not in [`EXP_REVERSE_BITS_LEN_BITS`].
+pub fn exp_reverse_bits_len<F: AbstractField + Copy>(
     mult: u32,
-    base: F,
-    exp: Vec<F>,
-    result: F,
+    result: Address<F>,
+    base: Address<F>,
+    exponent_bits: &[Address<F>],
 ) -> Instruction<F> {
+    assert!(
+        EXP_REVERSE_BITS_LEN_BITS.contains(&exponent_bits.len()),
+        "exponents of {} bits are not in the supported range {EXP_REVERSE_BITS_LEN_BITS:?}",
+        exponent_bits.len()
+    );
     Instruction::ExpReverseBitsLen(ExpReverseBitsInstr {
         mult: F::from_canonical_u32(mult),
-        addrs: ExpReverseBitsIo {
-            base: Address(base),
-            exp: exp.into_iter().map(Address).collect(),
-            result: Address(result),
-        },
+        addrs: ExpReverseBitsIo { base, exp: exponent_bits.to_vec(), result },
     })
 }
 
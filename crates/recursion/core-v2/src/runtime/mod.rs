@@ -2,6 +2,7 @@ pub mod instruction;
 mod memory;
 mod opcode;
 mod program;
+mod program_builder;
 mod record;
 
 // Avoid triggering annoying branch of thiserror derive macro.
@@ -11,6 +12,7 @@ use instruction::{FieldEltType, HintBitsInstr, HintExt2FeltsInstr, HintInstr, Pr
 use memory::*;
 pub use opcode::*;
 pub use program::*;
+pub use program_builder::*;
 pub use record::*;
 
 use std::{
@@ -114,6 +116,13 @@ pub struct Runtime<'a, F: PrimeField32, EF: ExtensionField<F>, Diffusion> {
     /// The stream that print statements write to.
     pub debug_stdout: Box<dyn Write + 'a>,
 
+    /// The maximum number of instructions to execute, if any. Set by [`Self::set_max_steps`].
+    max_steps: Option<usize>,
+
+    /// Whether events are only counted in [`ExecutionRecord::unrecorded_events`] instead of being
+    /// recorded. Set by [`Self::dry_run`].
+    dry_run: bool,
+
     /// Entries for dealing with the Poseidon2 hash state.
     perm: Option<
         Poseidon2<
@@ -130,6 +139,46 @@ pub struct Runtime<'a, F: PrimeField32, EF: ExtensionField<F>, Diffusion> {
     _marker_diffusion: PhantomData<Diffusion>,
 }
 
+/// The state of a [`Runtime`] at some point of the execution, see [`Runtime::snapshot`].
+#[derive(Debug, Clone)]
+pub struct RuntimeSnapshot<F> {
+    timestamp: usize,
+    nb_poseidons: usize,
+    nb_wide_poseidons: usize,
+    nb_bit_decompositions: usize,
+    nb_ext_ops: usize,
+    nb_base_ops: usize,
+    nb_memory_ops: usize,
+    nb_branch_ops: usize,
+    nb_exp_reverse_bits: usize,
+    nb_fri_fold: usize,
+    nb_print_f: usize,
+    nb_print_e: usize,
+    clk: F,
+    pc: F,
+    memory: MemVecMap<F>,
+    record: ExecutionRecord<F>,
+    witness_stream: VecDeque<Block<F>>,
+    cycle_tracker: HashMap<String, CycleTrackerEntry>,
+}
+
+impl<F: Copy> RuntimeSnapshot<F> {
+    /// The program counter when the snapshot was taken.
+    pub fn pc(&self) -> F {
+        self.pc
+    }
+
+    /// The number of instructions executed when the snapshot was taken.
+    pub fn timestamp(&self) -> usize {
+        self.timestamp
+    }
+
+    /// The execution record when the snapshot was taken.
+    pub fn record(&self) -> &ExecutionRecord<F> {
+        &self.record
+    }
+}
+
 #[derive(Error, Debug)]
 pub enum RuntimeError<F: Debug, EF: Debug> {
     #[error(
@@ -158,6 +207,23 @@ pub enum RuntimeError<F: Debug, EF: Debug> {
     DebugPrint(#[from] std::io::Error),
     #[error("attempted to read from empty witness stream")]
     EmptyWitnessStream,
+    #[error("exceeded the limit of {0} executed instructions")]
+    StepLimitExceeded(usize),
+    #[error("the program has finished, so there is no instruction to step")]
+    Finished,
+}
+
+/// What a single instruction did, as returned by [`Runtime::step`].
+#[derive(Debug, Clone)]
+pub struct StepInfo<F> {
+    /// The index of the instruction in the program.
+    pub pc: usize,
+    /// The instruction.
+    pub instruction: Instruction<F>,
+    /// The addresses the instruction read, with the values read, in the order it read them.
+    pub reads: Vec<(Address<F>, Block<F>)>,
+    /// The addresses the instruction wrote, with the values written.
+    pub writes: Vec<(Address<F>, Block<F>)>,
 }
 
 impl<'a, F: PrimeField32, EF: ExtensionField<F>, Diffusion> Runtime<'a, F, EF, Diffusion>
@@ -203,12 +269,44 @@ where
             witness_stream: VecDeque::new(),
             cycle_tracker: HashMap::new(),
             debug_stdout: Box::new(stdout()),
+            max_steps: None,
+            dry_run: false,
             perm: Some(perm),
             _marker_ef: PhantomData,
             _marker_diffusion: PhantomData,
         }
     }
 
+    /// Limits the execution to `max_steps` instructions, counted by [`Self::timestamp`]. Executing
+    /// one more instruction fails with [`RuntimeError::StepLimitExceeded`] instead.
+    ///
+    /// By default, the number of instructions is unbounded. Setting a limit protects hosts that
+    /// execute untrusted programs.
+    pub fn set_max_steps(&mut self, max_steps: usize) {
+        self.max_steps = Some(max_steps);
+    }
+
+    /// Runs `program` to the end without recording its events, and returns a record that only
+    /// counts them, see [`ExecutionRecord::event_counts`].
+    ///
+    /// Sizing the traces of a program only needs these counts, which a dry run gets without
+    /// allocating the events.
+    pub fn dry_run(
+        program: Arc<RecursionProgram<F>>,
+        perm: Poseidon2<
+            F,
+            Poseidon2ExternalMatrixGeneral,
+            Diffusion,
+            PERMUTATION_WIDTH,
+            POSEIDON2_SBOX_DEGREE,
+        >,
+    ) -> Result<ExecutionRecord<F>, RuntimeError<F, EF>> {
+        let mut runtime = Self::new(program, perm);
+        runtime.dry_run = true;
+        runtime.run()?;
+        Ok(runtime.record)
+    }
+
     pub fn print_stats(&self) {
         tracing::debug!("Total Cycles: {}", self.timestamp);
         tracing::debug!("Poseidon Skinny Operations: {}", self.nb_poseidons);
@@ -243,205 +341,319 @@ where
     }
 
     /// Compare to [sp1_recursion_core::runtime::Runtime::run].
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "execute recursion program", level = "info", skip_all)
+    )]
     pub fn run(&mut self) -> Result<(), RuntimeError<F, EF>> {
         let early_exit_ts = std::env::var("RECURSION_EARLY_EXIT_TS")
             .map_or(usize::MAX, |ts: String| ts.parse().unwrap());
+        self.run_until(early_exit_ts)?;
+
+        // In debug builds, check that a complete run produced a consistent record.
+        if cfg!(debug_assertions)
+            && self.pc == F::from_canonical_usize(self.program.instructions.len())
+        {
+            if let Err(e) = self.record.check_invariants() {
+                panic!("inconsistent execution record: {e}");
+            }
+        }
+        Ok(())
+    }
+
+    /// Executes instructions until the program ends or [`Self::timestamp`] reaches `timestamp`,
+    /// executing at least one instruction if any is left.
+    ///
+    /// Together with [`Self::snapshot`] and [`Self::restore`], this lets a program be stepped
+    /// through, inspected and rolled back. Calling [`Self::run`] afterwards runs it to the end.
+    pub fn run_until(&mut self, timestamp: usize) -> Result<(), RuntimeError<F, EF>> {
         while self.pc < F::from_canonical_u32(self.program.instructions.len() as u32) {
-            let idx = self.pc.as_canonical_u32() as usize;
-            let instruction = self.program.instructions[idx].clone();
-
-            let next_clk = self.clk + F::from_canonical_u32(4);
-            let next_pc = self.pc + F::one();
-            match instruction {
-                Instruction::BaseAlu(instr @ BaseAluInstr { opcode, mult, addrs }) => {
-                    self.nb_base_ops += 1;
-                    let in1 = self.memory.mr(addrs.in1).val[0];
-                    let in2 = self.memory.mr(addrs.in2).val[0];
-                    // Do the computation.
-                    let out = match opcode {
-                        BaseAluOpcode::AddF => in1 + in2,
-                        BaseAluOpcode::SubF => in1 - in2,
-                        BaseAluOpcode::MulF => in1 * in2,
-                        BaseAluOpcode::DivF => match in1.try_div(in2) {
-                            Some(x) => x,
-                            None => {
-                                // Check for division exceptions and error. Note that 0/0 is defined
-                                // to be 1.
-                                if in1.is_zero() {
-                                    AbstractField::one()
-                                } else {
-                                    return Err(RuntimeError::DivFOutOfDomain {
-                                        in1,
-                                        in2,
-                                        instr,
-                                        pc: self.pc.as_canonical_u32() as usize,
-                                        trace: self.nearest_pc_backtrace(),
-                                    });
-                                }
+            self.execute_instruction()?;
+            if self.timestamp >= timestamp {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// Executes exactly one instruction, the one at [`Self::pc`], and returns what it did: the
+    /// instruction, and the addresses it read and wrote with their values.
+    ///
+    /// This is the building block of an interactive debugger. Stepping and [`Self::run`] can be
+    /// mixed freely, as `run` executes instructions the same way, only without collecting the
+    /// [`StepInfo`] of each. Stepping past the end of the program fails with
+    /// [`RuntimeError::Finished`].
+    pub fn step(&mut self) -> Result<StepInfo<F>, RuntimeError<F, EF>> {
+        let pc = self.pc.as_canonical_u32() as usize;
+        let instruction =
+            self.program.instructions.get(pc).cloned().ok_or(RuntimeError::Finished)?;
+        self.execute_instruction()?;
+
+        // Every address is written once, so memory still holds the values read and written.
+        let (reads, writes) = memory_accesses(&instruction);
+        let with_value = |(addr, _): (Address<F>, F)| (addr, self.memory.0[addr.as_usize()].val);
+        Ok(StepInfo {
+            pc,
+            reads: reads.into_iter().map(with_value).collect(),
+            writes: writes.into_iter().map(with_value).collect(),
+            instruction,
+        })
+    }
+
+    /// Executes the instruction at [`Self::pc`], which must exist, and advances to the next one.
+    fn execute_instruction(&mut self) -> Result<(), RuntimeError<F, EF>> {
+        if let Some(max_steps) = self.max_steps.filter(|&max| self.timestamp >= max) {
+            return Err(RuntimeError::StepLimitExceeded(max_steps));
+        }
+
+        let idx = self.pc.as_canonical_u32() as usize;
+        let instruction = self.program.instructions[idx].clone();
+
+        let next_clk = self.clk + F::from_canonical_u32(4);
+        let next_pc = self.pc + F::one();
+        match instruction {
+            Instruction::BaseAlu(
+                instr @ BaseAluInstr { opcode, mult, addrs, flag_addr, flag_mult },
+            ) => {
+                self.nb_base_ops += 1;
+                let in1 = self.memory.mr(addrs.in1).val[0];
+                let in2 = self.memory.mr(addrs.in2).val[0];
+                // Do the computation.
+                let out = match opcode {
+                    BaseAluOpcode::AddF => in1 + in2,
+                    BaseAluOpcode::SubF => in1 - in2,
+                    BaseAluOpcode::MulF => in1 * in2,
+                    BaseAluOpcode::DivF => match in1.try_div(in2) {
+                        Some(x) => x,
+                        None => {
+                            // Check for division exceptions and error. Note that 0/0 is defined
+                            // to be 1.
+                            if in1.is_zero() {
+                                AbstractField::one()
+                            } else {
+                                return Err(RuntimeError::DivFOutOfDomain {
+                                    in1,
+                                    in2,
+                                    instr,
+                                    pc: self.pc.as_canonical_u32() as usize,
+                                    trace: self.nearest_pc_backtrace(),
+                                });
                             }
-                        },
-                    };
-                    self.memory.mw(addrs.out, Block::from(out), mult);
+                        }
+                    },
+                    BaseAluOpcode::DivFChecked => {
+                        let flag = F::from_bool(!in2.is_zero());
+                        self.memory.mw(flag_addr, Block::from(flag), flag_mult);
+                        in1.try_div(in2).unwrap_or_else(F::zero)
+                    }
+                };
+                self.memory.mw(addrs.out, Block::from(out), mult);
+                if self.dry_run {
+                    self.record.unrecorded_events.base_alu += 1;
+                } else {
                     self.record.base_alu_events.push(BaseAluEvent { out, in1, in2 });
                 }
-                Instruction::ExtAlu(instr @ ExtAluInstr { opcode, mult, addrs }) => {
-                    self.nb_ext_ops += 1;
-                    let in1 = self.memory.mr(addrs.in1).val;
-                    let in2 = self.memory.mr(addrs.in2).val;
-                    // Do the computation.
-                    let in1_ef = EF::from_base_slice(&in1.0);
-                    let in2_ef = EF::from_base_slice(&in2.0);
-                    let out_ef = match opcode {
-                        ExtAluOpcode::AddE => in1_ef + in2_ef,
-                        ExtAluOpcode::SubE => in1_ef - in2_ef,
-                        ExtAluOpcode::MulE => in1_ef * in2_ef,
-                        ExtAluOpcode::DivE => match in1_ef.try_div(in2_ef) {
-                            Some(x) => x,
-                            None => {
-                                // Check for division exceptions and error. Note that 0/0 is defined
-                                // to be 1.
-                                if in1_ef.is_zero() {
-                                    AbstractField::one()
-                                } else {
-                                    return Err(RuntimeError::DivEOutOfDomain {
-                                        in1: in1_ef,
-                                        in2: in2_ef,
-                                        instr,
-                                        pc: self.pc.as_canonical_u32() as usize,
-                                        trace: self.nearest_pc_backtrace(),
-                                    });
-                                }
+            }
+            Instruction::ExtAlu(
+                instr @ ExtAluInstr { opcode, mult, addrs, flag_addr, flag_mult },
+            ) => {
+                self.nb_ext_ops += 1;
+                let in1 = self.memory.mr(addrs.in1).val;
+                // Squaring reads its input once, and records it as `in2` too.
+                let in2 = if opcode == ExtAluOpcode::SquareE {
+                    in1
+                } else {
+                    self.memory.mr(addrs.in2).val
+                };
+                // Do the computation.
+                let in1_ef = EF::from_base_slice(&in1.0);
+                let in2_ef = EF::from_base_slice(&in2.0);
+                let out_ef = match opcode {
+                    ExtAluOpcode::AddE => in1_ef + in2_ef,
+                    ExtAluOpcode::SubE => in1_ef - in2_ef,
+                    ExtAluOpcode::MulE => in1_ef * in2_ef,
+                    ExtAluOpcode::SquareE => in1_ef.square(),
+                    ExtAluOpcode::DivE => match in1_ef.try_div(in2_ef) {
+                        Some(x) => x,
+                        None => {
+                            // Check for division exceptions and error. Note that 0/0 is defined
+                            // to be 1.
+                            if in1_ef.is_zero() {
+                                AbstractField::one()
+                            } else {
+                                return Err(RuntimeError::DivEOutOfDomain {
+                                    in1: in1_ef,
+                                    in2: in2_ef,
+                                    instr,
+                                    pc: self.pc.as_canonical_u32() as usize,
+                                    trace: self.nearest_pc_backtrace(),
+                                });
                             }
-                        },
-                    };
-                    let out = Block::from(out_ef.as_base_slice());
-                    self.memory.mw(addrs.out, out, mult);
+                        }
+                    },
+                    ExtAluOpcode::DivEChecked => {
+                        let flag = F::from_bool(!in2_ef.is_zero());
+                        self.memory.mw(flag_addr, Block::from(flag), flag_mult);
+                        in1_ef.try_div(in2_ef).unwrap_or_else(EF::zero)
+                    }
+                    ExtAluOpcode::InvE => match in1_ef.try_inverse() {
+                        Some(x) => x,
+                        None => {
+                            return Err(RuntimeError::DivEOutOfDomain {
+                                in1: EF::one(),
+                                in2: in1_ef,
+                                instr,
+                                pc: self.pc.as_canonical_u32() as usize,
+                                trace: self.nearest_pc_backtrace(),
+                            });
+                        }
+                    },
+                };
+                let out = Block::from(out_ef.as_base_slice());
+                self.memory.mw(addrs.out, out, mult);
+                if self.dry_run {
+                    self.record.unrecorded_events.ext_alu += 1;
+                } else {
                     self.record.ext_alu_events.push(ExtAluEvent { out, in1, in2 });
                 }
-                Instruction::Mem(MemInstr {
-                    addrs: MemIo { inner: addr },
-                    vals: MemIo { inner: val },
-                    mult,
-                    kind,
-                }) => {
-                    self.nb_memory_ops += 1;
-                    match kind {
-                        MemAccessKind::Read => {
-                            let mem_entry = self.memory.mr_mult(addr, mult);
-                            assert_eq!(
-                                mem_entry.val, val,
-                                "stored memory value should be the specified value"
-                            );
-                        }
-                        MemAccessKind::Write => drop(self.memory.mw(addr, val, mult)),
+            }
+            Instruction::Mem(MemInstr {
+                addrs: MemIo { inner: addr },
+                vals: MemIo { inner: val },
+                mult,
+                kind,
+            }) => {
+                self.nb_memory_ops += 1;
+                match kind {
+                    MemAccessKind::Read => {
+                        let mem_entry = self.memory.mr_mult(addr, mult);
+                        assert_eq!(
+                            mem_entry.val, val,
+                            "stored memory value should be the specified value"
+                        );
                     }
-                    self.record.mem_const_count += 1;
+                    MemAccessKind::Write => drop(self.memory.mw(addr, val, mult)),
                 }
-                Instruction::Poseidon2(instr) => {
-                    let Poseidon2Instr { addrs: Poseidon2Io { input, output }, mults } = *instr;
-                    self.nb_poseidons += 1;
-                    let in_vals = std::array::from_fn(|i| self.memory.mr(input[i]).val[0]);
-                    let perm_output = self.perm.as_ref().unwrap().permute(in_vals);
-
-                    perm_output.iter().zip(output).zip(mults).for_each(|((&val, addr), mult)| {
-                        self.memory.mw(addr, Block::from(val), mult);
-                    });
+                self.record.mem_const_count += 1;
+            }
+            Instruction::Poseidon2(instr) => {
+                let Poseidon2Instr { addrs: Poseidon2Io { input, output }, mults } = *instr;
+                self.nb_poseidons += 1;
+                let in_vals = std::array::from_fn(|i| self.memory.mr(input[i]).val[0]);
+                let perm_output = self.perm.as_ref().unwrap().permute(in_vals);
+
+                perm_output.iter().zip(output).zip(mults).for_each(|((&val, addr), mult)| {
+                    self.memory.mw(addr, Block::from(val), mult);
+                });
+                if self.dry_run {
+                    self.record.unrecorded_events.poseidon2 += 1;
+                } else {
                     self.record
                         .poseidon2_events
                         .push(Poseidon2Event { input: in_vals, output: perm_output });
                 }
-                Instruction::ExpReverseBitsLen(ExpReverseBitsInstr {
-                    addrs: ExpReverseBitsIo { base, exp, result },
-                    mult,
-                }) => {
-                    self.nb_exp_reverse_bits += 1;
-                    let base_val = self.memory.mr(base).val[0];
-                    let exp_bits: Vec<_> =
-                        exp.iter().map(|bit| self.memory.mr(*bit).val[0]).collect();
-                    let exp_val = exp_bits
-                        .iter()
-                        .enumerate()
-                        .fold(0, |acc, (i, &val)| acc + val.as_canonical_u32() * (1 << i));
-                    let out =
-                        base_val.exp_u64(reverse_bits_len(exp_val as usize, exp_bits.len()) as u64);
-                    self.memory.mw(result, Block::from(out), mult);
+            }
+            Instruction::ExpReverseBitsLen(ExpReverseBitsInstr {
+                addrs: ExpReverseBitsIo { base, exp, result },
+                mult,
+            }) => {
+                self.nb_exp_reverse_bits += 1;
+                let base_val = self.memory.mr(base).val[0];
+                let exp_bits: Vec<_> = exp.iter().map(|bit| self.memory.mr(*bit).val[0]).collect();
+                let exp_val = exp_bits
+                    .iter()
+                    .enumerate()
+                    .fold(0, |acc, (i, &val)| acc + val.as_canonical_u32() * (1 << i));
+                let out =
+                    base_val.exp_u64(reverse_bits_len(exp_val as usize, exp_bits.len()) as u64);
+                self.memory.mw(result, Block::from(out), mult);
+                if self.dry_run {
+                    self.record.unrecorded_events.exp_reverse_bits_len += 1;
+                } else {
                     self.record.exp_reverse_bits_len_events.push(ExpReverseBitsEvent {
                         result: out,
                         base: base_val,
                         exp: exp_bits,
                     });
                 }
-                Instruction::HintBits(HintBitsInstr { output_addrs_mults, input_addr }) => {
-                    self.nb_bit_decompositions += 1;
-                    let num = self.memory.mr_mult(input_addr, F::zero()).val[0].as_canonical_u32();
-                    // Decompose the num into LE bits.
-                    let bits = (0..output_addrs_mults.len())
-                        .map(|i| Block::from(F::from_canonical_u32((num >> i) & 1)))
-                        .collect::<Vec<_>>();
-                    // Write the bits to the array at dst.
-                    for (bit, (addr, mult)) in bits.into_iter().zip(output_addrs_mults) {
-                        self.memory.mw(addr, bit, mult);
+            }
+            Instruction::HintBits(HintBitsInstr { output_addrs_mults, input_addr }) => {
+                self.nb_bit_decompositions += 1;
+                let num = self.memory.mr_mult(input_addr, F::zero()).val[0].as_canonical_u32();
+                // Decompose the num into LE bits.
+                let bits = (0..output_addrs_mults.len())
+                    .map(|i| Block::from(F::from_canonical_u32((num >> i) & 1)))
+                    .collect::<Vec<_>>();
+                // Write the bits to the array at dst.
+                for (bit, (addr, mult)) in bits.into_iter().zip(output_addrs_mults) {
+                    self.memory.mw(addr, bit, mult);
+                    if self.dry_run {
+                        self.record.unrecorded_events.mem_var += 1;
+                    } else {
                         self.record.mem_var_events.push(MemEvent { inner: bit });
                     }
                 }
+            }
 
-                Instruction::FriFold(instr) => {
-                    let FriFoldInstr {
-                        base_single_addrs,
-                        ext_single_addrs,
-                        ext_vec_addrs,
-                        alpha_pow_mults,
-                        ro_mults,
-                    } = *instr;
-                    self.nb_fri_fold += 1;
-                    let x = self.memory.mr(base_single_addrs.x).val[0];
-                    let z = self.memory.mr(ext_single_addrs.z).val;
-                    let z: EF = z.ext();
-                    let alpha = self.memory.mr(ext_single_addrs.alpha).val;
-                    let alpha: EF = alpha.ext();
-                    let mat_opening = ext_vec_addrs
-                        .mat_opening
-                        .iter()
-                        .map(|addr| self.memory.mr(*addr).val)
-                        .collect_vec();
-                    let ps_at_z = ext_vec_addrs
-                        .ps_at_z
-                        .iter()
-                        .map(|addr| self.memory.mr(*addr).val)
-                        .collect_vec();
-
-                    for m in 0..ps_at_z.len() {
-                        // let m = F::from_canonical_u32(m);
-                        // Get the opening values.
-                        let p_at_x = mat_opening[m];
-                        let p_at_x: EF = p_at_x.ext();
-                        let p_at_z = ps_at_z[m];
-                        let p_at_z: EF = p_at_z.ext();
-
-                        // Calculate the quotient and update the values
-                        let quotient = (-p_at_z + p_at_x) / (-z + x);
-
-                        // First we peek to get the current value.
-                        let alpha_pow: EF =
-                            self.memory.mr(ext_vec_addrs.alpha_pow_input[m]).val.ext();
-
-                        let ro: EF = self.memory.mr(ext_vec_addrs.ro_input[m]).val.ext();
-
-                        let new_ro = ro + alpha_pow * quotient;
-                        let new_alpha_pow = alpha_pow * alpha;
-
-                        let _ = self.memory.mw(
-                            ext_vec_addrs.ro_output[m],
-                            Block::from(new_ro.as_base_slice()),
-                            ro_mults[m],
-                        );
-
-                        let _ = self.memory.mw(
-                            ext_vec_addrs.alpha_pow_output[m],
-                            Block::from(new_alpha_pow.as_base_slice()),
-                            alpha_pow_mults[m],
-                        );
-
+            Instruction::FriFold(instr) => {
+                let FriFoldInstr {
+                    base_single_addrs,
+                    ext_single_addrs,
+                    ext_vec_addrs,
+                    alpha_pow_mults,
+                    ro_mults,
+                } = *instr;
+                self.nb_fri_fold += 1;
+                let x = self.memory.mr(base_single_addrs.x).val[0];
+                let z = self.memory.mr(ext_single_addrs.z).val;
+                let z: EF = z.ext();
+                let alpha = self.memory.mr(ext_single_addrs.alpha).val;
+                let alpha: EF = alpha.ext();
+                let mat_opening = ext_vec_addrs
+                    .mat_opening
+                    .iter()
+                    .map(|addr| self.memory.mr(*addr).val)
+                    .collect_vec();
+                let ps_at_z = ext_vec_addrs
+                    .ps_at_z
+                    .iter()
+                    .map(|addr| self.memory.mr(*addr).val)
+                    .collect_vec();
+
+                for m in 0..ps_at_z.len() {
+                    // let m = F::from_canonical_u32(m);
+                    // Get the opening values.
+                    let p_at_x = mat_opening[m];
+                    let p_at_x: EF = p_at_x.ext();
+                    let p_at_z = ps_at_z[m];
+                    let p_at_z: EF = p_at_z.ext();
+
+                    // Calculate the quotient and update the values
+                    let quotient = (-p_at_z + p_at_x) / (-z + x);
+
+                    // First we peek to get the current value.
+                    let alpha_pow: EF = self.memory.mr(ext_vec_addrs.alpha_pow_input[m]).val.ext();
+
+                    let ro: EF = self.memory.mr(ext_vec_addrs.ro_input[m]).val.ext();
+
+                    let new_ro = ro + alpha_pow * quotient;
+                    let new_alpha_pow = alpha_pow * alpha;
+
+                    let _ = self.memory.mw(
+                        ext_vec_addrs.ro_output[m],
+                        Block::from(new_ro.as_base_slice()),
+                        ro_mults[m],
+                    );
+
+                    let _ = self.memory.mw(
+                        ext_vec_addrs.alpha_pow_output[m],
+                        Block::from(new_alpha_pow.as_base_slice()),
+                        alpha_pow_mults[m],
+                    );
+
+                    if self.dry_run {
+                        self.record.unrecorded_events.fri_fold += 1;
+                    } else {
                         self.record.fri_fold_events.push(FriFoldEvent {
                             base_single: FriFoldBaseIo { x },
                             ext_single: FriFoldExtSingleIo {
@@ -459,65 +671,141 @@ where
                         });
                     }
                 }
+            }
 
-                Instruction::CommitPublicValues(instr) => {
-                    let pv_addrs = instr.pv_addrs.to_vec();
-                    let pv_values: [F; RECURSIVE_PROOF_NUM_PV_ELTS] =
-                        array::from_fn(|i| self.memory.mr(pv_addrs[i]).val[0]);
-                    self.record.public_values = *pv_values.as_slice().borrow();
+            Instruction::CommitPublicValues(instr) => {
+                let pv_addrs = instr.pv_addrs.to_vec();
+                let pv_values: [F; RECURSIVE_PROOF_NUM_PV_ELTS] =
+                    array::from_fn(|i| self.memory.mr(pv_addrs[i]).val[0]);
+                self.record.public_values = *pv_values.as_slice().borrow();
+                if self.dry_run {
+                    self.record.unrecorded_events.commit_pv_hash += 1;
+                } else {
                     self.record
                         .commit_pv_hash_events
                         .push(CommitPublicValuesEvent { public_values: self.record.public_values });
                 }
+            }
 
-                Instruction::Print(PrintInstr { field_elt_type, addr }) => match field_elt_type {
-                    FieldEltType::Base => {
-                        self.nb_print_f += 1;
-                        let f = self.memory.mr_mult(addr, F::zero()).val[0];
-                        writeln!(self.debug_stdout, "PRINTF={f}")
-                    }
-                    FieldEltType::Extension => {
-                        self.nb_print_e += 1;
-                        let ef = self.memory.mr_mult(addr, F::zero()).val;
-                        writeln!(self.debug_stdout, "PRINTEF={ef:?}")
-                    }
+            Instruction::Print(PrintInstr { field_elt_type, addr }) => match field_elt_type {
+                FieldEltType::Base => {
+                    self.nb_print_f += 1;
+                    let f = self.memory.mr_mult(addr, F::zero()).val[0];
+                    writeln!(self.debug_stdout, "PRINTF={f}")
+                }
+                FieldEltType::Extension => {
+                    self.nb_print_e += 1;
+                    let ef = self.memory.mr_mult(addr, F::zero()).val;
+                    writeln!(self.debug_stdout, "PRINTEF={ef:?}")
                 }
-                .map_err(RuntimeError::DebugPrint)?,
-                Instruction::HintExt2Felts(HintExt2FeltsInstr {
-                    output_addrs_mults,
-                    input_addr,
-                }) => {
-                    self.nb_bit_decompositions += 1;
-                    let fs = self.memory.mr_mult(input_addr, F::zero()).val;
-                    // Write the bits to the array at dst.
-                    for (f, (addr, mult)) in fs.into_iter().zip(output_addrs_mults) {
-                        let felt = Block::from(f);
-                        self.memory.mw(addr, felt, mult);
+            }
+            .map_err(RuntimeError::DebugPrint)?,
+            Instruction::HintExt2Felts(HintExt2FeltsInstr { output_addrs_mults, input_addr }) => {
+                self.nb_bit_decompositions += 1;
+                let fs = self.memory.mr_mult(input_addr, F::zero()).val;
+                // Write the bits to the array at dst.
+                for (f, (addr, mult)) in fs.into_iter().zip(output_addrs_mults) {
+                    let felt = Block::from(f);
+                    self.memory.mw(addr, felt, mult);
+                    if self.dry_run {
+                        self.record.unrecorded_events.mem_var += 1;
+                    } else {
                         self.record.mem_var_events.push(MemEvent { inner: felt });
                     }
                 }
-                Instruction::Hint(HintInstr { output_addrs_mults }) => {
-                    // Check that enough Blocks can be read, so `drain` does not panic.
-                    if self.witness_stream.len() < output_addrs_mults.len() {
-                        return Err(RuntimeError::EmptyWitnessStream);
-                    }
-                    let witness = self.witness_stream.drain(0..output_addrs_mults.len());
-                    for ((addr, mult), val) in zip(output_addrs_mults, witness) {
-                        // Inline [`Self::mw`] to mutably borrow multiple fields of `self`.
-                        self.memory.mw(addr, val, mult);
+            }
+            Instruction::Hint(HintInstr { output_addrs_mults }) => {
+                // Check that enough Blocks can be read, so `drain` does not panic.
+                if self.witness_stream.len() < output_addrs_mults.len() {
+                    return Err(RuntimeError::EmptyWitnessStream);
+                }
+                let witness = self.witness_stream.drain(0..output_addrs_mults.len());
+                for ((addr, mult), val) in zip(output_addrs_mults, witness) {
+                    // Inline [`Self::mw`] to mutably borrow multiple fields of `self`.
+                    self.memory.mw(addr, val, mult);
+                    if self.dry_run {
+                        self.record.unrecorded_events.mem_var += 1;
+                    } else {
                         self.record.mem_var_events.push(MemEvent { inner: val });
                     }
                 }
             }
+        }
 
-            self.pc = next_pc;
-            self.clk = next_clk;
-            self.timestamp += 1;
+        self.pc = next_pc;
+        self.clk = next_clk;
+        self.timestamp += 1;
+        Ok(())
+    }
 
-            if self.timestamp >= early_exit_ts {
-                break;
-            }
+    /// Captures the state of the execution: the memory, the program counter, the clock, the
+    /// execution record, the remaining witness stream and the statistics.
+    ///
+    /// The snapshot is an independent copy, which [`Self::restore`] rolls the runtime back to no
+    /// matter how far it has run since. Only the program, which never changes, is shared.
+    pub fn snapshot(&self) -> RuntimeSnapshot<F> {
+        RuntimeSnapshot {
+            timestamp: self.timestamp,
+            nb_poseidons: self.nb_poseidons,
+            nb_wide_poseidons: self.nb_wide_poseidons,
+            nb_bit_decompositions: self.nb_bit_decompositions,
+            nb_ext_ops: self.nb_ext_ops,
+            nb_base_ops: self.nb_base_ops,
+            nb_memory_ops: self.nb_memory_ops,
+            nb_branch_ops: self.nb_branch_ops,
+            nb_exp_reverse_bits: self.nb_exp_reverse_bits,
+            nb_fri_fold: self.nb_fri_fold,
+            nb_print_f: self.nb_print_f,
+            nb_print_e: self.nb_print_e,
+            clk: self.clk,
+            pc: self.pc,
+            memory: self.memory.clone(),
+            record: self.record.clone(),
+            witness_stream: self.witness_stream.clone(),
+            cycle_tracker: self.cycle_tracker.clone(),
         }
-        Ok(())
+    }
+
+    /// Rolls the execution back to `snapshot`, taken by [`Self::snapshot`] on a runtime of the same
+    /// program.
+    pub fn restore(&mut self, snapshot: RuntimeSnapshot<F>) {
+        let RuntimeSnapshot {
+            timestamp,
+            nb_poseidons,
+            nb_wide_poseidons,
+            nb_bit_decompositions,
+            nb_ext_ops,
+            nb_base_ops,
+            nb_memory_ops,
+            nb_branch_ops,
+            nb_exp_reverse_bits,
+            nb_fri_fold,
+            nb_print_f,
+            nb_print_e,
+            clk,
+            pc,
+            memory,
+            record,
+            witness_stream,
+            cycle_tracker,
+        } = snapshot;
+        self.nb_poseidons = nb_poseidons;
+        self.nb_wide_poseidons = nb_wide_poseidons;
+        self.nb_bit_decompositions = nb_bit_decompositions;
+        self.nb_ext_ops = nb_ext_ops;
+        self.nb_base_ops = nb_base_ops;
+        self.nb_memory_ops = nb_memory_ops;
+        self.nb_branch_ops = nb_branch_ops;
+        self.nb_exp_reverse_bits = nb_exp_reverse_bits;
+        self.nb_fri_fold = nb_fri_fold;
+        self.nb_print_f = nb_print_f;
+        self.nb_print_e = nb_print_e;
+        self.timestamp = timestamp;
+        self.clk = clk;
+        self.pc = pc;
+        self.memory = memory;
+        self.record = record;
+        self.witness_stream = witness_stream;
+        self.cycle_tracker = cycle_tracker;
     }
 }
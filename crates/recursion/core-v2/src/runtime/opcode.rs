@@ -6,6 +6,9 @@ pub enum BaseAluOpcode {
     SubF,
     MulF,
     DivF,
+    /// Division that writes a quotient of zero and clears the flag instead of failing when the
+    /// divisor is zero.
+    DivFChecked,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
@@ -14,4 +17,13 @@ pub enum ExtAluOpcode {
     SubE,
     MulE,
     DivE,
+    /// Division that writes a quotient of zero and clears the flag instead of failing when the
+    /// divisor is zero.
+    DivEChecked,
+    /// The inverse of `in1`, which is also read as `in2`. Like `DivE`, the runtime fails and the
+    /// constraints are unsatisfiable when the input is zero.
+    InvE,
+    /// The square of `in1`. Unlike `MulE` with the same operand twice, the input is read once, and
+    /// `in2` is ignored.
+    SquareE,
 }
@@ -1,9 +1,25 @@
+use std::{
+    borrow::Borrow,
+    collections::{BTreeMap, BTreeSet},
+    fmt::Debug,
+    iter::{once, zip},
+};
+
 use backtrace::Backtrace;
-use p3_field::Field;
-use serde::{Deserialize, Serialize};
+use hashbrown::{HashMap, HashSet};
+use p3_field::{
+    extension::{BinomialExtensionField, BinomiallyExtendable},
+    AbstractExtensionField, AbstractField, Field, PrimeField32,
+};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use sp1_stark::air::MachineProgram;
+use thiserror::Error;
 
-use crate::*;
+use crate::{
+    instruction::{HintBitsInstr, HintExt2FeltsInstr, HintInstr},
+    machine::RecursionAir,
+    *,
+};
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct RecursionProgram<F> {
@@ -11,6 +27,521 @@ pub struct RecursionProgram<F> {
     pub total_memory: usize,
     #[serde(skip)]
     pub traces: Vec<Option<Backtrace>>,
+    /// Minimum log2 trace heights, keyed by chip name, used to pad chips to a fixed shape.
+    ///
+    /// Chips read this for both their preprocessed and main traces so the two stay the same height.
+    #[serde(default)]
+    pub min_log_heights: BTreeMap<String, usize>,
+    /// Exact log2 trace heights, keyed by chip name, set with [`Self::set_fixed_log2_rows`].
+    ///
+    /// A pinned chip ignores its entry in [`Self::min_log_heights`]. Generating a trace with more
+    /// rows than its pinned height panics.
+    #[serde(default)]
+    pub pinned_log_heights: BTreeMap<String, usize>,
+    /// Names of chips to leave out of machines built for this program, e.g. to measure their cost.
+    ///
+    /// Only [`RecursionAir::machine_for_program`] and [`RecursionAir::machine_wide_for_program`]
+    /// read this. Both panic if the program needs a disabled chip.
+    #[serde(default)]
+    pub disabled_chips: BTreeSet<String>,
+}
+
+impl<F> RecursionProgram<F> {
+    /// The minimum log2 height configured for the chip named `chip_name`, if any.
+    pub fn min_log_height(&self, chip_name: &str) -> Option<usize> {
+        self.min_log_heights.get(chip_name).copied()
+    }
+
+    /// Pins the traces of the chip named `chip_name` to exactly `2^log_height` rows, for building
+    /// programs of a fixed shape, see [`Self::pinned_log_heights`].
+    pub fn set_fixed_log2_rows(&mut self, chip_name: impl Into<String>, log_height: usize) {
+        self.pinned_log_heights.insert(chip_name.into(), log_height);
+    }
+
+    /// The log2 height pinned for the chip named `chip_name` by [`Self::set_fixed_log2_rows`], if
+    /// any.
+    pub fn fixed_log2_rows(&self, chip_name: &str) -> Option<usize> {
+        self.pinned_log_heights.get(chip_name).copied()
+    }
+
+    /// Leaves the chip named `chip_name` out of machines built for this program, see
+    /// [`Self::disabled_chips`].
+    pub fn disable_chip(&mut self, chip_name: impl Into<String>) {
+        self.disabled_chips.insert(chip_name.into());
+    }
+}
+
+impl<F: Serialize + DeserializeOwned> RecursionProgram<F> {
+    /// Serializes the program with `bincode`, e.g. to ship it to a prover in another process.
+    ///
+    /// The debug [`Self::traces`] are not serialized.
+    pub fn to_bytes(&self) -> bincode::Result<Vec<u8>> {
+        bincode::serialize(self)
+    }
+
+    /// Deserializes a program serialized by [`Self::to_bytes`].
+    pub fn from_bytes(bytes: &[u8]) -> bincode::Result<Self> {
+        bincode::deserialize(bytes)
+    }
+}
+
+/// A problem found in a program by [`RecursionProgram::validate_memory`] or
+/// [`RecursionProgram::validate_var_memory`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MemoryDiagnostic<F> {
+    /// The address is written by two instructions, given by their indices in the program.
+    DuplicateWrite { addr: Address<F>, first: usize, second: usize },
+    /// `Mem` reads of the address consume more than the multiplicity it was written with.
+    ReadsExceedWriteMult { addr: Address<F>, write_mult: u64, read_mult: u64 },
+    /// The instruction with the given index reads the address before any instruction writes it.
+    OrphanRead { addr: Address<F>, index: usize },
+    /// The reads of an address written by a hint instruction consume a different multiplicity
+    /// than it was written with.
+    VarMultMismatch { addr: Address<F>, write_mult: u64, read_mult: u64 },
+}
+
+/// An error returned by [`RecursionProgram::validate_addresses`].
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum AddressError<F: Debug> {
+    #[error(
+        "instruction {index} accesses {addr:?}, outside of an address space of size {max_addr}"
+    )]
+    OutOfRange { index: usize, addr: Address<F>, max_addr: usize },
+}
+
+impl<F: PrimeField32> RecursionProgram<F> {
+    /// Scans the `Mem` and hint instructions for duplicate writes and for addresses that are read
+    /// more often than their write multiplicity allows.
+    ///
+    /// This is an opt-in sanity check meant to run before proving. Other instructions also access
+    /// memory, so a clean result does not imply that the memory argument balances.
+    pub fn validate_memory(&self) -> Vec<MemoryDiagnostic<F>> {
+        let mut diagnostics = Vec::new();
+        // The index of the instruction writing each address, and its write multiplicity.
+        let mut writes = HashMap::<Address<F>, (usize, u64)>::new();
+        let mut reads = HashMap::<Address<F>, u64>::new();
+
+        let mut write = |addr: Address<F>, mult: F, index: usize| {
+            if let Some(&(first, _)) = writes.get(&addr) {
+                diagnostics.push(MemoryDiagnostic::DuplicateWrite { addr, first, second: index });
+            } else {
+                writes.insert(addr, (index, mult.as_canonical_u32() as u64));
+            }
+        };
+        for (index, instruction) in self.instructions.iter().enumerate() {
+            match instruction {
+                Instruction::Mem(MemInstr { addrs, mult, kind: MemAccessKind::Write, .. }) => {
+                    write(addrs.inner, *mult, index)
+                }
+                Instruction::Mem(MemInstr { addrs, mult, kind: MemAccessKind::Read, .. }) => {
+                    *reads.entry(addrs.inner).or_default() += mult.as_canonical_u32() as u64
+                }
+                Instruction::Hint(HintInstr { output_addrs_mults })
+                | Instruction::HintBits(HintBitsInstr { output_addrs_mults, .. }) => {
+                    for &(addr, mult) in output_addrs_mults {
+                        write(addr, mult, index);
+                    }
+                }
+                Instruction::HintExt2Felts(HintExt2FeltsInstr { output_addrs_mults, .. }) => {
+                    for &(addr, mult) in output_addrs_mults {
+                        write(addr, mult, index);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        let mut overread = reads
+            .into_iter()
+            .filter_map(|(addr, read_mult)| {
+                let &(index, write_mult) = writes.get(&addr)?;
+                (read_mult > write_mult).then_some((index, addr, write_mult, read_mult))
+            })
+            .collect::<Vec<_>>();
+        // Report in program order, for determinism.
+        overread.sort_by_key(|&(index, ..)| index);
+        diagnostics.extend(overread.into_iter().map(|(_, addr, write_mult, read_mult)| {
+            MemoryDiagnostic::ReadsExceedWriteMult { addr, write_mult, read_mult }
+        }));
+
+        diagnostics
+    }
+
+    /// Walks the program in order to check the variable memory written by hint instructions: every
+    /// read must come after a write of its address, and the reads of each hinted address must
+    /// consume exactly the multiplicity it was written with.
+    ///
+    /// Like [`Self::validate_memory`], this is an opt-in check meant to run before proving.
+    pub fn validate_var_memory(&self) -> Vec<MemoryDiagnostic<F>> {
+        let mut diagnostics = Vec::new();
+        let mut written = HashSet::<Address<F>>::new();
+        // The index of the hint instruction writing each address, its write multiplicity, and the
+        // multiplicity consumed by reads so far.
+        let mut hinted = HashMap::<Address<F>, (usize, u64, u64)>::new();
+
+        for (index, instruction) in self.instructions.iter().enumerate() {
+            let (reads, writes) = memory_accesses(instruction);
+            for (addr, mult) in reads {
+                if !written.contains(&addr) {
+                    diagnostics.push(MemoryDiagnostic::OrphanRead { addr, index });
+                } else if let Some((.., read_mult)) = hinted.get_mut(&addr) {
+                    *read_mult += mult.as_canonical_u32() as u64;
+                }
+            }
+            let is_hint = matches!(
+                instruction,
+                Instruction::Hint(_) | Instruction::HintBits(_) | Instruction::HintExt2Felts(_)
+            );
+            for (addr, mult) in writes {
+                written.insert(addr);
+                if is_hint {
+                    hinted.insert(addr, (index, mult.as_canonical_u32() as u64, 0));
+                }
+            }
+        }
+
+        let mut mismatched = hinted
+            .into_iter()
+            .filter(|(_, (_, write_mult, read_mult))| write_mult != read_mult)
+            .collect::<Vec<_>>();
+        // Report in program order, for determinism.
+        mismatched.sort_by_key(|&(addr, (index, ..))| (index, addr.0.as_canonical_u32()));
+        diagnostics.extend(mismatched.into_iter().map(|(addr, (_, write_mult, read_mult))| {
+            MemoryDiagnostic::VarMultMismatch { addr, write_mult, read_mult }
+        }));
+
+        diagnostics
+    }
+
+    /// Checks that every address the program reads or writes is below `max_addr`, the size of the
+    /// address space, such as [`Self::total_memory`]. Returns the first access out of range, in
+    /// program order.
+    ///
+    /// Like [`Self::validate_memory`], this is an opt-in check meant to run before proving, which
+    /// catches off-by-one errors in the address allocation of program generators.
+    pub fn validate_addresses(&self, max_addr: usize) -> Result<(), AddressError<F>> {
+        for (index, instruction) in self.instructions.iter().enumerate() {
+            let (reads, writes) = memory_accesses(instruction);
+            if let Some((addr, _)) =
+                reads.into_iter().chain(writes).find(|(addr, _)| addr.as_usize() >= max_addr)
+            {
+                return Err(AddressError::OutOfRange { index, addr, max_addr });
+            }
+        }
+        Ok(())
+    }
+
+    /// Renumbers the addresses of the program to `0, 1, 2, ...` in the order they are first
+    /// accessed, in the order of [`Self::validate_addresses`], and returns the number of distinct
+    /// addresses. A nonzero [`Self::total_memory`] is lowered to that number.
+    ///
+    /// Programs that only differ in how their addresses are numbered canonicalize to the same
+    /// program, so they serialize to the same bytes and have the same verifying key. Renumbering
+    /// does not change the values written or committed, so the proven result is the same. The
+    /// order of the instructions is kept, since reads must come after the writes they consume.
+    pub fn canonicalize(&mut self) -> usize {
+        let mut renumbered = HashMap::<Address<F>, Address<F>>::new();
+        for instruction in &mut self.instructions {
+            map_addresses(instruction, |addr| {
+                let next = Address(F::from_canonical_usize(renumbered.len()));
+                *renumbered.entry(addr).or_insert(next)
+            });
+        }
+
+        if self.total_memory != 0 {
+            self.total_memory = renumbered.len();
+        }
+        renumbered.len()
+    }
+}
+
+/// Replaces every address `instruction` accesses with `f` of it, calling `f` in the order of
+/// [`memory_accesses`]: reads before writes.
+///
+/// The flag address of an unchecked division is not accessed, so it is left as is. The second
+/// input of `SquareE` is visited, so it stays equal to the first.
+fn map_addresses<F: Field>(
+    instruction: &mut Instruction<F>,
+    mut f: impl FnMut(Address<F>) -> Address<F>,
+) {
+    let mut map = |addr: &mut Address<F>| *addr = f(*addr);
+    match instruction {
+        Instruction::BaseAlu(BaseAluInstr { opcode, addrs, flag_addr, .. }) => {
+            map(&mut addrs.in1);
+            map(&mut addrs.in2);
+            map(&mut addrs.out);
+            if *opcode == BaseAluOpcode::DivFChecked {
+                map(flag_addr);
+            }
+        }
+        Instruction::ExtAlu(ExtAluInstr { opcode, addrs, flag_addr, .. }) => {
+            map(&mut addrs.in1);
+            map(&mut addrs.in2);
+            map(&mut addrs.out);
+            if *opcode == ExtAluOpcode::DivEChecked {
+                map(flag_addr);
+            }
+        }
+        Instruction::Mem(MemInstr { addrs, .. }) => map(&mut addrs.inner),
+        Instruction::Poseidon2(instr) => {
+            instr.addrs.input.iter_mut().chain(&mut instr.addrs.output).for_each(map)
+        }
+        Instruction::ExpReverseBitsLen(ExpReverseBitsInstr { addrs, .. }) => {
+            once(&mut addrs.base)
+                .chain(&mut addrs.exp)
+                .chain(once(&mut addrs.result))
+                .for_each(map);
+        }
+        Instruction::FriFold(instr) => {
+            let vec_addrs = &mut instr.ext_vec_addrs;
+            once(&mut instr.base_single_addrs.x)
+                .chain(once(&mut instr.ext_single_addrs.z))
+                .chain(once(&mut instr.ext_single_addrs.alpha))
+                .chain(&mut vec_addrs.mat_opening)
+                .chain(&mut vec_addrs.ps_at_z)
+                .chain(&mut vec_addrs.alpha_pow_input)
+                .chain(&mut vec_addrs.ro_input)
+                .chain(&mut vec_addrs.ro_output)
+                .chain(&mut vec_addrs.alpha_pow_output)
+                .for_each(map);
+        }
+        Instruction::CommitPublicValues(instr) => {
+            let mut pv_addrs = instr.pv_addrs.to_vec();
+            pv_addrs.iter_mut().for_each(map);
+            let pv_addrs: &RecursionPublicValues<Address<F>> = pv_addrs.as_slice().borrow();
+            instr.pv_addrs = *pv_addrs;
+        }
+        Instruction::Print(instruction::PrintInstr { addr, .. }) => map(addr),
+        Instruction::Hint(HintInstr { output_addrs_mults }) => {
+            output_addrs_mults.iter_mut().for_each(|(addr, _)| map(addr))
+        }
+        Instruction::HintBits(HintBitsInstr { output_addrs_mults, input_addr }) => {
+            map(input_addr);
+            output_addrs_mults.iter_mut().for_each(|(addr, _)| map(addr));
+        }
+        Instruction::HintExt2Felts(HintExt2FeltsInstr { output_addrs_mults, input_addr }) => {
+            map(input_addr);
+            output_addrs_mults.iter_mut().for_each(|(addr, _)| map(addr));
+        }
+    }
+}
+
+/// Addresses accessed by an instruction, each with a multiplicity.
+type AddrsMults<F> = Vec<(Address<F>, F)>;
+
+/// The memory accesses the runtime performs for `instruction`, as the addresses it reads with the
+/// multiplicity each read consumes, and the addresses it writes with their multiplicities. Reads
+/// come before writes.
+pub(super) fn memory_accesses<F: Field>(
+    instruction: &Instruction<F>,
+) -> (AddrsMults<F>, AddrsMults<F>) {
+    let one = F::one;
+    match instruction {
+        Instruction::BaseAlu(BaseAluInstr { opcode, mult, addrs, flag_addr, flag_mult }) => {
+            let mut writes = vec![(addrs.out, *mult)];
+            if *opcode == BaseAluOpcode::DivFChecked {
+                writes.push((*flag_addr, *flag_mult));
+            }
+            (vec![(addrs.in1, one()), (addrs.in2, one())], writes)
+        }
+        Instruction::ExtAlu(ExtAluInstr { opcode, mult, addrs, flag_addr, flag_mult }) => {
+            let mut writes = vec![(addrs.out, *mult)];
+            if *opcode == ExtAluOpcode::DivEChecked {
+                writes.push((*flag_addr, *flag_mult));
+            }
+            if *opcode == ExtAluOpcode::SquareE {
+                return (vec![(addrs.in1, one())], writes);
+            }
+            (vec![(addrs.in1, one()), (addrs.in2, one())], writes)
+        }
+        Instruction::Mem(MemInstr { addrs, mult, kind: MemAccessKind::Read, .. }) => {
+            (vec![(addrs.inner, *mult)], vec![])
+        }
+        Instruction::Mem(MemInstr { addrs, mult, kind: MemAccessKind::Write, .. }) => {
+            (vec![], vec![(addrs.inner, *mult)])
+        }
+        Instruction::Poseidon2(instr) => (
+            instr.addrs.input.iter().map(|&addr| (addr, one())).collect(),
+            instr.addrs.output.into_iter().zip(instr.mults).collect(),
+        ),
+        Instruction::ExpReverseBitsLen(ExpReverseBitsInstr { addrs, mult }) => (
+            once(addrs.base).chain(addrs.exp.iter().copied()).map(|addr| (addr, one())).collect(),
+            vec![(addrs.result, *mult)],
+        ),
+        Instruction::FriFold(instr) => {
+            let vec_addrs = &instr.ext_vec_addrs;
+            let reads = [instr.base_single_addrs.x, instr.ext_single_addrs.z]
+                .into_iter()
+                .chain(once(instr.ext_single_addrs.alpha))
+                .chain(vec_addrs.mat_opening.iter().copied())
+                .chain(vec_addrs.ps_at_z.iter().copied())
+                .chain(vec_addrs.alpha_pow_input.iter().copied())
+                .chain(vec_addrs.ro_input.iter().copied())
+                .map(|addr| (addr, one()))
+                .collect();
+            let writes = zip(vec_addrs.ro_output.iter().copied(), instr.ro_mults.iter().copied())
+                .chain(zip(
+                    vec_addrs.alpha_pow_output.iter().copied(),
+                    instr.alpha_pow_mults.iter().copied(),
+                ))
+                .collect();
+            (reads, writes)
+        }
+        Instruction::CommitPublicValues(instr) => {
+            (instr.pv_addrs.to_vec().into_iter().map(|addr| (addr, one())).collect(), vec![])
+        }
+        Instruction::Print(instruction::PrintInstr { addr, .. }) => {
+            (vec![(*addr, F::zero())], vec![])
+        }
+        Instruction::Hint(HintInstr { output_addrs_mults }) => (vec![], output_addrs_mults.clone()),
+        Instruction::HintBits(HintBitsInstr { output_addrs_mults, input_addr }) => {
+            (vec![(*input_addr, F::zero())], output_addrs_mults.clone())
+        }
+        Instruction::HintExt2Felts(HintExt2FeltsInstr { output_addrs_mults, input_addr }) => {
+            (vec![(*input_addr, F::zero())], output_addrs_mults.to_vec())
+        }
+    }
+}
+
+/// Removes the items at `indices` from `items`, keeping the others in order.
+fn remove_indices<T>(items: &mut Vec<T>, indices: &HashSet<usize>) {
+    let mut index = 0;
+    items.retain(|_| {
+        index += 1;
+        !indices.contains(&(index - 1))
+    });
+}
+
+/// The instruction counts and trace heights of a [`RecursionProgram`], as returned by
+/// [`RecursionProgram::stats`]. Storing these across changes shows how the proof size evolves.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProgramStats {
+    /// The number of instructions of each variant, keyed by [`Instruction::name`].
+    pub instruction_counts: BTreeMap<String, usize>,
+    /// The trace heights keyed by chip name, see [`RecursionAir::heights`].
+    pub heights: BTreeMap<String, usize>,
+}
+
+impl<F: PrimeField32 + BinomiallyExtendable<D>> RecursionProgram<F> {
+    /// The instruction counts of the program and the trace heights of the recursion machine of
+    /// degree `DEGREE` for it.
+    pub fn stats<const DEGREE: usize>(&self) -> ProgramStats {
+        let mut instruction_counts = BTreeMap::new();
+        for instruction in &self.instructions {
+            *instruction_counts.entry(instruction.name().to_owned()).or_default() += 1;
+        }
+        let heights = RecursionAir::<F, DEGREE, 0>::heights(self).into_iter().collect();
+        ProgramStats { instruction_counts, heights }
+    }
+
+    /// Evaluates the `BaseAlu` and `ExtAlu` instructions whose inputs are written by `Mem`
+    /// instructions, directly or through other folded instructions, and replaces them with `Mem`
+    /// writes of their results. Returns the number of folded instructions.
+    ///
+    /// Each folded instruction releases one read of each of its inputs, so the multiplicities of
+    /// the writes of the inputs are lowered accordingly, and writes left with no reads are removed.
+    /// The folded program writes the same values, so it commits the same public values.
+    ///
+    /// Checked divisions, which also write a flag, divisions the runtime would reject, and
+    /// instructions reading a write with fewer reads left than they make are left in place.
+    pub fn constant_fold(&mut self) -> usize {
+        type EF<F> = BinomialExtensionField<F, D>;
+
+        // The index of the `Mem` write of each constant address, and the value written.
+        let mut constants = HashMap::<Address<F>, (usize, Block<F>)>::new();
+        // The `Mem` writes whose multiplicity was lowered to zero.
+        let mut unread = HashSet::new();
+        let mut folded = 0;
+
+        for index in 0..self.instructions.len() {
+            let (out, val, mult, in1, in2) = match &self.instructions[index] {
+                Instruction::Mem(MemInstr { addrs, vals, kind: MemAccessKind::Write, .. }) => {
+                    constants.insert(addrs.inner, (index, vals.inner));
+                    continue;
+                }
+                Instruction::BaseAlu(BaseAluInstr { opcode, mult, addrs, .. }) => {
+                    let (Some(&(_, in1)), Some(&(_, in2))) =
+                        (constants.get(&addrs.in1), constants.get(&addrs.in2))
+                    else {
+                        continue;
+                    };
+                    let (in1, in2) = (in1[0], in2[0]);
+                    let out = match opcode {
+                        BaseAluOpcode::AddF => in1 + in2,
+                        BaseAluOpcode::SubF => in1 - in2,
+                        BaseAluOpcode::MulF => in1 * in2,
+                        BaseAluOpcode::DivF if !in2.is_zero() => in1 / in2,
+                        // Like the runtime, 0/0 is defined to be 1.
+                        BaseAluOpcode::DivF if in1.is_zero() => F::one(),
+                        BaseAluOpcode::DivF | BaseAluOpcode::DivFChecked => continue,
+                    };
+                    (addrs.out, Block::from(out), *mult, addrs.in1, Some(addrs.in2))
+                }
+                Instruction::ExtAlu(ExtAluInstr { opcode, mult, addrs, .. }) => {
+                    // Squaring reads its input once, and ignores `in2`.
+                    let in2_addr = (*opcode != ExtAluOpcode::SquareE).then_some(addrs.in2);
+                    let (Some(&(_, in1)), Some(&(_, in2))) =
+                        (constants.get(&addrs.in1), constants.get(&in2_addr.unwrap_or(addrs.in1)))
+                    else {
+                        continue;
+                    };
+                    let (in1, in2) = (in1.ext::<EF<F>>(), in2.ext::<EF<F>>());
+                    let out = match opcode {
+                        ExtAluOpcode::AddE => in1 + in2,
+                        ExtAluOpcode::SubE => in1 - in2,
+                        ExtAluOpcode::MulE => in1 * in2,
+                        ExtAluOpcode::SquareE => in1.square(),
+                        ExtAluOpcode::DivE if !in2.is_zero() => in1 / in2,
+                        ExtAluOpcode::DivE if in1.is_zero() => EF::one(),
+                        ExtAluOpcode::InvE if !in1.is_zero() => in1.inverse(),
+                        ExtAluOpcode::DivE | ExtAluOpcode::DivEChecked | ExtAluOpcode::InvE => {
+                            continue
+                        }
+                    };
+                    (addrs.out, Block::from(out.as_base_slice()), *mult, addrs.in1, in2_addr)
+                }
+                _ => continue,
+            };
+
+            // A write with fewer reads than this instruction makes is malformed, and releasing
+            // its reads would wrap its multiplicity around, so such instructions are left in place.
+            let writers = once(in1).chain(in2).map(|input| constants[&input].0).collect::<Vec<_>>();
+            let releasable = writers.iter().all(|&writer| {
+                let Instruction::Mem(MemInstr { mult, .. }) = &self.instructions[writer] else {
+                    unreachable!("constants are written by `Mem` instructions");
+                };
+                let reads = writers.iter().filter(|&&other| other == writer).count();
+                mult.as_canonical_u32() as usize >= reads
+            });
+            if !releasable {
+                continue;
+            }
+            for writer in writers {
+                let Instruction::Mem(MemInstr { mult, .. }) = &mut self.instructions[writer] else {
+                    unreachable!("constants are written by `Mem` instructions");
+                };
+                *mult -= F::one();
+                if mult.is_zero() {
+                    unread.insert(writer);
+                }
+            }
+            self.instructions[index] = Instruction::Mem(MemInstr {
+                addrs: MemIo { inner: out },
+                vals: MemIo { inner: val },
+                mult,
+                kind: MemAccessKind::Write,
+            });
+            constants.insert(out, (index, val));
+            folded += 1;
+        }
+
+        // The backtraces, when recorded, are indexed like the instructions.
+        if self.traces.len() == self.instructions.len() {
+            remove_indices(&mut self.traces, &unread);
+        }
+        remove_indices(&mut self.instructions, &unread);
+        folded
+    }
 }
 
 impl<F: Field> MachineProgram<F> for RecursionProgram<F> {
@@ -18,3 +549,430 @@ impl<F: Field> MachineProgram<F> for RecursionProgram<F> {
         F::zero()
     }
 }
+
+#[cfg(feature = "debug-record")]
+impl<F: Field> RecursionProgram<F> {
+    /// Renders the program with one line per instruction, named after the `instr::` helper that
+    /// builds it. Useful to eyeball a hand-built program when a proof fails.
+    pub fn disassemble(&self) -> String {
+        use std::fmt::Write;
+
+        let mut out = String::new();
+        for (index, instruction) in self.instructions.iter().enumerate() {
+            writeln!(out, "{index:>4}: {}", disassemble_instruction(instruction)).unwrap();
+        }
+        out
+    }
+}
+
+#[cfg(feature = "debug-record")]
+fn disassemble_instruction<F: Field>(instruction: &Instruction<F>) -> String {
+    use itertools::Itertools;
+
+    let addrs = |addrs: &[Address<F>]| format!("[{}]", addrs.iter().map(|a| a.0).join(", "));
+    let addrs_mults = |addrs_mults: &[(Address<F>, F)]| {
+        format!("[{}]", addrs_mults.iter().map(|(a, m)| format!("{}x{m}", a.0)).join(", "))
+    };
+
+    match instruction {
+        Instruction::BaseAlu(BaseAluInstr { opcode, mult, addrs: io, flag_addr, flag_mult }) => {
+            let mut line = format!(
+                "base_alu {opcode:?} mult={mult} out={} in1={} in2={}",
+                io.out.0, io.in1.0, io.in2.0
+            );
+            if *opcode == BaseAluOpcode::DivFChecked {
+                line += &format!(" flag={}x{flag_mult}", flag_addr.0);
+            }
+            line
+        }
+        Instruction::ExtAlu(ExtAluInstr { opcode, mult, addrs: io, flag_addr, flag_mult }) => {
+            let mut line = format!(
+                "ext_alu {opcode:?} mult={mult} out={} in1={} in2={}",
+                io.out.0, io.in1.0, io.in2.0
+            );
+            if *opcode == ExtAluOpcode::DivEChecked {
+                line += &format!(" flag={}x{flag_mult}", flag_addr.0);
+            }
+            line
+        }
+        Instruction::Mem(MemInstr { addrs: io, vals, mult, kind }) => {
+            format!(
+                "mem {kind:?} mult={mult} addr={} val=[{}]",
+                io.inner.0,
+                vals.inner.0.iter().join(", ")
+            )
+        }
+        Instruction::Poseidon2(instr) => format!(
+            "poseidon2 mults=[{}] output={} input={}",
+            instr.mults.iter().join(", "),
+            addrs(&instr.addrs.output),
+            addrs(&instr.addrs.input),
+        ),
+        Instruction::ExpReverseBitsLen(ExpReverseBitsInstr { addrs: io, mult }) => format!(
+            "exp_reverse_bits_len mult={mult} base={} exp={} result={}",
+            io.base.0,
+            addrs(&io.exp),
+            io.result.0,
+        ),
+        Instruction::FriFold(instr) => {
+            let vec_addrs = &instr.ext_vec_addrs;
+            format!(
+                "fri_fold z={} alpha={} x={} mat_opening={} ps_at_z={} alpha_pow_input={} \
+                 ro_input={} alpha_pow_output={} ro_output={} alpha_mults=[{}] ro_mults=[{}]",
+                instr.ext_single_addrs.z.0,
+                instr.ext_single_addrs.alpha.0,
+                instr.base_single_addrs.x.0,
+                addrs(&vec_addrs.mat_opening),
+                addrs(&vec_addrs.ps_at_z),
+                addrs(&vec_addrs.alpha_pow_input),
+                addrs(&vec_addrs.ro_input),
+                addrs(&vec_addrs.alpha_pow_output),
+                addrs(&vec_addrs.ro_output),
+                instr.alpha_pow_mults.iter().join(", "),
+                instr.ro_mults.iter().join(", "),
+            )
+        }
+        Instruction::Print(instruction::PrintInstr { field_elt_type, addr }) => {
+            format!("print {field_elt_type:?} addr={}", addr.0)
+        }
+        Instruction::Hint(HintInstr { output_addrs_mults }) => {
+            format!("hint output={}", addrs_mults(output_addrs_mults))
+        }
+        Instruction::HintBits(HintBitsInstr { output_addrs_mults, input_addr }) => {
+            format!("hint_bits input={} output={}", input_addr.0, addrs_mults(output_addrs_mults))
+        }
+        Instruction::HintExt2Felts(HintExt2FeltsInstr { output_addrs_mults, input_addr }) => {
+            format!(
+                "hint_ext2felts input={} output={}",
+                input_addr.0,
+                addrs_mults(output_addrs_mults)
+            )
+        }
+        Instruction::CommitPublicValues(instr) => {
+            format!("commit_public_values pv_addrs={}", addrs(&instr.pv_addrs.to_vec()))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use p3_baby_bear::BabyBear;
+    use p3_field::{
+        extension::BinomialExtensionField, AbstractExtensionField, AbstractField, Field,
+    };
+
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::*;
+    use crate::{
+        instruction::{FieldEltType, PrintInstr},
+        machine::tests::{fibonacci_instructions, run_recursion_test_machines},
+        runtime::instruction as instr,
+    };
+
+    #[test]
+    fn stats() {
+        let program = RecursionProgram::<BabyBear> {
+            instructions: crate::machine::tests::fibonacci_instructions(),
+            ..Default::default()
+        };
+        let stats = program.stats::<3>();
+
+        let json = serde_json::to_string(&stats).unwrap();
+        let decoded: ProgramStats = serde_json::from_str(&json).unwrap();
+        assert_eq!(decoded, stats);
+
+        assert_eq!(
+            stats.instruction_counts,
+            BTreeMap::from([("BaseAlu".to_owned(), 9), ("Mem".to_owned(), 4)])
+        );
+        assert_eq!(
+            stats.heights,
+            RecursionAir::<BabyBear, 3, 0>::heights(&program).into_iter().collect()
+        );
+        assert!(stats.heights["BaseAlu"] >= 9);
+    }
+
+    #[test]
+    fn validate_memory_clean() {
+        let program = RecursionProgram::<BabyBear> {
+            instructions: vec![
+                instr::mem(MemAccessKind::Write, 2, 1, 2),
+                instr::mem(MemAccessKind::Read, 1, 1, 2),
+                instr::mem(MemAccessKind::Read, 1, 1, 2),
+                instr::mem(MemAccessKind::Write, 1, 2, 3),
+                instr::mem(MemAccessKind::Read, 1, 2, 3),
+            ],
+            ..Default::default()
+        };
+        assert!(program.validate_memory().is_empty());
+    }
+
+    #[test]
+    fn validate_memory_double_write() {
+        let program = RecursionProgram::<BabyBear> {
+            instructions: vec![
+                instr::mem(MemAccessKind::Write, 1, 1, 2),
+                instr::mem(MemAccessKind::Write, 1, 1, 2),
+                instr::mem(MemAccessKind::Read, 1, 1, 2),
+                instr::mem(MemAccessKind::Read, 1, 1, 2),
+            ],
+            ..Default::default()
+        };
+        let addr = Address(BabyBear::from_canonical_u32(1));
+        assert_eq!(
+            program.validate_memory(),
+            [
+                MemoryDiagnostic::DuplicateWrite { addr, first: 0, second: 1 },
+                MemoryDiagnostic::ReadsExceedWriteMult { addr, write_mult: 1, read_mult: 2 },
+            ]
+        );
+    }
+
+    #[test]
+    fn validate_var_memory_clean() {
+        let program = RecursionProgram::<BabyBear> {
+            instructions: vec![
+                instr::hint(&[(0, 2), (1, 1)]),
+                instr::base_alu(BaseAluOpcode::AddF, 1, 2, 0, 0),
+                instr::mem(MemAccessKind::Read, 1, 1, 0),
+                instr::mem(MemAccessKind::Read, 1, 2, 0),
+            ],
+            ..Default::default()
+        };
+        assert!(program.validate_var_memory().is_empty());
+    }
+
+    #[test]
+    fn validate_var_memory_orphan_read_and_bad_mult() {
+        let program = RecursionProgram::<BabyBear> {
+            instructions: vec![
+                instr::mem(MemAccessKind::Read, 1, 1, 0),
+                instr::hint(&[(0, 1), (1, 1)]),
+                instr::base_alu(BaseAluOpcode::AddF, 1, 2, 0, 0),
+                instr::mem(MemAccessKind::Read, 1, 1, 0),
+                instr::mem(MemAccessKind::Read, 1, 2, 0),
+            ],
+            ..Default::default()
+        };
+        let addr = |addr| Address(BabyBear::from_canonical_u32(addr));
+        assert_eq!(
+            program.validate_var_memory(),
+            [
+                MemoryDiagnostic::OrphanRead { addr: addr(1), index: 0 },
+                MemoryDiagnostic::VarMultMismatch { addr: addr(0), write_mult: 1, read_mult: 2 },
+            ]
+        );
+    }
+
+    #[test]
+    fn validate_addresses() {
+        let program = RecursionProgram::<BabyBear> {
+            instructions: vec![
+                instr::mem(MemAccessKind::Write, 2, 0, 1),
+                instr::mem(MemAccessKind::Write, 1, 1, 2),
+                instr::base_alu(BaseAluOpcode::AddF, 1, 2, 0, 1),
+                instr::base_alu(BaseAluOpcode::MulF, 1, 3, 0, 2),
+                instr::mem(MemAccessKind::Read, 1, 3, 3),
+            ],
+            ..Default::default()
+        };
+        assert_eq!(program.validate_addresses(4), Ok(()));
+        // The multiplication writes one past the end of the address space.
+        assert_eq!(
+            program.validate_addresses(3),
+            Err(AddressError::OutOfRange {
+                index: 3,
+                addr: Address(BabyBear::from_canonical_u32(3)),
+                max_addr: 3
+            })
+        );
+        // The second write, at index 1, is the first access to address 1.
+        assert_eq!(
+            program.validate_addresses(1),
+            Err(AddressError::OutOfRange { index: 1, addr: Address(BabyBear::one()), max_addr: 1 })
+        );
+    }
+
+    #[test]
+    fn canonicalize() {
+        let program = || RecursionProgram::<BabyBear> {
+            instructions: fibonacci_instructions(),
+            total_memory: 11,
+            ..Default::default()
+        };
+        let mut renumbered = RecursionProgram { total_memory: 1001, ..program() };
+        for instruction in &mut renumbered.instructions {
+            map_addresses(instruction, |addr| {
+                Address(BabyBear::from_canonical_usize(1000 - 7 * addr.as_usize()))
+            });
+        }
+        assert_ne!(renumbered.to_bytes().unwrap(), program().to_bytes().unwrap());
+
+        let mut canonical = program();
+        assert_eq!(canonical.canonicalize(), 11);
+        assert_eq!(renumbered.canonicalize(), 11);
+        assert_eq!(renumbered.to_bytes().unwrap(), canonical.to_bytes().unwrap());
+        assert_eq!(renumbered.total_memory, 11);
+
+        // Canonicalizing is idempotent, and the canonical program still proves.
+        let bytes = canonical.to_bytes().unwrap();
+        canonical.canonicalize();
+        assert_eq!(canonical.to_bytes().unwrap(), bytes);
+        run_recursion_test_machines(renumbered);
+    }
+
+    #[test]
+    fn validate_addresses_arbitrary_valid() {
+        let mut rng = StdRng::seed_from_u64(0xDEADBEEF);
+        let program = RecursionProgram::<BabyBear>::arbitrary_valid(&mut rng);
+        let total_memory = program.total_memory;
+        assert_eq!(program.validate_addresses(total_memory), Ok(()));
+
+        // The last instruction before the final reads writes the last address.
+        let last = Address(BabyBear::from_canonical_usize(total_memory - 1));
+        assert_eq!(
+            program.validate_addresses(total_memory - 1),
+            Err(AddressError::OutOfRange {
+                index: total_memory - 1,
+                addr: last,
+                max_addr: total_memory - 1
+            })
+        );
+    }
+
+    #[test]
+    fn bytes_round_trip() {
+        let addr = |addr: u32| Address(BabyBear::from_canonical_u32(addr));
+        let mut program = RecursionProgram::<BabyBear> {
+            instructions: vec![
+                instr::mem(MemAccessKind::Write, 2, 1, 7),
+                instr::base_alu(BaseAluOpcode::AddF, 1, 2, 1, 1),
+                instr::ext_alu(ExtAluOpcode::MulE, 0, 5, 2, 2),
+                instr::poseidon2([1; WIDTH], [2; WIDTH], [3; WIDTH]),
+                instr::exp_reverse_bits_len(1, addr(0), addr(1), &[addr(2)]),
+                Instruction::HintBits(HintBitsInstr {
+                    output_addrs_mults: vec![(addr(8), BabyBear::one())],
+                    input_addr: addr(9),
+                }),
+                instr::fri_fold(
+                    1,
+                    2,
+                    3,
+                    vec![4],
+                    vec![5],
+                    vec![6],
+                    vec![7],
+                    vec![8],
+                    vec![9],
+                    vec![1],
+                    vec![0],
+                ),
+                Instruction::Print(PrintInstr {
+                    field_elt_type: FieldEltType::Extension,
+                    addr: addr(5),
+                }),
+                Instruction::HintExt2Felts(HintExt2FeltsInstr {
+                    output_addrs_mults: std::array::from_fn(|i| {
+                        (addr(10 + i as u32), BabyBear::one())
+                    }),
+                    input_addr: addr(5),
+                }),
+                instr::commit_public_values(&RecursionPublicValues::default()),
+                instr::hint(&[(14, 3)]),
+            ],
+            total_memory: 15,
+            ..Default::default()
+        };
+        program.set_fixed_log2_rows("MemoryConst", 5);
+
+        let decoded =
+            RecursionProgram::<BabyBear>::from_bytes(&program.to_bytes().unwrap()).unwrap();
+        assert_eq!(format!("{:?}", decoded.instructions), format!("{:?}", program.instructions));
+        assert_eq!(decoded.total_memory, program.total_memory);
+        assert_eq!(decoded.pinned_log_heights, program.pinned_log_heights);
+
+        assert!(RecursionProgram::<BabyBear>::from_bytes(&[1, 2, 3]).is_err());
+    }
+
+    #[cfg(feature = "debug-record")]
+    #[test]
+    fn disassemble() {
+        let addr = |addr: u32| Address(BabyBear::from_canonical_u32(addr));
+        let program = RecursionProgram::<BabyBear> {
+            instructions: vec![
+                instr::mem(MemAccessKind::Write, 2, 1, 7),
+                instr::base_alu(BaseAluOpcode::AddF, 1, 2, 1, 1),
+                instr::base_alu_div_checked(1, 3, 2, 1, 1, 4),
+                instr::ext_alu(ExtAluOpcode::MulE, 0, 5, 2, 2),
+                instr::mem(MemAccessKind::Read, 1, 3, 1),
+                instr::exp_reverse_bits_len(1, addr(0), addr(1), &[addr(2)]),
+            ],
+            ..Default::default()
+        };
+        assert_eq!(
+            program.disassemble(),
+            "   0: mem Write mult=2 addr=1 val=[7, 0, 0, 0]
+   1: base_alu AddF mult=1 out=2 in1=1 in2=1
+   2: base_alu DivFChecked mult=1 out=3 in1=2 in2=1 flag=4x1
+   3: ext_alu MulE mult=0 out=5 in1=2 in2=2
+   4: mem Read mult=1 addr=3 val=[1, 0, 0, 0]
+   5: exp_reverse_bits_len mult=1 base=1 exp=[2] result=0
+"
+        );
+    }
+
+    #[test]
+    fn constant_fold() {
+        type EF = BinomialExtensionField<BabyBear, 4>;
+
+        let a = EF::from_base_fn(|i| BabyBear::from_canonical_usize(i + 2));
+        let b = EF::from_base_fn(|i| BabyBear::from_canonical_usize(3 * i + 1));
+        let mut program = RecursionProgram::<BabyBear> {
+            instructions: vec![
+                instr::mem_ext(MemAccessKind::Write, 3, 0, a),
+                instr::mem_ext(MemAccessKind::Write, 1, 1, b),
+                instr::mem(MemAccessKind::Write, 1, 2, 0),
+                instr::mem(MemAccessKind::Write, 1, 3, 5),
+                instr::ext_alu(ExtAluOpcode::MulE, 1, 4, 0, 1),
+                instr::ext_alu_inv(1, 5, 0),
+                // Dividing by zero fails at runtime, so it is not folded.
+                instr::base_alu(BaseAluOpcode::DivF, 0, 6, 3, 2),
+                instr::mem_ext(MemAccessKind::Read, 1, 4, a * b),
+                instr::mem_ext(MemAccessKind::Read, 1, 5, a.inverse()),
+            ],
+            ..Default::default()
+        };
+
+        assert_eq!(program.constant_fold(), 2);
+        // The writes of `a` and `b` have no reads left.
+        let expected: Vec<Instruction<BabyBear>> = vec![
+            instr::mem(MemAccessKind::Write, 1, 2, 0),
+            instr::mem(MemAccessKind::Write, 1, 3, 5),
+            instr::mem_ext(MemAccessKind::Write, 1, 4, a * b),
+            instr::mem_ext(MemAccessKind::Write, 1, 5, a.inverse()),
+            instr::base_alu(BaseAluOpcode::DivF, 0, 6, 3, 2),
+            instr::mem_ext(MemAccessKind::Read, 1, 4, a * b),
+            instr::mem_ext(MemAccessKind::Read, 1, 5, a.inverse()),
+        ];
+        assert_eq!(format!("{:?}", program.instructions), format!("{expected:?}"));
+    }
+
+    #[test]
+    fn constant_fold_keeps_overread_writes() {
+        // Adding address 0 to itself reads it twice, but its write only allows one read.
+        let instructions = vec![
+            instr::mem(MemAccessKind::Write, 1, 0, 2),
+            instr::mem(MemAccessKind::Write, 0, 1, 3),
+            instr::base_alu(BaseAluOpcode::AddF, 1, 2, 0, 0),
+            instr::base_alu(BaseAluOpcode::AddF, 1, 3, 0, 1),
+        ];
+        let mut program = RecursionProgram::<BabyBear> {
+            instructions: instructions.clone(),
+            ..Default::default()
+        };
+
+        assert_eq!(program.constant_fold(), 0);
+        assert_eq!(format!("{:?}", program.instructions), format!("{instructions:?}"));
+    }
+}
@@ -0,0 +1,143 @@
+use p3_field::PrimeField64;
+
+use crate::*;
+
+/// Assembles a [`RecursionProgram`] from high-level operations.
+///
+/// Every value gets a fresh address, and the multiplicity of the instruction writing it is set to
+/// the number of times it is read, so the program's memory accesses always balance.
+#[derive(Debug, Clone, Default)]
+pub struct ProgramBuilder<F> {
+    instructions: Vec<Instruction<F>>,
+    /// For each allocated address, the index of the instruction writing it and its reads so far.
+    writes: Vec<(usize, u32)>,
+}
+
+impl<F: PrimeField64> ProgramBuilder<F> {
+    pub fn new() -> Self {
+        Self { instructions: Vec::new(), writes: Vec::new() }
+    }
+
+    /// Writes `value` to a fresh address.
+    pub fn write(&mut self, value: F) -> Address<F> {
+        let addr = self.alloc();
+        self.instructions.push(Instruction::Mem(MemInstr {
+            addrs: MemIo { inner: addr },
+            vals: MemIo { inner: value.into() },
+            mult: F::zero(),
+            kind: MemAccessKind::Write,
+        }));
+        addr
+    }
+
+    /// Reads `addr`, which must hold `expected` when the program runs.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `addr` was not allocated by this builder.
+    pub fn read(&mut self, addr: Address<F>, expected: F) {
+        self.use_addr(addr);
+        self.instructions.push(Instruction::Mem(MemInstr {
+            addrs: MemIo { inner: addr },
+            vals: MemIo { inner: expected.into() },
+            mult: F::one(),
+            kind: MemAccessKind::Read,
+        }));
+    }
+
+    /// Applies `opcode` to the values at `in1` and `in2`, writing the result to a fresh address.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `in1` or `in2` was not allocated by this builder.
+    pub fn base_alu(
+        &mut self,
+        opcode: BaseAluOpcode,
+        in1: Address<F>,
+        in2: Address<F>,
+    ) -> Address<F> {
+        self.use_addr(in1);
+        self.use_addr(in2);
+        let out = self.alloc();
+        self.instructions.push(Instruction::BaseAlu(BaseAluInstr {
+            opcode,
+            mult: F::zero(),
+            addrs: BaseAluIo { out, in1, in2 },
+            flag_addr: Address(F::zero()),
+            flag_mult: F::zero(),
+        }));
+        out
+    }
+
+    pub fn add(&mut self, in1: Address<F>, in2: Address<F>) -> Address<F> {
+        self.base_alu(BaseAluOpcode::AddF, in1, in2)
+    }
+
+    pub fn sub(&mut self, in1: Address<F>, in2: Address<F>) -> Address<F> {
+        self.base_alu(BaseAluOpcode::SubF, in1, in2)
+    }
+
+    pub fn mul(&mut self, in1: Address<F>, in2: Address<F>) -> Address<F> {
+        self.base_alu(BaseAluOpcode::MulF, in1, in2)
+    }
+
+    /// The program, with the multiplicity of each write set to the number of reads of its address.
+    pub fn build(mut self) -> RecursionProgram<F> {
+        for (index, reads) in self.writes {
+            let mult = F::from_canonical_u32(reads);
+            match &mut self.instructions[index] {
+                Instruction::Mem(instr) => instr.mult = mult,
+                Instruction::BaseAlu(instr) => instr.mult = mult,
+                _ => unreachable!("only memory and ALU instructions allocate addresses"),
+            }
+        }
+        RecursionProgram { instructions: self.instructions, ..Default::default() }
+    }
+
+    /// Allocates the address written by the next instruction.
+    fn alloc(&mut self) -> Address<F> {
+        let addr = Address(F::from_canonical_usize(self.writes.len()));
+        self.writes.push((self.instructions.len(), 0));
+        addr
+    }
+
+    fn use_addr(&mut self, addr: Address<F>) {
+        let (_, reads) = self
+            .writes
+            .get_mut(addr.as_usize())
+            .unwrap_or_else(|| panic!("address {} was not allocated", addr.as_usize()));
+        *reads += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use p3_baby_bear::BabyBear;
+    use p3_field::AbstractField;
+
+    use super::*;
+    use crate::machine::tests::run_recursion_test_machines;
+
+    type F = BabyBear;
+
+    #[test]
+    pub fn fibonacci() {
+        let mut builder = ProgramBuilder::<F>::new();
+        let (mut a, mut b) = (builder.write(F::zero()), builder.write(F::one()));
+        for _ in 2..=10 {
+            (a, b) = (b, builder.add(a, b));
+        }
+        builder.read(a, F::from_canonical_u32(34));
+        builder.read(b, F::from_canonical_u32(55));
+
+        run_recursion_test_machines(builder.build());
+    }
+
+    #[test]
+    #[should_panic = "was not allocated"]
+    pub fn read_unallocated() {
+        let mut builder = ProgramBuilder::<F>::new();
+        builder.write(F::one());
+        builder.read(Address(F::one()), F::one());
+    }
+}
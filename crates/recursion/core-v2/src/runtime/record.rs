@@ -1,8 +1,9 @@
-use std::{array, sync::Arc};
+use std::{array, iter::zip, ops::AddAssign, sync::Arc};
 
 use p3_field::{AbstractField, PrimeField32};
 use sp1_recursion_core::air::RecursionPublicValues;
 use sp1_stark::{MachineRecord, SP1CoreOpts, PROOF_MAX_NUM_PVS};
+use thiserror::Error;
 
 // TODO expand glob imports
 use crate::*;
@@ -24,6 +25,37 @@ pub struct ExecutionRecord<F> {
     pub exp_reverse_bits_len_events: Vec<ExpReverseBitsEvent<F>>,
     pub fri_fold_events: Vec<FriFoldEvent<F>>,
     pub commit_pv_hash_events: Vec<CommitPublicValuesEvent<F>>,
+
+    /// The events a dry run counted without recording them, see [`Runtime::dry_run`]. Constant
+    /// memory accesses are always only counted, in `mem_const_count`.
+    pub unrecorded_events: EventCounts,
+}
+
+/// The number of events of each kind in an [`ExecutionRecord`], see
+/// [`ExecutionRecord::event_counts`].
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq)]
+pub struct EventCounts {
+    pub base_alu: usize,
+    pub ext_alu: usize,
+    pub mem_const: usize,
+    pub mem_var: usize,
+    pub poseidon2: usize,
+    pub exp_reverse_bits_len: usize,
+    pub fri_fold: usize,
+    pub commit_pv_hash: usize,
+}
+
+impl AddAssign for EventCounts {
+    fn add_assign(&mut self, rhs: Self) {
+        self.base_alu += rhs.base_alu;
+        self.ext_alu += rhs.ext_alu;
+        self.mem_const += rhs.mem_const;
+        self.mem_var += rhs.mem_var;
+        self.poseidon2 += rhs.poseidon2;
+        self.exp_reverse_bits_len += rhs.exp_reverse_bits_len;
+        self.fri_fold += rhs.fri_fold;
+        self.commit_pv_hash += rhs.commit_pv_hash;
+    }
 }
 
 impl<F: PrimeField32> MachineRecord for ExecutionRecord<F> {
@@ -47,6 +79,7 @@ impl<F: PrimeField32> MachineRecord for ExecutionRecord<F> {
             exp_reverse_bits_len_events,
             fri_fold_events,
             commit_pv_hash_events,
+            unrecorded_events,
         } = self;
         base_alu_events.append(&mut other.base_alu_events);
         ext_alu_events.append(&mut other.ext_alu_events);
@@ -56,6 +89,7 @@ impl<F: PrimeField32> MachineRecord for ExecutionRecord<F> {
         exp_reverse_bits_len_events.append(&mut other.exp_reverse_bits_len_events);
         fri_fold_events.append(&mut other.fri_fold_events);
         commit_pv_hash_events.append(&mut other.commit_pv_hash_events);
+        *unrecorded_events += other.unrecorded_events;
     }
 
     fn public_values<T: AbstractField>(&self) -> Vec<T> {
@@ -72,3 +106,361 @@ impl<F: PrimeField32> MachineRecord for ExecutionRecord<F> {
         ret.to_vec()
     }
 }
+
+/// An inconsistency between an [`ExecutionRecord`] and its program, found by
+/// [`ExecutionRecord::check_invariants`].
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum RecordError {
+    #[error("the record has {actual} {kind}, but executing the program produces {expected}")]
+    CountMismatch { kind: &'static str, expected: usize, actual: usize },
+    #[error(
+        "exp-reverse-bits-len event {index} has {actual} exponent bits, but its instruction has \
+        {expected}"
+    )]
+    ExpLenMismatch { index: usize, expected: usize, actual: usize },
+}
+
+impl<F> ExecutionRecord<F> {
+    /// Returns the number of events of each kind, whether they were recorded or only counted by a
+    /// dry run.
+    pub fn event_counts(&self) -> EventCounts {
+        let mut counts = EventCounts {
+            base_alu: self.base_alu_events.len(),
+            ext_alu: self.ext_alu_events.len(),
+            mem_const: self.mem_const_count,
+            mem_var: self.mem_var_events.len(),
+            poseidon2: self.poseidon2_events.len(),
+            exp_reverse_bits_len: self.exp_reverse_bits_len_events.len(),
+            fri_fold: self.fri_fold_events.len(),
+            commit_pv_hash: self.commit_pv_hash_events.len(),
+        };
+        counts += self.unrecorded_events;
+        counts
+    }
+}
+
+impl<F: PrimeField32> ExecutionRecord<F> {
+    /// Checks that the events of the record are those that running its program to the end
+    /// produces: every instruction runs exactly once, so there is one event of each kind per
+    /// instruction of its kind (one per output for hints, and one per opening for FRI folds).
+    ///
+    /// Catches inconsistencies that would otherwise only surface as a failed proof. It only
+    /// applies to a record of a complete run, or to records appended after setting the program to
+    /// the concatenated program.
+    pub fn check_invariants(&self) -> Result<(), RecordError> {
+        let (mut base_alu, mut ext_alu, mut mem_const, mut mem_var) = (0, 0, 0, 0);
+        let (mut poseidon2, mut fri_fold, mut commit_pv_hash) = (0, 0, 0);
+        let mut exp_lens = Vec::new();
+        for instruction in &self.program.instructions {
+            match instruction {
+                Instruction::BaseAlu(_) => base_alu += 1,
+                Instruction::ExtAlu(_) => ext_alu += 1,
+                Instruction::Mem(_) => mem_const += 1,
+                Instruction::Poseidon2(_) => poseidon2 += 1,
+                Instruction::ExpReverseBitsLen(instr) => exp_lens.push(instr.addrs.exp.len()),
+                Instruction::FriFold(instr) => fri_fold += instr.ext_vec_addrs.ps_at_z.len(),
+                Instruction::CommitPublicValues(_) => commit_pv_hash += 1,
+                Instruction::HintBits(instruction::HintBitsInstr {
+                    output_addrs_mults, ..
+                })
+                | Instruction::Hint(instruction::HintInstr { output_addrs_mults }) => {
+                    mem_var += output_addrs_mults.len()
+                }
+                Instruction::HintExt2Felts(_) => mem_var += D,
+                Instruction::Print(_) => {}
+            }
+        }
+
+        let actual = self.event_counts();
+        let counts = [
+            ("base ALU events", base_alu, actual.base_alu),
+            ("ext ALU events", ext_alu, actual.ext_alu),
+            ("constant memory accesses", mem_const, actual.mem_const),
+            ("variable memory events", mem_var, actual.mem_var),
+            ("Poseidon2 events", poseidon2, actual.poseidon2),
+            ("exp-reverse-bits-len events", exp_lens.len(), actual.exp_reverse_bits_len),
+            ("FRI fold events", fri_fold, actual.fri_fold),
+            ("public values commitment events", commit_pv_hash, actual.commit_pv_hash),
+        ];
+        for (kind, expected, actual) in counts {
+            if expected != actual {
+                return Err(RecordError::CountMismatch { kind, expected, actual });
+            }
+        }
+
+        for (index, (&expected, event)) in
+            zip(&exp_lens, &self.exp_reverse_bits_len_events).enumerate()
+        {
+            if event.exp.len() != expected {
+                return Err(RecordError::ExpLenMismatch {
+                    index,
+                    expected,
+                    actual: event.exp.len(),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Appends the events of `other` after those of `self`, as if the programs the two records were
+    /// executed from had run one after the other. The program, shard index and public values of
+    /// `self` are kept.
+    ///
+    /// Trace generation only depends on the order of the events of each kind, so proving the merged
+    /// record is the same as proving the record of the concatenated program, provided the merged
+    /// record's program is set to that concatenated program.
+    pub fn append(&mut self, mut other: Self) {
+        MachineRecord::append(self, &mut other);
+    }
+}
+
+/// The number of events printed at each end of an event list by
+/// [`ExecutionRecord::dump_summary`].
+#[cfg(feature = "debug-record")]
+const DUMP_EDGE_EVENTS: usize = 3;
+
+#[cfg(feature = "debug-record")]
+impl<F: std::fmt::Debug> ExecutionRecord<F> {
+    /// Writes the number of events for each chip, along with the first and last few events, to
+    /// `w`. Useful to see what the runtime produced when a proof fails.
+    pub fn dump_summary(&self, w: &mut impl std::io::Write) -> std::io::Result<()> {
+        // Exhaustive destructuring so that new event kinds are not forgotten.
+        let Self {
+            program,
+            index,
+            base_alu_events,
+            ext_alu_events,
+            mem_const_count,
+            mem_var_events,
+            public_values: _,
+            poseidon2_events,
+            exp_reverse_bits_len_events,
+            fri_fold_events,
+            commit_pv_hash_events,
+            unrecorded_events,
+        } = self;
+        writeln!(w, "shard {index}: {} instructions", program.instructions.len())?;
+        writeln!(w, "mem_const_count: {mem_const_count}")?;
+        if *unrecorded_events != EventCounts::default() {
+            writeln!(w, "unrecorded_events: {unrecorded_events:?}")?;
+        }
+        dump_events(w, "mem_var_events", mem_var_events)?;
+        dump_events(w, "base_alu_events", base_alu_events)?;
+        dump_events(w, "ext_alu_events", ext_alu_events)?;
+        dump_events(w, "poseidon2_events", poseidon2_events)?;
+        dump_events(w, "exp_reverse_bits_len_events", exp_reverse_bits_len_events)?;
+        dump_events(w, "fri_fold_events", fri_fold_events)?;
+        dump_events(w, "commit_pv_hash_events", commit_pv_hash_events)
+    }
+}
+
+/// A builder for [`ExecutionRecord`], for setting up records in tests.
+///
+/// Fields that are not set keep their default value.
+#[cfg(any(test, feature = "test-util"))]
+#[derive(Clone, Default, Debug)]
+pub struct ExecutionRecordBuilder<F> {
+    record: ExecutionRecord<F>,
+}
+
+#[cfg(any(test, feature = "test-util"))]
+impl<F: Default> ExecutionRecord<F> {
+    /// Create a new record builder. See [`ExecutionRecordBuilder`] for more details.
+    #[must_use]
+    pub fn builder() -> ExecutionRecordBuilder<F> {
+        ExecutionRecordBuilder::default()
+    }
+}
+
+#[cfg(any(test, feature = "test-util"))]
+impl<F: Default> ExecutionRecordBuilder<F> {
+    /// Build and return the [`ExecutionRecord`].
+    ///
+    /// Clears and resets the builder, allowing it to be reused.
+    pub fn build(&mut self) -> ExecutionRecord<F> {
+        core::mem::take(&mut self.record)
+    }
+
+    /// Set the program the record was executed from.
+    pub fn program(&mut self, program: Arc<RecursionProgram<F>>) -> &mut Self {
+        self.record.program = program;
+        self
+    }
+
+    /// Set the index of the shard.
+    pub fn index(&mut self, index: u32) -> &mut Self {
+        self.record.index = index;
+        self
+    }
+
+    /// Set the base field ALU events.
+    pub fn base_alu(&mut self, events: Vec<BaseAluEvent<F>>) -> &mut Self {
+        self.record.base_alu_events = events;
+        self
+    }
+
+    /// Set the extension field ALU events.
+    pub fn ext_alu(&mut self, events: Vec<ExtAluEvent<F>>) -> &mut Self {
+        self.record.ext_alu_events = events;
+        self
+    }
+
+    /// Set the number of constant memory accesses.
+    pub fn mem_const_count(&mut self, count: usize) -> &mut Self {
+        self.record.mem_const_count = count;
+        self
+    }
+
+    /// Set the variable memory events.
+    pub fn mem_var(&mut self, events: Vec<MemEvent<F>>) -> &mut Self {
+        self.record.mem_var_events = events;
+        self
+    }
+
+    /// Set the public values.
+    pub fn public_values(&mut self, public_values: RecursionPublicValues<F>) -> &mut Self {
+        self.record.public_values = public_values;
+        self
+    }
+
+    /// Set the Poseidon2 events.
+    pub fn poseidon2(&mut self, events: Vec<Poseidon2Event<F>>) -> &mut Self {
+        self.record.poseidon2_events = events;
+        self
+    }
+
+    /// Set the exp-reverse-bits-len events.
+    pub fn exp_reverse_bits_len(&mut self, events: Vec<ExpReverseBitsEvent<F>>) -> &mut Self {
+        self.record.exp_reverse_bits_len_events = events;
+        self
+    }
+
+    /// Set the FRI fold events.
+    pub fn fri_fold(&mut self, events: Vec<FriFoldEvent<F>>) -> &mut Self {
+        self.record.fri_fold_events = events;
+        self
+    }
+
+    /// Set the public values commitment events.
+    pub fn commit_pv_hash(&mut self, events: Vec<CommitPublicValuesEvent<F>>) -> &mut Self {
+        self.record.commit_pv_hash_events = events;
+        self
+    }
+}
+
+#[cfg(feature = "debug-record")]
+fn dump_events<T: std::fmt::Debug>(
+    w: &mut impl std::io::Write,
+    name: &str,
+    events: &[T],
+) -> std::io::Result<()> {
+    writeln!(w, "{name}: {}", events.len())?;
+    if events.len() <= 2 * DUMP_EDGE_EVENTS {
+        for event in events {
+            writeln!(w, "    {event:?}")?;
+        }
+    } else {
+        for event in &events[..DUMP_EDGE_EVENTS] {
+            writeln!(w, "    {event:?}")?;
+        }
+        writeln!(w, "    ... {} more", events.len() - 2 * DUMP_EDGE_EVENTS)?;
+        for event in &events[events.len() - DUMP_EDGE_EVENTS..] {
+            writeln!(w, "    {event:?}")?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use p3_baby_bear::{BabyBear, DiffusionMatrixBabyBear};
+    use p3_field::{extension::BinomialExtensionField, AbstractField};
+    use sp1_stark::baby_bear_poseidon2::BabyBearPoseidon2;
+
+    use crate::{
+        machine::tests::fibonacci_instructions, runtime::instruction as instr, Address,
+        ExecutionRecord, ExpReverseBitsEvent, RecordError, RecursionProgram, Runtime,
+    };
+
+    #[test]
+    fn check_invariants() {
+        let program =
+            RecursionProgram { instructions: fibonacci_instructions(), ..Default::default() };
+        let mut runtime = Runtime::<
+            BabyBear,
+            BinomialExtensionField<BabyBear, 4>,
+            DiffusionMatrixBabyBear,
+        >::new(Arc::new(program), BabyBearPoseidon2::new().perm);
+        runtime.run().unwrap();
+        let record = runtime.record;
+        assert_eq!(record.check_invariants(), Ok(()));
+
+        let mut corrupted = record.clone();
+        corrupted.mem_const_count += 1;
+        assert_eq!(
+            corrupted.check_invariants(),
+            Err(RecordError::CountMismatch {
+                kind: "constant memory accesses",
+                expected: 4,
+                actual: 5
+            })
+        );
+
+        let mut corrupted = record;
+        corrupted.base_alu_events.pop();
+        assert_eq!(
+            corrupted.check_invariants(),
+            Err(RecordError::CountMismatch { kind: "base ALU events", expected: 9, actual: 8 })
+        );
+    }
+
+    #[test]
+    fn check_invariants_exp_len() {
+        let one = BabyBear::one();
+        let program = RecursionProgram {
+            instructions: vec![instr::exp_reverse_bits_len(
+                1,
+                Address(one),
+                Address(one),
+                &[Address(one); 3],
+            )],
+            ..Default::default()
+        };
+        let record = ExecutionRecord::builder()
+            .program(Arc::new(program))
+            .exp_reverse_bits_len(vec![ExpReverseBitsEvent {
+                base: one,
+                exp: vec![one; 2],
+                result: one,
+            }])
+            .build();
+        assert_eq!(
+            record.check_invariants(),
+            Err(RecordError::ExpLenMismatch { index: 0, expected: 3, actual: 2 })
+        );
+    }
+
+    #[cfg(feature = "debug-record")]
+    #[test]
+    fn dump_summary() {
+        use crate::BaseAluIo;
+
+        let event = BaseAluIo { out: BabyBear::one(), in1: BabyBear::one(), in2: BabyBear::one() };
+        let record = ExecutionRecord {
+            base_alu_events: vec![event; 10],
+            mem_const_count: 4,
+            ..Default::default()
+        };
+
+        let mut out = Vec::new();
+        record.dump_summary(&mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+
+        assert!(out.contains("mem_const_count: 4"));
+        assert!(out.contains("base_alu_events: 10"));
+        assert!(out.contains("... 4 more"));
+        assert!(out.contains("fri_fold_events: 0"));
+    }
+}
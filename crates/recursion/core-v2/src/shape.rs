@@ -0,0 +1,178 @@
+use hashbrown::HashMap;
+use serde::{Deserialize, Serialize};
+
+/// The shape of a recursion machine: the log2 height of each chip's trace, keyed by chip name.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecursionShape {
+    pub inner: HashMap<String, usize>,
+}
+
+/// A chip whose trace does not fit in a [`RecursionShape`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShapeViolation {
+    /// The name of the chip.
+    pub chip: String,
+    /// The height of the chip's trace.
+    pub height: usize,
+    /// The log2 height the shape allows for the chip, or `None` if the shape has no entry for it.
+    pub log_height: Option<usize>,
+}
+
+impl RecursionShape {
+    /// Checks that traces of the given heights, keyed by chip name, fit in this shape, and
+    /// returns every chip that does not otherwise. A chip missing from the shape only fits if its
+    /// trace is empty.
+    pub fn check_fits(&self, heights: &[(String, usize)]) -> Result<(), Vec<ShapeViolation>> {
+        let violations = heights
+            .iter()
+            .filter_map(|(chip, height)| {
+                let log_height = self.inner.get(chip).copied();
+                let fits = log_height.map_or(*height == 0, |log_height| *height <= 1 << log_height);
+                (!fits).then(|| ShapeViolation { chip: chip.clone(), height: *height, log_height })
+            })
+            .collect::<Vec<_>>();
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(violations)
+        }
+    }
+
+    /// Lists every chip whose log2 height differs between this shape and `other`, as
+    /// `(chip, self_log_height, other_log_height)` sorted by chip name. A chip present in only one
+    /// of the shapes has `None` for the other.
+    ///
+    /// An empty result means the shapes are equal, so this can guard a pinned shape against drift.
+    pub fn diff(&self, other: &RecursionShape) -> Vec<(String, Option<usize>, Option<usize>)> {
+        let mut chips = self.inner.keys().chain(other.inner.keys()).collect::<Vec<_>>();
+        chips.sort_unstable();
+        chips.dedup();
+        chips
+            .into_iter()
+            .filter_map(|chip| {
+                let (ours, theirs) =
+                    (self.inner.get(chip).copied(), other.inner.get(chip).copied());
+                (ours != theirs).then(|| (chip.clone(), ours, theirs))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use hashbrown::HashMap;
+    use p3_baby_bear::DiffusionMatrixBabyBear;
+    use p3_matrix::Matrix;
+    use sp1_stark::{air::MachineAir, baby_bear_poseidon2::BabyBearPoseidon2, StarkGenericConfig};
+
+    use super::{RecursionShape, ShapeViolation};
+    use crate::{
+        machine::{tests::fibonacci_instructions, RecursionAir},
+        runtime::instruction as instr,
+        BaseAluOpcode, ExecutionRecord, RecursionProgram, Runtime,
+    };
+
+    type SC = BabyBearPoseidon2;
+    type F = <SC as StarkGenericConfig>::Val;
+    type EF = <SC as StarkGenericConfig>::Challenge;
+
+    #[test]
+    fn json_round_trip() {
+        let shape = RecursionShape {
+            inner: HashMap::from([
+                ("MemoryConst".to_string(), 16),
+                ("MemoryVar".to_string(), 18),
+                ("Poseidon2Wide".to_string(), 16),
+            ]),
+        };
+        let json = serde_json::to_string(&shape).unwrap();
+        let decoded: RecursionShape = serde_json::from_str(&json).unwrap();
+        assert_eq!(shape, decoded);
+    }
+
+    #[test]
+    fn diff() {
+        let shape = RecursionShape {
+            inner: HashMap::from([
+                ("BaseAlu".to_string(), 16),
+                ("MemoryConst".to_string(), 16),
+                ("MemoryVar".to_string(), 18),
+            ]),
+        };
+        assert!(shape.diff(&shape.clone()).is_empty());
+
+        let mut modified = shape.clone();
+        modified.inner.insert("MemoryVar".to_string(), 19);
+        modified.inner.remove("BaseAlu");
+        modified.inner.insert("FriFold".to_string(), 10);
+        assert_eq!(
+            shape.diff(&modified),
+            [
+                ("BaseAlu".to_string(), Some(16), None),
+                ("FriFold".to_string(), None, Some(10)),
+                ("MemoryVar".to_string(), Some(18), Some(19)),
+            ]
+        );
+        assert_eq!(modified.diff(&shape)[0], ("BaseAlu".to_string(), None, Some(16)));
+    }
+
+    #[test]
+    fn check_fits() {
+        type A = RecursionAir<F, 3, 0>;
+
+        let heights = |program: &Arc<RecursionProgram<F>>| {
+            let mut runtime =
+                Runtime::<F, EF, DiffusionMatrixBabyBear>::new(program.clone(), SC::new().perm);
+            runtime.run().unwrap();
+            A::get_all_wide()
+                .into_iter()
+                .map(|chip| {
+                    let trace =
+                        chip.generate_trace(&runtime.record, &mut ExecutionRecord::default());
+                    (chip.name(), trace.height())
+                })
+                .collect::<Vec<_>>()
+        };
+
+        // The executed traces of a program fit the shrink shape derived from its preprocessed
+        // traces.
+        let program = Arc::new(RecursionProgram {
+            instructions: fibonacci_instructions(),
+            ..Default::default()
+        });
+        let shape = A::shrink_shape(&program);
+        let fits = heights(&program);
+        assert_eq!(shape.check_fits(&fits), Ok(()));
+
+        // A larger program overflows the base ALU of that shape, and only it.
+        let mut instructions = fibonacci_instructions();
+        instructions
+            .extend((0..1024).map(|i| instr::base_alu(BaseAluOpcode::AddF, 0, 100 + i, 0, 1)));
+        let larger = heights(&Arc::new(RecursionProgram { instructions, ..Default::default() }));
+        let base_alu = larger.iter().find(|(chip, _)| chip == "BaseAlu").unwrap().1;
+        assert!(base_alu > 1 << shape.inner["BaseAlu"]);
+        assert_eq!(
+            shape.check_fits(&larger).unwrap_err(),
+            [ShapeViolation {
+                chip: "BaseAlu".to_string(),
+                height: base_alu,
+                log_height: Some(shape.inner["BaseAlu"]),
+            }]
+        );
+
+        // A chip missing from the shape only fits if its trace is empty.
+        let mut missing = shape.clone();
+        missing.inner.remove("MemoryConst");
+        let mem_const = fits.iter().find(|(chip, _)| chip == "MemoryConst").unwrap().1;
+        assert_eq!(
+            missing.check_fits(&fits).unwrap_err(),
+            [ShapeViolation {
+                chip: "MemoryConst".to_string(),
+                height: mem_const,
+                log_height: None
+            }]
+        );
+    }
+}
@@ -4,7 +4,7 @@ use serde::{Deserialize, Serialize};
 use sp1_derive::AlignedBorrow;
 use sp1_stark::air::{BinomialExtension, ExtensionAirBuilder, SP1AirBuilder};
 
-use std::ops::{Index, IndexMut};
+use std::ops::{Add, Index, IndexMut, Mul};
 
 use crate::runtime::D;
 
@@ -46,7 +46,20 @@ impl<T> Block<T> {
     }
 }
 
+impl<T: AbstractField> Block<T> {
+    /// The block holding `value` in its first slot and zeros elsewhere, i.e. the embedding of a
+    /// base field element into the extension field.
+    pub fn from_base(value: T) -> Self {
+        Self::from(value)
+    }
+}
+
 impl<T: Clone> Block<T> {
+    /// The block holding `value` in every slot.
+    pub fn splat(value: T) -> Self {
+        Self(core::array::from_fn(|_| value.clone()))
+    }
+
     pub fn as_extension<AB: ExtensionAirBuilder<Var = T>>(&self) -> BinomialExtension<AB::Expr> {
         let arr: [AB::Expr; 4] = self.0.clone().map(|x| AB::Expr::zero() + x);
         BinomialExtension(arr)
@@ -82,6 +95,25 @@ impl<T: Copy> From<&[T]> for Block<T> {
     }
 }
 
+impl<T: Add<U>, U> Add<Block<U>> for Block<T> {
+    type Output = Block<T::Output>;
+
+    /// Elementwise addition, which is also addition in the extension field.
+    fn add(self, rhs: Block<U>) -> Self::Output {
+        let mut rhs = rhs.0.into_iter();
+        self.map(|l| l + rhs.next().unwrap())
+    }
+}
+
+impl<T: Mul<U>, U: Clone> Mul<U> for Block<T> {
+    type Output = Block<T::Output>;
+
+    /// Multiplication of every slot by the scalar `rhs`.
+    fn mul(self, rhs: U) -> Self::Output {
+        self.map(|l| l * rhs.clone())
+    }
+}
+
 impl<T, I> Index<I> for Block<T>
 where
     [T]: Index<I>,
@@ -112,3 +144,42 @@ impl<T> IntoIterator for Block<T> {
         self.0.into_iter()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use p3_baby_bear::BabyBear;
+    use p3_field::{
+        extension::BinomialExtensionField, AbstractExtensionField, AbstractField, PrimeField32,
+    };
+
+    use super::Block;
+
+    type F = BabyBear;
+    type EF = BinomialExtensionField<F, 4>;
+
+    fn block(values: [u32; 4]) -> Block<F> {
+        Block(values.map(F::from_canonical_u32))
+    }
+
+    #[test]
+    fn from_base_and_splat() {
+        let f = F::from_canonical_u32(7);
+        assert_eq!(Block::from_base(f), block([7, 0, 0, 0]));
+        assert_eq!(Block::from_base(f).ext::<EF>(), EF::from_base(f));
+        assert_eq!(Block::splat(f), block([7, 7, 7, 7]));
+    }
+
+    #[test]
+    fn add() {
+        let (a, b) = (block([1, 2, 3, 4]), block([10, 20, 30, F::ORDER_U32 - 1]));
+        assert_eq!(a + b, block([11, 22, 33, 3]));
+        assert_eq!((a + b).ext::<EF>(), a.ext::<EF>() + b.ext::<EF>());
+    }
+
+    #[test]
+    fn scalar_mul() {
+        let (a, s) = (block([1, 2, 3, 4]), F::from_canonical_u32(5));
+        assert_eq!(a * s, block([5, 10, 15, 20]));
+        assert_eq!((a * s).ext::<EF>(), a.ext::<EF>() * s);
+    }
+}
@@ -42,6 +42,33 @@ impl<F: PrimeField32 + BinomiallyExtendable<D>, const DEGREE: usize> RecursionAi
         StarkMachine::new(config, chips, PROOF_MAX_NUM_PVS)
     }
 
+    /// The machine of the compress stage, which proves the recursion programs that lift core shard
+    /// proofs into recursion proofs and that compress a proof of many shards into one.
+    ///
+    /// It has the chips of [`Self::machine`]: the program, CPU, global memory, wide Poseidon2, FRI
+    /// fold, range check and `ExpReverseBitsLen` chips, all with dynamic trace sizes.
+    pub fn compress_machine<SC: StarkGenericConfig<Val = F>>(config: SC) -> StarkMachine<SC, Self> {
+        Self::machine(config)
+    }
+
+    /// The machine of the combine stage, which proves the recursion programs that verify a batch of
+    /// recursion proofs and reduce them to one.
+    ///
+    /// Combining verifies the same kind of proofs as compressing, so it uses the same chips, see
+    /// [`Self::compress_machine`]. Its verifying keys are interchangeable with those of the
+    /// compress machine for the same program.
+    pub fn combine_machine<SC: StarkGenericConfig<Val = F>>(config: SC) -> StarkMachine<SC, Self> {
+        Self::compress_machine(config)
+    }
+
+    /// The machine of the shrink stage, which reproves the compressed proof with a larger FRI
+    /// blowup so that it is cheaper to verify in the wrap stage.
+    ///
+    /// It has the chips of [`Self::wrap_machine_dyn`], with dynamic trace sizes.
+    pub fn shrink_machine<SC: StarkGenericConfig<Val = F>>(config: SC) -> StarkMachine<SC, Self> {
+        Self::wrap_machine_dyn(config)
+    }
+
     /// A recursion machine with fixed trace sizes tuned to work specifically for the wrap layer.
     pub fn wrap_machine<SC: StarkGenericConfig<Val = F>>(config: SC) -> StarkMachine<SC, Self> {
         let chips = Self::get_wrap_all().into_iter().map(Chip::new).collect::<Vec<_>>();
@@ -105,3 +132,64 @@ impl<F: PrimeField32 + BinomiallyExtendable<D>, const DEGREE: usize> RecursionAi
             .collect()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use p3_field::{AbstractField, PrimeField32};
+    use sp1_core_machine::utils::run_test_machine;
+    use sp1_stark::{baby_bear_poseidon2::BabyBearPoseidon2, StarkGenericConfig};
+
+    use super::RecursionAirWideDeg3;
+    use crate::runtime::{Instruction, Opcode, RecursionProgram, Runtime};
+
+    type SC = BabyBearPoseidon2;
+    type F = <SC as StarkGenericConfig>::Val;
+    type EF = <SC as StarkGenericConfig>::Challenge;
+
+    #[test]
+    fn combine_machine() {
+        let add = |a: u32, b: [F; 4], c: u32, imm_b| {
+            Instruction::new(
+                Opcode::ADD,
+                F::from_canonical_u32(a),
+                b,
+                [F::from_canonical_u32(c), F::zero(), F::zero(), F::zero()],
+                F::zero(),
+                F::zero(),
+                imm_b,
+                true,
+                String::new(),
+            )
+        };
+        // Computes 1 + 2 + 3 + 4 on the stack, then halts.
+        let addr = |addr: u32| [F::from_canonical_u32(addr), F::zero(), F::zero(), F::zero()];
+        let program = RecursionProgram {
+            traces: vec![],
+            instructions: vec![
+                add(1, [F::one(), F::zero(), F::zero(), F::zero()], 2, true),
+                add(2, addr(1), 3, false),
+                add(3, addr(2), 4, false),
+                Instruction::new(
+                    Opcode::HALT,
+                    F::zero(),
+                    [F::zero(); 4],
+                    [F::zero(); 4],
+                    F::zero(),
+                    F::zero(),
+                    true,
+                    true,
+                    String::new(),
+                ),
+            ],
+        };
+
+        let machine = RecursionAirWideDeg3::combine_machine(SC::default());
+        let mut runtime = Runtime::<F, EF, _>::new(&program, machine.config().perm.clone());
+        runtime.run().unwrap();
+        let sum = (runtime.fp + F::from_canonical_u32(3)).as_canonical_u32() as usize;
+        assert_eq!(runtime.memory[&sum].value[0], F::from_canonical_u32(10));
+
+        let (pk, vk) = machine.setup(&program);
+        run_test_machine(vec![runtime.record], machine, pk, vk).unwrap();
+    }
+}
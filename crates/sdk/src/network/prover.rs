@@ -164,12 +164,19 @@ fn warn_if_not_default(opts: &SP1ProverOpts, context: &SP1Context) {
         tracing::warn!("custom SP1ProverOpts are currently unsupported by the network prover");
     }
     // Exhaustive match is done to ensure we update the warnings if the types change.
-    let SP1Context { hook_registry, subproof_verifier, .. } = context;
+    let SP1Context { hook_registry, syscall_registry, subproof_verifier, .. } = context;
     if hook_registry.is_some() {
         tracing::warn!("non-default context.hook_registry will be ignored: {:?}", hook_registry);
         tracing::warn!("custom runtime hooks are currently unsupported by the network prover");
         tracing::warn!("proving may fail due to missing hooks");
     }
+    if syscall_registry.is_some() {
+        tracing::warn!(
+            "non-default context.syscall_registry will be ignored: {:?}",
+            syscall_registry
+        );
+        tracing::warn!("custom syscalls are currently unsupported by the network prover");
+    }
     if subproof_verifier.is_some() {
         tracing::warn!("non-default context.subproof_verifier will be ignored");
         tracing::warn!("custom subproof verifiers are currently unsupported by the network prover");
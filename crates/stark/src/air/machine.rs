@@ -41,6 +41,14 @@ pub trait MachineAir<F: Field>: BaseAir<F> + 'static + Send + Sync {
     fn generate_preprocessed_trace(&self, _program: &Self::Program) -> Option<RowMajorMatrix<F>> {
         None
     }
+
+    /// Whether generating the permutation trace can skip the interactions with zero multiplicity.
+    ///
+    /// This pays off for chips whose padding rows, usually most of the trace, only have such
+    /// interactions, like the memory chips.
+    fn skip_zero_multiplicity(&self) -> bool {
+        false
+    }
 }
 
 /// A program that defines the control flow of a machine through a program counter.
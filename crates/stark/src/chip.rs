@@ -112,6 +112,7 @@ where
     ) -> RowMajorMatrix<EF>
     where
         F: PrimeField,
+        A: MachineAir<F>,
     {
         let batch_size = self.logup_batch_size();
         generate_permutation_trace(
@@ -121,6 +122,7 @@ where
             main,
             random_elements,
             batch_size,
+            <A as MachineAir<F>>::skip_zero_multiplicity(&self.air),
         )
     }
 
@@ -195,6 +197,10 @@ where
     fn included(&self, shard: &Self::Record) -> bool {
         self.air.included(shard)
     }
+
+    fn skip_zero_multiplicity(&self) -> bool {
+        self.air.skip_zero_multiplicity()
+    }
 }
 
 // Implement AIR directly on Chip, evaluating both execution and permutation constraints.
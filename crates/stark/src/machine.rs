@@ -41,6 +41,13 @@ impl<SC: StarkGenericConfig, A> StarkMachine<SC, A> {
     pub const fn new(config: SC, chips: Vec<Chip<Val<SC>, A>>, num_pv_elts: usize) -> Self {
         Self { config, chips, num_pv_elts }
     }
+
+    /// Replaces the number of public values elements the machine observes.
+    #[must_use]
+    pub fn with_num_pv_elts(mut self, num_pv_elts: usize) -> Self {
+        self.num_pv_elts = num_pv_elts;
+        self
+    }
 }
 
 /// A proving key for a STARK.
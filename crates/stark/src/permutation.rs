@@ -17,6 +17,9 @@ pub const fn permutation_trace_width(num_interactions: usize, batch_size: usize)
 }
 
 /// Populates a permutation row.
+///
+/// If `skip_zero_multiplicity` is set, interactions whose multiplicity is zero on this row are left
+/// out of their batch sum instead of having their fingerprint inverted.
 #[inline]
 #[allow(clippy::too_many_arguments)]
 #[allow(clippy::needless_pass_by_value)]
@@ -29,6 +32,7 @@ pub fn populate_permutation_row<F: PrimeField, EF: ExtensionField<F>>(
     alpha: EF,
     betas: Powers<EF>,
     batch_size: usize,
+    skip_zero_multiplicity: bool,
 ) {
     let interaction_chunks = &sends
         .iter()
@@ -40,7 +44,16 @@ pub fn populate_permutation_row<F: PrimeField, EF: ExtensionField<F>>(
     for (value, chunk) in row.iter_mut().zip(interaction_chunks) {
         *value = chunk
             .into_iter()
-            .map(|(interaction, is_send)| {
+            .filter_map(|(interaction, is_send)| {
+                let mut mult = interaction.multiplicity.apply::<F, F>(preprocessed_row, main_row);
+
+                // An interaction with zero multiplicity contributes nothing to the sum, so its
+                // fingerprint and the inversion can be skipped. The constraints are the same, and
+                // the value is the one they require.
+                if skip_zero_multiplicity && mult.is_zero() {
+                    return None;
+                }
+
                 let mut denominator = alpha;
                 let mut betas = betas.clone();
                 denominator +=
@@ -48,13 +61,12 @@ pub fn populate_permutation_row<F: PrimeField, EF: ExtensionField<F>>(
                 for (columns, beta) in interaction.values.iter().zip(betas) {
                     denominator += beta * columns.apply::<F, F>(preprocessed_row, main_row);
                 }
-                let mut mult = interaction.multiplicity.apply::<F, F>(preprocessed_row, main_row);
 
                 if !is_send {
                     mult = -mult;
                 }
 
-                EF::from_base(mult) / denominator
+                Some(EF::from_base(mult) / denominator)
             })
             .sum();
     }
@@ -71,6 +83,7 @@ pub fn generate_permutation_trace<F: PrimeField, EF: ExtensionField<F>>(
     main: &RowMajorMatrix<F>,
     random_elements: &[EF],
     batch_size: usize,
+    skip_zero_multiplicity: bool,
 ) -> RowMajorMatrix<EF> {
     // Generate the RLC elements to uniquely identify each interaction.
     let alpha = random_elements[0];
@@ -109,6 +122,7 @@ pub fn generate_permutation_trace<F: PrimeField, EF: ExtensionField<F>>(
                         alpha,
                         betas.clone(),
                         batch_size,
+                        skip_zero_multiplicity,
                     );
                 });
         }
@@ -124,6 +138,7 @@ pub fn generate_permutation_trace<F: PrimeField, EF: ExtensionField<F>>(
                         alpha,
                         betas.clone(),
                         batch_size,
+                        skip_zero_multiplicity,
                     );
                 },
             );
@@ -0,0 +1,39 @@
+//! Assertions that report their failure to the host.
+
+use alloc::format;
+
+use crate::{exit::EXIT_ASSERTION_FAILED, syscalls::sys_abort};
+
+/// Asserts that a boolean expression is `true`.
+///
+/// On failure inside the zkVM, the message is prefixed with the file and line of the assertion,
+/// committed to the public values and the program halts with [`EXIT_ASSERTION_FAILED`], so
+/// the host can tell why execution stopped. On other targets this is the same as [`assert!`].
+///
+/// ### Examples
+/// ```ignore
+/// sp1_zkvm::zkvm_assert!(n > 0, "n must be positive, got {}", n);
+/// ```
+#[macro_export]
+macro_rules! zkvm_assert {
+    ($cond:expr $(,)?) => {
+        $crate::zkvm_assert!($cond, "assertion failed: {}", stringify!($cond))
+    };
+    ($cond:expr, $($arg:tt)+) => {{
+        #[cfg(target_os = "zkvm")]
+        if !$cond {
+            $crate::assert::assertion_failed(file!(), line!(), format_args!($($arg)+));
+        }
+        #[cfg(not(target_os = "zkvm"))]
+        assert!($cond, $($arg)+);
+    }};
+}
+
+/// Commits the failure location and message to the public values and halts with
+/// [`EXIT_ASSERTION_FAILED`].
+#[doc(hidden)]
+#[cold]
+pub fn assertion_failed(file: &str, line: u32, args: core::fmt::Arguments) -> ! {
+    let msg = format!("{file}:{line}: {args}");
+    unsafe { sys_abort(EXIT_ASSERTION_FAILED, msg.as_ptr(), msg.len()) }
+}
@@ -0,0 +1,9 @@
+//! The exit codes the entrypoint halts with.
+//!
+//! These match the reserved codes the executor classifies in `sp1_core_executor::syscalls::ExitCode`,
+//! as both are defined in `sp1_primitives::consts::exit`. Any other code passed to
+//! `sys_exit_group` or `io::abort` is program-defined.
+
+pub use sp1_primitives::consts::exit::{
+    EXIT_ABORT, EXIT_ASSERTION_FAILED, EXIT_OK, EXIT_OOM, EXIT_PANIC, EXIT_RAND_EXHAUSTED,
+};
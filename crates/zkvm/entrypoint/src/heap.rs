@@ -1,10 +1,13 @@
 use core::alloc::{GlobalAlloc, Layout};
 
-use crate::syscalls::sys_alloc_aligned;
+use crate::syscalls::{
+    check_heap_bounds, sys_alloc_aligned, sys_dealloc_aligned, sys_heap_peak, sys_init_heap,
+};
 
 /// A simple heap allocator.
 ///
-/// Allocates memory from left to right, without any deallocation.
+/// Allocates memory from left to right. Only the most recent allocation is ever freed, so memory
+/// is reused when allocations are dropped in the reverse order they were made.
 pub struct SimpleAlloc;
 
 unsafe impl GlobalAlloc for SimpleAlloc {
@@ -12,5 +15,59 @@ unsafe impl GlobalAlloc for SimpleAlloc {
         sys_alloc_aligned(layout.size(), layout.align())
     }
 
-    unsafe fn dealloc(&self, _: *mut u8, _: Layout) {}
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        sys_dealloc_aligned(ptr, layout.size())
+    }
+}
+
+/// A [`SimpleAlloc`] whose heap is the `size` bytes starting at a fixed `base`, for programs with a
+/// custom memory map. By default the heap spans from the end of the program image to
+/// [`MAX_MEMORY`](crate::layout::MAX_MEMORY).
+///
+/// Use it through `entrypoint!(main, heap = ArenaAlloc::with_base(base, size))`, which places the
+/// heap before `main` runs. Like the default allocator, it halts with
+/// [`EXIT_OOM`](crate::exit::EXIT_OOM) once the heap is exhausted.
+pub struct ArenaAlloc {
+    base: usize,
+    size: usize,
+}
+
+impl ArenaAlloc {
+    /// An allocator for the heap of `size` bytes starting at `base`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `base` is 0 or if the heap does not end below
+    /// [`MAX_MEMORY`](crate::layout::MAX_MEMORY). In a `const` context, this is a compile error.
+    pub const fn with_base(base: usize, size: usize) -> Self {
+        check_heap_bounds(base, size);
+        Self { base, size }
+    }
+
+    /// The peak usage of this heap so far, in bytes. See [`sys_heap_peak`].
+    ///
+    /// With the `mem-report` feature, it is also written to stderr when the program exits, which
+    /// helps pick the smallest `size` that fits.
+    pub fn peak(&self) -> usize {
+        sys_heap_peak()
+    }
+
+    /// Places the heap. Called by the `entrypoint!` macro before `main`.
+    ///
+    /// # Safety
+    ///
+    /// Same as [`sys_init_heap`].
+    pub unsafe fn init(&self) {
+        sys_init_heap(self.base, self.size);
+    }
+}
+
+unsafe impl GlobalAlloc for ArenaAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        sys_alloc_aligned(layout.size(), layout.align())
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        sys_dealloc_aligned(ptr, layout.size())
+    }
 }
@@ -0,0 +1,52 @@
+//! The memory layout of a program in the zkVM.
+//!
+//! These constants are the single source of truth for the stack setup in `_start`, the heap
+//! allocator and host tooling that inspects guest memory. They are available on every target.
+
+use core::ops::Range;
+
+/// The initial value of the stack pointer. The stack grows down from here.
+pub const STACK_TOP: u32 = 0x0020_0400;
+
+/// The address the program image is linked at, as passed to the linker with `-Ttext` by
+/// `sp1-build`. By default, the heap starts at the end of the image (the `_end` linker symbol).
+pub const IMAGE_BASE: u32 = 0x0020_0800;
+
+/// The upper bound (exclusive) of the memory available to the program.
+///
+/// Memory addresses must be lower than the BabyBear prime. The heap grows up from the end of the
+/// program image to this bound, so `MAX_MEMORY` minus the size of the image is the most a program
+/// can allocate.
+pub const MAX_MEMORY: usize = 0x7800_0000;
+
+/// The regions of memory a program uses, see [`MemoryLayout::DEFAULT`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MemoryLayout {
+    /// The addresses the stack can grow into, from [`STACK_TOP`] down.
+    pub stack: Range<u32>,
+    /// The program image, followed by the heap up to [`MAX_MEMORY`].
+    pub image_and_heap: Range<u32>,
+}
+
+impl MemoryLayout {
+    /// The layout every program is built with.
+    pub const DEFAULT: Self =
+        Self { stack: 0..STACK_TOP, image_and_heap: IMAGE_BASE..MAX_MEMORY as u32 };
+}
+
+// The stack must fit below the program image, which must fit below the heap ceiling.
+const _: () = assert!(STACK_TOP <= IMAGE_BASE && (IMAGE_BASE as usize) < MAX_MEMORY);
+
+#[cfg(test)]
+mod tests {
+    use super::{MemoryLayout, IMAGE_BASE, MAX_MEMORY, STACK_TOP};
+
+    #[test]
+    fn regions_do_not_overlap() {
+        let MemoryLayout { stack, image_and_heap } = MemoryLayout::DEFAULT;
+        assert!(!stack.is_empty() && !image_and_heap.is_empty());
+        assert!(stack.end <= image_and_heap.start);
+        assert_eq!(stack.end, STACK_TOP);
+        assert_eq!(image_and_heap, IMAGE_BASE..MAX_MEMORY as u32);
+    }
+}
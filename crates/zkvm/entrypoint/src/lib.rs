@@ -1,6 +1,9 @@
 extern crate alloc;
 
+pub mod assert;
+pub mod exit;
 pub mod heap;
+pub mod layout;
 pub mod syscalls;
 
 #[cfg(feature = "lib")]
@@ -16,13 +19,15 @@ pub mod lib {
 #[cfg(all(target_os = "zkvm", feature = "libm"))]
 mod libm;
 
-/// The number of 32 bit words that the public values digest is composed of.
-pub const PV_DIGEST_NUM_WORDS: usize = 8;
+#[cfg(all(target_os = "zkvm", feature = "libm-fallback", not(feature = "libm")))]
+mod libm_fallback;
+
+pub use sp1_primitives::consts::{NUM_PUBLIC_VALUES_CHANNELS, PV_DIGEST_NUM_WORDS};
 pub const POSEIDON_NUM_WORDS: usize = 8;
 
 #[cfg(target_os = "zkvm")]
 mod zkvm {
-    use crate::syscalls::syscall_halt;
+    use crate::{exit::EXIT_OK, syscalls::halt};
 
     use cfg_if::cfg_if;
     use sha2::{Digest, Sha256};
@@ -36,17 +41,25 @@ mod zkvm {
         }
     }
 
-    pub static mut PUBLIC_VALUES_HASHER: Option<Sha256> = None;
+    /// The running hashers of the public values channels, indexed by channel id. The hashers of
+    /// channels other than 0 are created on first use.
+    pub static mut PUBLIC_VALUES_HASHERS: [Option<Sha256>; crate::NUM_PUBLIC_VALUES_CHANNELS] =
+        [None, None, None, None];
+
+    /// Initializes the global state that the syscalls rely on.
+    unsafe fn init() {
+        PUBLIC_VALUES_HASHERS[0] = Some(Sha256::new());
+        #[cfg(feature = "verify")]
+        {
+            DEFERRED_PROOFS_DIGEST = Some([BabyBear::zero(); 8]);
+        }
+    }
 
     #[cfg(not(feature = "interface"))]
     #[no_mangle]
     unsafe extern "C" fn __start() {
         {
-            PUBLIC_VALUES_HASHER = Some(Sha256::new());
-            #[cfg(feature = "verify")]
-            {
-                DEFERRED_PROOFS_DIGEST = Some([BabyBear::zero(); 8]);
-            }
+            init();
 
             extern "C" {
                 fn main();
@@ -54,10 +67,37 @@ mod zkvm {
             main()
         }
 
-        syscall_halt(0);
+        halt(EXIT_OK);
     }
 
-    static STACK_TOP: u32 = 0x0020_0400;
+    /// With the `interface` feature, the embedder provides the program's entry point as
+    /// `sp1_interface_start` instead of a `main` function, e.g.
+    ///
+    /// ```ignore
+    /// #[no_mangle]
+    /// extern "C" fn sp1_interface_start() {
+    ///     // ...
+    /// }
+    /// ```
+    ///
+    /// It is called once the public values hasher (and, with `verify`, the deferred proofs digest)
+    /// is initialized, and the program halts with exit code 0 when it returns.
+    #[cfg(feature = "interface")]
+    #[no_mangle]
+    unsafe extern "C" fn __start() {
+        {
+            init();
+
+            extern "C" {
+                fn sp1_interface_start();
+            }
+            sp1_interface_start()
+        }
+
+        halt(EXIT_OK);
+    }
+
+    static STACK_TOP: u32 = crate::layout::STACK_TOP;
 
     core::arch::global_asm!(include_str!("memset.s"));
     core::arch::global_asm!(include_str!("memcpy.s"));
@@ -89,8 +129,35 @@ mod zkvm {
     getrandom::register_custom_getrandom!(zkvm_getrandom);
 }
 
+/// Declares the entry point of the program, along with its global allocator.
+///
+/// By default, the heap spans from the end of the program image to
+/// [`MAX_MEMORY`](layout::MAX_MEMORY). With `heap = ArenaAlloc::with_base(base, size)`, it is the
+/// `size` bytes starting at `base` instead:
+///
+/// ```ignore
+/// sp1_zkvm::entrypoint!(main, heap = ArenaAlloc::with_base(0x1000_0000, 0x100_0000));
+/// ```
 #[macro_export]
 macro_rules! entrypoint {
+    ($path:path, heap = $heap:expr) => {
+        const ZKVM_ENTRY: fn() = $path;
+
+        use $crate::heap::ArenaAlloc;
+
+        #[global_allocator]
+        static HEAP: ArenaAlloc = $heap;
+
+        mod zkvm_generated_main {
+
+            #[no_mangle]
+            fn main() {
+                // SAFETY: Nothing has been allocated yet.
+                unsafe { super::HEAP.init() };
+                super::ZKVM_ENTRY()
+            }
+        }
+    };
     ($path:path) => {
         const ZKVM_ENTRY: fn() = $path;
 
@@ -0,0 +1,137 @@
+//! Weak software fallbacks for the C math symbols, enabled by the `libm-fallback` feature when the
+//! `libm` feature is disabled.
+//!
+//! With the `libm` feature enabled, the `libm` module exports strong definitions of these symbols.
+//! Without either feature, guests that call `sin`, `exp`, etc. (directly or through `f64` methods)
+//! fail to link. With `libm-fallback`, each symbol is defined here as a weak alias to a pure-Rust
+//! implementation from the `libm` crate, so a guest can still override any of them with its own
+//! definition.
+//!
+//! Note that the zkVM has no floating point unit: every operation is emulated in software on top
+//! of RV32IM, so a single transcendental call typically costs thousands of cycles. Guests on a
+//! tight cycle budget should prefer fixed-point arithmetic over these routines.
+
+/// Defines `__sp1_libm_<name>` forwarding to `libm::<name>` and makes `<name>` a weak alias to it.
+macro_rules! weak_libm {
+    ($($name:ident($($arg:ident: $ty:ty),*) -> $ret:ty;)*) => {
+        $(
+            const _: () = {
+                #[export_name = concat!("__sp1_libm_", stringify!($name))]
+                extern "C" fn fallback($($arg: $ty),*) -> $ret {
+                    libm::$name($($arg),*)
+                }
+            };
+
+            core::arch::global_asm!(concat!(
+                ".weak ", stringify!($name), "\n",
+                ".set ", stringify!($name), ", __sp1_libm_", stringify!($name),
+            ));
+        )*
+    };
+}
+
+weak_libm! {
+    acos(x: f64) -> f64;
+    acosf(x: f32) -> f32;
+    acosh(x: f64) -> f64;
+    acoshf(x: f32) -> f32;
+    asin(x: f64) -> f64;
+    asinf(x: f32) -> f32;
+    asinh(x: f64) -> f64;
+    asinhf(x: f32) -> f32;
+    atan(x: f64) -> f64;
+    atan2(y: f64, x: f64) -> f64;
+    atan2f(y: f32, x: f32) -> f32;
+    atanf(x: f32) -> f32;
+    atanh(x: f64) -> f64;
+    atanhf(x: f32) -> f32;
+    cbrt(x: f64) -> f64;
+    cbrtf(x: f32) -> f32;
+    ceil(x: f64) -> f64;
+    ceilf(x: f32) -> f32;
+    copysign(x: f64, y: f64) -> f64;
+    copysignf(x: f32, y: f32) -> f32;
+    cos(x: f64) -> f64;
+    cosf(x: f32) -> f32;
+    cosh(x: f64) -> f64;
+    coshf(x: f32) -> f32;
+    erf(x: f64) -> f64;
+    erfc(x: f64) -> f64;
+    erfcf(x: f32) -> f32;
+    erff(x: f32) -> f32;
+    exp(x: f64) -> f64;
+    exp2(x: f64) -> f64;
+    exp2f(x: f32) -> f32;
+    exp10(x: f64) -> f64;
+    exp10f(x: f32) -> f32;
+    expf(x: f32) -> f32;
+    expm1(x: f64) -> f64;
+    expm1f(x: f32) -> f32;
+    fabs(x: f64) -> f64;
+    fabsf(x: f32) -> f32;
+    fdim(x: f64, y: f64) -> f64;
+    fdimf(x: f32, y: f32) -> f32;
+    floor(x: f64) -> f64;
+    floorf(x: f32) -> f32;
+    fma(x: f64, y: f64, z: f64) -> f64;
+    fmaf(x: f32, y: f32, z: f32) -> f32;
+    fmax(x: f64, y: f64) -> f64;
+    fmaxf(x: f32, y: f32) -> f32;
+    fmin(x: f64, y: f64) -> f64;
+    fminf(x: f32, y: f32) -> f32;
+    fmod(x: f64, y: f64) -> f64;
+    fmodf(x: f32, y: f32) -> f32;
+    hypot(x: f64, y: f64) -> f64;
+    hypotf(x: f32, y: f32) -> f32;
+    ilogb(x: f64) -> i32;
+    ilogbf(x: f32) -> i32;
+    j0(x: f64) -> f64;
+    j0f(x: f32) -> f32;
+    j1(x: f64) -> f64;
+    j1f(x: f32) -> f32;
+    jn(n: i32, x: f64) -> f64;
+    jnf(n: i32, x: f32) -> f32;
+    ldexp(x: f64, n: i32) -> f64;
+    ldexpf(x: f32, n: i32) -> f32;
+    lgamma(x: f64) -> f64;
+    log(x: f64) -> f64;
+    log1p(x: f64) -> f64;
+    log1pf(x: f32) -> f32;
+    log2(x: f64) -> f64;
+    log2f(x: f32) -> f32;
+    log10(x: f64) -> f64;
+    log10f(x: f32) -> f32;
+    logf(x: f32) -> f32;
+    nextafter(x: f64, y: f64) -> f64;
+    nextafterf(x: f32, y: f32) -> f32;
+    pow(x: f64, y: f64) -> f64;
+    powf(x: f32, y: f32) -> f32;
+    remainder(x: f64, y: f64) -> f64;
+    remainderf(x: f32, y: f32) -> f32;
+    rint(arg: f64) -> f64;
+    rintf(arg: f32) -> f32;
+    round(x: f64) -> f64;
+    roundf(x: f32) -> f32;
+    scalbn(x: f64, n: i32) -> f64;
+    scalbnf(x: f32, n: i32) -> f32;
+    sin(x: f64) -> f64;
+    sinf(x: f32) -> f32;
+    sinh(x: f64) -> f64;
+    sinhf(x: f32) -> f32;
+    sqrt(x: f64) -> f64;
+    sqrtf(x: f32) -> f32;
+    tan(x: f64) -> f64;
+    tanf(x: f32) -> f32;
+    tanh(x: f64) -> f64;
+    tanhf(x: f32) -> f32;
+    tgamma(x: f64) -> f64;
+    tgammaf(x: f32) -> f32;
+    trunc(x: f64) -> f64;
+    truncf(x: f32) -> f32;
+    y0(x: f64) -> f64;
+    y0f(x: f32) -> f32;
+    y1(x: f64) -> f64;
+    y1f(x: f32) -> f32;
+    yn(n: i32, x: f64) -> f64;
+    ynf(n: i32, x: f32) -> f32;
+}
@@ -46,3 +46,60 @@ pub extern "C" fn sys_bigint(
         syscall_uint256_mulmod(result_ptr, concat_ptr);
     }
 }
+
+/// Sets `out` to `(a * b) % m`, like the EVM `MULMOD` opcode, where `a`, `b`, `m` and `out` point to
+/// 32-byte big-endian integers.
+///
+/// This is [`sys_bigint`] with the EVM's byte order, so it is proven by the `UINT256_MUL`
+/// precompile.
+///
+/// ### Panics
+///
+/// Panics if `m` is zero. The EVM defines the result as 0 in that case, but [`sys_bigint`] would
+/// reduce modulo 2^256 instead, so callers must handle it themselves.
+///
+/// ### Safety
+///
+/// The caller must ensure that `a`, `b`, `m` and `out` are valid pointers to 32 bytes. They need not
+/// be aligned.
+#[no_mangle]
+pub extern "C" fn sys_uint256_mulmod(a: *const u8, b: *const u8, m: *const u8, out: *mut u8) {
+    let [a, b, m] = [a, b, m]
+        .map(|ptr| uint256_from_be_bytes(unsafe { &ptr.cast::<[u8; 32]>().read_unaligned() }));
+    assert!(m != [0; N], "sys_uint256_mulmod: the modulus is zero");
+
+    let mut result = [0; N];
+    sys_bigint(&mut result, 0, &a, &b, &m);
+    unsafe { out.cast::<[u8; 32]>().write_unaligned(uint256_to_be_bytes(&result)) };
+}
+
+/// The little-endian words of the 32-byte big-endian integer `bytes`.
+pub fn uint256_from_be_bytes(bytes: &[u8; 32]) -> [u32; N] {
+    core::array::from_fn(|i| {
+        let end = 32 - 4 * i;
+        u32::from_be_bytes(bytes[end - 4..end].try_into().unwrap())
+    })
+}
+
+/// The 32-byte big-endian encoding of the integer with little-endian words `words`.
+pub fn uint256_to_be_bytes(words: &[u32; N]) -> [u8; 32] {
+    let mut bytes = [0; 32];
+    for (chunk, word) in bytes.rchunks_exact_mut(4).zip(words) {
+        chunk.copy_from_slice(&word.to_be_bytes());
+    }
+    bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{uint256_from_be_bytes, uint256_to_be_bytes};
+
+    #[test]
+    fn uint256_be_bytes_round_trip() {
+        let bytes: [u8; 32] = core::array::from_fn(|i| i as u8);
+        let words = uint256_from_be_bytes(&bytes);
+        assert_eq!(words[0], 0x1c1d1e1f);
+        assert_eq!(words[7], 0x00010203);
+        assert_eq!(uint256_to_be_bytes(&words), bytes);
+    }
+}
@@ -0,0 +1,210 @@
+#[cfg(target_os = "zkvm")]
+use core::arch::asm;
+
+/// The BLAKE3 initialization vector.
+const IV: [u32; 8] = [
+    0x6A09E667, 0xBB67AE85, 0x3C6EF372, 0xA54FF53A, 0x510E527F, 0x9B05688C, 0x1F83D9AB, 0x5BE0CD19,
+];
+
+/// The number of bytes in a message block.
+const BLOCK_LEN: usize = 64;
+
+/// The number of bytes in a chunk, the leaves of the BLAKE3 tree.
+const CHUNK_LEN: usize = 1024;
+
+const CHUNK_START: u32 = 1 << 0;
+const CHUNK_END: u32 = 1 << 1;
+const PARENT: u32 = 1 << 2;
+const ROOT: u32 = 1 << 3;
+
+/// The maximum height of the tree, which is enough for inputs of up to 2^64 bytes.
+const MAX_DEPTH: usize = 54;
+
+/// Executes the BLAKE3 compression function on the given state and message block.
+///
+/// `state` holds the initial state of the compression function: the input chaining value, the
+/// first four words of the IV, the low and high words of the counter, the block length and the
+/// flags. It is overwritten with the 16-word output, whose first eight words are the output
+/// chaining value.
+///
+/// ### Safety
+///
+/// The caller must ensure that `state` and `block` are valid pointers to data that is aligned
+/// along a four byte boundary.
+#[allow(unused_variables)]
+#[no_mangle]
+pub extern "C" fn syscall_blake3_compress(state: *mut [u32; 16], block: *const [u32; 16]) {
+    #[cfg(target_os = "zkvm")]
+    unsafe {
+        asm!(
+            "ecall",
+            in("t0") crate::syscalls::BLAKE3_COMPRESS,
+            in("a0") state,
+            in("a1") block,
+        );
+    }
+}
+
+/// Writes the 32-byte BLAKE3 hash of the `len` bytes at `input` to `out`.
+///
+/// Every compression is proven by the `BLAKE3_COMPRESS` precompile.
+///
+/// ### Safety
+///
+/// The caller must ensure that `input` is a valid pointer to `len` bytes, and that `out` is a valid
+/// pointer to 32 bytes. They need not be aligned.
+#[no_mangle]
+pub unsafe extern "C" fn sys_blake3(input: *const u8, len: usize, out: *mut u8) {
+    let input = core::slice::from_raw_parts(input, len);
+    let hash = hash(input, |state, block| syscall_blake3_compress(state, block));
+    out.cast::<[u8; 32]>().write_unaligned(hash);
+}
+
+/// The inputs of a compression whose output is not known to be the root of the tree yet.
+struct Output {
+    cv: [u32; 8],
+    block: [u32; 16],
+    counter: u64,
+    block_len: u32,
+    flags: u32,
+}
+
+/// Hashes `input` with BLAKE3, computing each compression with `compress`.
+fn hash(input: &[u8], mut compress: impl FnMut(&mut [u32; 16], &[u32; 16])) -> [u8; 32] {
+    let mut compress = |output: &Output, extra_flags: u32| {
+        let mut state = [0; 16];
+        state[..8].copy_from_slice(&output.cv);
+        state[8..12].copy_from_slice(&IV[..4]);
+        state[12] = output.counter as u32;
+        state[13] = (output.counter >> 32) as u32;
+        state[14] = output.block_len;
+        state[15] = output.flags | extra_flags;
+        compress(&mut state, &output.block);
+        let mut cv = [0; 8];
+        cv.copy_from_slice(&state[..8]);
+        cv
+    };
+
+    let mut stack = [[0u32; 8]; MAX_DEPTH];
+    let mut stack_len = 0;
+
+    // Every chunk but the last is merged into the tree as soon as it is hashed. The last chunk,
+    // which is empty for an empty input, is finalized together with the rest of the tree below.
+    let num_chunks = input.len().div_ceil(CHUNK_LEN).max(1);
+    let mut chunks = input.chunks(CHUNK_LEN);
+    for counter in 0..num_chunks as u64 - 1 {
+        let chunk = chunks.next().unwrap();
+        let output = chunk_output(chunk, counter, &mut compress);
+        let mut cv = compress(&output, 0);
+
+        // Merge the subtrees that this chunk completes, one per trailing zero of the number of
+        // chunks hashed so far.
+        let mut total_chunks = counter + 1;
+        while total_chunks & 1 == 0 {
+            stack_len -= 1;
+            cv = compress(&parent_output(&stack[stack_len], &cv), 0);
+            total_chunks >>= 1;
+        }
+        stack[stack_len] = cv;
+        stack_len += 1;
+    }
+
+    let last_chunk = chunks.next().unwrap_or(&[]);
+    let mut output = chunk_output(last_chunk, num_chunks as u64 - 1, &mut compress);
+    while stack_len > 0 {
+        stack_len -= 1;
+        let cv = compress(&output, 0);
+        output = parent_output(&stack[stack_len], &cv);
+    }
+
+    let root = compress(&output, ROOT);
+    let mut hash = [0; 32];
+    for (bytes, word) in hash.chunks_exact_mut(4).zip(root) {
+        bytes.copy_from_slice(&word.to_le_bytes());
+    }
+    hash
+}
+
+/// Compresses every block of `chunk` but the last, and returns the compression of the last block.
+fn chunk_output(
+    chunk: &[u8],
+    counter: u64,
+    compress: &mut impl FnMut(&Output, u32) -> [u32; 8],
+) -> Output {
+    let num_blocks = chunk.len().div_ceil(BLOCK_LEN).max(1);
+    let mut output = Output { cv: IV, block: [0; 16], counter, block_len: 0, flags: 0 };
+    for i in 0..num_blocks {
+        if i > 0 {
+            output.cv = compress(&output, 0);
+        }
+        let block = &chunk[i * BLOCK_LEN..chunk.len().min((i + 1) * BLOCK_LEN)];
+        let mut bytes = [0; BLOCK_LEN];
+        bytes[..block.len()].copy_from_slice(block);
+        for (word, bytes) in output.block.iter_mut().zip(bytes.chunks_exact(4)) {
+            *word = u32::from_le_bytes(bytes.try_into().unwrap());
+        }
+        output.block_len = block.len() as u32;
+        output.flags = if i == 0 { CHUNK_START } else { 0 };
+        if i == num_blocks - 1 {
+            output.flags |= CHUNK_END;
+        }
+    }
+    output
+}
+
+/// The compression of the parent node of the subtrees with chaining values `left` and `right`.
+fn parent_output(left: &[u32; 8], right: &[u32; 8]) -> Output {
+    let mut block = [0; 16];
+    block[..8].copy_from_slice(left);
+    block[8..].copy_from_slice(right);
+    Output { cv: IV, block, counter: 0, block_len: BLOCK_LEN as u32, flags: PARENT }
+}
+
+#[cfg(test)]
+mod tests {
+    use sp1_primitives::blake3::blake3_compress;
+
+    use super::hash;
+
+    /// The official BLAKE3 test vectors. The input of length `n` is the bytes `0, 1, ..., n - 1`
+    /// modulo 251.
+    const VECTORS: [(usize, &str); 25] = [
+        (0, "af1349b9f5f9a1a6a0404dea36dcc9499bcb25c9adc112b7cc9a93cae41f3262"),
+        (1, "2d3adedff11b61f14c886e35afa036736dcd87a74d27b5c1510225d0f592e213"),
+        (63, "e9bc37a594daad83be9470df7f7b3798297c3d834ce80ba85d6e207627b7db7b"),
+        (64, "4eed7141ea4a5cd4b788606bd23f46e212af9cacebacdc7d1f4c6dc7f2511b98"),
+        (65, "de1e5fa0be70df6d2be8fffd0e99ceaa8eb6e8c93a63f2d8d1c30ecb6b263dee"),
+        (1023, "10108970eeda3eb932baac1428c7a2163b0e924c9a9e25b35bba72b28f70bd11"),
+        (1024, "42214739f095a406f3fc83deb889744ac00df831c10daa55189b5d121c855af7"),
+        (1025, "d00278ae47eb27b34faecf67b4fe263f82d5412916c1ffd97c8cb7fb814b8444"),
+        (2048, "e776b6028c7cd22a4d0ba182a8bf62205d2ef576467e838ed6f2529b85fba24a"),
+        (2049, "5f4d72f40d7a5f82b15ca2b2e44b1de3c2ef86c426c95c1af0b6879522563030"),
+        (3072, "b98cb0ff3623be03326b373de6b9095218513e64f1ee2edd2525c7ad1e5cffd2"),
+        (3073, "7124b49501012f81cc7f11ca069ec9226cecb8a2c850cfe644e327d22d3e1cd3"),
+        (4096, "015094013f57a5277b59d8475c0501042c0b642e531b0a1c8f58d2163229e969"),
+        (4097, "9b4052b38f1c5fc8b1f9ff7ac7b27cd242487b3d890d15c96a1c25b8aa0fb995"),
+        (5120, "9cadc15fed8b5d854562b26a9536d9707cadeda9b143978f319ab34230535833"),
+        (5121, "628bd2cb2004694adaab7bbd778a25df25c47b9d4155a55f8fbd79f2fe154cff"),
+        (6144, "3e2e5b74e048f3add6d21faab3f83aa44d3b2278afb83b80b3c35164ebeca205"),
+        (6145, "f1323a8631446cc50536a9f705ee5cb619424d46887f3c376c695b70e0f0507f"),
+        (7168, "61da957ec2499a95d6b8023e2b0e604ec7f6b50e80a9678b89d2628e99ada77a"),
+        (7169, "a003fc7a51754a9b3c7fae0367ab3d782dccf28855a03d435f8cfe74605e7817"),
+        (8192, "aae792484c8efe4f19e2ca7d371d8c467ffb10748d8a5a1ae579948f718a2a63"),
+        (8193, "bab6c09cb8ce8cf459261398d2e7aef35700bf488116ceb94a36d0f5f1b7bc3b"),
+        (16384, "f875d6646de28985646f34ee13be9a576fd515f76b5b0a26bb324735041ddde4"),
+        (31744, "62b6960e1a44bcc1eb1a611a8d6235b6b4b78f32e7abc4fb4c6cdcce94895c47"),
+        (102400, "bc3e3d41a1146b069abffad3c0d44860cf664390afce4d9661f7902e7943e085"),
+    ];
+
+    #[test]
+    fn blake3_official_vectors() {
+        for (len, expected) in VECTORS {
+            let input = (0..len).map(|i| (i % 251) as u8).collect::<Vec<_>>();
+            let hash = hash(&input, |state, block| *state = blake3_compress(*state, *block));
+            let expected: [u8; 32] = core::array::from_fn(|i| {
+                u8::from_str_radix(&expected[2 * i..2 * i + 2], 16).unwrap()
+            });
+            assert_eq!(hash, expected, "input length {len}");
+        }
+    }
+}
@@ -3,7 +3,9 @@ cfg_if::cfg_if! {
         use core::arch::asm;
         use sha2::Digest;
         use crate::zkvm;
-        use crate::{PV_DIGEST_NUM_WORDS, POSEIDON_NUM_WORDS};
+        use crate::{NUM_PUBLIC_VALUES_CHANNELS, PV_DIGEST_NUM_WORDS, POSEIDON_NUM_WORDS};
+
+        const FD_PUBLIC_VALUES: u32 = 3;
     }
 }
 
@@ -18,14 +20,90 @@ cfg_if::cfg_if! {
 /// Before halting, the syscall will commit to the public values.
 #[allow(unused_variables)]
 pub extern "C" fn syscall_halt(exit_code: u8) -> ! {
+    halt(exit_code as u32)
+}
+
+/// Halts the program with the given exit code, and guarantees that nothing else runs afterwards.
+///
+/// Like [`syscall_halt`], this commits to the public values first, and the exit code is proven the
+/// same way. Unlike `HALT`, which only ends the current flow of execution, `EXIT_GROUP` also marks
+/// the whole execution as terminated, so the executor runs no further instruction in any shard.
+/// Use it when the program detects a fatal condition.
+#[allow(unused_variables)]
+#[no_mangle]
+pub extern "C" fn sys_exit_group(exit_code: u32) -> ! {
+    exit(crate::syscalls::EXIT_GROUP, exit_code, false)
+}
+
+/// Commits the given exit code to the public values, then halts the program with it.
+///
+/// The code is written as 4 little-endian bytes at the very end of the public values stream, after
+/// the digests of the extra channels, so the public values digest that the proof commits to also
+/// binds the exit code the program reports. On the host, it is read back with
+/// `SP1PublicValues::committed_exit_code`.
+#[allow(unused_variables)]
+#[no_mangle]
+pub extern "C" fn sys_commit_exit(exit_code: u32) -> ! {
+    exit(crate::syscalls::HALT, exit_code, true)
+}
+
+/// Commits to the public values and halts the program with the given exit code.
+pub(crate) fn halt(exit_code: u32) -> ! {
+    exit(crate::syscalls::HALT, exit_code, false)
+}
+
+/// The hooks registered with [`register_halt_hook`], in registration order.
+static mut HALT_HOOKS: Vec<Box<dyn FnOnce()>> = Vec::new();
+
+/// Registers `hook` to run when the program halts, e.g. to flush buffered profiling data to the
+/// host before it is lost.
+///
+/// Hooks run in registration order before the public values are committed, whether the program
+/// returns from `main` or halts with [`syscall_halt`], [`sys_exit_group`] or [`sys_commit_exit`].
+/// Each hook runs at most once, even if a hook halts the program itself.
+pub fn register_halt_hook(hook: impl FnOnce() + 'static) {
+    unsafe { (*core::ptr::addr_of_mut!(HALT_HOOKS)).push(Box::new(hook)) };
+}
+
+/// Unregisters the hooks registered with [`register_halt_hook`] and runs them.
+#[cfg(any(test, target_os = "zkvm"))]
+fn run_halt_hooks() {
+    let hooks = unsafe { core::mem::take(&mut *core::ptr::addr_of_mut!(HALT_HOOKS)) };
+    for hook in hooks {
+        hook();
+    }
+}
+
+/// Commits to the public values and invokes `syscall`, one of `HALT` and `EXIT_GROUP`, with the
+/// given exit code. With `commit_code`, the exit code is committed last, see [`sys_commit_exit`].
+#[allow(unused_variables)]
+fn exit(syscall: u32, exit_code: u32, commit_code: bool) -> ! {
     #[cfg(target_os = "zkvm")]
     unsafe {
+        run_halt_hooks();
+
+        #[cfg(feature = "mem-report")]
+        crate::syscalls::report_heap_peak();
+
+        // If any of the extra public values channels was used, append the digests of all of them
+        // to the public values stream, in channel order. Programs that only use channel 0 are
+        // unaffected.
+        let hashers = core::ptr::addr_of_mut!(zkvm::PUBLIC_VALUES_HASHERS);
+        if (*hashers)[1..].iter().any(Option::is_some) {
+            for channel in 1..NUM_PUBLIC_VALUES_CHANNELS {
+                let digest = (*hashers)[channel].take().unwrap_or_default().finalize();
+                crate::syscalls::syscall_write(FD_PUBLIC_VALUES, digest.as_ptr(), digest.len());
+            }
+        }
+
+        if commit_code {
+            let code = exit_code.to_le_bytes();
+            crate::syscalls::syscall_write(FD_PUBLIC_VALUES, code.as_ptr(), code.len());
+        }
+
         // When we halt, we retrieve the public values finalized digest.  This is the hash of all
         // the bytes written to the public values fd.
-        let pv_digest_bytes =
-            core::mem::take(&mut *core::ptr::addr_of_mut!(zkvm::PUBLIC_VALUES_HASHER))
-                .unwrap()
-                .finalize();
+        let pv_digest_bytes = (*hashers)[0].take().unwrap().finalize();
 
         // For each digest word, call COMMIT ecall.  In the runtime, this will store the digest
         // words into the runtime's execution record's public values digest.  In the AIR, it
@@ -53,7 +131,7 @@ pub extern "C" fn syscall_halt(exit_code: u8) -> ! {
 
         asm!(
             "ecall",
-            in("t0") crate::syscalls::HALT,
+            in("t0") syscall,
             in("a0") exit_code
         );
         unreachable!()
@@ -62,3 +140,41 @@ pub extern "C" fn syscall_halt(exit_code: u8) -> ! {
     #[cfg(not(target_os = "zkvm"))]
     unreachable!()
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    };
+
+    use super::{register_halt_hook, run_halt_hooks};
+
+    /// Serializes the tests that register and run hooks, which share [`super::HALT_HOOKS`].
+    static HOOKS_LOCK: Mutex<()> = Mutex::new(());
+
+    #[test]
+    fn halt_hook_runs_once() {
+        let _guard = HOOKS_LOCK.lock().unwrap();
+        static RUNS: AtomicUsize = AtomicUsize::new(0);
+        register_halt_hook(|| {
+            RUNS.fetch_add(1, Ordering::SeqCst);
+        });
+
+        run_halt_hooks();
+        run_halt_hooks();
+        assert_eq!(RUNS.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn halt_hooks_run_in_registration_order() {
+        let _guard = HOOKS_LOCK.lock().unwrap();
+        static ORDER: Mutex<Vec<usize>> = Mutex::new(Vec::new());
+        for i in 0..3 {
+            register_halt_hook(move || ORDER.lock().unwrap().push(i));
+        }
+
+        run_halt_hooks();
+        assert_eq!(*ORDER.lock().unwrap(), [0, 1, 2]);
+    }
+}
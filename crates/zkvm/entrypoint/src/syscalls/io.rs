@@ -2,7 +2,7 @@ cfg_if::cfg_if! {
     if #[cfg(target_os = "zkvm")] {
         use core::arch::asm;
         use crate::zkvm;
-        use sha2::digest::Update;
+        use sha2::{digest::{FixedOutput, Update}, Sha256};
     }
 }
 
@@ -28,7 +28,7 @@ pub extern "C" fn syscall_write(fd: u32, write_buf: *const u8, nbytes: usize) {
             // version of this hash.
             if fd == FD_PUBLIC_VALUES {
                 let pi_slice: &[u8] = unsafe { core::slice::from_raw_parts(write_buf, nbytes) };
-                unsafe { zkvm::PUBLIC_VALUES_HASHER.as_mut().unwrap().update(pi_slice) };
+                unsafe { zkvm::PUBLIC_VALUES_HASHERS[0].as_mut().unwrap().update(pi_slice) };
             }
         } else {
             unreachable!()
@@ -36,6 +36,91 @@ pub extern "C" fn syscall_write(fd: u32, write_buf: *const u8, nbytes: usize) {
     }
 }
 
+/// Commits `nbytes` of data from `write_buf` to the given public values channel.
+///
+/// Channel 0 is the public values stream itself, so committing to it is the same as writing to the
+/// public values file descriptor. The other channels are only hashed, and their digests are
+/// appended to the public values stream in channel order when the program halts.
+#[allow(unused_variables)]
+#[no_mangle]
+pub extern "C" fn sys_commit_to_channel(channel: u8, write_buf: *const u8, nbytes: usize) {
+    cfg_if::cfg_if! {
+        if #[cfg(target_os = "zkvm")] {
+            const FD_PUBLIC_VALUES: u32 = 3;
+            if channel == 0 {
+                syscall_write(FD_PUBLIC_VALUES, write_buf, nbytes);
+                return;
+            }
+
+            assert!(
+                (channel as usize) < crate::NUM_PUBLIC_VALUES_CHANNELS,
+                "invalid public values channel: {}",
+                channel
+            );
+            let slice: &[u8] = unsafe { core::slice::from_raw_parts(write_buf, nbytes) };
+            unsafe {
+                (*core::ptr::addr_of_mut!(zkvm::PUBLIC_VALUES_HASHERS[channel as usize]))
+                    .get_or_insert_with(Sha256::default)
+                    .update(slice)
+            };
+        } else {
+            unreachable!()
+        }
+    }
+}
+
+/// Writes the digest of the public values committed so far to `out`.
+///
+/// The running hasher is cloned and the copy is finalized, so the program can keep committing
+/// public values afterwards and the digest committed at halt is unaffected. Cloning only copies
+/// the SHA-256 state and the partially filled block, so this is cheap.
+#[allow(unused_variables)]
+#[no_mangle]
+pub extern "C" fn sys_public_values_digest(out: *mut [u8; 32]) {
+    #[cfg(target_os = "zkvm")]
+    unsafe {
+        let hasher = (*core::ptr::addr_of!(zkvm::PUBLIC_VALUES_HASHERS[0])).clone().unwrap();
+        (*out).copy_from_slice(&hasher.finalize_fixed());
+    }
+
+    #[cfg(not(target_os = "zkvm"))]
+    unreachable!()
+}
+
+/// Commits the verification key digest at `vkey_ptr`, [`PV_DIGEST_NUM_WORDS`] words, to the public
+/// values as little-endian bytes.
+///
+/// Aggregation programs call this for each inner proof they verify, so that the public values
+/// digest of the outer proof binds which programs were aggregated. Like the rest of the public
+/// values, the words are hashed by the program and written to the public values stream.
+///
+/// [`PV_DIGEST_NUM_WORDS`]: crate::PV_DIGEST_NUM_WORDS
+#[allow(unused_variables)]
+#[no_mangle]
+pub extern "C" fn sys_commit_vkey(vkey_ptr: *const [u32; crate::PV_DIGEST_NUM_WORDS]) {
+    #[cfg(target_os = "zkvm")]
+    unsafe {
+        const FD_PUBLIC_VALUES: u32 = 3;
+
+        // `WRITE` does not see memory the program has not loaded yet, so commit a copy.
+        let bytes = vkey_bytes(&core::ptr::read_volatile(vkey_ptr));
+        syscall_write(FD_PUBLIC_VALUES, bytes.as_ptr(), bytes.len());
+    }
+
+    #[cfg(not(target_os = "zkvm"))]
+    unreachable!()
+}
+
+/// The bytes that [`sys_commit_vkey`] writes to the public values for `vkey`.
+#[cfg(any(test, target_os = "zkvm"))]
+fn vkey_bytes(vkey: &[u32; crate::PV_DIGEST_NUM_WORDS]) -> [u8; 4 * crate::PV_DIGEST_NUM_WORDS] {
+    let mut bytes = [0u8; 4 * crate::PV_DIGEST_NUM_WORDS];
+    for (chunk, word) in bytes.chunks_exact_mut(4).zip(vkey) {
+        chunk.copy_from_slice(&word.to_le_bytes());
+    }
+    bytes
+}
+
 /// Returns the length of the next element in the hint stream.
 #[allow(unused_variables)]
 #[no_mangle]
@@ -72,3 +157,129 @@ pub extern "C" fn syscall_hint_read(ptr: *mut u8, len: usize) {
     #[cfg(not(target_os = "zkvm"))]
     unreachable!()
 }
+
+/// Looks up the UTF-8 key of `key_len` bytes at `key_ptr` in the config provided by the host and
+/// returns the length of its value, or 0 if the key is absent.
+///
+/// The value is written to `val_ptr` only if it fits in `val_cap` bytes. Otherwise nothing is
+/// written and the returned length is the capacity needed. Like [`syscall_hint_read`], the value
+/// is hinted into memory, so `val_ptr` must be word aligned and point to memory that was never
+/// accessed before.
+#[allow(unused_variables)]
+#[no_mangle]
+pub extern "C" fn sys_config_get(
+    key_ptr: *const u8,
+    key_len: usize,
+    val_ptr: *mut u8,
+    val_cap: usize,
+) -> usize {
+    #[cfg(target_os = "zkvm")]
+    unsafe {
+        let len;
+        asm!(
+            "ecall",
+            in("t0") crate::syscalls::CONFIG_GET,
+            in("a0") key_ptr,
+            in("a1") key_len,
+            in("a2") val_ptr,
+            in("a3") val_cap,
+            lateout("t0") len,
+        );
+        len
+    }
+
+    #[cfg(not(target_os = "zkvm"))]
+    unreachable!()
+}
+
+/// Copies up to `len` bytes of the input blob provided by the host, starting at `offset`, to `buf`
+/// and returns the number of bytes copied.
+///
+/// The range is clamped to the end of the blob, so fewer than `len` bytes are copied when it
+/// extends past the end, and none when `offset` is past the end. Unlike [`syscall_hint_read`],
+/// this does not advance the input stream, so any part of the blob can be read any number of
+/// times. Like [`syscall_hint_read`], the bytes are hinted into memory, so `buf` must be word
+/// aligned and point to memory that was never accessed before.
+#[allow(unused_variables)]
+#[no_mangle]
+pub extern "C" fn sys_input_read_at(offset: usize, buf: *mut u8, len: usize) -> usize {
+    #[cfg(target_os = "zkvm")]
+    unsafe {
+        let read;
+        asm!(
+            "ecall",
+            in("t0") crate::syscalls::INPUT_READ_AT,
+            in("a0") offset,
+            in("a1") buf,
+            in("a2") len,
+            lateout("t0") read,
+        );
+        read
+    }
+
+    #[cfg(not(target_os = "zkvm"))]
+    unreachable!()
+}
+
+/// Copies the next bytes of the named input stream `stream_id` provided by the host, up to `len`,
+/// to `buf` and returns the number of bytes copied.
+///
+/// Every stream is read sequentially from its own position, independently of the other streams
+/// and of the input stream read by [`syscall_hint_read`]. Fewer than `len` bytes are copied at the
+/// end of a stream, and none from a stream the host did not provide. Like [`syscall_hint_read`],
+/// the bytes are hinted into memory, so `buf` must be word aligned and point to memory that was
+/// never accessed before.
+#[allow(unused_variables)]
+#[no_mangle]
+pub extern "C" fn sys_read_stream(stream_id: u32, buf: *mut u8, len: usize) -> usize {
+    #[cfg(target_os = "zkvm")]
+    unsafe {
+        let read;
+        asm!(
+            "ecall",
+            in("t0") crate::syscalls::READ_STREAM,
+            in("a0") stream_id,
+            in("a1") buf,
+            in("a2") len,
+            lateout("t0") read,
+        );
+        read
+    }
+
+    #[cfg(not(target_os = "zkvm"))]
+    unreachable!()
+}
+
+#[cfg(test)]
+mod tests {
+    use sha2::{Digest, Sha256};
+    use sp1_primitives::consts::words_to_bytes_le_vec;
+
+    use super::vkey_bytes;
+
+    #[test]
+    fn commit_vkey_digest() {
+        let vkeys = [[1, 2, 3, 0xdead_beef, 5, 6, 7, u32::MAX], [0; 8]];
+
+        // At halt, the program commits the digest of every byte written to the public values.
+        let digest = |vkeys: &[[u32; 8]]| {
+            let mut hasher = Sha256::new();
+            for vkey in vkeys {
+                hasher.update(vkey_bytes(vkey));
+            }
+            hasher.finalize()
+        };
+
+        // A verifier recomputes it from the words of the verification keys it expects.
+        let expected = Sha256::digest(
+            vkeys.iter().flat_map(|vkey| words_to_bytes_le_vec(vkey)).collect::<Vec<_>>(),
+        );
+        assert_eq!(digest(&vkeys), expected);
+
+        // The digest binds both the keys and their order.
+        let mut other = vkeys;
+        other[0][3] ^= 1;
+        assert_ne!(digest(&other), expected);
+        assert_ne!(digest(&[vkeys[1], vkeys[0]]), expected);
+    }
+}
@@ -12,40 +12,331 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-// Memory addresses must be lower than BabyBear prime.
-const MAX_MEMORY: usize = 0x78000000;
+pub use crate::layout::{MAX_MEMORY, STACK_TOP};
+
+// Pointer to next heap address to use, or 0 if the heap has not yet been initialized.
+static mut HEAP_POS: usize = 0;
+
+// The start of the heap, or 0 if the heap has not yet been initialized.
+static mut HEAP_BASE: usize = 0;
+
+// The highest heap position reached so far. Memory below it may have been written and freed.
+static mut HEAP_TOP: usize = 0;
+
+// The upper bound (exclusive) of the heap.
+static mut HEAP_END: usize = MAX_MEMORY;
 
 #[allow(clippy::missing_safety_doc)]
 #[no_mangle]
 pub unsafe extern "C" fn sys_alloc_aligned(bytes: usize, align: usize) -> *mut u8 {
+    // SAFETY: Single threaded, so nothing else can touch this while we're working.
+    let (ptr, heap_pos) = bump(unsafe { heap_pos() }, bytes, align, unsafe { HEAP_END });
+    unsafe { set_heap_pos(heap_pos) };
+    ptr as *mut u8
+}
+
+/// Allocates `bytes` bytes aligned to `align` from memory that has never been allocated before.
+///
+/// Hint reads fill memory through its initial values, so their buffers must come from here rather
+/// than from memory that [`sys_dealloc_aligned`] made available again.
+///
+/// # Safety
+///
+/// Same as [`sys_alloc_aligned`].
+#[no_mangle]
+pub unsafe extern "C" fn sys_alloc_fresh(bytes: usize, align: usize) -> *mut u8 {
+    // SAFETY: Single threaded, so nothing else can touch this while we're working.
+    let (ptr, heap_pos) =
+        bump(unsafe { heap_pos().max(HEAP_TOP) }, bytes, align, unsafe { HEAP_END });
+    unsafe { set_heap_pos(heap_pos) };
+    ptr as *mut u8
+}
+
+/// Frees the `bytes` bytes at `ptr` if they are the most recent allocation, by rewinding the heap
+/// pointer. Any other allocation is never freed.
+///
+/// # Safety
+///
+/// `ptr` and `bytes` must describe a live allocation returned by [`sys_alloc_aligned`] or
+/// [`sys_alloc_fresh`].
+pub unsafe fn sys_dealloc_aligned(ptr: *mut u8, bytes: usize) {
+    // SAFETY: Single threaded, so nothing else can touch this while we're working.
+    unsafe { HEAP_POS = rewind(HEAP_POS, ptr as usize, bytes) };
+}
+
+/// Zeroes the `len` bytes at `ptr`, e.g. to wipe secrets from scratch memory before halting.
+///
+/// The stores are volatile, so the compiler cannot elide them even if the memory is never read
+/// again, and each one is a real store instruction that is proven like any other memory write.
+///
+/// # Safety
+///
+/// `ptr` must be valid for writes of `len` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn sys_memzero(ptr: *mut u8, len: usize) {
+    let mut i = 0;
+    // Zero byte by byte up to the first word boundary, then a word at a time.
+    while i < len && (ptr as usize + i) % 4 != 0 {
+        unsafe { core::ptr::write_volatile(ptr.add(i), 0) };
+        i += 1;
+    }
+    while i + 4 <= len {
+        unsafe { core::ptr::write_volatile(ptr.add(i) as *mut u32, 0) };
+        i += 4;
+    }
+    while i < len {
+        unsafe { core::ptr::write_volatile(ptr.add(i), 0) };
+        i += 1;
+    }
+    core::sync::atomic::compiler_fence(core::sync::atomic::Ordering::SeqCst);
+}
+
+/// Places the heap at the `size` bytes starting at `base`, instead of between the end of the
+/// program image and [`MAX_MEMORY`].
+///
+/// # Safety
+///
+/// Must be called before anything is allocated, and the memory must not be used by anything else.
+///
+/// # Panics
+///
+/// Panics if the heap is already in use, or if it overlaps the program image or [`MAX_MEMORY`].
+pub unsafe fn sys_init_heap(base: usize, size: usize) {
+    assert_eq!(HEAP_POS, 0, "the heap must be placed before the first allocation");
+    assert!(base >= image_end(), "the heap must start above the program image");
+    check_heap_bounds(base, size);
+    HEAP_POS = base;
+    HEAP_BASE = base;
+    HEAP_TOP = base;
+    HEAP_END = base + size;
+}
+
+/// The peak heap usage so far, in bytes: the distance from the start of the heap to the highest
+/// address ever allocated, including alignment padding.
+///
+/// Memory freed by [`sys_dealloc_aligned`] still counts, so this is the size of the smallest heap
+/// the program could have run with up to this point.
+pub fn sys_heap_peak() -> usize {
+    // SAFETY: Single threaded, so nothing else can touch this while we're working.
+    unsafe { HEAP_TOP.saturating_sub(HEAP_BASE) }
+}
+
+/// Writes the peak heap usage to stderr, without allocating. Called at exit with the `mem-report`
+/// feature.
+#[cfg(all(target_os = "zkvm", feature = "mem-report"))]
+pub(crate) fn report_heap_peak() {
+    let mut buf = [0; HEAP_REPORT_LEN];
+    let report = heap_report(sys_heap_peak(), &mut buf);
+    crate::syscalls::syscall_write(2, report.as_ptr(), report.len());
+}
+
+#[cfg(any(test, all(target_os = "zkvm", feature = "mem-report")))]
+const HEAP_REPORT_PREFIX: &[u8] = b"heap peak: ";
+#[cfg(any(test, all(target_os = "zkvm", feature = "mem-report")))]
+const HEAP_REPORT_SUFFIX: &[u8] = b" bytes\n";
+#[cfg(any(test, all(target_os = "zkvm", feature = "mem-report")))]
+const HEAP_REPORT_LEN: usize = HEAP_REPORT_PREFIX.len() + 20 + HEAP_REPORT_SUFFIX.len();
+
+/// Formats the report of a `peak` heap usage into `buf`, returning the written part.
+#[cfg(any(test, all(target_os = "zkvm", feature = "mem-report")))]
+fn heap_report(peak: usize, buf: &mut [u8; HEAP_REPORT_LEN]) -> &[u8] {
+    let mut digits = [0; 20];
+    let mut start = digits.len();
+    let mut n = peak;
+    loop {
+        start -= 1;
+        digits[start] = b'0' + (n % 10) as u8;
+        n /= 10;
+        if n == 0 {
+            break;
+        }
+    }
+
+    let mut len = 0;
+    for part in [HEAP_REPORT_PREFIX, &digits[start..], HEAP_REPORT_SUFFIX] {
+        buf[len..len + part.len()].copy_from_slice(part);
+        len += part.len();
+    }
+    &buf[..len]
+}
+
+/// Checks that the heap of `size` bytes starting at `base` is not empty and ends below
+/// [`MAX_MEMORY`].
+pub const fn check_heap_bounds(base: usize, size: usize) {
+    assert!(base != 0, "the heap cannot start at address 0");
+    assert!(base <= MAX_MEMORY && size <= MAX_MEMORY - base, "the heap must end below MAX_MEMORY");
+}
+
+/// The end of the program image, where the heap starts by default.
+fn image_end() -> usize {
     extern "C" {
         // https://lld.llvm.org/ELF/linker_script.html#sections-command
         static _end: u8;
     }
 
-    // Pointer to next heap address to use, or 0 if the heap has not yet been
-    // initialized.
-    static mut HEAP_POS: usize = 0;
-
-    // SAFETY: Single threaded, so nothing else can touch this while we're working.
-    let mut heap_pos = unsafe { HEAP_POS };
+    unsafe { (&_end) as *const u8 as usize }
+}
 
-    if heap_pos == 0 {
-        heap_pos = unsafe { (&_end) as *const u8 as usize };
+/// The next heap address to use, initializing the heap on first use.
+unsafe fn heap_pos() -> usize {
+    if HEAP_POS == 0 {
+        HEAP_POS = image_end();
+        HEAP_BASE = HEAP_POS;
     }
+    HEAP_POS
+}
+
+unsafe fn set_heap_pos(heap_pos: usize) {
+    HEAP_POS = heap_pos;
+    HEAP_TOP = HEAP_TOP.max(heap_pos);
+}
 
-    let offset = heap_pos & (align - 1);
+/// Allocates `bytes` bytes aligned to `align` at `heap_pos`, below `heap_end`, returning the address
+/// of the allocation and the new heap position.
+fn bump(heap_pos: usize, bytes: usize, align: usize, heap_end: usize) -> (usize, usize) {
+    let mut ptr = heap_pos;
+    let offset = ptr & (align - 1);
     if offset != 0 {
-        heap_pos += align - offset;
+        ptr += align - offset;
+    }
+
+    let (heap_pos, overflowed) = ptr.overflowing_add(bytes);
+    if overflowed || heap_end < heap_pos {
+        out_of_memory(heap_end);
+    }
+
+    (ptr, heap_pos)
+}
+
+/// Reports that the heap ending at `heap_end` is exhausted.
+///
+/// Inside the zkVM this halts with [`EXIT_OOM`](crate::exit::EXIT_OOM) rather than panicking, so
+/// the host can tell an exhausted heap apart from other panics, and so that nothing is allocated to
+/// format the message.
+#[cold]
+fn out_of_memory(heap_end: usize) -> ! {
+    #[cfg(target_os = "zkvm")]
+    {
+        let _ = heap_end;
+        let msg = b"Memory limit exceeded\n";
+        crate::syscalls::syscall_write(2, msg.as_ptr(), msg.len());
+        crate::syscalls::halt(crate::exit::EXIT_OOM);
+    }
+
+    #[cfg(not(target_os = "zkvm"))]
+    panic!("Memory limit exceeded ({heap_end:#x})");
+}
+
+/// The heap position after freeing the `bytes` bytes at `ptr`, which only moves if they end at
+/// `heap_pos`.
+fn rewind(heap_pos: usize, ptr: usize, bytes: usize) -> usize {
+    if ptr + bytes == heap_pos {
+        ptr
+    } else {
+        heap_pos
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        bump, check_heap_bounds, heap_report, rewind, sys_memzero, HEAP_REPORT_LEN, MAX_MEMORY,
+    };
+
+    const END: usize = MAX_MEMORY;
+
+    #[test]
+    fn rewind_most_recent_allocation() {
+        let start = 0x1000;
+        let (a, pos) = bump(start, 12, 4, END);
+        let (b, pos) = bump(pos, 8, 4, END);
+        assert_eq!((a, b), (0x1000, 0x100c));
+
+        // Freeing an older allocation is a no-op.
+        assert_eq!(rewind(pos, a, 12), pos);
+
+        // Freeing the most recent allocation lets the next one reuse its memory.
+        let pos = rewind(pos, b, 8);
+        assert_eq!(pos, b);
+        let (c, pos) = bump(pos, 16, 4, END);
+        assert_eq!(c, b);
+
+        let pos = rewind(pos, c, 16);
+        assert_eq!(rewind(pos, a, 12), start);
+    }
+
+    #[test]
+    fn fresh_allocation_skips_freed_memory() {
+        let start = 0x1000;
+        let (a, pos) = bump(start, 0x20, 4, END);
+        let (b, top) = bump(pos, 0x10, 4, END);
+        let pos = rewind(top, b, 0x10);
+
+        // A fresh allocation starts at the highest position ever reached, past both allocations,
+        // even though the second was freed.
+        let (fresh, _) = bump(pos.max(top), 0x10, 8, END);
+        assert!(fresh >= b + 0x10);
+        assert!(fresh >= a + 0x20);
+        assert_eq!(fresh % 8, 0);
+    }
+
+    #[test]
+    fn heap_peak_report() {
+        // 0x101 bytes, then 0x40 bytes after 7 bytes of alignment padding, which are freed and
+        // reused by a smaller allocation: the peak is 0x148 bytes.
+        let start = 0x1000;
+        let (_, pos) = bump(start, 0x101, 4, END);
+        let (ptr, top) = bump(pos, 0x40, 8, END);
+        let (_, pos) = bump(rewind(top, ptr, 0x40), 0x10, 8, END);
+        assert_eq!(top - start, 0x148);
+        assert!(pos < top);
+
+        let mut buf = [0; HEAP_REPORT_LEN];
+        assert_eq!(heap_report(top - start, &mut buf), b"heap peak: 328 bytes\n");
+        assert_eq!(heap_report(0, &mut buf), b"heap peak: 0 bytes\n");
+        assert_eq!(
+            heap_report(usize::MAX, &mut buf).len(),
+            format!("heap peak: {} bytes\n", usize::MAX).len()
+        );
     }
 
-    let ptr = heap_pos as *mut u8;
-    let (heap_pos, overflowed) = heap_pos.overflowing_add(bytes);
+    #[test]
+    #[should_panic(expected = "Memory limit exceeded (0x2000)")]
+    fn bump_past_heap_end() {
+        let (_, pos) = bump(0x1000, 0x1000, 4, 0x2000);
+        bump(pos, 1, 1, 0x2000);
+    }
+
+    #[test]
+    fn memzero() {
+        #[repr(align(4))]
+        struct Aligned([u8; 32]);
+
+        let mut buf = Aligned([0xAA; 32]);
+        // An unaligned start and length exercise both the byte and the word loops.
+        unsafe { sys_memzero(buf.0.as_mut_ptr().add(3), 22) };
+        assert_eq!(buf.0[..3], [0xAA; 3]);
+        assert_eq!(buf.0[3..25], [0; 22]);
+        assert_eq!(buf.0[25..], [0xAA; 7]);
 
-    if overflowed || MAX_MEMORY < heap_pos {
-        panic!("Memory limit exceeded (0x78000000)");
+        // An empty region is left untouched.
+        unsafe { sys_memzero(buf.0.as_mut_ptr().add(25), 0) };
+        assert_eq!(buf.0[25..], [0xAA; 7]);
+
+        // A whole aligned buffer only takes the word loop.
+        unsafe { sys_memzero(buf.0.as_mut_ptr(), buf.0.len()) };
+        assert_eq!(buf.0, [0; 32]);
+    }
+
+    #[test]
+    fn heap_bounds() {
+        check_heap_bounds(0x1000, 0x1000);
+        check_heap_bounds(MAX_MEMORY - 0x1000, 0x1000);
     }
 
-    unsafe { HEAP_POS = heap_pos };
-    ptr
+    #[test]
+    #[should_panic(expected = "the heap must end below MAX_MEMORY")]
+    fn heap_bounds_past_max_memory() {
+        check_heap_bounds(MAX_MEMORY - 0x1000, 0x1001);
+    }
 }
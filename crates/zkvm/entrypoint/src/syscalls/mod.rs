@@ -1,4 +1,5 @@
 mod bigint;
+mod blake3;
 mod bls12381;
 mod bn254;
 mod ed25519;
@@ -7,16 +8,23 @@ mod halt;
 mod io;
 mod keccak_permute;
 mod memory;
+mod mode;
+mod pc;
+mod poseidon2;
+mod profile;
 mod secp256k1;
 mod sha_compress;
 mod sha_extend;
+mod shard;
 mod sys;
+mod time;
 mod uint256_mul;
 mod unconstrained;
 #[cfg(feature = "verify")]
 mod verify;
 
 pub use bigint::*;
+pub use blake3::*;
 pub use bls12381::*;
 pub use bn254::*;
 pub use ed25519::*;
@@ -25,10 +33,16 @@ pub use halt::*;
 pub use io::*;
 pub use keccak_permute::*;
 pub use memory::*;
+pub use mode::*;
+pub use pc::*;
+pub use poseidon2::*;
+pub use profile::*;
 pub use secp256k1::*;
 pub use sha_compress::*;
 pub use sha_extend::*;
+pub use shard::*;
 pub use sys::*;
+pub use time::*;
 pub use uint256_mul::*;
 pub use unconstrained::*;
 #[cfg(feature = "verify")]
@@ -40,6 +54,11 @@ pub use verify::*;
 /// Halts the program.
 pub const HALT: u32 = 0x00_00_00_00;
 
+/// Halts the program and terminates the execution.
+///
+/// An alias of [`HALT`] in the proof: the two only differ in byte 3, which the CPU table ignores.
+pub const EXIT_GROUP: u32 = 0x01_00_00_00;
+
 /// Writes to a file descriptor. Currently only used for `STDOUT/STDERR`.
 pub const WRITE: u32 = 0x00_00_00_02;
 
@@ -94,6 +113,15 @@ pub const HINT_LEN: u32 = 0x00_00_00_F0;
 /// Executes `HINT_READ`.
 pub const HINT_READ: u32 = 0x00_00_00_F1;
 
+/// Executes `CONFIG_GET`.
+pub const CONFIG_GET: u32 = 0x00_00_00_F2;
+
+/// Executes `INPUT_READ_AT`.
+pub const INPUT_READ_AT: u32 = 0x00_00_00_F3;
+
+/// Executes `READ_STREAM`.
+pub const READ_STREAM: u32 = 0x00_00_00_F4;
+
 /// Executes `BLS12381_DECOMPRESS`.
 pub const BLS12381_DECOMPRESS: u32 = 0x00_00_01_1C;
 
@@ -141,3 +169,21 @@ pub const BN254_FP2_SUB: u32 = 0x00_01_01_2A;
 
 /// Executes the `BN254_FP2_MUL` precompile.
 pub const BN254_FP2_MUL: u32 = 0x00_01_01_2B;
+
+/// Executes the `POSEIDON2_PERMUTE` precompile.
+pub const POSEIDON2_PERMUTE: u32 = 0x00_01_01_2C;
+
+/// Executes the `BLAKE3_COMPRESS` precompile.
+pub const BLAKE3_COMPRESS: u32 = 0x00_01_01_2D;
+
+/// Executes the `PROFILE_BEGIN` syscall.
+pub const PROFILE_BEGIN: u32 = 0x00_00_00_30;
+
+/// Executes the `PROFILE_END` syscall.
+pub const PROFILE_END: u32 = 0x00_00_00_31;
+
+/// Executes the `SHARD_INDEX` syscall.
+pub const SHARD_INDEX: u32 = 0x00_00_00_33;
+
+/// Executes the `READ_PC` syscall.
+pub const READ_PC: u32 = 0x00_00_00_34;
@@ -0,0 +1,27 @@
+#[cfg(target_os = "zkvm")]
+use crate::syscalls::{sys_alloc_fresh, sys_config_get};
+
+/// The reserved config key the executor answers with the proving mode. Must match
+/// `sp1_core_executor::PROVING_CONFIG_KEY`.
+pub const PROVING_CONFIG_KEY: &str = "sp1.proving";
+
+/// Returns whether the execution is being proven, as opposed to only executed.
+///
+/// The flag is a hint from the executor, not constrained by the proof. Programs may use it to
+/// choose how much work to do, e.g. running extra debug checks only when executing, but it must not
+/// change the public values they commit. Otherwise the values proven would differ from the values
+/// seen when executing, and the proof would not attest to what the program computes.
+#[no_mangle]
+pub extern "C" fn sys_is_proving() -> bool {
+    #[cfg(target_os = "zkvm")]
+    unsafe {
+        // Like `CONFIG_GET` requires, the buffer is word aligned and was never accessed.
+        let key = PROVING_CONFIG_KEY.as_bytes();
+        let buf = sys_alloc_fresh(4, 4);
+        let len = sys_config_get(key.as_ptr(), key.len(), buf, 4);
+        len == 1 && core::ptr::read_volatile(buf) != 0
+    }
+
+    #[cfg(not(target_os = "zkvm"))]
+    unreachable!()
+}
@@ -0,0 +1,26 @@
+#[cfg(target_os = "zkvm")]
+use core::arch::asm;
+
+/// Returns the program counter of the `ecall` instruction inside this function, that is the
+/// address of the syscall site rather than of the instruction execution resumes at, which is 4
+/// bytes further.
+///
+/// The returned address lies within this function, so it identifies the call site only once
+/// combined with the return address of the caller. It can be mapped back to a location with the
+/// symbols of the guest ELF.
+#[no_mangle]
+pub extern "C" fn sys_read_pc() -> u32 {
+    #[cfg(target_os = "zkvm")]
+    unsafe {
+        let pc;
+        asm!(
+            "ecall",
+            in("t0") crate::syscalls::READ_PC,
+            lateout("t0") pc,
+        );
+        pc
+    }
+
+    #[cfg(not(target_os = "zkvm"))]
+    unreachable!()
+}
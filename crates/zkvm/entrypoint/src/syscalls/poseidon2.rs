@@ -0,0 +1,28 @@
+#[cfg(target_os = "zkvm")]
+use core::arch::asm;
+
+/// Executes the Poseidon2 permutation over `BabyBear` on the given state.
+///
+/// Each word of the state must hold a canonical `BabyBear` element. The only supported `width`
+/// is 16.
+///
+/// ### Safety
+///
+/// The caller must ensure that `state` is valid pointer to `width` words of data that is aligned
+/// along a four byte boundary.
+#[allow(unused_variables)]
+#[no_mangle]
+pub extern "C" fn sys_poseidon2_permute(state: *mut u32, width: usize) {
+    #[cfg(target_os = "zkvm")]
+    unsafe {
+        asm!(
+            "ecall",
+            in("t0") crate::syscalls::POSEIDON2_PERMUTE,
+            in("a0") state,
+            in("a1") width
+        );
+    }
+
+    #[cfg(not(target_os = "zkvm"))]
+    unreachable!()
+}
@@ -0,0 +1,42 @@
+#[cfg(target_os = "zkvm")]
+use core::arch::asm;
+
+/// Opens a named profiling span.
+///
+/// When profiling is enabled on the host, the executor records the label together with the
+/// current clock so that a flamegraph of the guest execution can be produced. Otherwise, the
+/// executor ignores the call.
+#[allow(unused_variables)]
+#[no_mangle]
+pub extern "C" fn sys_profile_begin(label_ptr: *const u8, label_len: usize) {
+    #[cfg(target_os = "zkvm")]
+    unsafe {
+        asm!(
+            "ecall",
+            in("t0") crate::syscalls::PROFILE_BEGIN,
+            in("a0") label_ptr,
+            in("a1") label_len,
+        );
+    }
+
+    #[cfg(not(target_os = "zkvm"))]
+    unreachable!()
+}
+
+/// Closes the named profiling span opened by [`sys_profile_begin`] with the same label.
+#[allow(unused_variables)]
+#[no_mangle]
+pub extern "C" fn sys_profile_end(label_ptr: *const u8, label_len: usize) {
+    #[cfg(target_os = "zkvm")]
+    unsafe {
+        asm!(
+            "ecall",
+            in("t0") crate::syscalls::PROFILE_END,
+            in("a0") label_ptr,
+            in("a1") label_len,
+        );
+    }
+
+    #[cfg(not(target_os = "zkvm"))]
+    unreachable!()
+}
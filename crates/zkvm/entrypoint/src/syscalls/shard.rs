@@ -0,0 +1,25 @@
+#[cfg(target_os = "zkvm")]
+use core::arch::asm;
+
+/// Returns the index of the current execution shard, starting at 1.
+///
+/// The executor splits long executions into shards of `shard_size` cycles, so the index depends
+/// on the sharding options used to prove the program. It is only meaningful during proving:
+/// under pure execution the host is free to pick any shard size, and the value should not affect
+/// the program's outputs.
+#[no_mangle]
+pub extern "C" fn sys_shard_index() -> u32 {
+    #[cfg(target_os = "zkvm")]
+    unsafe {
+        let shard;
+        asm!(
+            "ecall",
+            in("t0") crate::syscalls::SHARD_INDEX,
+            lateout("t0") shard,
+        );
+        shard
+    }
+
+    #[cfg(not(target_os = "zkvm"))]
+    unreachable!()
+}
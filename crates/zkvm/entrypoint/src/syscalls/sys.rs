@@ -3,7 +3,10 @@ use std::sync::Mutex;
 use lazy_static::lazy_static;
 use rand::{rngs::StdRng, Rng, SeedableRng};
 
-use crate::syscalls::{syscall_halt, syscall_write};
+use crate::{
+    exit::EXIT_PANIC,
+    syscalls::{halt, sys_commit_to_channel, syscall_write},
+};
 
 /// The random number generator seed for the zkVM.
 ///
@@ -35,11 +38,26 @@ pub unsafe extern "C" fn sys_rand(recv_buf: *mut u8, words: usize) {
     }
 }
 
+/// Writes the panic message to stderr and halts with [`EXIT_PANIC`].
 #[allow(clippy::missing_safety_doc)]
 #[no_mangle]
 pub unsafe extern "C" fn sys_panic(msg_ptr: *const u8, len: usize) -> ! {
     sys_write(2, msg_ptr, len);
-    syscall_halt(1);
+    halt(EXIT_PANIC);
+}
+
+/// Aborts the program with the given error code and message.
+///
+/// The message is committed to the public values before the exit code is set, so the reason for
+/// the failure is covered by the public values digest the program halts with.
+///
+/// # Safety
+///
+/// Make sure that `msg_ptr` points to at least `msg_len` readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn sys_abort(code: u32, msg_ptr: *const u8, msg_len: usize) -> ! {
+    sys_commit_to_channel(0, msg_ptr, msg_len);
+    halt(code);
 }
 
 #[allow(unused_variables)]
@@ -0,0 +1,36 @@
+#[cfg(target_os = "zkvm")]
+use crate::syscalls::{sys_alloc_fresh, sys_config_get, syscall_write};
+
+pub use sp1_primitives::consts::TIME_CONFIG_KEY;
+
+/// Commits the timestamp provided by the host to the public values, as 8 little-endian bytes, and
+/// returns it.
+///
+/// The host sets the timestamp with `SP1ContextBuilder::time`. It is attested by the host, not
+/// trustless: the prover picks the value, and the proof only shows that the program committed it.
+/// Verifiers that rely on it must trust the prover or check it against another source.
+///
+/// # Panics
+///
+/// Panics if the host did not provide a timestamp.
+#[no_mangle]
+pub extern "C" fn sys_commit_time() -> u64 {
+    #[cfg(target_os = "zkvm")]
+    unsafe {
+        const FD_PUBLIC_VALUES: u32 = 3;
+
+        // Like `CONFIG_GET` requires, the buffer is word aligned and was never accessed.
+        let key = TIME_CONFIG_KEY.as_bytes();
+        let buf = sys_alloc_fresh(8, 4);
+        let len = sys_config_get(key.as_ptr(), key.len(), buf, 8);
+        assert_eq!(len, 8, "the host did not provide a timestamp");
+
+        // `WRITE` does not see memory the program has not loaded yet, so commit a copy.
+        let time = core::ptr::read_volatile(buf as *const [u8; 8]);
+        syscall_write(FD_PUBLIC_VALUES, time.as_ptr(), time.len());
+        u64::from_le_bytes(time)
+    }
+
+    #[cfg(not(target_os = "zkvm"))]
+    unreachable!()
+}
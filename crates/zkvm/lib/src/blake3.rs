@@ -0,0 +1,10 @@
+use crate::sys_blake3;
+
+/// Hashes `input` with BLAKE3, using the `BLAKE3_COMPRESS` precompile for every compression.
+pub fn blake3(input: &[u8]) -> [u8; 32] {
+    let mut out = [0; 32];
+    unsafe {
+        sys_blake3(input.as_ptr(), input.len(), out.as_mut_ptr());
+    }
+    out
+}
@@ -1,9 +1,18 @@
 #![allow(unused_unsafe)]
-use crate::{syscall_hint_len, syscall_hint_read, syscall_write};
+use crate::{
+    sha256::Sha256Hasher, sys_abort, sys_alloc_aligned, sys_alloc_fresh, sys_commit_exit,
+    sys_commit_time, sys_commit_to_channel, sys_commit_vkey, sys_config_get, sys_exit_group,
+    sys_input_read_at, sys_is_proving, sys_public_values_digest, sys_rand, sys_read_stream,
+    syscall_hint_len, syscall_hint_read, syscall_write,
+};
+use p3_baby_bear::BabyBear;
+use p3_field::{AbstractField, PrimeField32};
 use serde::{de::DeserializeOwned, Serialize};
+use sp1_primitives::consts::{exit::EXIT_RAND_EXHAUSTED, PV_DIGEST_NUM_WORDS};
 use std::{
     alloc::Layout,
-    io::{Result, Write},
+    io::{Read, Result, Write},
+    ptr::addr_of_mut,
 };
 
 /// The file descriptor for public values.
@@ -27,6 +36,9 @@ impl Write for SyscallWriter {
         unsafe {
             syscall_write(self.fd, write_buf, nbytes);
         }
+        if self.fd == FD_PUBLIC_VALUES {
+            count_committed(nbytes);
+        }
         Ok(nbytes)
     }
 
@@ -35,6 +47,38 @@ impl Write for SyscallWriter {
     }
 }
 
+/// The length `syscall_hint_len` returns once every element of the input stream has been read.
+const HINT_LEN_END_OF_STREAM: usize = sp1_primitives::consts::HINT_LEN_END_OF_STREAM as usize;
+
+/// An error reading from the input stream.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IoError {
+    /// Every element of the input stream has already been read.
+    EndOfStream,
+    /// The next element of the input stream does not have the requested length. The element is
+    /// left in the stream.
+    LengthMismatch { expected: usize, actual: usize },
+    /// A word read as a field element is not the canonical value of one, i.e. it is at least the
+    /// field's order.
+    NonCanonicalField(u32),
+}
+
+impl std::fmt::Display for IoError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            IoError::EndOfStream => write!(f, "the input stream is exhausted"),
+            IoError::LengthMismatch { expected, actual } => {
+                write!(f, "expected an input of {expected} bytes, got {actual}")
+            }
+            IoError::NonCanonicalField(value) => {
+                write!(f, "{value} is not a canonical field element")
+            }
+        }
+    }
+}
+
+impl std::error::Error for IoError {}
+
 /// Read a buffer from the input stream.
 ///
 /// ### Examples
@@ -42,29 +86,302 @@ impl Write for SyscallWriter {
 /// let data: Vec<u8> = sp1_zkvm::io::read_vec();
 /// ```
 pub fn read_vec() -> Vec<u8> {
-    // Round up to the nearest multiple of 4 so that the memory allocated is in whole words
     let len = unsafe { syscall_hint_len() };
+    assert_ne!(len, HINT_LEN_END_OF_STREAM, "failed reading stdin: {}", IoError::EndOfStream);
+    read_hint(len)
+}
+
+/// Read the next element of the input stream into `buf`, which must have exactly its length.
+///
+/// Unlike [`read_vec`], a missing or differently sized input is reported as an error instead of
+/// aborting the program. In that case `buf` is left untouched.
+///
+/// ### Examples
+/// ```ignore
+/// let mut header = [0u8; 32];
+/// sp1_zkvm::io::read_exact(&mut header).expect("missing header");
+/// ```
+pub fn read_exact(buf: &mut [u8]) -> std::result::Result<(), IoError> {
+    let len = unsafe { syscall_hint_len() };
+    check_hint_len(len, buf.len())?;
+    buf.copy_from_slice(&read_hint(len));
+    Ok(())
+}
+
+/// Checks that the next element of the input stream, of length `len`, can be read into a buffer of
+/// `expected` bytes.
+fn check_hint_len(len: usize, expected: usize) -> std::result::Result<(), IoError> {
+    if len == HINT_LEN_END_OF_STREAM {
+        Err(IoError::EndOfStream)
+    } else if len != expected {
+        Err(IoError::LengthMismatch { expected, actual: len })
+    } else {
+        Ok(())
+    }
+}
+
+/// Read a field element from the next element of the input stream, written by
+/// `SP1Stdin::write_fields` with a single field element.
+///
+/// The element must be a single little-endian 32-bit word holding a canonical value. A non-canonical
+/// word is rejected rather than reduced, so every field element has exactly one encoding. In that
+/// case the element is still consumed.
+///
+/// ### Examples
+/// ```ignore
+/// let x = sp1_zkvm::io::read_field().expect("invalid field element");
+/// ```
+pub fn read_field() -> std::result::Result<BabyBear, IoError> {
+    Ok(read_fields(1)?[0])
+}
+
+/// Read `n` field elements from the next element of the input stream, written by
+/// `SP1Stdin::write_fields`.
+///
+/// The element must hold exactly `n` words, decoded as by [`decode_fields`].
+///
+/// ### Examples
+/// ```ignore
+/// let coeffs = sp1_zkvm::io::read_fields(8).expect("invalid coefficients");
+/// ```
+pub fn read_fields(n: usize) -> std::result::Result<Vec<BabyBear>, IoError> {
+    let mut bytes = vec![0; 4 * n];
+    read_exact(&mut bytes)?;
+    decode_fields(&bytes)
+}
+
+/// Decodes field elements from the little-endian 32-bit words of their canonical values, the
+/// inverse of [`encode_fields`].
+///
+/// Trailing bytes that do not make up a whole word are ignored.
+pub fn decode_fields(bytes: &[u8]) -> std::result::Result<Vec<BabyBear>, IoError> {
+    bytes
+        .chunks_exact(4)
+        .map(|word| {
+            let value = u32::from_le_bytes(word.try_into().unwrap());
+            if value < BabyBear::ORDER_U32 {
+                Ok(BabyBear::from_canonical_u32(value))
+            } else {
+                Err(IoError::NonCanonicalField(value))
+            }
+        })
+        .collect()
+}
+
+/// Read the value of `key` in the config provided by the host, or an empty buffer if the key is
+/// absent.
+///
+/// ### Examples
+/// ```ignore
+/// let chain_id = sp1_zkvm::io::config_get("chain_id");
+/// ```
+pub fn config_get(key: &str) -> Vec<u8> {
+    let len = unsafe { sys_config_get(key.as_ptr(), key.len(), std::ptr::null_mut(), 0) };
+    if len == 0 {
+        return Vec::new();
+    }
+    read_fresh(len, |ptr| unsafe {
+        sys_config_get(key.as_ptr(), key.len(), ptr, len);
+        len
+    })
+}
+
+/// Reads up to `len` bytes of the input blob provided by the host, starting at `offset`.
+///
+/// The blob is set with `SP1ContextBuilder::input_blob` and is read-only, so unlike [`read_vec`]
+/// this does not consume anything, and any range can be read again. The range is clamped to the
+/// end of the blob, so the result is shorter than `len` if it extends past the end, and empty if
+/// `offset` is past the end.
+///
+/// ### Examples
+/// ```ignore
+/// let header = sp1_zkvm::io::read_input_at(0, 32);
+/// let record = sp1_zkvm::io::read_input_at(1024, 64);
+/// ```
+pub fn read_input_at(offset: usize, len: usize) -> Vec<u8> {
+    read_fresh(len, |ptr| unsafe { sys_input_read_at(offset, ptr, len) })
+}
+
+/// Reads up to `len` bytes from the named input stream `stream_id` provided by the host.
+///
+/// The streams are set with `SP1ContextBuilder::input_stream`, and each is read sequentially from
+/// its own position, so reads from different streams can be interleaved in any order. The result
+/// is shorter than `len` at the end of a stream, and empty for a stream the host did not provide.
+///
+/// ### Examples
+/// ```ignore
+/// let header = sp1_zkvm::io::read_stream(0, 32);
+/// let witness = sp1_zkvm::io::read_stream(1, 1024);
+/// ```
+pub fn read_stream(stream_id: u32, len: usize) -> Vec<u8> {
+    read_fresh(len, |ptr| unsafe { sys_read_stream(stream_id, ptr, len) })
+}
+
+/// Reads the next element of the input stream, which has length `len`.
+fn read_hint(len: usize) -> Vec<u8> {
+    read_fresh(len, |ptr| unsafe {
+        syscall_hint_read(ptr, len);
+        len
+    })
+}
+
+/// Allocates a buffer of `len` bytes from never-used memory and lets `read` hint its contents,
+/// returning the number of bytes it read, at most `len`.
+fn read_fresh(len: usize, read: impl FnOnce(*mut u8) -> usize) -> Vec<u8> {
+    // Round up to the nearest multiple of 4 so that the memory allocated is in whole words
     let capacity = (len + 3) / 4 * 4;
 
     // Allocate a buffer of the required length that is 4 byte aligned
     let layout = Layout::from_size_align(capacity, 4).expect("vec is too large");
-    let ptr = unsafe { std::alloc::alloc(layout) };
+    let ptr = unsafe { sys_alloc_fresh(layout.size(), layout.align()) };
 
     // SAFETY:
-    // 1. `ptr` was allocated using alloc
-    // 2. We assuume that the VM global allocator doesn't dealloc
+    // 1. `ptr` was allocated by the VM heap, which backs the global allocator
+    // 2. The global allocator only frees the most recent allocation, which is fine for `ptr`
     // 3/6. Size is correct from above
     // 4/5. Length is 0
     // 7. Layout::from_size_align already checks this
     let mut vec = unsafe { Vec::from_raw_parts(ptr, 0, capacity) };
 
     // Read the vec into uninitialized memory. The syscall assumes the memory is uninitialized,
-    // which should be true because the allocator does not dealloc, so a new alloc should be fresh.
+    // which holds because `sys_alloc_fresh` never hands out memory that was allocated before.
+    let read = read(ptr);
+    assert!(read <= len);
+    unsafe { vec.set_len(read) };
+    hash_input(&vec);
+    vec
+}
+
+/// The hasher of the input read since [`enable_input_commitment`] was called, if it was.
+static mut INPUT_HASHER: Option<Sha256Hasher> = None;
+
+/// Hashes an input element that was just read, prefixed with its length, if
+/// [`enable_input_commitment`] was called.
+fn hash_input(bytes: &[u8]) {
+    if let Some(hasher) = unsafe { (*addr_of_mut!(INPUT_HASHER)).as_mut() } {
+        hasher.update(&(bytes.len() as u64).to_le_bytes());
+        hasher.update(bytes);
+    }
+}
+
+/// Starts hashing the input the program reads, so that [`commit_input_commitment`] can bind the
+/// proof to the input it consumed.
+///
+/// Every element read from now on is hashed in the order it is read, as its length in 8
+/// little-endian bytes followed by its bytes. The length prefix binds how the input is split into
+/// elements, so a host cannot re-split the same bytes into different values under the same
+/// digest. Calling this again has no effect.
+///
+/// The elements are those read with [`read`], [`read_vec`], [`read_exact`], [`read_field`],
+/// [`read_fields`] and [`read_hint_stream`] from the input stream, the ranges read with
+/// [`read_input_at`], the chunks read with [`read_stream`], and the values read with
+/// [`config_get`]. An element [`read_hint_stream`] deserializes from is one element of the input
+/// stream, not one deserialized value.
+///
+/// ### Examples
+/// ```ignore
+/// sp1_zkvm::io::enable_input_commitment();
+/// let data: Vec<u8> = sp1_zkvm::io::read_vec();
+/// sp1_zkvm::io::commit_input_commitment();
+/// ```
+pub fn enable_input_commitment() {
+    unsafe { (*addr_of_mut!(INPUT_HASHER)).get_or_insert_with(Sha256Hasher::new) };
+}
+
+/// Commits the SHA-256 digest of the input read since [`enable_input_commitment`] to the public
+/// values stream, returns it, and stops hashing input.
+///
+/// A verifier that knows the input recomputes the digest as the SHA-256 hash of the elements the
+/// program read, each encoded as its length in 8 little-endian bytes followed by its bytes, see
+/// [`enable_input_commitment`].
+///
+/// # Panics
+///
+/// Panics if [`enable_input_commitment`] was not called.
+pub fn commit_input_commitment() -> [u8; 32] {
+    let hasher = unsafe { (*addr_of_mut!(INPUT_HASHER)).take() };
+    let digest = hasher.expect("the input commitment is not enabled").finalize();
+    commit_slice(&digest);
+    digest
+}
+
+/// Allocates a region of `len` bytes, aligned to 8 bytes, with every byte set to zero. The region
+/// is never freed.
+///
+/// The zeroing is done with ordinary stores, so unlike the initial values of fresh memory, which
+/// the proof does not constrain, the region is proven to be zero.
+///
+/// ### Examples
+/// ```ignore
+/// let scratch = sp1_zkvm::io::alloc_zeroed_region(1 << 20);
+/// ```
+pub fn alloc_zeroed_region(len: usize) -> *mut u8 {
     unsafe {
-        syscall_hint_read(ptr, len);
-        vec.set_len(len);
+        let region = sys_alloc_aligned(len, 8);
+        core::ptr::write_bytes(region, 0, len);
+        region
+    }
+}
+
+/// Read a deserializable object that spans one or more consecutive elements of the input stream,
+/// as written by `SP1Stdin::write_chunked`.
+///
+/// Elements are pulled from the input stream only as the deserializer needs them, so a large object
+/// never has to be buffered as a whole before being deserialized. Any bytes left in the last element
+/// read are discarded. If the input stream ends before the object is complete, the elements read so
+/// far are consumed and [`IoError::EndOfStream`] is returned.
+///
+/// ### Examples
+/// ```ignore
+/// let data: Vec<u64> = sp1_zkvm::io::read_hint_stream().expect("truncated input");
+/// ```
+pub fn read_hint_stream<T: DeserializeOwned>() -> std::result::Result<T, IoError> {
+    deserialize_stream(std::iter::from_fn(|| {
+        let len = unsafe { syscall_hint_len() };
+        (len != HINT_LEN_END_OF_STREAM).then(|| read_hint(len))
+    }))
+}
+
+/// Deserializes `T` from the concatenation of `chunks`, pulling each chunk only when needed.
+fn deserialize_stream<T: DeserializeOwned>(
+    chunks: impl Iterator<Item = Vec<u8>>,
+) -> std::result::Result<T, IoError> {
+    let mut reader = ChunkReader { chunks, chunk: Vec::new(), pos: 0, exhausted: false };
+    match bincode::deserialize_from(&mut reader) {
+        Ok(value) => Ok(value),
+        Err(_) if reader.exhausted => Err(IoError::EndOfStream),
+        Err(err) => panic!("deserialization failed: {err}"),
+    }
+}
+
+/// A reader over the concatenation of a sequence of chunks.
+struct ChunkReader<I> {
+    chunks: I,
+    chunk: Vec<u8>,
+    pos: usize,
+    /// Whether a read found no chunks left.
+    exhausted: bool,
+}
+
+impl<I: Iterator<Item = Vec<u8>>> Read for ChunkReader<I> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        while self.pos == self.chunk.len() {
+            match self.chunks.next() {
+                Some(chunk) => {
+                    self.chunk = chunk;
+                    self.pos = 0;
+                }
+                None => {
+                    self.exhausted = true;
+                    return Ok(0);
+                }
+            }
+        }
+        let n = buf.len().min(self.chunk.len() - self.pos);
+        buf[..n].copy_from_slice(&self.chunk[self.pos..self.pos + n]);
+        self.pos += n;
+        Ok(n)
     }
-    vec
 }
 
 /// Read a deserializable object from the input stream.
@@ -109,6 +426,47 @@ pub fn commit<T: Serialize>(value: &T) {
     bincode::serialize_into(writer, value).expect("serialization failed");
 }
 
+/// The number of bytes committed to the public values stream so far, see [`committed_len`].
+static mut COMMITTED_LEN: usize = 0;
+
+/// Counts `len` bytes that were just committed to the public values stream.
+fn count_committed(len: usize) {
+    unsafe { *addr_of_mut!(COMMITTED_LEN) += len };
+}
+
+/// Returns the number of bytes committed to the public values stream so far.
+///
+/// This counts the bytes committed through this module, with [`commit`], [`commit_slice`],
+/// [`commit_fields`], the `commit_u*` helpers, [`commit_to_channel`] on channel 0,
+/// [`commit_vkey`], [`commit_time`] and [`write`] to [`FD_PUBLIC_VALUES`]. The digests of the
+/// extra channels and the committed exit code, appended when the program halts, are not counted.
+///
+/// ### Examples
+/// ```ignore
+/// sp1_zkvm::io::commit_slice(&[1, 2, 3, 4]);
+/// assert_eq!(sp1_zkvm::io::committed_len(), 4);
+/// ```
+pub fn committed_len() -> usize {
+    unsafe { *addr_of_mut!(COMMITTED_LEN) }
+}
+
+/// Commits [`committed_len`] to the public values stream as 8 little-endian bytes, and returns it.
+///
+/// Committed last, this trailer lets the host split variable-length public values without
+/// ambiguity, and the public values digest binds it like the rest of the public values. The
+/// returned length does not include the trailer itself.
+///
+/// ### Examples
+/// ```ignore
+/// sp1_zkvm::io::commit(&items);
+/// sp1_zkvm::io::commit_len_trailer();
+/// ```
+pub fn commit_len_trailer() -> usize {
+    let len = committed_len();
+    commit_slice(&(len as u64).to_le_bytes());
+    len
+}
+
 /// Commit bytes to the public values stream.
 ///
 /// ### Examples
@@ -121,6 +479,325 @@ pub fn commit_slice(buf: &[u8]) {
     my_writer.write_all(buf).unwrap();
 }
 
+/// Commit field elements to the public values stream.
+///
+/// Each element is committed as its canonical value in `[0, p)`, encoded as a little-endian
+/// 32-bit word (see [`encode_fields`]), so the public values can be read back on the host with
+/// `SP1PublicValues::read_fields`.
+///
+/// ### Examples
+/// ```ignore
+/// use p3_baby_bear::BabyBear;
+/// use p3_field::AbstractField;
+///
+/// sp1_zkvm::io::commit_fields(&[BabyBear::one(), BabyBear::two()]);
+/// ```
+pub fn commit_fields(fields: &[BabyBear]) {
+    commit_slice(&encode_fields(fields));
+}
+
+/// Encodes field elements as the little-endian 32-bit words of their canonical values.
+///
+/// This is the encoding used by [`commit_fields`].
+pub fn encode_fields(fields: &[BabyBear]) -> Vec<u8> {
+    fields.iter().flat_map(|field| field.as_canonical_u32().to_le_bytes()).collect()
+}
+
+macro_rules! commit_int {
+    ($($ty:ty: $le:ident, $be:ident;)*) => {$(
+        #[doc = concat!(
+            "Commits a `", stringify!($ty), "` to the public values stream as its little-endian ",
+            "bytes, which the host decodes with `", stringify!($ty), "::from_le_bytes`."
+        )]
+        pub fn $le(value: $ty) {
+            commit_slice(&value.to_le_bytes());
+        }
+
+        #[doc = concat!(
+            "Commits a `", stringify!($ty), "` to the public values stream as its big-endian ",
+            "bytes, which the host decodes with `", stringify!($ty), "::from_be_bytes`."
+        )]
+        pub fn $be(value: $ty) {
+            commit_slice(&value.to_be_bytes());
+        }
+    )*};
+}
+
+commit_int! {
+    u16: commit_u16_le, commit_u16_be;
+    u32: commit_u32_le, commit_u32_be;
+    u64: commit_u64_le, commit_u64_be;
+    u128: commit_u128_le, commit_u128_be;
+}
+
+/// Commits the root of a Merkle tree over `leaves` to the public values stream and returns it, so
+/// that the leaves can be disclosed individually and checked against the root.
+///
+/// The tree is the Merkle tree hash of RFC 6962, with SHA-256 as the hash. Leaves and inner nodes
+/// are hashed with distinct prefixes, so that a leaf can never be mistaken for a node:
+/// - each leaf is hashed as `sha256(0x00 || leaf)`;
+/// - each pair of adjacent nodes is hashed into their parent as `sha256(0x01 || left || right)`.
+///
+/// The tree is built bottom-up, level by level, starting from the hashed leaves. If a level has an
+/// odd number of nodes, its last node is promoted to the next level unchanged, which gives the same
+/// root as the RFC's split at the largest power of two. The root of a single leaf is its leaf hash.
+/// The host can recompute the root with [`merkle_root`].
+///
+/// # Panics
+///
+/// Panics if `leaves` is empty.
+///
+/// ### Examples
+/// ```ignore
+/// let leaves = [[1u8; 32], [2u8; 32], [3u8; 32]];
+/// let root = sp1_zkvm::io::commit_merkle(&leaves);
+/// ```
+pub fn commit_merkle(leaves: &[[u8; 32]]) -> [u8; 32] {
+    let root = merkle_root(leaves, crate::sha256::sha256);
+    commit_slice(&root);
+    root
+}
+
+/// The prefix of a hashed Merkle leaf, see [`commit_merkle`].
+pub const MERKLE_LEAF_PREFIX: u8 = 0x00;
+
+/// The prefix of a hashed Merkle node, see [`commit_merkle`].
+pub const MERKLE_NODE_PREFIX: u8 = 0x01;
+
+/// The root of the Merkle tree over `leaves` that [`commit_merkle`] builds, where `hash` is the
+/// SHA-256 of a message.
+///
+/// # Panics
+///
+/// Panics if `leaves` is empty.
+pub fn merkle_root(leaves: &[[u8; 32]], hash: impl Fn(&[u8]) -> [u8; 32]) -> [u8; 32] {
+    assert!(!leaves.is_empty(), "a Merkle tree needs at least one leaf");
+    let mut level: Vec<[u8; 32]> = leaves
+        .iter()
+        .map(|leaf| {
+            let mut message = [0u8; 33];
+            message[0] = MERKLE_LEAF_PREFIX;
+            message[1..].copy_from_slice(leaf);
+            hash(&message)
+        })
+        .collect();
+    while level.len() > 1 {
+        level = level
+            .chunks(2)
+            .map(|pair| match pair {
+                [left, right] => {
+                    let mut message = [0u8; 65];
+                    message[0] = MERKLE_NODE_PREFIX;
+                    message[1..33].copy_from_slice(left);
+                    message[33..].copy_from_slice(right);
+                    hash(&message)
+                }
+                [last] => *last,
+                _ => unreachable!(),
+            })
+            .collect();
+    }
+    level[0]
+}
+
+/// Chains this program's public values to the public values digest of a previous proof.
+///
+/// The digest is committed as the first 32 bytes of the public values, so this must be called
+/// before anything else is committed. The digest this program commits to at halt is then
+/// `sha256(prev_digest || outputs)`, and a verifier holding the outputs of every program in the
+/// chain can recompute each digest from the previous one. Unlike deferred proofs, this only links
+/// the hashes: the previous proof still has to be verified separately.
+///
+/// ### Examples
+/// ```ignore
+/// let prev_digest: [u8; 32] = sp1_zkvm::io::read();
+/// sp1_zkvm::io::fold_commitment(prev_digest);
+/// sp1_zkvm::io::commit(&42u32);
+/// ```
+pub fn fold_commitment(prev_digest: [u8; 32]) {
+    commit_slice(&prev_digest);
+}
+
+/// Commit bytes to the given public values channel.
+///
+/// Channel 0 is the public values stream, so `commit_to_channel(0, data)` is the same as
+/// `commit_slice(data)`. The other channels are hashed independently, and when the program halts
+/// the SHA-256 digests of channels `1..NUM_PUBLIC_VALUES_CHANNELS` are appended to the public
+/// values stream in channel order.
+///
+/// ### Examples
+/// ```ignore
+/// sp1_zkvm::io::commit_to_channel(1, &[1, 2, 3, 4]);
+/// ```
+pub fn commit_to_channel(channel: u8, data: &[u8]) {
+    unsafe { sys_commit_to_channel(channel, data.as_ptr(), data.len()) };
+    if channel == 0 {
+        count_committed(data.len());
+    }
+}
+
+/// Commits the verification key digest `vkey` of a program to the public values stream, as the
+/// little-endian bytes of its words.
+///
+/// Aggregation programs commit the verification key of each proof they verify with
+/// `verify_proof`, so that the outer proof binds which programs it aggregated. The verifier
+/// recomputes the public values from the expected verification keys and compares the digests.
+///
+/// ### Examples
+/// ```ignore
+/// let vkey: [u32; 8] = sp1_zkvm::io::read();
+/// sp1_zkvm::io::commit_vkey(&vkey);
+/// ```
+pub fn commit_vkey(vkey: &[u32; PV_DIGEST_NUM_WORDS]) {
+    unsafe { sys_commit_vkey(vkey) };
+    count_committed(std::mem::size_of_val(vkey));
+}
+
+/// Commits the timestamp provided by the host to the public values stream, as 8 little-endian
+/// bytes, and returns it.
+///
+/// The timestamp is attested by the host, not proven, so verifiers that rely on it must trust the
+/// prover.
+///
+/// ### Examples
+/// ```ignore
+/// let proven_at = sp1_zkvm::io::commit_time();
+/// ```
+pub fn commit_time() -> u64 {
+    let time = unsafe { sys_commit_time() };
+    count_committed(std::mem::size_of_val(&time));
+    time
+}
+
+/// Returns whether the execution is being proven, as opposed to only executed.
+///
+/// The flag is not constrained by the proof. Use it only to decide how much work to do, for
+/// example to run extra debug assertions when executing and skip them when proving. It must never
+/// change the committed public values: if they differed between modes, the proof would attest to
+/// values that executing the program does not produce.
+///
+/// ### Examples
+/// ```ignore
+/// if !sp1_zkvm::io::is_proving() {
+///     assert!(expensive_self_check());
+/// }
+/// ```
+pub fn is_proving() -> bool {
+    unsafe { sys_is_proving() }
+}
+
+/// The number of random values [`rand_below`] draws before giving up.
+///
+/// Each draw is rejected with probability less than 1/2, so an honest host exhausts them with
+/// probability less than `2^-64`.
+pub const RAND_BELOW_MAX_ITERATIONS: u32 = 64;
+
+/// Returns a uniformly random value in `[0, n)`, drawn from the host's random values.
+///
+/// Values that would bias the result are rejected and drawn again, at most
+/// [`RAND_BELOW_MAX_ITERATIONS`] times, after which the program halts with the reserved exit code
+/// [`EXIT_RAND_EXHAUSTED`] rather than loop for as long as a misbehaving host keeps it going. Like
+/// `sys_rand`, the values are not constrained by the proof.
+///
+/// # Panics
+///
+/// Panics if `n` is zero.
+///
+/// ### Examples
+/// ```ignore
+/// let die = sp1_zkvm::io::rand_below(6) + 1;
+/// ```
+pub fn rand_below(n: u32) -> u32 {
+    let next = || {
+        let mut buf = [0u8; 4];
+        unsafe { sys_rand(buf.as_mut_ptr(), buf.len()) };
+        u32::from_le_bytes(buf)
+    };
+    match rand_below_with(n, next) {
+        Some(value) => value,
+        None => unsafe { sys_exit_group(EXIT_RAND_EXHAUSTED) },
+    }
+}
+
+/// Maps values drawn from `next` to a uniformly random value in `[0, n)`, or returns `None` if
+/// [`RAND_BELOW_MAX_ITERATIONS`] draws in a row are rejected.
+fn rand_below_with(n: u32, mut next: impl FnMut() -> u32) -> Option<u32> {
+    assert_ne!(n, 0, "cannot sample from an empty range");
+    // `2^32 mod n` values are rejected, so that the rest are a multiple of `n`.
+    let threshold = n.wrapping_neg() % n;
+    (0..RAND_BELOW_MAX_ITERATIONS).map(|_| next()).find(|&x| x >= threshold).map(|x| x % n)
+}
+
+/// Commits `msg` to the public values stream and halts the program with exit code `code`.
+///
+/// The message is committed before the program halts, so the reason for the failure is part of
+/// the proven public values.
+pub fn abort(code: u32, msg: &[u8]) -> ! {
+    unsafe { sys_abort(code, msg.as_ptr(), msg.len()) }
+}
+
+/// Commits `code` to the public values stream and halts the program with it as the exit code.
+///
+/// The code is committed as the last 4 bytes of the public values, in little-endian order, after
+/// everything the program committed and the digests of the extra channels. The public values
+/// digest of the proof thus binds the code, and the host reads it back with
+/// `SP1PublicValues::committed_exit_code` instead of trusting the exit code reported alongside
+/// the proof.
+///
+/// ### Examples
+/// ```ignore
+/// let valid: bool = check_input();
+/// sp1_zkvm::io::commit_exit(u32::from(!valid));
+/// ```
+pub fn commit_exit(code: u32) -> ! {
+    unsafe { sys_commit_exit(code) }
+}
+
+/// Returns the SHA-256 digest of the public values committed so far, without finalizing the
+/// running hash.
+///
+/// Further commits are still allowed afterwards, and the digest committed when the program halts
+/// covers everything committed over the whole execution.
+///
+/// ### Examples
+/// ```ignore
+/// sp1_zkvm::io::commit_slice(&[1, 2, 3, 4]);
+/// let partial = sp1_zkvm::io::peek_public_values_digest();
+/// sp1_zkvm::io::commit(&partial);
+/// ```
+pub fn peek_public_values_digest() -> [u8; 32] {
+    let mut digest = [0u8; 32];
+    unsafe { sys_public_values_digest(&mut digest) };
+    digest
+}
+
+/// Verifies the next proof of the proof input stream against the pinned verification key digest
+/// `vkey` and the public values `public_values` it must have committed to, and folds the proof into
+/// the deferred proofs digest of this program. Panics if the proof does not verify.
+///
+/// The proofs are not part of the input stream read by [`read`]: the host supplies them, in the
+/// order they are verified, with `SP1Stdin::write_proof`, along with their verifying keys. Each call
+/// consumes one proof. `public_values` are the raw public values of the proof, whose SHA-256 digest
+/// is what the proof commits to.
+///
+/// Enable this function by adding the `verify` feature to both the `sp1-lib` AND `sp1-zkvm` crates.
+///
+/// ### Examples
+/// ```ignore
+/// let vkey: [u32; 8] = sp1_zkvm::io::read();
+/// let public_values: Vec<u8> = sp1_zkvm::io::read();
+/// sp1_zkvm::io::verify_proof(&vkey, &public_values);
+/// ```
+#[cfg(feature = "verify")]
+pub fn verify_proof(vkey: &[u32; 8], public_values: &[u8]) {
+    // The syscall reads the digest as words, so it must be word-aligned.
+    #[repr(align(4))]
+    struct Digest([u8; 32]);
+
+    let digest = Digest(crate::sha256::sha256(public_values));
+    crate::verify::verify_sp1_proof(vkey, &digest.0);
+}
+
 /// Hint a serializable object to the hint stream.
 ///
 /// ### Examples
@@ -166,3 +843,311 @@ pub fn hint_slice(buf: &[u8]) {
 pub fn write(fd: u32, buf: &[u8]) {
     SyscallWriter { fd }.write_all(buf).unwrap();
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::VecDeque, sync::Mutex};
+
+    use sha2::{Digest, Sha256};
+
+    use p3_baby_bear::BabyBear;
+    use p3_field::{AbstractField, PrimeField32};
+
+    use super::{
+        check_hint_len, decode_fields, deserialize_stream, encode_fields, merkle_root,
+        rand_below_with, IoError, HINT_LEN_END_OF_STREAM, RAND_BELOW_MAX_ITERATIONS,
+    };
+    use crate::sha256::Sha256Hasher;
+
+    #[test]
+    fn read_exact_len() {
+        assert_eq!(check_hint_len(32, 32), Ok(()));
+    }
+
+    #[test]
+    fn read_exact_short() {
+        assert_eq!(
+            check_hint_len(31, 32),
+            Err(IoError::LengthMismatch { expected: 32, actual: 31 })
+        );
+    }
+
+    #[test]
+    fn read_exact_empty() {
+        assert_eq!(check_hint_len(0, 0), Ok(()));
+        assert_eq!(check_hint_len(HINT_LEN_END_OF_STREAM, 0), Err(IoError::EndOfStream));
+        assert_eq!(check_hint_len(HINT_LEN_END_OF_STREAM, 32), Err(IoError::EndOfStream));
+    }
+
+    #[test]
+    fn decode_fields_round_trip() {
+        let fields = [0, 1, 0x1234_5678, BabyBear::ORDER_U32 - 1].map(BabyBear::from_canonical_u32);
+        assert_eq!(decode_fields(&encode_fields(&fields)), Ok(fields.to_vec()));
+    }
+
+    #[test]
+    fn decode_fields_non_canonical() {
+        let mut bytes = encode_fields(&[BabyBear::one()]);
+        bytes.extend(BabyBear::ORDER_U32.to_le_bytes());
+        assert_eq!(decode_fields(&bytes), Err(IoError::NonCanonicalField(BabyBear::ORDER_U32)));
+        assert_eq!(
+            decode_fields(&u32::MAX.to_le_bytes()),
+            Err(IoError::NonCanonicalField(u32::MAX))
+        );
+    }
+
+    #[test]
+    fn read_hint_stream_matches_buffered_read() {
+        let data: Vec<u64> =
+            (0..100_000u64).map(|i| i.wrapping_mul(0x9E37_79B9_7F4A_7C15)).collect();
+        let bytes = bincode::serialize(&data).unwrap();
+        let buffered: Vec<u64> = bincode::deserialize(&bytes).unwrap();
+
+        for chunk_len in [1, 7, 4096, bytes.len()] {
+            let chunks = bytes.chunks(chunk_len).map(<[u8]>::to_vec);
+            assert_eq!(deserialize_stream::<Vec<u64>>(chunks), Ok(buffered.clone()));
+        }
+    }
+
+    #[test]
+    fn read_hint_stream_truncated() {
+        let bytes = bincode::serialize(&vec![1u64; 1000]).unwrap();
+        let chunks = bytes[..bytes.len() - 1].chunks(64).map(<[u8]>::to_vec);
+        assert_eq!(deserialize_stream::<Vec<u64>>(chunks), Err(IoError::EndOfStream));
+        assert_eq!(deserialize_stream::<u64>(std::iter::empty()), Err(IoError::EndOfStream));
+    }
+
+    /// A deterministic stream of uniform values, from the SplitMix64 generator.
+    fn split_mix(seed: u64) -> impl FnMut() -> u32 {
+        let mut state = seed;
+        move || {
+            state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+            let mut z = state;
+            z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+            ((z ^ (z >> 31)) >> 32) as u32
+        }
+    }
+
+    #[test]
+    fn rand_below_uniform() {
+        const N: u32 = 10;
+        const SAMPLES: u32 = 100_000;
+
+        let mut next = split_mix(0);
+        let mut counts = [0u32; N as usize];
+        for _ in 0..SAMPLES {
+            counts[rand_below_with(N, &mut next).unwrap() as usize] += 1;
+        }
+
+        // Pearson's chi-squared statistic, which for 9 degrees of freedom exceeds 27.88 with
+        // probability 0.001.
+        let expected = f64::from(SAMPLES / N);
+        let chi_squared: f64 =
+            counts.iter().map(|&count| (f64::from(count) - expected).powi(2) / expected).sum();
+        assert!(chi_squared < 27.88, "counts {counts:?} are not uniform");
+    }
+
+    #[test]
+    fn rand_below_rejects_biased_values() {
+        // `2^32 mod 3 = 1`, so only 0 is rejected.
+        let mut values = [0, 0, 7].into_iter();
+        assert_eq!(rand_below_with(3, || values.next().unwrap()), Some(1));
+
+        // Powers of two reject nothing.
+        assert_eq!(rand_below_with(1 << 4, || 0), Some(0));
+        assert_eq!(rand_below_with(1, || u32::MAX), Some(0));
+    }
+
+    #[test]
+    fn rand_below_exhausted() {
+        let mut draws = 0;
+        let value = rand_below_with(3, || {
+            draws += 1;
+            0
+        });
+        assert_eq!(value, None);
+        assert_eq!(draws, RAND_BELOW_MAX_ITERATIONS);
+    }
+
+    /// The hasher [`super::enable_input_commitment`] uses, with the compression function of `sha2`
+    /// instead of the precompiles.
+    fn input_hasher() -> Sha256Hasher {
+        Sha256Hasher::with_compress(|state, block| sha2::compress256(state, &[(*block).into()]))
+    }
+
+    #[test]
+    fn input_commitment_matches_sha256() {
+        let bytes = (0..1000u32).map(|i| (i * 37 % 251) as u8).collect::<Vec<_>>();
+        // Elements of every length around a block boundary, and an empty one.
+        let elements = [&bytes[..0], &bytes[..3], &bytes[3..66], &bytes[66..130], &bytes[130..]];
+
+        let mut hasher = input_hasher();
+        for element in elements {
+            hasher.update(element);
+        }
+        assert_eq!(hasher.finalize(), <[u8; 32]>::from(Sha256::digest(&bytes)));
+
+        for len in 0..200 {
+            let mut hasher = input_hasher();
+            hasher.update(&bytes[..len]);
+            assert_eq!(hasher.finalize(), <[u8; 32]>::from(Sha256::digest(&bytes[..len])));
+        }
+    }
+
+    /// The bytes written to the public values stream by the tests, in place of the syscall.
+    static PUBLIC_VALUES: Mutex<Vec<u8>> = Mutex::new(Vec::new());
+
+    #[no_mangle]
+    extern "C" fn syscall_write(fd: u32, write_buf: *const u8, nbytes: usize) {
+        assert_eq!(fd, super::FD_PUBLIC_VALUES);
+        let buf = unsafe { std::slice::from_raw_parts(write_buf, nbytes) };
+        PUBLIC_VALUES.lock().unwrap().extend_from_slice(buf);
+    }
+
+    /// Serializes the tests that commit, which share [`PUBLIC_VALUES`] and the committed length.
+    static COMMIT_LOCK: Mutex<()> = Mutex::new(());
+
+    /// The elements of the input stream read by the tests, in place of the host.
+    static HINTS: Mutex<VecDeque<Vec<u8>>> = Mutex::new(VecDeque::new());
+
+    #[no_mangle]
+    extern "C" fn syscall_hint_len() -> usize {
+        HINTS.lock().unwrap().front().map_or(HINT_LEN_END_OF_STREAM, Vec::len)
+    }
+
+    #[no_mangle]
+    extern "C" fn syscall_hint_read(ptr: *mut u8, len: usize) {
+        let hint = HINTS.lock().unwrap().pop_front().unwrap();
+        assert_eq!(hint.len(), len);
+        unsafe { std::ptr::copy_nonoverlapping(hint.as_ptr(), ptr, len) };
+    }
+
+    /// Leaks a buffer of exactly `bytes` bytes, which `read_fresh` turns back into a `Vec`.
+    #[no_mangle]
+    extern "C" fn sys_alloc_fresh(bytes: usize, _align: usize) -> *mut u8 {
+        std::mem::ManuallyDrop::new(Vec::<u8>::with_capacity(bytes)).as_mut_ptr()
+    }
+
+    /// Leaves the message schedule to [`syscall_sha256_compress`], which only needs its first 16
+    /// words.
+    #[no_mangle]
+    extern "C" fn syscall_sha256_extend(_w: *mut [u32; 64]) {}
+
+    #[no_mangle]
+    extern "C" fn syscall_sha256_compress(w: *mut [u32; 64], state: *mut [u32; 8]) {
+        let mut block = [0u8; 64];
+        for (bytes, word) in block.chunks_exact_mut(4).zip(unsafe { &*w }) {
+            bytes.copy_from_slice(&word.to_be_bytes());
+        }
+        sha2::compress256(unsafe { &mut *state }, &[block.into()]);
+    }
+
+    /// Reads `elements` from the input stream with input commitment enabled, and returns the
+    /// committed digest.
+    fn commit_read_input(elements: &[&[u8]]) -> [u8; 32] {
+        HINTS.lock().unwrap().extend(elements.iter().map(|element| element.to_vec()));
+        PUBLIC_VALUES.lock().unwrap().clear();
+        super::enable_input_commitment();
+        for element in elements {
+            if element.len() == 32 {
+                let mut buf = [0; 32];
+                super::read_exact(&mut buf).unwrap();
+                assert_eq!(&buf, element);
+            } else {
+                assert_eq!(super::read_vec(), *element);
+            }
+        }
+        let digest = super::commit_input_commitment();
+        assert_eq!(std::mem::take(&mut *PUBLIC_VALUES.lock().unwrap()), digest);
+        digest
+    }
+
+    #[test]
+    fn input_commitment_frames_elements() {
+        let _guard = COMMIT_LOCK.lock().unwrap();
+        let elements: [&[u8]; 4] = [b"ab", b"c", &[], &[7; 32]];
+        let digest = commit_read_input(&elements);
+
+        let mut framed = Vec::new();
+        for element in elements {
+            framed.extend((element.len() as u64).to_le_bytes());
+            framed.extend(element);
+        }
+        assert_eq!(digest, <[u8; 32]>::from(Sha256::digest(&framed)));
+
+        // The same bytes split into different elements commit to a different digest.
+        let resplit: [&[u8]; 4] = [b"a", b"bc", &[], &[7; 32]];
+        assert_ne!(commit_read_input(&resplit), digest);
+    }
+
+    #[test]
+    fn commit_int_endianness() {
+        let _guard = COMMIT_LOCK.lock().unwrap();
+        let public_values = {
+            PUBLIC_VALUES.lock().unwrap().clear();
+            super::commit_u16_le(0x0102);
+            super::commit_u16_be(0x0102);
+            super::commit_u32_le(0x01020304);
+            super::commit_u32_be(0x01020304);
+            super::commit_u64_le(0x0102030405060708);
+            super::commit_u64_be(0x0102030405060708);
+            super::commit_u128_le(1);
+            super::commit_u128_be(1);
+            std::mem::take(&mut *PUBLIC_VALUES.lock().unwrap())
+        };
+
+        let mut expected = vec![2, 1, 1, 2, 4, 3, 2, 1, 1, 2, 3, 4];
+        expected.extend([8, 7, 6, 5, 4, 3, 2, 1, 1, 2, 3, 4, 5, 6, 7, 8]);
+        expected.extend([1].into_iter().chain([0; 15]));
+        expected.extend([0; 15].into_iter().chain([1]));
+        assert_eq!(public_values, expected);
+        assert_eq!(Sha256::digest(&public_values), Sha256::digest(&expected));
+    }
+
+    #[test]
+    fn committed_len_counts_bytes() {
+        let _guard = COMMIT_LOCK.lock().unwrap();
+        PUBLIC_VALUES.lock().unwrap().clear();
+        let start = super::committed_len();
+
+        super::commit_slice(&[1, 2, 3]);
+        super::commit(&vec![7u32; 5]);
+        super::commit_fields(&[BabyBear::one(), BabyBear::two()]);
+        super::commit_u64_be(42);
+        super::write(super::FD_PUBLIC_VALUES, &[0; 10]);
+        let written = PUBLIC_VALUES.lock().unwrap().len();
+        assert_eq!(super::committed_len() - start, written);
+
+        assert_eq!(super::commit_len_trailer() - start, written);
+        let public_values = std::mem::take(&mut *PUBLIC_VALUES.lock().unwrap());
+        assert_eq!(public_values[written..], ((start + written) as u64).to_le_bytes());
+        assert_eq!(super::committed_len() - start, written + 8);
+    }
+
+    #[test]
+    fn merkle_root_small_tree() {
+        let sha256 = |message: &[u8]| -> [u8; 32] { Sha256::digest(message).into() };
+        let leaf = |leaf: &[u8; 32]| sha256(&[&[0x00], &leaf[..]].concat());
+        let node =
+            |left: &[u8; 32], right: &[u8; 32]| sha256(&[&[0x01], &left[..], &right[..]].concat());
+        let leaves = [[1u8; 32], [2u8; 32], [3u8; 32], [4u8; 32], [5u8; 32]];
+        let hashed = leaves.map(|l| leaf(&l));
+
+        assert_eq!(merkle_root(&leaves[..1], sha256), hashed[0]);
+        assert_eq!(merkle_root(&leaves[..2], sha256), node(&hashed[0], &hashed[1]));
+
+        let left = node(&node(&hashed[0], &hashed[1]), &node(&hashed[2], &hashed[3]));
+        assert_eq!(merkle_root(&leaves, sha256), node(&left, &hashed[4]));
+
+        // A root disclosed as a leaf does not prove the tree it is the root of.
+        let root = merkle_root(&leaves[..2], sha256);
+        assert_ne!(merkle_root(&[root], sha256), root);
+    }
+
+    #[test]
+    #[should_panic(expected = "at least one leaf")]
+    fn merkle_root_empty() {
+        merkle_root(&[], |_| [0; 32]);
+    }
+}
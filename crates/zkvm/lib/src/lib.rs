@@ -3,20 +3,28 @@
 //! Documentation for these syscalls can be found in the zkVM entrypoint
 //! `sp1_zkvm::syscalls` module.
 
+pub mod blake3;
 pub mod bls12381;
 pub mod bn254;
 pub mod ed25519;
 pub mod io;
+pub mod poseidon2;
 pub mod secp256k1;
+pub mod sha256;
 pub mod unconstrained;
 pub mod utils;
 #[cfg(feature = "verify")]
 pub mod verify;
 
+use sp1_primitives::consts::PV_DIGEST_NUM_WORDS;
+
 extern "C" {
     /// Halts the program with the given exit code.
     pub fn syscall_halt(exit_code: u8) -> !;
 
+    /// Halts the program with the given exit code and terminates the execution.
+    pub fn sys_exit_group(exit_code: u32) -> !;
+
     /// Writes the bytes in the given buffer to the given file descriptor.
     pub fn syscall_write(fd: u32, write_buf: *const u8, nbytes: usize);
 
@@ -62,6 +70,15 @@ extern "C" {
     /// Executes an uint256 multiplication on the given inputs.
     pub fn syscall_uint256_mulmod(x: *mut [u32; 8], y: *const [u32; 8]);
 
+    /// Executes the Poseidon2 permutation over `BabyBear` on the given state.
+    pub fn sys_poseidon2_permute(state: *mut u32, width: usize);
+
+    /// Executes the BLAKE3 compression function on the given state and message block.
+    pub fn syscall_blake3_compress(state: *mut [u32; 16], block: *const [u32; 16]);
+
+    /// Writes the BLAKE3 hash of the given buffer to the given 32-byte output buffer.
+    pub fn sys_blake3(input: *const u8, len: usize, out: *mut u8);
+
     /// Enters unconstrained mode.
     pub fn syscall_enter_unconstrained() -> bool;
 
@@ -77,12 +94,62 @@ extern "C" {
     /// Reads the next element in the hint stream into the given buffer.
     pub fn syscall_hint_read(ptr: *mut u8, len: usize);
 
+    /// Looks up a key in the config provided by the host, writing its value to the given buffer if
+    /// it fits, and returns the length of the value.
+    pub fn sys_config_get(
+        key_ptr: *const u8,
+        key_len: usize,
+        val_ptr: *mut u8,
+        val_cap: usize,
+    ) -> usize;
+
+    /// Commits the bytes in the given buffer to the given public values channel.
+    pub fn sys_commit_to_channel(channel: u8, write_buf: *const u8, nbytes: usize);
+
+    /// Commits the timestamp provided by the host to the public values and returns it.
+    pub fn sys_commit_time() -> u64;
+
+    /// Commits the verification key digest at the given pointer to the public values.
+    pub fn sys_commit_vkey(vkey_ptr: *const [u32; PV_DIGEST_NUM_WORDS]);
+
+    /// Returns whether the execution is being proven, as opposed to only executed.
+    pub fn sys_is_proving() -> bool;
+
+    /// Fills the given buffer with `len` random bytes provided by the host.
+    pub fn sys_rand(recv_buf: *mut u8, len: usize);
+
+    /// Copies a range of the input blob provided by the host to the given buffer and returns the
+    /// number of bytes copied.
+    pub fn sys_input_read_at(offset: usize, buf: *mut u8, len: usize) -> usize;
+
+    /// Copies the next bytes of a named input stream provided by the host to the given buffer and
+    /// returns the number of bytes copied.
+    pub fn sys_read_stream(stream_id: u32, buf: *mut u8, len: usize) -> usize;
+
+    /// Writes the digest of the public values committed so far to the given buffer.
+    pub fn sys_public_values_digest(out: *mut [u8; 32]);
+
+    /// Commits the given message to the public values and halts with the given exit code.
+    pub fn sys_abort(code: u32, msg_ptr: *const u8, msg_len: usize) -> !;
+
+    /// Commits the given exit code to the public values and halts with it.
+    pub fn sys_commit_exit(exit_code: u32) -> !;
+
     /// Allocates a buffer aligned to the given alignment.
     pub fn sys_alloc_aligned(bytes: usize, align: usize) -> *mut u8;
 
+    /// Allocates a buffer aligned to the given alignment from memory that was never allocated.
+    pub fn sys_alloc_fresh(bytes: usize, align: usize) -> *mut u8;
+
+    /// Zeroes the given region with stores the compiler cannot elide.
+    pub fn sys_memzero(ptr: *mut u8, len: usize);
+
     /// Decompresses a BLS12-381 point.
     pub fn syscall_bls12381_decompress(point: &mut [u8; 96], is_odd: bool);
 
+    /// Computes `(a * b) % m` on 32-byte big-endian integers, like the EVM `MULMOD` opcode.
+    pub fn sys_uint256_mulmod(a: *const u8, b: *const u8, m: *const u8, out: *mut u8);
+
     /// Computes a big integer operation with a modulus.
     pub fn sys_bigint(
         result: *mut [u32; 8],
@@ -128,4 +195,16 @@ extern "C" {
     /// Executes a BN254 Fp2 multiplication on the given inputs.
     pub fn syscall_bn254_fp2_mulmod(p: *mut u32, q: *const u32);
 
+    /// Opens a named profiling span.
+    pub fn sys_profile_begin(label_ptr: *const u8, label_len: usize);
+
+    /// Closes a named profiling span.
+    pub fn sys_profile_end(label_ptr: *const u8, label_len: usize);
+
+    /// Returns the index of the current execution shard.
+    pub fn sys_shard_index() -> u32;
+
+    /// Returns the program counter of the syscall site, the `ecall` instruction inside
+    /// `sys_read_pc`.
+    pub fn sys_read_pc() -> u32;
 }
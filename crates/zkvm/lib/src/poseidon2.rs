@@ -0,0 +1,19 @@
+use p3_baby_bear::BabyBear;
+use p3_field::{AbstractField, PrimeField32};
+
+use crate::sys_poseidon2_permute;
+
+/// The width of the Poseidon2 permutation state.
+pub const POSEIDON2_WIDTH: usize = 16;
+
+/// Applies the Poseidon2 permutation to `state` using the `POSEIDON2_PERMUTE` precompile.
+///
+/// The permutation is the same one used by the recursion verifier, so digests computed in the
+/// guest match the ones computed while verifying SP1 proofs.
+pub fn poseidon2_permute(state: [BabyBear; POSEIDON2_WIDTH]) -> [BabyBear; POSEIDON2_WIDTH] {
+    let mut words = state.map(|x| x.as_canonical_u32());
+    unsafe {
+        sys_poseidon2_permute(words.as_mut_ptr(), POSEIDON2_WIDTH);
+    }
+    words.map(BabyBear::from_canonical_u32)
+}
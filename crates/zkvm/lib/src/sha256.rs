@@ -0,0 +1,95 @@
+use crate::{syscall_sha256_compress, syscall_sha256_extend};
+
+/// The SHA-256 initial hash value.
+pub const SHA256_INITIAL_STATE: [u32; 8] = [
+    0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19,
+];
+
+/// Compresses a 64-byte block into the SHA-256 state using the `SHA_EXTEND` and `SHA_COMPRESS`
+/// precompiles.
+pub fn sha256_compress(state: &mut [u32; 8], block: &[u8; 64]) {
+    let mut w = [0u32; 64];
+    for (word, bytes) in w.iter_mut().zip(block.chunks_exact(4)) {
+        *word = u32::from_be_bytes(bytes.try_into().unwrap());
+    }
+    unsafe {
+        syscall_sha256_extend(&mut w);
+        syscall_sha256_compress(&mut w, state);
+    }
+}
+
+/// Computes the SHA-256 digest of `data` using the SHA-256 precompiles.
+pub fn sha256(data: &[u8]) -> [u8; 32] {
+    let mut hasher = Sha256Hasher::new();
+    hasher.update(data);
+    hasher.finalize()
+}
+
+/// An incremental SHA-256 hasher using the SHA-256 precompiles, for data that arrives in pieces.
+///
+/// ### Examples
+/// ```ignore
+/// let mut hasher = sp1_lib::sha256::Sha256Hasher::new();
+/// hasher.update(b"ab");
+/// hasher.update(b"c");
+/// assert_eq!(hasher.finalize(), sp1_lib::sha256::sha256(b"abc"));
+/// ```
+#[derive(Clone)]
+pub struct Sha256Hasher {
+    state: [u32; 8],
+    /// The bytes of the current, incomplete block.
+    block: [u8; 64],
+    block_len: usize,
+    /// The number of bytes hashed so far.
+    len: u64,
+    compress: fn(&mut [u32; 8], &[u8; 64]),
+}
+
+impl Default for Sha256Hasher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Sha256Hasher {
+    /// A hasher that has not hashed anything yet.
+    pub const fn new() -> Self {
+        Self::with_compress(sha256_compress)
+    }
+
+    /// A hasher using `compress` as the compression function, so it can run outside of the zkVM.
+    pub(crate) const fn with_compress(compress: fn(&mut [u32; 8], &[u8; 64])) -> Self {
+        Self { state: SHA256_INITIAL_STATE, block: [0; 64], block_len: 0, len: 0, compress }
+    }
+
+    /// Hashes `data` after everything hashed before.
+    pub fn update(&mut self, mut data: &[u8]) {
+        self.len += data.len() as u64;
+        while !data.is_empty() {
+            let n = data.len().min(64 - self.block_len);
+            self.block[self.block_len..self.block_len + n].copy_from_slice(&data[..n]);
+            self.block_len += n;
+            data = &data[n..];
+            if self.block_len == 64 {
+                (self.compress)(&mut self.state, &self.block);
+                self.block_len = 0;
+            }
+        }
+    }
+
+    /// The digest of everything hashed.
+    pub fn finalize(mut self) -> [u8; 32] {
+        // Pad with a one bit, zeros, and the message length in bits.
+        let bit_len = self.len * 8;
+        let zeros = (119 - self.block_len) % 64;
+        self.update(&[0x80]);
+        self.update(&[0; 64][..zeros]);
+        self.update(&bit_len.to_be_bytes());
+
+        let mut digest = [0u8; 32];
+        for (bytes, word) in digest.chunks_exact_mut(4).zip(self.state) {
+            bytes.copy_from_slice(&word.to_be_bytes());
+        }
+        digest
+    }
+}
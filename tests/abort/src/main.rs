@@ -0,0 +1,9 @@
+#![no_main]
+sp1_zkvm::entrypoint!(main);
+
+use sp1_zkvm::exit::EXIT_ABORT;
+
+pub fn main() {
+    sp1_zkvm::io::commit(&42u32);
+    sp1_zkvm::io::abort(EXIT_ABORT, b"invalid input");
+}
@@ -0,0 +1,7 @@
+#![no_main]
+sp1_zkvm::entrypoint!(main);
+
+pub fn main() {
+    sp1_zkvm::io::commit(&42u32);
+    sp1_zkvm::io::commit_exit(0);
+}
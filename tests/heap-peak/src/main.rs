@@ -0,0 +1,19 @@
+#![no_main]
+sp1_zkvm::entrypoint!(main, heap = ArenaAlloc::with_base(0x1000_0000, 0x100_0000));
+
+/// The size of the largest allocation. The heap starts at a fixed base and this allocation is
+/// made first, so it is also the peak heap usage.
+const PEAK: usize = 0x1_0000;
+
+pub fn main() {
+    let buf = vec![0xffu8; PEAK];
+    std::hint::black_box(&buf);
+    drop(buf);
+
+    // The freed memory is reused, so a smaller allocation does not raise the peak.
+    let small = vec![0xffu8; 0x100];
+    std::hint::black_box(&small);
+
+    // Committing allocates far less than `PEAK` bytes, so the peak reported at exit is the same.
+    sp1_zkvm::io::commit(&(HEAP.peak() as u32));
+}
@@ -0,0 +1,12 @@
+#![no_main]
+sp1_zkvm::entrypoint!(main);
+
+pub fn main() {
+    // Reserve 16 MiB at a time without touching it, until the heap is exhausted and the allocator
+    // halts with `EXIT_OOM`.
+    let mut chunks = Vec::new();
+    loop {
+        chunks.push(Vec::<u8>::with_capacity(1 << 24));
+        std::hint::black_box(&chunks);
+    }
+}
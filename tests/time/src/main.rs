@@ -0,0 +1,7 @@
+#![no_main]
+sp1_zkvm::entrypoint!(main);
+
+pub fn main() {
+    let time = sp1_zkvm::io::commit_time();
+    sp1_zkvm::io::commit(&time);
+}
@@ -0,0 +1,21 @@
+#![no_main]
+sp1_zkvm::entrypoint!(main);
+
+use sp1_zkvm::io::alloc_zeroed_region;
+
+pub fn main() {
+    // Dirty the heap first, so that the region cannot be zero by accident.
+    let dirty = vec![0xffu8; 1 << 12];
+    std::hint::black_box(&dirty);
+    drop(dirty);
+
+    let len = 1 << 16;
+    let region = alloc_zeroed_region(len);
+    let bytes = unsafe { core::slice::from_raw_parts(region, len) };
+    sp1_zkvm::io::commit(&(region as u32 % 8));
+    sp1_zkvm::io::commit(&bytes.iter().all(|&byte| byte == 0));
+
+    // The region is never handed out again.
+    let next = alloc_zeroed_region(8);
+    sp1_zkvm::io::commit(&(next as u32 >= region as u32 + len as u32));
+}